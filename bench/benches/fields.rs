@@ -0,0 +1,34 @@
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use horcrux::field::Field;
+use horcrux_bench::for_all_fields;
+use std::hint::black_box;
+
+fn bench_mul<F: Field>(group: &mut BenchmarkGroup<WallTime>, field: &str) {
+    let mut rng = horcrux_bench::rng();
+    let x = F::uniform(&mut rng);
+    let y = F::uniform(&mut rng);
+    group.bench_function(field, |b| b.iter(|| black_box(x) * &black_box(y)));
+}
+
+fn bench_invert<F: Field>(group: &mut BenchmarkGroup<WallTime>, field: &str) {
+    let x = F::uniform(&mut horcrux_bench::rng());
+    group.bench_function(field, |b| b.iter(|| black_box(x).invert()));
+}
+
+fn fields(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mul");
+    for_all_fields!(F, field => {
+        bench_mul::<F>(&mut group, field);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("invert");
+    for_all_fields!(F, field => {
+        bench_invert::<F>(&mut group, field);
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fields);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use horcrux::field::Field;
+use horcrux::shamir::{CompactShamir, RandomShamir, Shamir};
+use horcrux_bench::{for_all_fields, for_fast_fields};
+use std::fmt::Display;
+use std::hint::black_box;
+
+fn bench_split<F: Field, S: Shamir<F>>(
+    group: &mut BenchmarkGroup<WallTime>,
+    field: &str,
+    k: usize,
+    n: usize,
+) {
+    let secret = F::uniform(&mut horcrux_bench::rng());
+    group.bench_function(field, |b| b.iter(|| S::split(black_box(&secret), k, n)));
+}
+
+fn bench_reconstruct<F: Field, S: Shamir<F>>(
+    group: &mut BenchmarkGroup<WallTime>,
+    field: &str,
+    k: usize,
+    n: usize,
+) {
+    let mut rng = horcrux_bench::rng();
+    let secret = F::uniform(&mut rng);
+    let shares = S::split_with_rng(&secret, k, n, &mut rng).unwrap();
+    group.bench_function(field, |b| b.iter(|| S::reconstruct(black_box(&shares), k)));
+}
+
+fn bench_format_share<F: Field, S: Shamir<F>>(group: &mut BenchmarkGroup<WallTime>, field: &str)
+where
+    S::Share: Display,
+{
+    let mut rng = horcrux_bench::rng();
+    let secret = F::uniform(&mut rng);
+    let shares = S::split_with_rng(&secret, 10, 10, &mut rng).unwrap();
+    group.bench_function(field, |b| b.iter(|| black_box(&shares[0]).to_string()));
+}
+
+fn bench_parse_share<F: Field, S: Shamir<F>>(group: &mut BenchmarkGroup<WallTime>, field: &str)
+where
+    S::Share: Display,
+{
+    let mut rng = horcrux_bench::rng();
+    let secret = F::uniform(&mut rng);
+    let shares = S::split_with_rng(&secret, 10, 10, &mut rng).unwrap();
+    let share = shares[0].to_string();
+    group.bench_function(field, |b| b.iter(|| S::parse_share(black_box(&share))));
+}
+
+fn compact(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compact_split_10");
+    for_all_fields!(F, field => {
+        bench_split::<F, CompactShamir>(&mut group, field, 10, 10);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("compact_reconstruct_10");
+    for_all_fields!(F, field => {
+        bench_reconstruct::<F, CompactShamir>(&mut group, field, 10, 10);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("compact_split_255");
+    for_fast_fields!(F, field => {
+        bench_split::<F, CompactShamir>(&mut group, field, 255, 255);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("compact_reconstruct_255");
+    for_fast_fields!(F, field => {
+        bench_reconstruct::<F, CompactShamir>(&mut group, field, 255, 255);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("compact_format_share");
+    for_all_fields!(F, field => {
+        bench_format_share::<F, CompactShamir>(&mut group, field);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("compact_parse_share");
+    for_all_fields!(F, field => {
+        bench_parse_share::<F, CompactShamir>(&mut group, field);
+    });
+    group.finish();
+}
+
+fn random(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random_split_10");
+    for_all_fields!(F, field => {
+        bench_split::<F, RandomShamir>(&mut group, field, 10, 10);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("random_reconstruct_10");
+    for_all_fields!(F, field => {
+        bench_reconstruct::<F, RandomShamir>(&mut group, field, 10, 10);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("random_format_share");
+    for_all_fields!(F, field => {
+        bench_format_share::<F, RandomShamir>(&mut group, field);
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("random_parse_share");
+    for_all_fields!(F, field => {
+        bench_parse_share::<F, RandomShamir>(&mut group, field);
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compact, random);
+criterion_main!(benches);
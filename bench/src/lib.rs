@@ -0,0 +1,53 @@
+//! Benchmarks of the `horcrux` library, measured with criterion on stable Rust.
+//!
+//! Each benchmark group is an operation (e.g. `mul` or `compact_split_10`), and each benchmark in
+//! a group is a field (e.g. `gf128`), so that criterion writes the estimates of a run to
+//! `target/criterion/<operation>/<field>/<baseline>/estimates.json`. The `plot` example reads
+//! these JSON files to draw the benchmarks of several baselines, e.g. with and without native
+//! instructions.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// Runs the given block for each benchmarked field, with `$F` bound to the field type and `$name`
+/// to the benchmark name of the field.
+#[macro_export]
+macro_rules! for_all_fields {
+    ($F:ident, $name:ident => $body:block) => {{
+        $crate::for_fast_fields!($F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF512, "gf512", $F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF1024, "gf1024", $F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF2048, "gf2048", $F, $name => $body);
+        $crate::for_field!(horcrux::gfp::Secp256k1Scalar, "secp256k1", $F, $name => $body);
+        $crate::for_field!(horcrux::gfp::Ed25519Scalar, "ed25519", $F, $name => $body);
+    }};
+}
+
+/// Same as `for_all_fields!`, restricted to the binary fields of up to 256 bits, for the
+/// benchmarks that would take too long with the larger fields.
+#[macro_export]
+macro_rules! for_fast_fields {
+    ($F:ident, $name:ident => $body:block) => {{
+        $crate::for_field!(horcrux::gf2n::GF8, "gf008", $F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF16, "gf016", $F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF32, "gf032", $F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF64, "gf064", $F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF128, "gf128", $F, $name => $body);
+        $crate::for_field!(horcrux::gf2n::GF256, "gf256", $F, $name => $body);
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! for_field {
+    ($field:ty, $field_name:expr, $F:ident, $name:ident => $body:block) => {{
+        type $F = $field;
+        let $name = $field_name;
+        $body
+    }};
+}
+
+/// Returns a deterministic RNG, so that the benchmarks measure the same values on each run.
+pub fn rng() -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(42)
+}
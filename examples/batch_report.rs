@@ -0,0 +1,78 @@
+//! Measures the throughput of splitting many small secrets, e.g. per-customer data encryption keys,
+//! one by one and with the batch API, and prints a CSV report.
+//!
+//! Usage: `cargo run --release --example batch_report -- [secrets] [threshold] [nshares] > report.csv`
+//!
+//! The defaults correspond to splitting 10,000 keys 2-of-3. Each measurement is the best of several
+//! runs, to limit the noise of other processes.
+
+use horcrux::batch;
+use horcrux::field::Field;
+use horcrux::gf2n::{GF128, GF256, GF64};
+use horcrux::gfp::{Ed25519Scalar, Secp256k1Scalar};
+use horcrux::shamir::{CompactShamir, Shamir};
+use rand::thread_rng;
+use std::fmt::{Debug, Display};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Number of runs of each measurement.
+const RUNS: usize = 5;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let arg = |i: usize, default: usize| {
+        args.get(i)
+            .map(|a| a.parse::<usize>().expect("Invalid number"))
+            .unwrap_or(default)
+    };
+    let m = arg(1, 10_000);
+    let k = arg(2, 2);
+    let n = arg(3, 3);
+
+    println!("field,secrets,threshold,nshares,method,total_us,per_secret_ns,speedup");
+    report::<GF64>("gf64", m, k, n);
+    report::<GF128>("gf128", m, k, n);
+    report::<GF256>("gf256", m, k, n);
+    report::<Secp256k1Scalar>("secp256k1", m, k, n);
+    report::<Ed25519Scalar>("ed25519", m, k, n);
+}
+
+fn report<F: Field + Debug + Display>(name: &str, m: usize, k: usize, n: usize) {
+    let mut rng = thread_rng();
+    let secrets: Vec<F> = (0..m).map(|_| F::uniform(&mut rng)).collect();
+
+    let individual = best_of(|| {
+        for secret in &secrets {
+            black_box(CompactShamir::split(black_box(secret), k, n).unwrap());
+        }
+    });
+    let batch = best_of(|| {
+        black_box(batch::split(black_box(&secrets), k, n).unwrap());
+    });
+
+    for (method, time) in [("individual", individual), ("batch", batch)] {
+        println!(
+            "{},{},{},{},{},{},{:.1},{:.2}",
+            name,
+            m,
+            k,
+            n,
+            method,
+            time.as_micros(),
+            time.as_nanos() as f64 / m.max(1) as f64,
+            individual.as_secs_f64() / time.as_secs_f64()
+        );
+    }
+}
+
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
@@ -0,0 +1,187 @@
+//! Compares a benchmark run against a baseline, and fails if any benchmark regressed by more than a
+//! given percentage.
+//!
+//! Usage: `cargo run --example bench_gate -- <baseline.txt> <current.txt> [max-regression-percent]`
+//!
+//! Both files contain the output of `cargo bench`. The relative change of every benchmark present in
+//! both runs is plotted to `bench-gate.svg`, with the regressions highlighted.
+
+use plotters::chart::ChartBuilder;
+use plotters::drawing::IntoDrawingArea;
+use plotters::element::{Rectangle, Text};
+use plotters::prelude::SVGBackend;
+use plotters::series::LineSeries;
+use plotters::style::colors::{BLACK, RED};
+use plotters::style::{Color, IntoFont, RGBColor};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// Default maximal slowdown, in percent.
+const DEFAULT_MAX_REGRESSION: f64 = 10.0;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 || args.len() > 4 {
+        eprintln!(
+            "Usage: {} <baseline.txt> <current.txt> [max-regression-percent]",
+            args[0]
+        );
+        std::process::exit(2);
+    }
+    let max_regression = args
+        .get(3)
+        .map(|p| p.parse::<f64>().expect("Invalid percentage"))
+        .unwrap_or(DEFAULT_MAX_REGRESSION);
+
+    let baseline = parse(BufReader::new(File::open(&args[1]).unwrap()));
+    let current = parse(BufReader::new(File::open(&args[2]).unwrap()));
+
+    let changes: Vec<Change> = current
+        .iter()
+        .filter_map(|(name, &avg)| {
+            // Benchmarks that are too fast to be measured can't be compared.
+            let base = *baseline.get(name).filter(|&&base| base != 0)?;
+            Some(Change {
+                name: name.clone(),
+                percent: 100.0 * (avg as f64 - base as f64) / base as f64,
+            })
+        })
+        .collect();
+    if changes.is_empty() {
+        eprintln!("No benchmark in common between the baseline and the current run");
+        std::process::exit(2);
+    }
+
+    let regressions: Vec<&Change> = changes
+        .iter()
+        .filter(|c| c.percent > max_regression)
+        .collect();
+    for change in &changes {
+        println!(
+            "{:>+8.1}% {}{}",
+            change.percent,
+            change.name,
+            if change.percent > max_regression {
+                "  REGRESSION"
+            } else {
+                ""
+            }
+        );
+    }
+
+    draw(&changes, max_regression);
+
+    if !regressions.is_empty() {
+        eprintln!(
+            "{} of {} benchmarks regressed by more than {}%",
+            regressions.len(),
+            changes.len(),
+            max_regression
+        );
+        std::process::exit(1);
+    }
+    println!(
+        "No regression above {}% among {} benchmarks",
+        max_regression,
+        changes.len()
+    );
+}
+
+struct Change {
+    name: String,
+    percent: f64,
+}
+
+fn draw(changes: &[Change], max_regression: f64) {
+    let width = 1000;
+    let height = 600;
+    let drawing_area = SVGBackend::new("bench-gate.svg", (width, height)).into_drawing_area();
+
+    let min = changes
+        .iter()
+        .map(|c| c.percent)
+        .fold(-max_regression, f64::min);
+    let max = changes
+        .iter()
+        .map(|c| c.percent)
+        .fold(max_regression, f64::max);
+    let margin = (max - min) * 0.1;
+    let n = changes.len() as i32;
+
+    let mut chart = ChartBuilder::on(&drawing_area)
+        .x_label_area_size(20)
+        .y_label_area_size(60)
+        .margin(10)
+        .margin_right(40)
+        .caption("Change relative to the baseline", ("sans-serif", 30))
+        .build_cartesian_2d(0..n, (min - margin)..(max + margin))
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .disable_x_axis()
+        .label_style(("sans-serif", 14))
+        .y_desc("Time change")
+        .y_label_formatter(&|v| format!("{:+.0}%", v))
+        .draw()
+        .unwrap();
+
+    chart
+        .draw_series(changes.iter().enumerate().map(|(i, c)| {
+            let color = if c.percent > max_regression {
+                RED
+            } else {
+                RGBColor(0x80, 0x80, 0xC0)
+            };
+            Rectangle::new([(i as i32, 0.0), (i as i32 + 1, c.percent)], color.filled())
+        }))
+        .unwrap();
+
+    chart
+        .draw_series(LineSeries::new(
+            [(0, max_regression), (n, max_regression)],
+            RED.stroke_width(1),
+        ))
+        .unwrap();
+    chart
+        .draw_series(LineSeries::new([(0, 0.0), (n, 0.0)], BLACK))
+        .unwrap();
+
+    // Label the regressions.
+    chart
+        .draw_series(
+            changes
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.percent > max_regression)
+                .map(|(i, c)| {
+                    Text::new(
+                        format!("{} ({:+.0}%)", c.name, c.percent),
+                        (i as i32, c.percent + margin / 4.0),
+                        ("sans-serif", 12).into_font().color(&RED),
+                    )
+                }),
+        )
+        .unwrap();
+}
+
+/// Parses the benchmarks of a `cargo bench` output, indexed by their full name.
+fn parse(input: impl BufRead) -> BTreeMap<String, u64> {
+    let re_bench =
+        Regex::new(r"^test ([0-9a-z_:]+)\s+\.{3} bench:\s+([0-9,]+) ns/iter \(\+/\- ([0-9,]+)\)$")
+            .unwrap();
+
+    let mut benches = BTreeMap::new();
+    for line in input.lines() {
+        let line = line.unwrap();
+        if let Some(caps) = re_bench.captures(&line) {
+            let mut avg = caps[2].to_owned();
+            avg.retain(|c| c != ',');
+            benches.insert(caps[1].to_owned(), avg.parse().unwrap());
+        }
+    }
+    benches
+}
@@ -1,3 +1,9 @@
+//! Plots the criterion benchmarks of the `horcrux-bench` crate, saved as the `portable`, `native`
+//! and `native-clmul` baselines (see the README).
+//!
+//! Usage: `cargo run --example plot -- [criterion-dir]`, where the criterion directory defaults to
+//! `target/criterion`.
+
 use plotters::chart::{ChartBuilder, ChartContext, SeriesLabelPosition};
 use plotters::coord::{cartesian::Cartesian2d, combinators::IntoLogRange, ranged1d::Ranged, Shift};
 use plotters::drawing::{DrawingArea, IntoDrawingArea};
@@ -9,12 +15,18 @@ use plotters::series::LineSeries;
 use plotters::style::colors::{BLACK, WHITE};
 use plotters::style::{Color, Palette, Palette99, RGBAColor, ShapeStyle, SizeDesc};
 use plotters_backend::{BackendCoord, DrawingBackend, DrawingErrorKind};
-use regex::Regex;
+use serde_json::Value;
 use std::cmp;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
 
 fn main() {
+    let criterion_dir = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "target/criterion".to_owned());
+    let criterion_dir = Path::new(&criterion_dir);
+
     let width = 800;
     let height = 600;
     println!("Drawing area: {}, {}", width, height);
@@ -64,11 +76,11 @@ fn main() {
         },
     ];
 
-    let benches = parse(BufReader::new(File::open("benchmark.txt").unwrap()));
+    let benches = parse(criterion_dir, "portable");
     let drawing_area = SVGBackend::new("plot.svg", (width, height)).into_drawing_area();
     draw_bench("Benchmarks", drawing_area, line_styles, &benches);
 
-    let benches_native = parse(BufReader::new(File::open("benchmark-native.txt").unwrap()));
+    let benches_native = parse(criterion_dir, "native");
     let drawing_area = SVGBackend::new("plot-native.svg", (width, height)).into_drawing_area();
     draw_bench(
         "Benchmarks (native)",
@@ -77,9 +89,7 @@ fn main() {
         &benches_native,
     );
 
-    let benches_native_clmul = parse(BufReader::new(
-        File::open("benchmark-native-clmul.txt").unwrap(),
-    ));
+    let benches_native_clmul = parse(criterion_dir, "native-clmul");
     let drawing_area =
         SVGBackend::new("plot-native-clmul.svg", (width, height)).into_drawing_area();
     draw_bench(
@@ -115,6 +125,10 @@ fn main() {
         &benches_native,
         &benches_native_clmul,
     );
+
+    let drawing_area =
+        SVGBackend::new("plot-serialization.svg", (width, height)).into_drawing_area();
+    draw_serialization(drawing_area, line_styles.iter(), &benches);
 }
 
 fn draw_field_ops<'a, DB: DrawingBackend>(
@@ -129,7 +143,7 @@ fn draw_field_ops<'a, DB: DrawingBackend>(
             .iter()
             .chain(benches_native.iter())
             .chain(benches_native_clmul.iter()),
-        &["bench_mul", "bench_invert"],
+        &["mul", "invert"],
     );
 
     let mut chart = ChartBuilder::on(&drawing_area)
@@ -165,21 +179,21 @@ fn draw_field_ops<'a, DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "bench_mul",
+        "mul",
         Some("mul"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native,
-        "bench_mul",
+        "mul",
         Some("mul (native)"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native_clmul,
-        "bench_mul",
+        "mul",
         Some("mul (clmul)"),
         line_styles.next().unwrap(),
     );
@@ -187,21 +201,21 @@ fn draw_field_ops<'a, DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "bench_invert",
+        "invert",
         Some("invert"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native,
-        "bench_invert",
+        "invert",
         Some("invert (native)"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native_clmul,
-        "bench_invert",
+        "invert",
         Some("invert (clmul)"),
         line_styles.next().unwrap(),
     );
@@ -209,7 +223,7 @@ fn draw_field_ops<'a, DB: DrawingBackend>(
     chart
         .configure_series_labels()
         .position(SeriesLabelPosition::LowerRight)
-        .border_style(&BLACK)
+        .border_style(BLACK)
         .background_style(WHITE.filled())
         .label_font(("sans-serif", 14))
         .draw()
@@ -228,7 +242,7 @@ fn draw_compact<'a, DB: DrawingBackend>(
             .iter()
             .chain(benches_native.iter())
             .chain(benches_native_clmul.iter()),
-        &["compact::bench_split_10", "compact::bench_reconstruct_10"],
+        &["compact_split_10", "compact_reconstruct_10"],
     );
 
     let mut chart = ChartBuilder::on(&drawing_area)
@@ -264,21 +278,21 @@ fn draw_compact<'a, DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "compact::bench_split_10",
+        "compact_split_10",
         Some("split_10"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native,
-        "compact::bench_split_10",
+        "compact_split_10",
         Some("split_10 (native)"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native_clmul,
-        "compact::bench_split_10",
+        "compact_split_10",
         Some("split_10 (clmul)"),
         line_styles.next().unwrap(),
     );
@@ -286,21 +300,21 @@ fn draw_compact<'a, DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "compact::bench_reconstruct_10",
+        "compact_reconstruct_10",
         Some("reconstruct_10"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native,
-        "compact::bench_reconstruct_10",
+        "compact_reconstruct_10",
         Some("reconstruct_10 (native)"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native_clmul,
-        "compact::bench_reconstruct_10",
+        "compact_reconstruct_10",
         Some("reconstruct_10 (clmul)"),
         line_styles.next().unwrap(),
     );
@@ -308,7 +322,7 @@ fn draw_compact<'a, DB: DrawingBackend>(
     chart
         .configure_series_labels()
         .position(SeriesLabelPosition::LowerRight)
-        .border_style(&BLACK)
+        .border_style(BLACK)
         .background_style(WHITE.filled())
         .label_font(("sans-serif", 14))
         .draw()
@@ -327,7 +341,7 @@ fn draw_random<'a, DB: DrawingBackend>(
             .iter()
             .chain(benches_native.iter())
             .chain(benches_native_clmul.iter()),
-        &["random::bench_split_10", "random::bench_reconstruct_10"],
+        &["random_split_10", "random_reconstruct_10"],
     );
 
     let mut chart = ChartBuilder::on(&drawing_area)
@@ -363,21 +377,21 @@ fn draw_random<'a, DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "random::bench_split_10",
+        "random_split_10",
         Some("split_10"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native,
-        "random::bench_split_10",
+        "random_split_10",
         Some("split_10 (native)"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native_clmul,
-        "random::bench_split_10",
+        "random_split_10",
         Some("split_10 (clmul)"),
         line_styles.next().unwrap(),
     );
@@ -385,21 +399,21 @@ fn draw_random<'a, DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "random::bench_reconstruct_10",
+        "random_reconstruct_10",
         Some("reconstruct_10"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native,
-        "random::bench_reconstruct_10",
+        "random_reconstruct_10",
         Some("reconstruct_10 (native)"),
         line_styles.next().unwrap(),
     );
     line_bench(
         &mut chart,
         benches_native_clmul,
-        "random::bench_reconstruct_10",
+        "random_reconstruct_10",
         Some("reconstruct_10 (clmul)"),
         line_styles.next().unwrap(),
     );
@@ -407,28 +421,25 @@ fn draw_random<'a, DB: DrawingBackend>(
     chart
         .configure_series_labels()
         .position(SeriesLabelPosition::LowerRight)
-        .border_style(&BLACK)
+        .border_style(BLACK)
         .background_style(WHITE.filled())
         .label_font(("sans-serif", 14))
         .draw()
         .unwrap();
 }
 
-fn draw_bench<DB: DrawingBackend>(
-    title: &str,
+fn draw_serialization<'a, DB: DrawingBackend>(
     drawing_area: DrawingArea<DB, Shift>,
-    line_styles: &[LineStyle],
+    mut line_styles: impl Iterator<Item = &'a LineStyle>,
     benches: &[Bench],
 ) {
     let (min, max) = chart_limits(
         benches.iter(),
         &[
-            "bench_mul",
-            "bench_invert",
-            "compact::bench_split_10",
-            "compact::bench_reconstruct_10",
-            "compact::bench_split_big_all",
-            "compact::bench_reconstruct_big_all",
+            "compact_format_share",
+            "compact_parse_share",
+            "random_format_share",
+            "random_parse_share",
         ],
     );
 
@@ -437,7 +448,7 @@ fn draw_bench<DB: DrawingBackend>(
         .y_label_area_size(60)
         .margin(10)
         .margin_right(40)
-        .caption(title, ("sans-serif", 30))
+        .caption("Share serialization", ("sans-serif", 30))
         .build_cartesian_2d(0..8, (min..max).log_scale())
         .unwrap();
 
@@ -465,14 +476,95 @@ fn draw_bench<DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "bench_mul",
-        Some("mul"),
-        &line_styles[0],
+        "compact_format_share",
+        Some("format (compact)"),
+        line_styles.next().unwrap(),
+    );
+    line_bench(
+        &mut chart,
+        benches,
+        "compact_parse_share",
+        Some("parse (compact)"),
+        line_styles.next().unwrap(),
+    );
+    line_bench(
+        &mut chart,
+        benches,
+        "random_format_share",
+        Some("format (random)"),
+        line_styles.next().unwrap(),
+    );
+    line_bench(
+        &mut chart,
+        benches,
+        "random_parse_share",
+        Some("parse (random)"),
+        line_styles.next().unwrap(),
+    );
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::LowerRight)
+        .border_style(BLACK)
+        .background_style(WHITE.filled())
+        .label_font(("sans-serif", 14))
+        .draw()
+        .unwrap();
+}
+
+fn draw_bench<DB: DrawingBackend>(
+    title: &str,
+    drawing_area: DrawingArea<DB, Shift>,
+    line_styles: &[LineStyle],
+    benches: &[Bench],
+) {
+    let (min, max) = chart_limits(
+        benches.iter(),
+        &[
+            "mul",
+            "invert",
+            "compact_split_10",
+            "compact_reconstruct_10",
+            "compact_split_255",
+            "compact_reconstruct_255",
+        ],
     );
+
+    let mut chart = ChartBuilder::on(&drawing_area)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .margin(10)
+        .margin_right(40)
+        .caption(title, ("sans-serif", 30))
+        .build_cartesian_2d(0..8, (min..max).log_scale())
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .label_style(("sans-serif", 14))
+        .x_desc("Field")
+        .y_desc("Time")
+        .x_label_formatter(&|&v| field_formatter(v).to_owned())
+        .y_label_formatter(&|&v| {
+            if v < 1000 {
+                format!("{} ns", v)
+            } else if v < 1_000_000 {
+                format!("{} µs", v as f64 / 1e3)
+            } else if v < 1_000_000_000 {
+                format!("{} ms", v as f64 / 1e6)
+            } else {
+                format!("{} s", v as f64 / 1e9)
+            }
+        })
+        .draw()
+        .unwrap();
+
+    line_bench(&mut chart, benches, "mul", Some("mul"), &line_styles[0]);
     line_bench(
         &mut chart,
         benches,
-        "bench_invert",
+        "invert",
         Some("invert"),
         &line_styles[3],
     );
@@ -480,14 +572,14 @@ fn draw_bench<DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "compact::bench_split_10",
+        "compact_split_10",
         Some("split_10"),
         &line_styles[1],
     );
     line_bench(
         &mut chart,
         benches,
-        "compact::bench_reconstruct_10",
+        "compact_reconstruct_10",
         Some("reconstruct_10"),
         &line_styles[4],
     );
@@ -495,14 +587,14 @@ fn draw_bench<DB: DrawingBackend>(
     line_bench(
         &mut chart,
         benches,
-        "compact::bench_split_big_all",
+        "compact_split_255",
         Some("split_255"),
         &line_styles[2],
     );
     line_bench(
         &mut chart,
         benches,
-        "compact::bench_reconstruct_big_all",
+        "compact_reconstruct_255",
         Some("reconstruct_255"),
         &line_styles[5],
     );
@@ -510,7 +602,7 @@ fn draw_bench<DB: DrawingBackend>(
     chart
         .configure_series_labels()
         .position(SeriesLabelPosition::LowerRight)
-        .border_style(&BLACK)
+        .border_style(BLACK)
         .background_style(WHITE.filled())
         .label_font(("sans-serif", 14))
         .draw()
@@ -773,33 +865,57 @@ fn field_formatter(value: i32) -> &'static str {
     }
 }
 
-fn parse(input: impl BufRead) -> Vec<Bench> {
-    let re_bench =
-        Regex::new(r"^test ([0-9a-z_]+)::test::([0-9a-z_]+)::([0-9a-z_:]+)\s+\.{3} bench:\s+([0-9,]+) ns/iter \(\+/\- ([0-9,]+)\)$").unwrap();
-
+/// Reads the benchmarks of a criterion baseline, from the estimates written to
+/// `<criterion_dir>/<test>/<field>/<baseline>/estimates.json` by `cargo bench -p horcrux-bench`.
+fn parse(criterion_dir: &Path, baseline: &str) -> Vec<Bench> {
     let mut benches = Vec::new();
-    for line in input.lines() {
-        let line = line.unwrap();
-        if let Some(caps) = re_bench.captures(&line) {
-            println!("Line matches bench: {}", line);
-            let field = caps[2].to_owned();
-            let test = caps[3].to_owned();
-
-            let mut avg = caps[4].to_owned();
-            avg.retain(|c| c != ',');
+    for test in read_dir_names(criterion_dir) {
+        for field in read_dir_names(&criterion_dir.join(&test)) {
+            let path = criterion_dir
+                .join(&test)
+                .join(&field)
+                .join(baseline)
+                .join("estimates.json");
+            let estimates: Value = match File::open(&path) {
+                Ok(file) => serde_json::from_reader(BufReader::new(file)).unwrap(),
+                Err(_) => continue,
+            };
+            println!("Found estimates: {}", path.display());
+            let avg = estimates["mean"]["point_estimate"]
+                .as_f64()
+                .expect("Invalid estimates");
 
             benches.push(Bench {
                 field,
-                test,
-                avg: avg.parse().unwrap(),
+                test: test.clone(),
+                avg: avg.round() as u64,
             });
         }
     }
+    if benches.is_empty() {
+        panic!(
+            "No benchmark found for the baseline {:?} in {}",
+            baseline,
+            criterion_dir.display()
+        );
+    }
 
     benches.sort_by_key(|b| field_index(b.field.as_ref()));
     benches
 }
 
+/// Returns the names of the sub-directories of a directory, in sorted order.
+fn read_dir_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| entry.file_type().unwrap().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
 #[derive(Debug)]
 struct Bench {
     field: String,
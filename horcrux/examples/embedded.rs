@@ -0,0 +1,20 @@
+//! Splits and reconstructs a key with the embedded profile, as firmware would. The tests of the
+//! `embedded` module build this example to track the footprint of the profile.
+
+use horcrux::embedded::{reconstruct, split};
+use horcrux::gf2n::{GF128, GF8};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+fn main() {
+    // The seed depends on the arguments so that nothing is evaluated at compile time.
+    let mut rng = ChaCha20Rng::seed_from_u64(std::env::args().count() as u64);
+
+    let key = GF128::from(42);
+    let shares = split::<_, _, 2, 3>(&key, &mut rng).unwrap();
+    assert_eq!(reconstruct(&[shares[2], shares[0]]), Ok(key));
+
+    let byte = GF8::from(42);
+    let shares = split::<_, _, 2, 3>(&byte, &mut rng).unwrap();
+    assert_eq!(reconstruct(&[shares[1], shares[0]]), Ok(byte));
+}
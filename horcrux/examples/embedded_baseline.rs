@@ -0,0 +1,10 @@
+//! Same setup as the `embedded` example, without horcrux, as the baseline to measure the
+//! footprint of the embedded profile against.
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+fn main() {
+    let mut rng = ChaCha20Rng::seed_from_u64(std::env::args().count() as u64);
+    assert_ne!(rng.next_u64(), 0);
+}
@@ -0,0 +1,777 @@
+//! Access policies over named custodians, such as `(alice and bob) or (any 3 of c1..c5)`, compiled
+//! into nested threshold sharings.
+//!
+//! A policy is a tree whose leaves are custodians and whose inner nodes are thresholds: `any k of
+//! (...)` needs k of its children, `and` needs all of them and `or` needs any one of them. The
+//! secret is split with compact Shamir shares among the children of the root, each child's share
+//! being in turn split among its own children, down to the custodians. A custodian who appears
+//! several times in the policy receives one share per appearance.
+//!
+//! Each share is identified by the path from the root to its leaf, i.e. the x coordinates of the
+//! intermediate shares, e.g. `alice:1.1|<hex>` for the first child of the first child of the root.
+//! Reconstruction evaluates the policy on the shares that were supplied, and reports the
+//! custodians whose shares were used.
+
+use crate::field::Field;
+use crate::shamir::{CompactShamir, GetX, Shamir, ShamirError, Share};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand::{CryptoRng, Rng};
+
+/// Maximal nesting depth of a policy, to bound the recursion on untrusted expressions.
+const MAX_DEPTH: usize = 32;
+
+/// Access policy over named custodians.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessPolicy {
+    /// A custodian, identified by their name.
+    Custodian(String),
+    /// A threshold of sub-policies: `Threshold(k, children)` is satisfied when at least k of the
+    /// children are.
+    Threshold(usize, Vec<AccessPolicy>),
+}
+
+/// Error returned when parsing an invalid policy expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyError(pub String);
+
+impl Display for PolicyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "invalid policy: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PolicyError {}
+
+/// Share of a custodian, at the leaf given by its path in the policy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessShare<F> {
+    /// Name of the custodian.
+    pub custodian: String,
+    /// Indices of the children (starting at 1) from the root of the policy to the leaf.
+    pub path: Vec<u8>,
+    /// Value of the share.
+    pub value: F,
+}
+
+/// Formats the share as `<custodian>:<path>|<value>`, with the indices of the path separated by
+/// dots, e.g. `alice:1.2|<hex>`.
+impl<F: Display> Display for AccessShare<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}:", self.custodian)?;
+        for (i, x) in self.path.iter().enumerate() {
+            if i != 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", x)?;
+        }
+        write!(f, "|{}", self.value)
+    }
+}
+
+/// Secret reconstructed from the shares of custodians satisfying a policy.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reconstruction<F> {
+    /// The reconstructed secret.
+    pub secret: F,
+    /// Custodians whose shares were used, in sorted order. Shares of the other custodians weren't
+    /// needed to satisfy the policy.
+    pub custodians: Vec<String>,
+}
+
+impl AccessPolicy {
+    /// Returns the names of the custodians of the policy, in sorted order and without duplicates.
+    pub fn custodians(&self) -> Vec<&str> {
+        let mut custodians = Vec::new();
+        self.collect_custodians(&mut custodians);
+        custodians.sort_unstable();
+        custodians.dedup();
+        custodians
+    }
+
+    fn collect_custodians<'a>(&'a self, custodians: &mut Vec<&'a str>) {
+        match self {
+            AccessPolicy::Custodian(name) => custodians.push(name),
+            AccessPolicy::Threshold(_, children) => {
+                children
+                    .iter()
+                    .for_each(|child| child.collect_custodians(custodians));
+            }
+        }
+    }
+
+    /// Returns whether the given custodians together satisfy the policy.
+    pub fn is_satisfied_by(&self, custodians: &[&str]) -> bool {
+        match self {
+            AccessPolicy::Custodian(name) => custodians.contains(&name.as_str()),
+            AccessPolicy::Threshold(k, children) => {
+                children
+                    .iter()
+                    .filter(|child| child.is_satisfied_by(custodians))
+                    .count()
+                    >= *k
+            }
+        }
+    }
+
+    /// Returns the custodian at the leaf of the given path, if any.
+    fn custodian_at(&self, path: &[u8]) -> Option<&str> {
+        match (self, path.split_first()) {
+            (AccessPolicy::Custodian(name), None) => Some(name),
+            (AccessPolicy::Threshold(_, children), Some((&x, rest))) => children
+                .get((x as usize).checked_sub(1)?)?
+                .custodian_at(rest),
+            _ => None,
+        }
+    }
+
+    /// Splits a secret according to the policy. Returns the shares of the custodians, in the
+    /// order of the leaves of the policy.
+    #[cfg(feature = "std")]
+    pub fn split<F: Field + Debug + Display>(
+        &self,
+        secret: &F,
+    ) -> Result<Vec<AccessShare<F>>, ShamirError> {
+        self.split_with_rng(secret, &mut thread_rng())
+    }
+
+    /// Splits a secret according to the policy like `split()`, but drawing all the randomness
+    /// from the given random number generator.
+    pub fn split_with_rng<F: Field + Debug + Display, R: Rng + CryptoRng + ?Sized>(
+        &self,
+        secret: &F,
+        rng: &mut R,
+    ) -> Result<Vec<AccessShare<F>>, ShamirError> {
+        let mut shares = Vec::new();
+        self.split_node(*secret, &mut Vec::new(), &mut shares, rng)?;
+        Ok(shares)
+    }
+
+    fn split_node<F: Field + Debug + Display, R: Rng + CryptoRng + ?Sized>(
+        &self,
+        secret: F,
+        path: &mut Vec<u8>,
+        shares: &mut Vec<AccessShare<F>>,
+        rng: &mut R,
+    ) -> Result<(), ShamirError> {
+        match self {
+            AccessPolicy::Custodian(name) => shares.push(AccessShare {
+                custodian: name.clone(),
+                path: path.clone(),
+                value: secret,
+            }),
+            AccessPolicy::Threshold(k, children) => {
+                let split = CompactShamir::split_with_rng(&secret, *k, children.len(), rng)?;
+                for (child, share) in children.iter().zip(&split) {
+                    path.push(share.getx());
+                    child.split_node(*share.y(), path, shares, rng)?;
+                    path.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reconstructs a secret from shares of custodians that satisfy the policy. Returns an error if
+    /// the custodians don't satisfy the policy, if a share doesn't belong to a leaf of the policy
+    /// (e.g. a share of another split), or if several shares are at the same leaf.
+    pub fn reconstruct<F: Field + Debug + Display>(
+        &self,
+        shares: &[AccessShare<F>],
+    ) -> Result<Reconstruction<F>, ShamirError> {
+        for (i, share) in shares.iter().enumerate() {
+            if self.custodian_at(&share.path) != Some(share.custodian.as_str()) {
+                return Err(ShamirError::InvalidX);
+            }
+            if shares[..i].iter().any(|s| s.path == share.path) {
+                return Err(ShamirError::DuplicateX);
+            }
+        }
+
+        let mut used = Vec::new();
+        let secret = self
+            .reconstruct_node(&mut Vec::new(), shares, &mut used)?
+            .ok_or(ShamirError::NotEnoughShares)?;
+        let mut custodians: Vec<String> = used
+            .into_iter()
+            .map(|i| shares[i].custodian.clone())
+            .collect();
+        custodians.sort_unstable();
+        custodians.dedup();
+        Ok(Reconstruction { secret, custodians })
+    }
+
+    /// Reconstructs the value of the node at the given path, or returns `None` if the shares don't
+    /// satisfy this node. The indices of the shares that were used are appended to `used`.
+    fn reconstruct_node<F: Field + Debug + Display>(
+        &self,
+        path: &mut Vec<u8>,
+        shares: &[AccessShare<F>],
+        used: &mut Vec<usize>,
+    ) -> Result<Option<F>, ShamirError> {
+        match self {
+            AccessPolicy::Custodian(_) => {
+                Ok(shares.iter().position(|s| s.path == *path).map(|i| {
+                    used.push(i);
+                    shares[i].value
+                }))
+            }
+            AccessPolicy::Threshold(k, children) => {
+                let start = used.len();
+                let mut points = Vec::with_capacity(*k);
+                for (x, child) in (1..=children.len() as u8).zip(children) {
+                    if points.len() == *k {
+                        break;
+                    }
+                    path.push(x);
+                    let value = child.reconstruct_node(path, shares, used)?;
+                    path.pop();
+                    if let Some(y) = value {
+                        points.push(Share::new_unchecked(x, y));
+                    }
+                }
+                if points.len() < *k {
+                    used.truncate(start);
+                    return Ok(None);
+                }
+                CompactShamir::reconstruct(&points, *k).map(Some)
+            }
+        }
+    }
+
+    /// Parses a share of a custodian from a string. Returns an error if the parsing fails.
+    #[cfg(feature = "parse")]
+    pub fn parse_share<F: Field>(s: &str) -> Result<AccessShare<F>, ShamirError> {
+        let (custodian, rest) = s.split_once(':').ok_or(ShamirError::Parse)?;
+        let (path, value) = rest.split_once('|').ok_or(ShamirError::Parse)?;
+        if !is_name(custodian) {
+            return Err(ShamirError::Parse);
+        }
+        let path = match path {
+            "" => Vec::new(),
+            _ => path
+                .split('.')
+                .map(crate::shamir::parse_decimal::<u8>)
+                .collect::<Result<Vec<u8>, ShamirError>>()?,
+        };
+        if path.contains(&0) {
+            return Err(ShamirError::InvalidX);
+        }
+        Ok(AccessShare {
+            custodian: custodian.to_string(),
+            path,
+            value: crate::shamir::parse_field(value)?,
+        })
+    }
+}
+
+/// Formats the policy as an expression that parses back to the same policy, e.g.
+/// `(alice and bob) or (any 2 of (carol, dave, erin))`.
+impl Display for AccessPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            AccessPolicy::Custodian(name) => f.write_str(name),
+            AccessPolicy::Threshold(k, children) => {
+                let (open, separator, close) = match (*k, children.len()) {
+                    (k, n) if n >= 2 && k == n => ("", " and ", ""),
+                    (1, n) if n >= 2 => ("", " or ", ""),
+                    (k, _) => {
+                        write!(f, "any {} of ", k)?;
+                        ("(", ", ", ")")
+                    }
+                };
+                f.write_str(open)?;
+                for (i, child) in children.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(separator)?;
+                    }
+                    // Lists already delimit their items, but operands of `and` and `or` need
+                    // parentheses.
+                    match child {
+                        AccessPolicy::Threshold(..) if open.is_empty() => write!(f, "({})", child)?,
+                        _ => write!(f, "{}", child)?,
+                    }
+                }
+                f.write_str(close)
+            }
+        }
+    }
+}
+
+/// Parses a policy expression. Custodians are named with letters, digits, `-` and `_`, and can be
+/// combined with `and`, `or` (which has a lower precedence than `and`), parentheses, and
+/// thresholds such as `any 2 of (alice, bob, carol)`. The list of a threshold can contain ranges
+/// of custodians such as `c1..c5` or `C..G`, and can be a single range without parentheses, e.g.
+/// `any 3 of c1..c5`.
+impl FromStr for AccessPolicy {
+    type Err = PolicyError;
+
+    fn from_str(s: &str) -> Result<Self, PolicyError> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            depth: 0,
+        };
+        let policy = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(policy),
+            Some(token) => Err(PolicyError(alloc::format!("unexpected {}", token))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Comma,
+    Range,
+    Word(&'a str),
+}
+
+impl Display for Token<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Token::Open => f.write_str("'('"),
+            Token::Close => f.write_str("')'"),
+            Token::Comma => f.write_str("','"),
+            Token::Range => f.write_str("'..'"),
+            Token::Word(word) => write!(f, "'{}'", word),
+        }
+    }
+}
+
+const KEYWORDS: [&str; 4] = ["and", "or", "any", "of"];
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+fn is_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(is_name_char) && !KEYWORDS.contains(&s)
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token<'_>>, PolicyError> {
+    let mut tokens = Vec::new();
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = match c {
+            _ if c.is_whitespace() => (None, c.len_utf8()),
+            '(' => (Some(Token::Open), 1),
+            ')' => (Some(Token::Close), 1),
+            ',' => (Some(Token::Comma), 1),
+            '.' if rest.starts_with("..") => (Some(Token::Range), 2),
+            _ if is_name_char(c) => {
+                let len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+                (Some(Token::Word(&rest[..len])), len)
+            }
+            _ => return Err(PolicyError(alloc::format!("unexpected character {:?}", c))),
+        };
+        tokens.extend(token);
+        rest = &rest[len..];
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token<'a>],
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token<'a>> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<&'a Token<'a>, PolicyError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or_else(|| PolicyError("unexpected end of the expression".to_string()))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), PolicyError> {
+        match self.next()? {
+            token if *token == expected => Ok(()),
+            token => Err(PolicyError(alloc::format!(
+                "expected {}, found {}",
+                expected,
+                token
+            ))),
+        }
+    }
+
+    /// Parses operands separated by the given keyword, which are combined with a threshold given
+    /// the number of operands.
+    fn parse_operands(
+        &mut self,
+        keyword: &str,
+        operand: fn(&mut Self) -> Result<AccessPolicy, PolicyError>,
+        threshold: fn(usize) -> usize,
+    ) -> Result<AccessPolicy, PolicyError> {
+        let mut operands = alloc::vec![operand(self)?];
+        while self.peek() == Some(&Token::Word(keyword)) {
+            self.position += 1;
+            operands.push(operand(self)?);
+        }
+        match operands.len() {
+            1 => Ok(operands.pop().unwrap()),
+            n => threshold_policy(threshold(n), operands),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<AccessPolicy, PolicyError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(PolicyError(
+                "the expression is nested too deeply".to_string(),
+            ));
+        }
+        let policy = self.parse_operands("or", Self::parse_and, |_| 1)?;
+        self.depth -= 1;
+        Ok(policy)
+    }
+
+    fn parse_and(&mut self) -> Result<AccessPolicy, PolicyError> {
+        self.parse_operands("and", Self::parse_atom, |n| n)
+    }
+
+    fn parse_atom(&mut self) -> Result<AccessPolicy, PolicyError> {
+        match self.next()? {
+            Token::Open => {
+                let policy = self.parse_or()?;
+                self.expect(Token::Close)?;
+                Ok(policy)
+            }
+            Token::Word("any") => {
+                let k = match self.next()? {
+                    Token::Word(k) => k.parse::<usize>().ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| PolicyError("expected a threshold after 'any'".to_string()))?;
+                self.expect(Token::Word("of"))?;
+                let children = match self.peek() {
+                    Some(Token::Open) => {
+                        self.position += 1;
+                        let mut children = self.parse_item()?;
+                        while self.peek() == Some(&Token::Comma) {
+                            self.position += 1;
+                            children.extend(self.parse_item()?);
+                        }
+                        self.expect(Token::Close)?;
+                        children
+                    }
+                    _ => match self.parse_item()? {
+                        children if children.len() > 1 => children,
+                        _ => {
+                            return Err(PolicyError(
+                                "expected a list or a range after 'of'".to_string(),
+                            ))
+                        }
+                    },
+                };
+                threshold_policy(k, children)
+            }
+            Token::Word(name) if is_name(name) => {
+                if self.peek() == Some(&Token::Range) {
+                    return Err(PolicyError(alloc::format!(
+                        "the range starting at {} must be in the list of a threshold",
+                        name
+                    )));
+                }
+                Ok(AccessPolicy::Custodian(name.to_string()))
+            }
+            token => Err(PolicyError(alloc::format!("unexpected {}", token))),
+        }
+    }
+
+    /// Parses an item of the list of a threshold: a range of custodians, or a policy.
+    fn parse_item(&mut self) -> Result<Vec<AccessPolicy>, PolicyError> {
+        match (self.peek(), self.tokens.get(self.position + 1)) {
+            (Some(Token::Word(start)), Some(Token::Range)) => {
+                self.position += 2;
+                match self.next()? {
+                    Token::Word(end) => expand_range(start, end),
+                    token => Err(PolicyError(alloc::format!(
+                        "expected the end of the range, found {}",
+                        token
+                    ))),
+                }
+            }
+            _ => Ok(alloc::vec![self.parse_or()?]),
+        }
+    }
+}
+
+/// Returns a threshold policy, checking that `1 <= k <= n <= 255`.
+fn threshold_policy(k: usize, children: Vec<AccessPolicy>) -> Result<AccessPolicy, PolicyError> {
+    if k == 0 || k > children.len() || children.len() > 255 {
+        return Err(PolicyError(alloc::format!(
+            "invalid threshold {} of {}",
+            k,
+            children.len()
+        )));
+    }
+    Ok(AccessPolicy::Threshold(k, children))
+}
+
+/// Expands a range of custodians, either numbered with the same prefix (e.g. `c1..c5` or
+/// `c08..c12`) or single letters (e.g. `C..G`).
+fn expand_range(start: &str, end: &str) -> Result<Vec<AccessPolicy>, PolicyError> {
+    let invalid = || PolicyError(alloc::format!("invalid range {}..{}", start, end));
+    if !is_name(start) || !is_name(end) {
+        return Err(invalid());
+    }
+    // Splits a name into its prefix and its trailing digits.
+    fn split_number(s: &str) -> (&str, &str) {
+        s.split_at(s.trim_end_matches(|c: char| c.is_ascii_digit()).len())
+    }
+
+    let names: Vec<String> = match (split_number(start), split_number(end)) {
+        ((prefix, first), (end_prefix, last))
+            if !first.is_empty() && !last.is_empty() && prefix == end_prefix =>
+        {
+            let first_value = first.parse::<usize>().map_err(|_| invalid())?;
+            let last_value = last.parse::<usize>().map_err(|_| invalid())?;
+            if last_value < first_value || last_value - first_value >= 255 {
+                return Err(invalid());
+            }
+            // Numbers are padded to the width of the start of the range, e.g. c08..c12.
+            let width = first.len();
+            (first_value..=last_value)
+                .map(|i| alloc::format!("{}{:0width$}", prefix, i, width = width))
+                .collect()
+        }
+        _ => match (start.as_bytes(), end.as_bytes()) {
+            ([first], [last])
+                if first.is_ascii_alphabetic()
+                    && last.is_ascii_alphabetic()
+                    && first.is_ascii_uppercase() == last.is_ascii_uppercase()
+                    && first <= last =>
+            {
+                (*first..=*last)
+                    .map(|c| char::from(c).to_string())
+                    .collect()
+            }
+            _ => return Err(invalid()),
+        },
+    };
+    names
+        .into_iter()
+        .map(|name| match is_name(&name) {
+            true => Ok(AccessPolicy::Custodian(name)),
+            false => Err(invalid()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gf2n::GF256;
+
+    fn custodian(name: &str) -> AccessPolicy {
+        AccessPolicy::Custodian(name.to_string())
+    }
+
+    fn parse(s: &str) -> AccessPolicy {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn can_parse_policies() {
+        assert_eq!(
+            parse("(A and B) or (any 3 of C..G)"),
+            AccessPolicy::Threshold(
+                1,
+                alloc::vec![
+                    AccessPolicy::Threshold(2, alloc::vec![custodian("A"), custodian("B")]),
+                    AccessPolicy::Threshold(
+                        3,
+                        ["C", "D", "E", "F", "G"]
+                            .iter()
+                            .map(|c| custodian(c))
+                            .collect()
+                    ),
+                ]
+            )
+        );
+        // `and` has a higher precedence than `or`.
+        assert_eq!(parse("A and B or C"), parse("(A and B) or C"));
+        assert_eq!(parse("A or B and C"), parse("A or (B and C)"));
+        assert_eq!(
+            parse("any 2 of (alice, bob and carol, c08..c10)"),
+            AccessPolicy::Threshold(
+                2,
+                alloc::vec![
+                    custodian("alice"),
+                    AccessPolicy::Threshold(2, alloc::vec![custodian("bob"), custodian("carol")]),
+                    custodian("c08"),
+                    custodian("c09"),
+                    custodian("c10"),
+                ]
+            )
+        );
+        assert_eq!(parse("  alice "), custodian("alice"));
+    }
+
+    #[test]
+    fn policies_roundtrip() {
+        for s in [
+            "A and B and C",
+            "(A and B) or (any 3 of (C, D, E, F, G))",
+            "any 2 of (alice, bob and carol, (dave or erin) and frank)",
+            "any 1 of (A)",
+            "A",
+        ] {
+            let policy = parse(s);
+            assert_eq!(policy.to_string(), s);
+            assert_eq!(parse(&policy.to_string()), policy);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_policies() {
+        for s in [
+            "",
+            "A and",
+            "(A or B",
+            "A B",
+            "A, B",
+            "and",
+            "any 0 of (A, B)",
+            "any 3 of (A, B)",
+            "any of (A, B)",
+            "any 2 of A",
+            "A..C",
+            "any 2 of c5..c1",
+            "any 2 of a..C",
+            "any 2 of A..",
+            "any 2 of ab..cd",
+            "A & B",
+            "any 2 of x1..x300",
+        ] {
+            assert!(s.parse::<AccessPolicy>().is_err(), "{}", s);
+        }
+        let nested = alloc::format!("{}A{}", "(".repeat(100), ")".repeat(100));
+        assert!(nested.parse::<AccessPolicy>().is_err());
+    }
+
+    #[test]
+    fn can_evaluate_policies() {
+        let policy = parse("(A and B) or (any 3 of C..G)");
+        assert_eq!(policy.custodians(), ["A", "B", "C", "D", "E", "F", "G"]);
+        assert!(policy.is_satisfied_by(&["A", "B"]));
+        assert!(policy.is_satisfied_by(&["C", "E", "G"]));
+        assert!(policy.is_satisfied_by(&["A", "D", "E", "F"]));
+        assert!(!policy.is_satisfied_by(&["A", "C", "D"]));
+        assert!(!policy.is_satisfied_by(&[]));
+    }
+
+    #[test]
+    fn can_reconstruct() {
+        let secret = GF256::uniform(&mut thread_rng());
+        let policy = parse("(A and B) or (any 3 of C..G) or (A and any 2 of (B, C, D))");
+        let shares = policy.split(&secret).unwrap();
+        // A, B, C and D appear twice each.
+        assert_eq!(shares.len(), 11);
+
+        let shares_of = |custodians: &[&str]| -> Vec<AccessShare<GF256>> {
+            shares
+                .iter()
+                .filter(|s| custodians.contains(&s.custodian.as_str()))
+                .cloned()
+                .collect()
+        };
+        for custodians in [
+            &["A", "B"][..],
+            &["C", "D", "E"],
+            &["A", "C", "D"],
+            &["D", "E", "F", "G"],
+            &["A", "B", "C", "D", "E", "F", "G"],
+        ] {
+            let result = policy.reconstruct(&shares_of(custodians)).unwrap();
+            assert_eq!(result.secret, secret);
+            assert!(policy.is_satisfied_by(
+                &result
+                    .custodians
+                    .iter()
+                    .map(|c| c.as_str())
+                    .collect::<Vec<_>>()
+            ));
+        }
+        // The first satisfied branch is used.
+        assert_eq!(
+            policy
+                .reconstruct(&shares_of(&["A", "B", "C", "D", "E"]))
+                .unwrap()
+                .custodians,
+            ["A", "B"]
+        );
+
+        for custodians in [&["A"][..], &["B", "C"], &["A", "E", "F"], &[]] {
+            assert_eq!(
+                policy.reconstruct(&shares_of(custodians)),
+                Err(ShamirError::NotEnoughShares)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_shares() {
+        let policy = parse("A and any 2 of (B, C, D)");
+        let shares = policy.split(&GF256::from(42)).unwrap();
+        assert_eq!(
+            policy.reconstruct(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]),
+            Err(ShamirError::DuplicateX)
+        );
+
+        let mut renamed = shares[1].clone();
+        renamed.custodian = "C".to_string();
+        assert_eq!(policy.reconstruct(&[renamed]), Err(ShamirError::InvalidX));
+        let mut moved = shares[1].clone();
+        moved.path.push(1);
+        assert_eq!(policy.reconstruct(&[moved]), Err(ShamirError::InvalidX));
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn can_parse_shares() {
+        let policy = parse("A and any 2 of (B, C, D)");
+        let shares = policy.split(&GF256::from(42)).unwrap();
+        for share in &shares {
+            let text = share.to_string();
+            assert_eq!(AccessPolicy::parse_share(&text), Ok(share.clone()));
+        }
+        assert!(shares[1].to_string().starts_with("B:2.1|"));
+
+        for s in [
+            "A|00",
+            "A:1",
+            "A:0|00",
+            "A:1..2|00",
+            "and:1|00",
+            "A:1|zz",
+            "A:01|00",
+        ] {
+            assert!(AccessPolicy::parse_share::<GF256>(s).is_err(), "{}", s);
+        }
+        assert_eq!(
+            AccessPolicy::parse_share::<GF256>(&alloc::format!("A:|{}", GF256::from(1))),
+            Ok(AccessShare {
+                custodian: "A".to_string(),
+                path: Vec::new(),
+                value: GF256::from(1),
+            })
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Report of the implementations of field arithmetic that are live in this build, on this CPU.
+//!
+//! Every accelerated implementation has a portable fallback, selected automatically, so that the
+//! library builds for any target with the default features:
+//! - carry-less multiplication (`clmul` feature, enabled by default) is only compiled in when the
+//!   target enables the required CPU features, i.e. `sse2` and `pclmulqdq` on x86_64 or `neon` and
+//!   `aes` on aarch64 (e.g. with `-C target-cpu=native`), and is disabled at runtime if it
+//!   disagrees with the portable implementation on this CPU,
+//! - GF(2^8) uses a constant-time implementation, unless the `gf8-tables` feature opts into
+//!   logarithm tables,
+//! - GF(2^16) uses the portable implementation, unless the `gf16-tables` feature opts into
+//!   logarithm tables.
+//!
+//! Prime fields always use the portable Montgomery multiplication.
+
+use core::fmt::Display;
+
+/// Implementation of the multiplication in a family of fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// Portable bitwise multiplication of binary polynomials.
+    Portable,
+    /// Carry-less multiplication with the PCLMULQDQ instruction on x86_64.
+    ClmulX86,
+    /// Carry-less multiplication with the PMULL instruction on aarch64.
+    ClmulAarch64,
+    /// Portable constant-time multiplication in GF(2^8).
+    Gf8ConstantTime,
+    /// Multiplication in GF(2^8) with logarithm tables (`gf8-tables` feature).
+    Gf8Tables,
+    /// Multiplication in GF(2^16) with logarithm tables (`gf16-tables` feature).
+    Gf16Tables,
+    /// Portable Montgomery multiplication in prime fields.
+    Montgomery,
+}
+
+impl Backend {
+    /// Returns a short name of the backend.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Portable => "portable",
+            Backend::ClmulX86 => "clmul-x86_64",
+            Backend::ClmulAarch64 => "clmul-aarch64",
+            Backend::Gf8ConstantTime => "gf8-constant-time",
+            Backend::Gf8Tables => "gf8-tables",
+            Backend::Gf16Tables => "gf16-tables",
+            Backend::Montgomery => "montgomery",
+        }
+    }
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Backends that are live for each family of fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Backends {
+    /// Binary fields with 64-bit words, e.g. `GF64`, `GF128` and `GF256`.
+    pub gf2n_u64: Backend,
+    /// Binary fields with other word sizes, e.g. `GF128u32`.
+    pub gf2n_other: Backend,
+    /// GF(2^8).
+    pub gf8: Backend,
+    /// GF(2^16).
+    pub gf16: Backend,
+    /// Prime fields, i.e. the scalar fields of elliptic curves.
+    pub gfp: Backend,
+}
+
+/// Formats the backends as one `<fields>: <backend>` line per family of fields.
+impl Display for Backends {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        writeln!(f, "GF(2^n) with 64-bit words: {}", self.gf2n_u64)?;
+        writeln!(f, "GF(2^n) with other words: {}", self.gf2n_other)?;
+        writeln!(f, "GF(2^8): {}", self.gf8)?;
+        writeln!(f, "GF(2^16): {}", self.gf16)?;
+        writeln!(f, "Prime fields: {}", self.gfp)
+    }
+}
+
+/// Returns the backends that are live in this build, on this CPU. The first call may run the
+/// runtime self-check of the carry-less multiplication.
+pub fn active() -> Backends {
+    Backends {
+        gf2n_u64: crate::gf2n::u64_backend(),
+        gf2n_other: Backend::Portable,
+        gf8: match cfg!(feature = "gf8-tables") {
+            true => Backend::Gf8Tables,
+            false => Backend::Gf8ConstantTime,
+        },
+        gf16: match cfg!(feature = "gf16-tables") {
+            true => Backend::Gf16Tables,
+            false => Backend::Portable,
+        },
+        gfp: Backend::Montgomery,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn active_matches_build() {
+        let backends = active();
+        let clmul = cfg!(all(
+            feature = "clmul",
+            any(
+                all(
+                    target_arch = "x86_64",
+                    target_feature = "sse2",
+                    target_feature = "pclmulqdq"
+                ),
+                all(
+                    target_arch = "aarch64",
+                    target_feature = "neon",
+                    target_feature = "aes"
+                )
+            )
+        ));
+        // The self-check passes on any CPU that the tests run on.
+        assert_eq!(backends.gf2n_u64 != Backend::Portable, clmul);
+        assert_eq!(backends.gf2n_other, Backend::Portable);
+        assert_eq!(
+            backends.gf8 == Backend::Gf8Tables,
+            cfg!(feature = "gf8-tables")
+        );
+        assert_eq!(
+            backends.gf16 == Backend::Gf16Tables,
+            cfg!(feature = "gf16-tables")
+        );
+        assert_eq!(backends.gfp, Backend::Montgomery);
+    }
+
+    #[test]
+    fn can_display() {
+        let text = active().to_string();
+        assert_eq!(text.lines().count(), 5);
+        assert!(text.contains("Prime fields: montgomery"));
+    }
+}
@@ -0,0 +1,262 @@
+//! Splitting of many small secrets at once, e.g. thousands of per-customer data encryption keys.
+//!
+//! Splitting such secrets one by one is dominated by the overhead of each call: validating the
+//! parameters, fetching the thread-local RNG, allocating the polynomial and computing the powers of
+//! the x coordinates again for every secret. Here, all the secrets are split with the same
+//! parameters, at the same x coordinates 1, 2, ..., n as `CompactShamir`, so that:
+//! - the random coefficients of all the polynomials are drawn in a single pass from one RNG,
+//! - the powers of each x coordinate are computed once for the whole batch,
+//! - the polynomials are evaluated coefficient by coefficient over all the secrets, in simple loops
+//!   that the compiler can vectorize.
+//!
+//! The shares are the same as those of `CompactShamir`, and can be reconstructed individually.
+
+use crate::field::Field;
+use crate::polynomial::{barycentric_weights, lagrange_coefficients};
+use crate::secret::Secret;
+use crate::shamir::{check_split_parameters, Coefficients, GetX, ShamirError, Share};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand::{CryptoRng, Rng};
+#[cfg(feature = "std")]
+use rand_chacha::rand_core::SeedableRng;
+#[cfg(feature = "std")]
+use rand_chacha::ChaCha20Rng;
+
+/// Splits each of the given secrets into n shares, with k of them being sufficient to reconstruct
+/// it. Returns the shares of each custodian, i.e. one vector per x coordinate (from 1 to n)
+/// containing the shares of all the secrets in order, or an error if the parameters are out of
+/// range.
+///
+/// The randomness is drawn from a ChaCha20 stream seeded once from the thread-local RNG.
+#[cfg(feature = "std")]
+pub fn split<F: Field>(
+    secrets: &[F],
+    k: usize,
+    n: usize,
+) -> Result<Vec<Vec<Share<u8, F>>>, ShamirError> {
+    let mut rng = ChaCha20Rng::from_rng(thread_rng()).unwrap();
+    split_with_rng(secrets, k, n, &mut rng)
+}
+
+/// Splits each of the given secrets like `split()`, but drawing all the randomness from the given
+/// random number generator.
+pub fn split_with_rng<F: Field, R: Rng + CryptoRng + ?Sized>(
+    secrets: &[F],
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<Vec<Share<u8, F>>>, ShamirError> {
+    check_split_parameters(k, n)?;
+
+    // The i-th row contains the coefficients of degree i + 1 of all the polynomials.
+    let m = secrets.len();
+    let coefficients: Vec<F> = (0..(k - 1) * m).map(|_| F::uniform(rng)).collect();
+    let coefficients = Coefficients::from(coefficients);
+
+    let mut shares = Vec::with_capacity(n);
+    let mut ys = Coefficients::from(vec![F::ZERO; m]);
+    for x in 1..=n as u8 {
+        ys.copy_from_slice(secrets);
+        let mut xi = F::ONE;
+        for row in coefficients.chunks_exact(m.max(1)) {
+            xi = xi * &F::from(x);
+            for (y, c) in ys.iter_mut().zip(row) {
+                *y += &(*c * &xi);
+            }
+        }
+        shares.push(
+            ys.iter()
+                .map(|&y| Share::<u8, F>::new(x, y).unwrap())
+                .collect(),
+        );
+    }
+    Ok(shares)
+}
+
+/// Reconstructs a batch of secrets from the shares of at least k custodians (as returned by
+/// `split()`), given the threshold k. Only the first k custodians are used. Returns the secrets in
+/// order, or an error if there are fewer than k custodians, if two custodians have the same x
+/// coordinate, or if the shares of some custodians (whose indices are returned) don't have the
+/// same length as the others or aren't all at the same x coordinate.
+pub fn reconstruct<F: Field>(
+    shares: &[Vec<Share<u8, F>>],
+    k: usize,
+) -> Result<Secret<Vec<F>>, ShamirError> {
+    if k == 0 || k > 255 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    if shares.len() < k {
+        return Err(ShamirError::NotEnoughShares);
+    }
+    let shares = &shares[..k];
+    let m = shares[0].len();
+    let inconsistent: Vec<usize> = shares
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.len() != m || s.iter().any(|t| t.getx() != s[0].getx()))
+        .map(|(i, _)| i)
+        .collect();
+    if !inconsistent.is_empty() {
+        return Err(ShamirError::InconsistentShares(inconsistent));
+    }
+    if m == 0 {
+        return Ok(Secret::new(Vec::new()));
+    }
+
+    // The Lagrange coefficients at 0 are the same for all the secrets.
+    let xs: Vec<u8> = shares.iter().map(|s| s[0].getx()).collect();
+    for (i, x) in xs.iter().enumerate() {
+        if xs[..i].contains(x) {
+            return Err(ShamirError::DuplicateX);
+        }
+    }
+    let xs: Vec<F> = xs.iter().map(|&x| F::from(x)).collect();
+    let lagrange = lagrange_coefficients(&xs, &barycentric_weights(&xs), &F::ZERO);
+
+    let mut secrets = Secret::new(vec![F::ZERO; m]);
+    for (custodian, l) in shares.iter().zip(&lagrange) {
+        for (secret, share) in secrets.expose_secret_mut().iter_mut().zip(custodian) {
+            *secret += &(*share.y() * l);
+        }
+    }
+    Ok(secrets)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{reconstruct, split};
+    use crate::field::Field;
+    use crate::gf2n::{GF128, GF64};
+    use crate::gfp::Ed25519Scalar;
+    use crate::shamir::{CompactShamir, GetX, Shamir, ShamirError};
+    use rand::thread_rng;
+    use std::fmt::{Debug, Display};
+    #[cfg(feature = "bench")]
+    use std::hint::black_box;
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+
+    fn random_secrets<F: Field>(m: usize) -> Vec<F> {
+        let mut rng = thread_rng();
+        (0..m).map(|_| F::uniform(&mut rng)).collect()
+    }
+
+    fn can_reconstruct<F: Field + Debug + Display>() {
+        let secrets = random_secrets::<F>(100);
+        let shares = split(&secrets, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        for (i, custodian) in shares.iter().enumerate() {
+            assert_eq!(custodian.len(), 100);
+            assert!(custodian.iter().all(|s| s.getx() as usize == i + 1));
+        }
+
+        let unordered = [shares[4].clone(), shares[0].clone(), shares[2].clone()];
+        for custodians in [&shares[..3], &shares[2..], &unordered[..]] {
+            let reconstructed = reconstruct(custodians, 3).unwrap();
+            assert_eq!(reconstructed.expose_secret(), &secrets);
+        }
+
+        // The shares are compact shares of each secret.
+        for (j, secret) in secrets.iter().enumerate() {
+            let individual: Vec<_> = shares.iter().map(|s| s[j]).collect();
+            assert_eq!(CompactShamir::reconstruct(&individual, 3), Ok(*secret));
+        }
+    }
+
+    #[test]
+    fn can_reconstruct_gf064() {
+        can_reconstruct::<GF64>();
+    }
+
+    #[test]
+    fn can_reconstruct_gf128() {
+        can_reconstruct::<GF128>();
+    }
+
+    #[test]
+    fn can_reconstruct_ed25519() {
+        can_reconstruct::<Ed25519Scalar>();
+    }
+
+    #[test]
+    fn can_split_empty_batch() {
+        let shares = split::<GF64>(&[], 2, 3).unwrap();
+        assert_eq!(shares, vec![vec![]; 3]);
+        assert!(reconstruct(&shares, 2).unwrap().expose_secret().is_empty());
+    }
+
+    #[test]
+    fn can_reject_invalid_parameters() {
+        let secrets = random_secrets::<GF64>(10);
+        assert_eq!(split(&secrets, 0, 3), Err(ShamirError::InvalidThreshold));
+        assert_eq!(split(&secrets, 4, 3), Err(ShamirError::InvalidThreshold));
+        assert_eq!(split(&secrets, 2, 256), Err(ShamirError::InvalidThreshold));
+
+        let shares = split(&secrets, 2, 3).unwrap();
+        assert_eq!(
+            reconstruct(&shares[..1], 2).unwrap_err(),
+            ShamirError::NotEnoughShares
+        );
+        assert_eq!(
+            reconstruct(&[shares[1].clone(), shares[1].clone()], 2).unwrap_err(),
+            ShamirError::DuplicateX
+        );
+
+        let mut truncated = shares.clone();
+        truncated[1].pop();
+        assert_eq!(
+            reconstruct(&truncated, 2).unwrap_err(),
+            ShamirError::InconsistentShares(vec![1])
+        );
+        let mut mixed = shares.clone();
+        mixed[0][5] = shares[2][5];
+        assert_eq!(
+            reconstruct(&mixed, 2).unwrap_err(),
+            ShamirError::InconsistentShares(vec![0])
+        );
+    }
+
+    #[cfg(feature = "bench")]
+    fn bench_split_batch<F: Field>(b: &mut Bencher, m: usize, k: usize, n: usize) {
+        let secrets = random_secrets::<F>(m);
+        b.iter(|| split(black_box(&secrets), k, n));
+    }
+
+    #[cfg(feature = "bench")]
+    fn bench_split_individually<F: Field + Debug + Display>(
+        b: &mut Bencher,
+        m: usize,
+        k: usize,
+        n: usize,
+    ) {
+        let secrets = random_secrets::<F>(m);
+        b.iter(|| {
+            black_box(&secrets)
+                .iter()
+                .map(|secret| CompactShamir::split(secret, k, n))
+                .collect::<Result<Vec<_>, _>>()
+        });
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_split_batch_10000_2_3(b: &mut Bencher) {
+        bench_split_batch::<GF128>(b, 10000, 2, 3);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_split_individually_10000_2_3(b: &mut Bencher) {
+        bench_split_individually::<GF128>(b, 10000, 2, 3);
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_reconstruct_batch_10000_2_3(b: &mut Bencher) {
+        let shares = split(&random_secrets::<GF128>(10000), 2, 3).unwrap();
+        b.iter(|| reconstruct(black_box(&shares[1..]), 2));
+    }
+}
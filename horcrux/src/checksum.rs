@@ -0,0 +1,150 @@
+//! Checksums to catch transcription errors in shares.
+//!
+//! The checksum consists of the two check symbols of a Reed-Solomon code over GF(2^8), computed
+//! over the bytes of the data. It detects any error in up to two bytes, and locates an error in a
+//! single byte (e.g. a mistyped character in a share in text format).
+
+use crate::field::Field;
+use crate::gf2n::GF8;
+
+/// Maximal length of the data, so that the codeword fits in the 255 non-zero elements of GF(2^8).
+pub const MAX_LEN: usize = 253;
+
+/// Location of a checksum mismatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    /// A single byte of the data is wrong, at the given index.
+    Data(usize),
+    /// A single byte of the checksum is wrong, at the given index.
+    Checksum(usize),
+    /// The error couldn't be located, i.e. several bytes are wrong.
+    Unknown,
+}
+
+/// Generator of the multiplicative group of GF(2^8), i.e. `x + 1`.
+fn generator() -> GF8 {
+    GF8::from(3)
+}
+
+/// Returns the syndromes `(sum(c_i), sum(c_i * g^i))` of the given symbols, starting at index 0.
+fn syndromes<'a>(symbols: impl Iterator<Item = &'a u8>) -> (GF8, GF8) {
+    let g = generator();
+    let mut power = GF8::ONE;
+    let (mut s0, mut s1) = (GF8::ZERO, GF8::ZERO);
+    for &c in symbols {
+        let c = GF8::from(c);
+        s0 += &c;
+        s1 += &(c * &power);
+        power *= &g;
+    }
+    (s0, s1)
+}
+
+/// Computes the checksum of the given data. Returns `None` if the data is longer than `MAX_LEN`.
+pub fn compute(data: &[u8]) -> Option<[u8; 2]> {
+    if data.len() > MAX_LEN {
+        return None;
+    }
+    // The check symbols p0 and p1 at indices L and L+1 cancel both syndromes:
+    // p0 + p1 = s0 and p0 * g^L + p1 * g^(L+1) = s1.
+    let (s0, s1) = syndromes(data.iter());
+    let g = generator();
+    let mut power = GF8::ONE;
+    for _ in 0..data.len() {
+        power *= &g;
+    }
+    let next = power * &g;
+    let p0 = (s1 - s0 * &next) * &(power * &(GF8::ONE - g)).invert();
+    let p1 = s0 - p0;
+    Some([p0.to_u8(), p1.to_u8()])
+}
+
+/// Verifies the checksum of the given data. Returns the location of the error on mismatch.
+pub fn verify(data: &[u8], checksum: [u8; 2]) -> Result<(), Mismatch> {
+    if data.len() > MAX_LEN {
+        return Err(Mismatch::Unknown);
+    }
+    let (s0, s1) = syndromes(data.iter().chain(checksum.iter()));
+    if s0 == GF8::ZERO && s1 == GF8::ZERO {
+        return Ok(());
+    }
+    if s0 == GF8::ZERO || s1 == GF8::ZERO {
+        return Err(Mismatch::Unknown);
+    }
+    // A single error e at index j yields s0 = e and s1 = e * g^j.
+    let target = s1 * &s0.invert();
+    let g = generator();
+    let mut power = GF8::ONE;
+    for j in 0..data.len() + 2 {
+        if power == target {
+            return Err(match j.checked_sub(data.len()) {
+                None => Mismatch::Data(j),
+                Some(i) => Mismatch::Checksum(i),
+            });
+        }
+        power *= &g;
+    }
+    Err(Mismatch::Unknown)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn generator_has_order_255() {
+        let g = generator();
+        let mut power = g;
+        for _ in 1..255 {
+            assert_ne!(power, GF8::ONE);
+            power *= &g;
+        }
+        assert_eq!(power, GF8::ONE);
+    }
+
+    #[test]
+    fn can_verify() {
+        for len in [0, 1, 2, 10, 129, MAX_LEN] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let checksum = compute(&data).unwrap();
+            assert_eq!(verify(&data, checksum), Ok(()));
+        }
+        assert_eq!(compute(&[0; MAX_LEN + 1]), None);
+    }
+
+    #[test]
+    fn can_locate_single_errors() {
+        let data = b"42|0123456789abcdef0123456789abcdef".to_vec();
+        let checksum = compute(&data).unwrap();
+        for i in 0..data.len() {
+            for flip in [1, 0x20, 0xff] {
+                let mut corrupted = data.clone();
+                corrupted[i] ^= flip;
+                assert_eq!(verify(&corrupted, checksum), Err(Mismatch::Data(i)));
+            }
+        }
+        for i in 0..2 {
+            let mut corrupted = checksum;
+            corrupted[i] ^= 0x10;
+            assert_eq!(verify(&data, corrupted), Err(Mismatch::Checksum(i)));
+        }
+    }
+
+    #[test]
+    fn can_detect_double_errors() {
+        let data = b"0123456789abcdef".to_vec();
+        let checksum = compute(&data).unwrap();
+        for i in 0..data.len() {
+            for j in i + 1..data.len() {
+                let mut corrupted = data.clone();
+                corrupted[i] ^= 0x01;
+                corrupted[j] ^= 0x03;
+                assert!(verify(&corrupted, checksum).is_err());
+            }
+        }
+        // Swapping adjacent characters is a common transcription error.
+        let mut swapped = data.clone();
+        swapped.swap(3, 4);
+        assert!(verify(&swapped, checksum).is_err());
+    }
+}
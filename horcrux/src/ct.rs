@@ -0,0 +1,173 @@
+//! Constant-time building blocks for the code that handles secret values.
+//!
+//! Branches and memory accesses that depend on secret data are replaced by arithmetic on masks,
+//! which are all ones for true and all zeros for false. The masks go through
+//! `core::hint::black_box()` before being used, so that the compiler doesn't turn a selection back
+//! into a branch. This is a best-effort barrier: Rust doesn't promise anything about the timing of
+//! the generated code, which should still be checked on the targets that matter.
+//!
+//! With these, the following operations are intended to run in constant time, i.e. with
+//! instructions and memory accesses that only depend on public values (the field, the threshold,
+//! the number of shares and their x coordinates):
+//! - additions, multiplications and inversions in the binary fields of the `gf2n` module, with the
+//!   portable and carry-less multiplication backends, but not with the `gf8-tables` and
+//!   `gf16-tables` features,
+//! - comparisons of elements of these fields with `==`, and their hexadecimal formatting,
+//! - splitting secrets, and reconstructing them from compact or random shares with Lagrange
+//!   interpolation,
+//! - the choice of the pivots when solving the linear system of `Shamir::reconstruct_robust()`,
+//!   whose control flow still depends on the rank of the system, i.e. on the number of corrupted
+//!   shares.
+//!
+//! The prime fields of the `gfp` module, the parsing of field elements and shares, and the
+//! consistency checks (whose result is returned anyway) aren't constant time.
+
+use crate::field::Field;
+use crate::gf2n::Word;
+use core::hint::black_box;
+
+/// Returns all ones if the word is non-zero, and all zeros otherwise.
+pub(crate) fn nonzero_mask<W: Word>(mut x: W) -> W {
+    // Fold all the bits into the least significant one.
+    let mut shift = W::NBITS / 2;
+    while shift > 0 {
+        x = x | (x >> shift);
+        shift /= 2;
+    }
+    black_box(x).lsb_mask()
+}
+
+/// Returns all ones if the words are equal, and all zeros otherwise.
+pub(crate) fn eq_mask<W: Word>(x: W, y: W) -> W {
+    !nonzero_mask(x ^ y)
+}
+
+/// Returns `a` if the mask is all ones, and `b` if it's all zeros.
+pub(crate) fn select<W: Word>(mask: W, a: W, b: W) -> W {
+    b ^ (black_box(mask) & (a ^ b))
+}
+
+/// Returns `table[index]`, reading every entry of the table so that the memory accesses don't
+/// depend on the index. Returns zero if the index is out of bounds.
+pub(crate) fn lookup<W: Word>(table: &[W], index: usize) -> W {
+    let mut result = W::ZERO;
+    for (i, &entry) in table.iter().enumerate() {
+        let mask = W::from(eq_mask(i as u64, index as u64) as u8).lsb_mask();
+        result ^= select(mask, entry, W::ZERO);
+    }
+    result
+}
+
+/// Returns all ones if the field element is non-zero, and all zeros otherwise, based on its bytes.
+pub(crate) fn nonzero_field_mask<F: Field>(x: &F) -> u8 {
+    let mut acc = 0u8;
+    for b in x.to_bytes() {
+        acc |= b;
+    }
+    nonzero_mask(acc)
+}
+
+/// Returns `a` if the mask is all ones, and `b` if it's all zeros, with field operations only.
+pub(crate) fn select_field<F: Field>(mask: u8, a: F, b: F) -> F {
+    let mut result = b;
+    result += &(F::from(black_box(mask) & 1) * &(a - b));
+    result
+}
+
+/// Swaps the field elements `a` and `b` if the mask is all ones, and leaves them unchanged if it's
+/// all zeros.
+pub(crate) fn swap_field<F: Field>(mask: u8, a: &mut F, b: &mut F) {
+    let (x, y) = (*a, *b);
+    *a = select_field(mask, y, x);
+    *b = select_field(mask, x, y);
+}
+
+/// Returns whether the byte strings are equal, comparing them in a time that only depends on their
+/// lengths, e.g. to check a MAC without leaking how much of it is correct.
+pub fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut acc = 0u8;
+    for (x, y) in a.iter().zip(b) {
+        acc |= x ^ y;
+    }
+    nonzero_mask(acc) == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gf2n::{GF128, GF8};
+    use crate::gfp::Ed25519Scalar;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn masks() {
+        assert_eq!(nonzero_mask(0u8), 0);
+        assert_eq!(nonzero_mask(0u128), 0);
+        for shift in 0..64 {
+            assert_eq!(nonzero_mask(1u64 << shift), u64::MAX);
+        }
+        assert_eq!(nonzero_mask(0x8000_0000u32), u32::MAX);
+        assert_eq!(nonzero_mask(u128::MAX), u128::MAX);
+
+        assert_eq!(eq_mask(42u16, 42), u16::MAX);
+        assert_eq!(eq_mask(42u16, 43), 0);
+        assert_eq!(eq_mask(0u32, u32::MAX), 0);
+    }
+
+    #[test]
+    fn select_words() {
+        assert_eq!(select(u8::MAX, 0x12, 0x34), 0x12);
+        assert_eq!(select(0u8, 0x12, 0x34), 0x34);
+        assert_eq!(select(u128::MAX, 1, u128::MAX), 1);
+    }
+
+    #[test]
+    fn lookup_reads_the_entry() {
+        let table: Vec<u32> = (0..300).map(|i| i * 7 + 1).collect();
+        for (i, &entry) in table.iter().enumerate() {
+            assert_eq!(lookup(&table, i), entry);
+        }
+        assert_eq!(lookup(&table, 300), 0);
+        assert_eq!(lookup(&table, usize::MAX), 0);
+        assert_eq!(lookup::<u8>(&[], 0), 0);
+    }
+
+    #[test]
+    fn compare_bytes() {
+        assert!(bytes_eq(b"", b""));
+        assert!(bytes_eq(b"horcrux", b"horcrux"));
+        assert!(!bytes_eq(b"horcrux", b"horcrud"));
+        assert!(!bytes_eq(b"\x80orcrux", b"horcrux"));
+        assert!(!bytes_eq(b"horcrux", b"horcru"));
+    }
+
+    fn check_fields<F: Field + core::fmt::Debug>() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let x = F::uniform(&mut rng);
+        let y = F::uniform(&mut rng);
+
+        assert_eq!(nonzero_field_mask(&F::ZERO), 0);
+        assert_eq!(nonzero_field_mask(&F::ONE), 0xff);
+        assert_eq!(nonzero_field_mask(&x), 0xff);
+
+        assert_eq!(select_field(0xff, x, y), x);
+        assert_eq!(select_field(0, x, y), y);
+
+        let (mut a, mut b) = (x, y);
+        swap_field(0, &mut a, &mut b);
+        assert_eq!((a, b), (x, y));
+        swap_field(0xff, &mut a, &mut b);
+        assert_eq!((a, b), (y, x));
+    }
+
+    #[test]
+    fn field_select_and_swap() {
+        check_fields::<GF8>();
+        check_fields::<GF128>();
+        check_fields::<Ed25519Scalar>();
+    }
+}
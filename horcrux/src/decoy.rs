@@ -0,0 +1,182 @@
+//! Decoy secrets, for reconstruction under duress.
+//!
+//! Each holder receives an envelope made of two shares at the same x coordinate: a share of the
+//! real secret, with threshold k, and a share of a decoy secret, with its own threshold. Each half
+//! is an ordinary share, so that under duress, holders can hand over only the decoy half, which
+//! reconstructs the decoy secret and is indistinguishable from a share of an ordinary split.
+//!
+//! This only provides deniability as long as the envelopes themselves remain hidden: anyone who
+//! sees an envelope learns that a decoy exists, and anyone who obtains k real halves reconstructs
+//! the real secret. The decoy threshold is typically lower than k, so that fewer holders can
+//! reconstruct the decoy than the real secret.
+
+use crate::field::Field;
+use crate::shamir::{GetX, Shamir, ShamirError};
+use alloc::vec::Vec;
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand::{CryptoRng, Rng};
+
+/// Envelope containing a share of the real secret and a share of the decoy secret, at the same x
+/// coordinate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecoyShare<T> {
+    /// Share of the real secret.
+    pub real: T,
+    /// Share of the decoy secret.
+    pub decoy: T,
+}
+
+/// Formats the envelope as the real share and the decoy share, separated by a semicolon.
+impl<T: Display> Display for DecoyShare<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{};{}", self.real, self.decoy)
+    }
+}
+
+/// Splits a secret into n envelopes, with k of them being sufficient to reconstruct the secret
+/// and `k_decoy` of them being sufficient to reconstruct the decoy. Returns an error if the
+/// parameters are out of range.
+#[cfg(feature = "std")]
+pub fn split<F: Field, S: Shamir<F> + ?Sized>(
+    secret: &F,
+    k: usize,
+    decoy: &F,
+    k_decoy: usize,
+    n: usize,
+) -> Result<Vec<DecoyShare<S::Share>>, ShamirError> {
+    split_with_rng::<F, S, _>(secret, k, decoy, k_decoy, n, &mut thread_rng())
+}
+
+/// Splits a secret and a decoy into n envelopes like `split()`, but drawing all the randomness
+/// from the given random number generator.
+pub fn split_with_rng<F: Field, S: Shamir<F> + ?Sized, R: Rng + CryptoRng + ?Sized>(
+    secret: &F,
+    k: usize,
+    decoy: &F,
+    k_decoy: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<DecoyShare<S::Share>>, ShamirError> {
+    let real = S::split_with_rng(secret, k, n, rng)?;
+    let decoy = S::split_with_rng(decoy, k_decoy, n, rng)?;
+    // Randomized shares of the two splits have different x coordinates, so the decoy polynomial is
+    // evaluated at the x coordinates of the real shares.
+    real.into_iter()
+        .map(|real| {
+            let decoy = S::reconstruct_at(&decoy[..k_decoy], k_decoy, real.getx())?;
+            Ok(DecoyShare { real, decoy })
+        })
+        .collect()
+}
+
+/// Reconstructs the real secret from a set of envelopes, given its threshold k.
+pub fn reconstruct<F: Field, S: Shamir<F> + ?Sized>(
+    shares: &[DecoyShare<S::Share>],
+    k: usize,
+) -> Result<F, ShamirError> {
+    let real: Vec<S::Share> = shares.iter().map(|share| share.real).collect();
+    S::reconstruct(&real, k)
+}
+
+/// Reconstructs the decoy secret from a set of envelopes, given its threshold `k_decoy`.
+pub fn reconstruct_decoy<F: Field, S: Shamir<F> + ?Sized>(
+    shares: &[DecoyShare<S::Share>],
+    k_decoy: usize,
+) -> Result<F, ShamirError> {
+    let decoy: Vec<S::Share> = shares.iter().map(|share| share.decoy).collect();
+    S::reconstruct(&decoy, k_decoy)
+}
+
+/// Parses an envelope from a string. Returns an error if the parsing fails, or if both halves
+/// don't have the same x coordinate.
+#[cfg(feature = "parse")]
+pub fn parse_share<F: Field, S: Shamir<F> + ?Sized>(
+    s: &str,
+) -> Result<DecoyShare<S::Share>, ShamirError> {
+    let (real, decoy) = s.split_once(';').ok_or(ShamirError::Parse)?;
+    let share = DecoyShare {
+        real: S::parse_share(real)?,
+        decoy: S::parse_share(decoy)?,
+    };
+    if share.real.getx() != share.decoy.getx() {
+        return Err(ShamirError::Parse);
+    }
+    Ok(share)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gf2n::GF256;
+    use crate::shamir::{CompactShamir, RandomShamir};
+    use core::fmt::Debug;
+
+    #[test]
+    fn can_reconstruct_compact() {
+        can_reconstruct::<CompactShamir>();
+    }
+
+    #[test]
+    fn can_reconstruct_random() {
+        can_reconstruct::<RandomShamir>();
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn can_parse_compact() {
+        can_parse::<CompactShamir>();
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn can_parse_random() {
+        can_parse::<RandomShamir>();
+    }
+
+    fn can_reconstruct<S: Shamir<GF256>>()
+    where
+        S::Share: Display,
+        S::X: Debug,
+    {
+        let mut rng = thread_rng();
+        let secret = GF256::uniform(&mut rng);
+        let decoy = GF256::uniform(&mut rng);
+        let shares = split::<GF256, S>(&secret, 4, &decoy, 2, 6).unwrap();
+        assert_eq!(shares.len(), 6);
+        for share in &shares {
+            assert_eq!(share.real.getx(), share.decoy.getx());
+        }
+
+        assert_eq!(reconstruct::<GF256, S>(&shares[2..], 4), Ok(secret));
+        assert_eq!(reconstruct_decoy::<GF256, S>(&shares[3..5], 2), Ok(decoy));
+        // The decoy halves alone are ordinary shares of the decoy.
+        let decoy_halves: Vec<S::Share> = shares.iter().map(|share| share.decoy).collect();
+        assert_eq!(S::reconstruct(&decoy_halves, 2), Ok(decoy));
+        // Below the real threshold, the real secret isn't reconstructed.
+        assert_ne!(reconstruct::<GF256, S>(&shares[..2], 2), Ok(secret));
+    }
+
+    #[cfg(feature = "parse")]
+    fn can_parse<S: Shamir<GF256>>()
+    where
+        S::Share: Display,
+    {
+        let mut rng = thread_rng();
+        let secret = GF256::uniform(&mut rng);
+        let decoy = GF256::uniform(&mut rng);
+        let shares = split::<GF256, S>(&secret, 3, &decoy, 2, 5).unwrap();
+        for share in &shares {
+            let text = share.to_string();
+            assert_eq!(parse_share::<GF256, S>(&text), Ok(*share));
+            let (real, _) = text.split_once(';').unwrap();
+            assert_eq!(parse_share::<GF256, S>(real), Err(ShamirError::Parse));
+        }
+        let mismatched = format!("{};{}", shares[0].real, shares[1].decoy);
+        assert_eq!(
+            parse_share::<GF256, S>(&mismatched),
+            Err(ShamirError::Parse)
+        );
+    }
+}
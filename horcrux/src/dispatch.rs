@@ -0,0 +1,446 @@
+//! Runtime selection of the built-in fields and Shamir schemes.
+//!
+//! The operations of the library are generic over the field and the scheme, so an application
+//! that selects them at runtime (e.g. from the command line or from a share header) would need
+//! nested matches over every combination. Instead, the selection is described by a `BuiltinField`
+//! or a `DynShamir`, and the generic code is written once as a visitor, which `dispatch()` calls
+//! with the matching types.
+//!
+//! Other crates can provide schemes of their own (e.g. hierarchical schemes) with a
+//! `SchemeProvider`. An application lists the providers that it links in a `SchemeRegistry`,
+//! typically behind Cargo features, and selects them by name with `DynShamir::from_name_in()` like
+//! the built-in schemes.
+
+use crate::field::Field;
+use crate::gf2n::{GF128, GF16, GF160, GF192, GF224, GF256, GF32, GF64, GF8};
+use crate::gfp::{Ed25519Scalar, Secp256k1Scalar};
+use crate::shamir::{CompactShamir, RandomShamir, Shamir};
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display};
+use core::marker::PhantomData;
+
+/// Generic code over a field, called by `BuiltinField::dispatch()`.
+pub trait FieldVisitor {
+    /// Result of the visit.
+    type Output;
+
+    /// Runs the code with the selected field.
+    fn visit<F: Field + Debug + Display>(self) -> Self::Output;
+}
+
+/// Generic code over a field and a Shamir scheme, called by `DynShamir::dispatch()`.
+pub trait ShamirVisitor {
+    /// Result of the visit.
+    type Output;
+
+    /// Runs the code with the selected field and scheme.
+    fn visit<F: Field + Debug + Display, S: Shamir<F>>(self) -> Self::Output
+    where
+        S::Share: Display;
+}
+
+/// Shamir scheme provided by another crate, over any field.
+pub trait SchemeProvider {
+    /// Type name of the scheme, which must differ from the built-in `compact` and `random`.
+    const NAME: &'static str;
+
+    /// Runs the visitor with the given field and the scheme over it.
+    fn visit<F: Field + Debug + Display, V: ShamirVisitor>(visitor: V) -> V::Output;
+}
+
+/// List of scheme providers linked into an application, as nested pairs: `()` is the empty
+/// registry, and `(P, R)` adds the provider `P` in front of the registry `R`.
+pub trait SchemeRegistry {
+    /// Returns the type names of the schemes, in order.
+    fn names() -> Vec<&'static str>;
+
+    /// Returns the type name of the scheme with the given name, if it's in the registry.
+    fn find(name: &str) -> Option<&'static str>;
+
+    /// Runs the visitor with the given field and the scheme with the given name, or returns the
+    /// visitor if the scheme isn't in the registry.
+    fn visit<F: Field + Debug + Display, V: ShamirVisitor>(
+        name: &str,
+        visitor: V,
+    ) -> Result<V::Output, V>;
+}
+
+impl SchemeRegistry for () {
+    fn names() -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    fn find(_name: &str) -> Option<&'static str> {
+        None
+    }
+
+    fn visit<F: Field + Debug + Display, V: ShamirVisitor>(
+        _name: &str,
+        visitor: V,
+    ) -> Result<V::Output, V> {
+        Err(visitor)
+    }
+}
+
+impl<P: SchemeProvider, R: SchemeRegistry> SchemeRegistry for (P, R) {
+    fn names() -> Vec<&'static str> {
+        let mut names = R::names();
+        names.insert(0, P::NAME);
+        names
+    }
+
+    fn find(name: &str) -> Option<&'static str> {
+        match name == P::NAME {
+            true => Some(P::NAME),
+            false => R::find(name),
+        }
+    }
+
+    fn visit<F: Field + Debug + Display, V: ShamirVisitor>(
+        name: &str,
+        visitor: V,
+    ) -> Result<V::Output, V> {
+        match name == P::NAME {
+            true => Ok(P::visit::<F, V>(visitor)),
+            false => R::visit::<F, V>(name, visitor),
+        }
+    }
+}
+
+/// Fields built into the library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BuiltinField {
+    /// GF(2^8), i.e. `GF8`.
+    Gf8,
+    /// GF(2^16), i.e. `GF16`.
+    Gf16,
+    /// GF(2^32), i.e. `GF32`.
+    Gf32,
+    /// GF(2^64), i.e. `GF64`.
+    Gf64,
+    /// GF(2^128), i.e. `GF128`.
+    Gf128,
+    /// GF(2^160), i.e. `GF160`.
+    Gf160,
+    /// GF(2^192), i.e. `GF192`.
+    Gf192,
+    /// GF(2^224), i.e. `GF224`.
+    Gf224,
+    /// GF(2^256), i.e. `GF256`.
+    Gf256,
+    /// Scalar field of the secp256k1 curve, i.e. `Secp256k1Scalar`.
+    Secp256k1,
+    /// Scalar field of the Ed25519 curve, i.e. `Ed25519Scalar`.
+    Ed25519,
+}
+
+impl BuiltinField {
+    /// All the built-in fields.
+    pub const ALL: [BuiltinField; 11] = [
+        BuiltinField::Gf8,
+        BuiltinField::Gf16,
+        BuiltinField::Gf32,
+        BuiltinField::Gf64,
+        BuiltinField::Gf128,
+        BuiltinField::Gf160,
+        BuiltinField::Gf192,
+        BuiltinField::Gf224,
+        BuiltinField::Gf256,
+        BuiltinField::Secp256k1,
+        BuiltinField::Ed25519,
+    ];
+
+    /// Returns the binary field GF(2^bitsize), if it's built in.
+    pub fn binary(bitsize: usize) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|f| f.is_binary() && f.bitsize() == bitsize)
+    }
+
+    /// Returns the field with the given name, as returned by `name()`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|f| f.name() == name)
+    }
+
+    /// Returns the name of the field: `gf<bitsize>` for binary fields, or the name of the elliptic
+    /// curve. This is the name used in share headers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinField::Gf8 => "gf8",
+            BuiltinField::Gf16 => "gf16",
+            BuiltinField::Gf32 => "gf32",
+            BuiltinField::Gf64 => "gf64",
+            BuiltinField::Gf128 => "gf128",
+            BuiltinField::Gf160 => "gf160",
+            BuiltinField::Gf192 => "gf192",
+            BuiltinField::Gf224 => "gf224",
+            BuiltinField::Gf256 => "gf256",
+            BuiltinField::Secp256k1 => "secp256k1",
+            BuiltinField::Ed25519 => "ed25519",
+        }
+    }
+
+    /// Returns whether this is a binary field.
+    pub fn is_binary(&self) -> bool {
+        !matches!(self, BuiltinField::Secp256k1 | BuiltinField::Ed25519)
+    }
+
+    /// Returns the size of the elements of the field in bits.
+    pub fn bitsize(&self) -> usize {
+        match self {
+            BuiltinField::Gf8 => 8,
+            BuiltinField::Gf16 => 16,
+            BuiltinField::Gf32 => 32,
+            BuiltinField::Gf64 => 64,
+            BuiltinField::Gf128 => 128,
+            BuiltinField::Gf160 => 160,
+            BuiltinField::Gf192 => 192,
+            BuiltinField::Gf224 => 224,
+            BuiltinField::Gf256 | BuiltinField::Secp256k1 | BuiltinField::Ed25519 => 256,
+        }
+    }
+
+    /// Calls the visitor with the type of this field.
+    pub fn dispatch<V: FieldVisitor>(&self, visitor: V) -> V::Output {
+        match self {
+            BuiltinField::Gf8 => visitor.visit::<GF8>(),
+            BuiltinField::Gf16 => visitor.visit::<GF16>(),
+            BuiltinField::Gf32 => visitor.visit::<GF32>(),
+            BuiltinField::Gf64 => visitor.visit::<GF64>(),
+            BuiltinField::Gf128 => visitor.visit::<GF128>(),
+            BuiltinField::Gf160 => visitor.visit::<GF160>(),
+            BuiltinField::Gf192 => visitor.visit::<GF192>(),
+            BuiltinField::Gf224 => visitor.visit::<GF224>(),
+            BuiltinField::Gf256 => visitor.visit::<GF256>(),
+            BuiltinField::Secp256k1 => visitor.visit::<Secp256k1Scalar>(),
+            BuiltinField::Ed25519 => visitor.visit::<Ed25519Scalar>(),
+        }
+    }
+}
+
+/// Shamir schemes over a built-in field: the schemes built into the library, or a scheme of a
+/// `SchemeRegistry`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DynShamir {
+    /// `CompactShamir` over the given field.
+    Compact(BuiltinField),
+    /// `RandomShamir` over the given field.
+    Random(BuiltinField),
+    /// The scheme of a registry with the given type name, over the given field.
+    External(&'static str, BuiltinField),
+}
+
+impl DynShamir {
+    /// Returns the scheme with the given type name (`compact` or `random`) over the given field.
+    pub fn from_name(shamir_type: &str, field: BuiltinField) -> Option<Self> {
+        Self::from_name_in::<()>(shamir_type, field)
+    }
+
+    /// Returns the scheme with the given type name over the given field, among the built-in
+    /// schemes and those of the registry.
+    pub fn from_name_in<R: SchemeRegistry>(shamir_type: &str, field: BuiltinField) -> Option<Self> {
+        match shamir_type {
+            "compact" => Some(DynShamir::Compact(field)),
+            "random" => Some(DynShamir::Random(field)),
+            _ => R::find(shamir_type).map(|name| DynShamir::External(name, field)),
+        }
+    }
+
+    /// Returns the type name of the scheme, i.e. `compact`, `random` or the name of the external
+    /// scheme.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DynShamir::Compact(_) => "compact",
+            DynShamir::Random(_) => "random",
+            DynShamir::External(name, _) => name,
+        }
+    }
+
+    /// Returns the field of the scheme.
+    pub fn field(&self) -> BuiltinField {
+        match self {
+            DynShamir::Compact(field)
+            | DynShamir::Random(field)
+            | DynShamir::External(_, field) => *field,
+        }
+    }
+
+    /// Calls the visitor with the types of this field and scheme.
+    ///
+    /// Panics if the scheme is external, which must be dispatched with `dispatch_in()`.
+    pub fn dispatch<V: ShamirVisitor>(&self, visitor: V) -> V::Output {
+        self.dispatch_in::<(), V>(visitor)
+    }
+
+    /// Calls the visitor with the types of this field and scheme, looking up external schemes in
+    /// the registry that they were selected from.
+    ///
+    /// Panics if the scheme is external and isn't in the registry.
+    pub fn dispatch_in<R: SchemeRegistry, V: ShamirVisitor>(&self, visitor: V) -> V::Output {
+        struct WithScheme<V, R> {
+            visitor: V,
+            scheme: DynShamir,
+            registry: PhantomData<R>,
+        }
+
+        impl<V: ShamirVisitor, R: SchemeRegistry> FieldVisitor for WithScheme<V, R> {
+            type Output = V::Output;
+
+            fn visit<F: Field + Debug + Display>(self) -> V::Output {
+                match self.scheme {
+                    DynShamir::Compact(_) => self.visitor.visit::<F, CompactShamir>(),
+                    DynShamir::Random(_) => self.visitor.visit::<F, RandomShamir>(),
+                    DynShamir::External(name, _) => R::visit::<F, V>(name, self.visitor)
+                        .unwrap_or_else(|_| panic!("Unknown Shamir scheme: {}", name)),
+                }
+            }
+        }
+
+        self.field().dispatch(WithScheme {
+            visitor,
+            scheme: *self,
+            registry: PhantomData::<R>,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    struct ZeroHex;
+
+    impl FieldVisitor for ZeroHex {
+        type Output = String;
+
+        fn visit<F: Field + Debug + Display>(self) -> String {
+            F::ZERO.to_string()
+        }
+    }
+
+    /// Splits a secret and returns the first share in text format, and whether the secret was
+    /// reconstructed.
+    struct SplitReconstruct;
+
+    impl ShamirVisitor for SplitReconstruct {
+        type Output = (String, bool);
+
+        fn visit<F: Field + Debug + Display, S: Shamir<F>>(self) -> (String, bool)
+        where
+            S::Share: Display,
+        {
+            let mut rng = ChaCha20Rng::seed_from_u64(42);
+            let secret = F::uniform(&mut rng);
+            let shares = S::split_with_rng(&secret, 2, 3, &mut rng).unwrap();
+            let reconstructed = S::reconstruct(&shares[1..], 2).unwrap();
+            (shares[0].to_string(), reconstructed == secret)
+        }
+    }
+
+    /// The compact scheme under another name, as if it were provided by another crate.
+    struct Alias;
+
+    impl SchemeProvider for Alias {
+        const NAME: &'static str = "alias";
+
+        fn visit<F: Field + Debug + Display, V: ShamirVisitor>(visitor: V) -> V::Output {
+            visitor.visit::<F, CompactShamir>()
+        }
+    }
+
+    /// A scheme that is never selected.
+    struct Other;
+
+    impl SchemeProvider for Other {
+        const NAME: &'static str = "other";
+
+        fn visit<F: Field + Debug + Display, V: ShamirVisitor>(_visitor: V) -> V::Output {
+            unreachable!()
+        }
+    }
+
+    type Registry = (Other, (Alias, ()));
+
+    #[test]
+    fn names_roundtrip() {
+        for field in BuiltinField::ALL {
+            assert_eq!(BuiltinField::from_name(field.name()), Some(field));
+            if field.is_binary() {
+                assert_eq!(BuiltinField::binary(field.bitsize()), Some(field));
+                assert_eq!(field.name(), alloc::format!("gf{}", field.bitsize()));
+            }
+            for shamir_type in ["compact", "random"] {
+                let scheme = DynShamir::from_name(shamir_type, field).unwrap();
+                assert_eq!(scheme.type_name(), shamir_type);
+                assert_eq!(scheme.field(), field);
+            }
+        }
+        assert_eq!(BuiltinField::binary(100), None);
+        assert_eq!(BuiltinField::from_name("gf100"), None);
+        assert_eq!(DynShamir::from_name("other", BuiltinField::Gf8), None);
+    }
+
+    #[test]
+    fn finds_registered_schemes() {
+        assert_eq!(<() as SchemeRegistry>::names(), Vec::<&str>::new());
+        assert_eq!(Registry::names(), ["other", "alias"]);
+        for name in Registry::names() {
+            let scheme = DynShamir::from_name_in::<Registry>(name, BuiltinField::Gf8).unwrap();
+            assert_eq!(scheme, DynShamir::External(name, BuiltinField::Gf8));
+            assert_eq!(scheme.type_name(), name);
+            assert_eq!(scheme.field(), BuiltinField::Gf8);
+        }
+        assert_eq!(
+            DynShamir::from_name_in::<Registry>("compact", BuiltinField::Gf8),
+            Some(DynShamir::Compact(BuiltinField::Gf8))
+        );
+        assert_eq!(
+            DynShamir::from_name_in::<Registry>("unknown", BuiltinField::Gf8),
+            None
+        );
+    }
+
+    #[test]
+    fn dispatches_to_the_registered_scheme() {
+        for field in [BuiltinField::Gf128, BuiltinField::Ed25519] {
+            let scheme = DynShamir::from_name_in::<Registry>("alias", field).unwrap();
+            assert_eq!(
+                scheme.dispatch_in::<Registry, _>(SplitReconstruct),
+                DynShamir::Compact(field).dispatch(SplitReconstruct)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown Shamir scheme: alias")]
+    fn external_scheme_needs_its_registry() {
+        DynShamir::External("alias", BuiltinField::Gf8).dispatch(SplitReconstruct);
+    }
+
+    #[test]
+    fn dispatches_to_the_field() {
+        for field in BuiltinField::ALL {
+            assert_eq!(field.dispatch(ZeroHex).len(), field.bitsize() / 4);
+        }
+    }
+
+    #[test]
+    fn dispatches_to_the_scheme() {
+        let shares: Vec<(String, bool)> = [BuiltinField::Gf128, BuiltinField::Ed25519]
+            .iter()
+            .flat_map(|&field| [DynShamir::Compact(field), DynShamir::Random(field)])
+            .map(|scheme| scheme.dispatch(SplitReconstruct))
+            .collect();
+        assert!(shares.iter().all(|(_, ok)| *ok));
+        // Compact shares are at x = 1, random shares at a random field element.
+        assert!(shares[0].0.starts_with("1|"));
+        assert_eq!(shares[1].0.find('|'), Some(32));
+        assert!(shares[2].0.starts_with("1|"));
+        assert_eq!(shares[3].0.find('|'), Some(64));
+    }
+}
@@ -0,0 +1,411 @@
+//! Distributed key generation without a dealer, with Pedersen's protocol over the Ed25519 curve.
+//!
+//! The n participants, numbered from 1 to n, jointly generate a secret scalar shared among them
+//! with a threshold k, together with the corresponding public key, without any party ever seeing
+//! the secret:
+//!
+//! 1. Each participant draws a random polynomial of degree k-1, and sends a `Commitment` to its
+//!    coefficients (their multiples of the base point) to all the participants, and a
+//!    `PrivateShare` of its polynomial to each participant.
+//! 2. Each participant verifies the private shares that it received against the commitments of
+//!    their senders, and adds them up into its share of the joint secret, i.e. the sum of the
+//!    constant terms of all the polynomials. The public key is the sum of the constant commitments.
+//!
+//! The messages have a text format, and their transport is left to the caller: the commitments
+//! must be broadcast so that all the participants receive the same ones, and each private share
+//! must be sent to its recipient over a confidential and authenticated channel. The shares of the
+//! joint secret are compact shares of the `Ed25519Scalar` field, at the indices of the participants,
+//! so that the usual reconstruction functions apply.
+//!
+//! As in Pedersen's original protocol, a participant that withholds its contribution after seeing
+//! the others' can bias the distribution of the public key (Gennaro et al., 2007).
+
+use crate::field::Field;
+use crate::gfp::Ed25519Scalar;
+use crate::shamir::{parse_decimal, Coefficients, CompactShamir, GetX, Shamir, ShamirError, Share};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::Display;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand::{thread_rng, CryptoRng, Rng};
+
+/// Broadcast message of a participant, committing to the coefficients of its polynomial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commitment {
+    /// Index of the participant that sent the commitment.
+    pub sender: u8,
+    /// Multiples of the Ed25519 base point by the coefficients of the polynomial, constant term
+    /// first.
+    pub points: Vec<EdwardsPoint>,
+}
+
+/// Private message from a participant to another one, containing the evaluation of the sender's
+/// polynomial at the index of the recipient.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrivateShare {
+    /// Index of the participant that sent the share.
+    pub sender: u8,
+    /// Share of the sender's polynomial, whose x coordinate is the index of the recipient.
+    pub share: Share<u8, Ed25519Scalar>,
+}
+
+/// Result of the key generation for a participant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Output {
+    /// Share of the joint secret, at the index of the participant.
+    pub share: Share<u8, Ed25519Scalar>,
+    /// Public key corresponding to the joint secret.
+    pub public_key: EdwardsPoint,
+}
+
+/// Participant in the key generation, holding its secret polynomial until the end of the
+/// protocol.
+pub struct Participant {
+    index: u8,
+    n: usize,
+    polynom: Coefficients<Ed25519Scalar>,
+}
+
+impl Participant {
+    /// Creates the participant at the given index, among n participants with a threshold k.
+    /// Returns an error if the parameters are out of range, or if the index isn't in `1..=n`.
+    pub fn new(index: u8, k: usize, n: usize) -> Result<Self, ShamirError> {
+        Self::new_with_rng(index, k, n, &mut thread_rng())
+    }
+
+    /// Creates the participant like `new()`, but drawing its polynomial from the given random
+    /// number generator.
+    pub fn new_with_rng<R: Rng + CryptoRng + ?Sized>(
+        index: u8,
+        k: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Self, ShamirError> {
+        if k == 0 || k > n || n > 255 {
+            return Err(ShamirError::InvalidThreshold);
+        }
+        if index == 0 || index as usize > n {
+            return Err(ShamirError::InvalidX);
+        }
+        let polynom: Vec<Ed25519Scalar> = (0..k).map(|_| Ed25519Scalar::uniform(rng)).collect();
+        Ok(Participant {
+            index,
+            n,
+            polynom: Coefficients::from(polynom),
+        })
+    }
+
+    /// Returns the commitment to broadcast to all the participants.
+    pub fn commitment(&self) -> Commitment {
+        Commitment {
+            sender: self.index,
+            points: self
+                .polynom
+                .iter()
+                .map(|&c| EdwardsPoint::mul_base(&to_scalar(c)))
+                .collect(),
+        }
+    }
+
+    /// Returns the private shares to send to each of the participants, including this one.
+    pub fn private_shares(&self) -> Vec<PrivateShare> {
+        (1..=self.n as u8)
+            .map(|x| {
+                let mut y = Ed25519Scalar::ZERO;
+                for c in self.polynom.iter().rev() {
+                    y *= &Ed25519Scalar::from(x);
+                    y += c;
+                }
+                PrivateShare {
+                    sender: self.index,
+                    share: Share::<u8, _>::new(x, y).unwrap(),
+                }
+            })
+            .collect()
+    }
+
+    /// Verifies the commitments and private shares received from all the participants (including
+    /// this one), and returns the share of the joint secret and the public key. Private shares
+    /// addressed to other participants are ignored.
+    ///
+    /// Returns an error listing the senders whose contributions are missing, duplicated, or whose
+    /// private share doesn't match their commitment. These senders should be reported to the
+    /// other participants, and the protocol restarted without them.
+    pub fn finish(
+        self,
+        commitments: &[Commitment],
+        shares: &[PrivateShare],
+    ) -> Result<Output, ShamirError> {
+        let k = self.polynom.len();
+        let mut y = Ed25519Scalar::ZERO;
+        let mut public_key = EdwardsPoint::identity();
+        let mut invalid = Vec::new();
+        for sender in 1..=self.n as u8 {
+            let commitment = match commitments
+                .iter()
+                .filter(|c| c.sender == sender)
+                .collect::<Vec<_>>()[..]
+            {
+                [c] if c.points.len() == k => c,
+                _ => {
+                    invalid.push(sender);
+                    continue;
+                }
+            };
+            let share = match shares
+                .iter()
+                .filter(|s| s.sender == sender && s.share.getx() == self.index)
+                .collect::<Vec<_>>()[..]
+            {
+                [s] if EdwardsPoint::mul_base(&to_scalar(*s.share.y()))
+                    == evaluate(&commitment.points, self.index) =>
+                {
+                    *s.share.y()
+                }
+                _ => {
+                    invalid.push(sender);
+                    continue;
+                }
+            };
+            y += &share;
+            public_key += commitment.points[0];
+        }
+
+        if !invalid.is_empty() {
+            return Err(ShamirError::InvalidContributions(invalid));
+        }
+        Ok(Output {
+            share: Share::<u8, _>::new(self.index, y).unwrap(),
+            public_key,
+        })
+    }
+}
+
+/// Returns the public counterpart of the share of the joint secret at index x, i.e. the multiple
+/// of the base point by this share, which can be computed by anyone from the commitments of all
+/// the participants.
+pub fn public_share(commitments: &[Commitment], x: u8) -> EdwardsPoint {
+    commitments.iter().map(|c| evaluate(&c.points, x)).sum()
+}
+
+/// Evaluates a commitment to a polynomial at x, which yields the multiple of the base point by the
+/// evaluation of the polynomial, with Horner's method.
+pub(crate) fn evaluate(points: &[EdwardsPoint], x: u8) -> EdwardsPoint {
+    let x = Scalar::from(x);
+    let mut y = EdwardsPoint::identity();
+    for point in points.iter().rev() {
+        y = y * x + point;
+    }
+    y
+}
+
+/// Converts a field element to a scalar of the curve, which have the same modulus.
+pub(crate) fn to_scalar(x: Ed25519Scalar) -> Scalar {
+    let mut bytes = [0u8; 32];
+    for (chunk, limb) in bytes.chunks_mut(8).zip(x.to_limbs()) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    Scalar::from_canonical_bytes(bytes).unwrap()
+}
+
+/// Parses a point in the usual compressed encoding of Ed25519 in hexadecimal. Points outside of
+/// the prime-order subgroup are rejected.
+pub(crate) fn parse_point(s: &str) -> Result<EdwardsPoint, ShamirError> {
+    let bytes: [u8; 32] = hex::decode(s)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ShamirError::Parse)?;
+    CompressedEdwardsY(bytes)
+        .decompress()
+        .filter(|point| point.is_torsion_free())
+        .ok_or(ShamirError::Parse)
+}
+
+/// Formats the commitment as the index of the sender and the compressed points in hexadecimal,
+/// separated by commas: `<sender>|<point0>,...,<pointk-1>`.
+impl Display for Commitment {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}|", self.sender)?;
+        for (i, point) in self.points.iter().enumerate() {
+            if i != 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(&hex::encode(point.compress().as_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the private share as the index of the sender followed by the share:
+/// `<sender>|<recipient>|<y>`.
+impl Display for PrivateShare {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}|{}", self.sender, self.share)
+    }
+}
+
+impl Commitment {
+    /// Parses a commitment from a string. Returns an error if the parsing fails, or if a point
+    /// isn't in the prime-order subgroup of the curve.
+    pub fn parse(s: &str) -> Result<Self, ShamirError> {
+        let (sender, points) = s.split_once('|').ok_or(ShamirError::Parse)?;
+        Ok(Commitment {
+            sender: parse_sender(sender)?,
+            points: points
+                .split(',')
+                .map(parse_point)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+impl PrivateShare {
+    /// Parses a private share from a string. Returns an error if the parsing fails.
+    pub fn parse(s: &str) -> Result<Self, ShamirError> {
+        let (sender, share) = s.split_once('|').ok_or(ShamirError::Parse)?;
+        Ok(PrivateShare {
+            sender: parse_sender(sender)?,
+            share: <CompactShamir as Shamir<Ed25519Scalar>>::parse_share(share)?,
+        })
+    }
+}
+
+fn parse_sender(s: &str) -> Result<u8, ShamirError> {
+    match parse_decimal(s)? {
+        0 => Err(ShamirError::InvalidX),
+        sender => Ok(sender),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Runs the protocol among n participants with a threshold k, and returns the messages and
+    /// the participants before they finish.
+    fn run(k: usize, n: usize) -> (Vec<Participant>, Vec<Commitment>, Vec<PrivateShare>) {
+        let participants: Vec<Participant> = (1..=n as u8)
+            .map(|i| Participant::new(i, k, n).unwrap())
+            .collect();
+        let commitments = participants.iter().map(|p| p.commitment()).collect();
+        let shares = participants
+            .iter()
+            .flat_map(|p| p.private_shares())
+            .collect();
+        (participants, commitments, shares)
+    }
+
+    #[test]
+    fn can_generate_key() {
+        let (participants, commitments, shares) = run(3, 5);
+        let outputs: Vec<Output> = participants
+            .into_iter()
+            .map(|p| p.finish(&commitments, &shares).unwrap())
+            .collect();
+
+        let public_key = outputs[0].public_key;
+        assert!(outputs.iter().all(|o| o.public_key == public_key));
+        for output in &outputs {
+            assert_eq!(
+                EdwardsPoint::mul_base(&to_scalar(*output.share.y())),
+                public_share(&commitments, output.share.getx())
+            );
+        }
+
+        let shares: Vec<_> = outputs.iter().map(|o| o.share).collect();
+        let secret = CompactShamir::reconstruct(&shares[..3], 3).unwrap();
+        assert_eq!(CompactShamir::reconstruct(&shares[2..], 3), Ok(secret));
+        assert_eq!(EdwardsPoint::mul_base(&to_scalar(secret)), public_key);
+        assert_ne!(CompactShamir::reconstruct(&shares[..2], 2), Ok(secret));
+    }
+
+    #[test]
+    fn can_detect_invalid_contributions() {
+        let (mut participants, commitments, shares) = run(2, 4);
+
+        // A private share that doesn't match the commitment of its sender.
+        let mut corrupted = shares.clone();
+        let i = corrupted
+            .iter()
+            .position(|s| s.sender == 2 && s.share.getx() == 1)
+            .unwrap();
+        corrupted[i].share = corrupted[i].share.add(&corrupted[i].share).unwrap();
+        assert_eq!(
+            participants.remove(0).finish(&commitments, &corrupted),
+            Err(ShamirError::InvalidContributions(vec![2]))
+        );
+
+        // Missing and duplicated commitments, and commitments of the wrong degree.
+        let mut invalid = commitments.clone();
+        invalid.remove(3);
+        invalid.push(commitments[1].clone());
+        invalid[2].points.pop();
+        assert_eq!(
+            participants.remove(0).finish(&invalid, &shares),
+            Err(ShamirError::InvalidContributions(vec![2, 3, 4]))
+        );
+
+        // Missing private share.
+        let mut missing = shares.clone();
+        missing.retain(|s| !(s.sender == 4 && s.share.getx() == 3));
+        assert_eq!(
+            participants.remove(0).finish(&commitments, &missing),
+            Err(ShamirError::InvalidContributions(vec![4]))
+        );
+    }
+
+    #[test]
+    fn can_parse_messages() {
+        let (_, commitments, shares) = run(3, 4);
+        for commitment in &commitments {
+            let text = commitment.to_string();
+            assert_eq!(text.matches(',').count(), 2);
+            assert_eq!(Commitment::parse(&text), Ok(commitment.clone()));
+        }
+        for share in &shares {
+            let text = share.to_string();
+            assert_eq!(text.matches('|').count(), 2);
+            assert_eq!(PrivateShare::parse(&text), Ok(share.clone()));
+        }
+
+        let text = commitments[0].to_string();
+        assert_eq!(
+            Commitment::parse(&text.replacen("1|", "0|", 1)),
+            Err(ShamirError::InvalidX)
+        );
+        assert_eq!(
+            Commitment::parse(&text[..text.len() - 2]),
+            Err(ShamirError::Parse)
+        );
+        // The point (0, -1) has order 2, outside of the prime-order subgroup.
+        let torsion = format!("1|ec{}7f", "ff".repeat(30));
+        assert_eq!(Commitment::parse(&torsion), Err(ShamirError::Parse));
+        assert_eq!(PrivateShare::parse("1|2"), Err(ShamirError::Parse));
+        assert_eq!(
+            PrivateShare::parse(&format!("0|{}", shares[0].share)),
+            Err(ShamirError::InvalidX)
+        );
+    }
+
+    #[test]
+    fn can_reject_parameters() {
+        assert!(matches!(
+            Participant::new(1, 0, 3),
+            Err(ShamirError::InvalidThreshold)
+        ));
+        assert!(matches!(
+            Participant::new(1, 4, 3),
+            Err(ShamirError::InvalidThreshold)
+        ));
+        assert!(matches!(
+            Participant::new(0, 2, 3),
+            Err(ShamirError::InvalidX)
+        ));
+        assert!(matches!(
+            Participant::new(4, 2, 3),
+            Err(ShamirError::InvalidX)
+        ));
+    }
+}
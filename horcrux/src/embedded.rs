@@ -0,0 +1,180 @@
+//! Small-footprint API for embedded targets such as hardware wallet firmware, enabled by the
+//! `profile-embedded` feature.
+//!
+//! The secret is split into and reconstructed from arrays of shares whose sizes are const
+//! generics, so that nothing is allocated, and the fields are restricted to `GF8` and `GF128`,
+//! which use table-free constant-time arithmetic without the `gf8-tables` feature. The profile
+//! is meant to be used with `default-features = false`, so that neither the standard library nor
+//! the parsing dependencies (`regex`, `hex`) are linked. The shares are ordinary compact shares,
+//! so they can be reconstructed by the rest of the library or by the command line tool.
+//!
+//! The footprint of this API is tracked by the `embedded` example, which the tests of this module
+//! build in release mode and check against a size budget.
+
+use crate::field::Field;
+use crate::gf2n::{GF128, GF8};
+use crate::shamir::{ShamirError, Share};
+use rand::{CryptoRng, Rng};
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for crate::gf2n::GF8 {}
+    impl Sealed for crate::gf2n::GF128 {}
+}
+
+/// Fields supported by the embedded profile, i.e. `GF8` and `GF128`.
+pub trait EmbeddedField: Field + sealed::Sealed {}
+impl EmbeddedField for GF8 {}
+impl EmbeddedField for GF128 {}
+
+/// Wipes the coefficients of a polynomial.
+fn wipe<F: Field>(values: &mut [F]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(values);
+    #[cfg(not(feature = "zeroize"))]
+    values.iter_mut().for_each(|v| *v = F::ZERO);
+}
+
+/// Splits a secret into N compact shares at x = 1, ..., N, with K shares being sufficient to
+/// reconstruct it. Returns an error if the parameters are out of range, i.e. not
+/// `1 <= K <= N <= 255`.
+pub fn split<F: EmbeddedField, R: Rng + CryptoRng + ?Sized, const K: usize, const N: usize>(
+    secret: &F,
+    rng: &mut R,
+) -> Result<[Share<u8, F>; N], ShamirError> {
+    if K == 0 || K > N || N > 255 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+
+    let mut coefficients = [F::ZERO; K];
+    coefficients[0] = *secret;
+    for c in &mut coefficients[1..] {
+        *c = F::uniform(rng);
+    }
+    let shares = core::array::from_fn(|i| {
+        let x = F::from(i as u8 + 1);
+        // Horner's method, starting from the highest coefficient.
+        let mut y = F::ZERO;
+        for c in coefficients.iter().rev() {
+            y = y * &x;
+            y += c;
+        }
+        Share::new_unchecked(i as u8 + 1, y)
+    });
+    wipe(&mut coefficients);
+    Ok(shares)
+}
+
+/// Reconstructs a secret from exactly K shares. Returns an error if a share is at x = 0 or if
+/// several shares have the same x coordinate.
+pub fn reconstruct<F: EmbeddedField, const K: usize>(
+    shares: &[Share<u8, F>; K],
+) -> Result<F, ShamirError> {
+    if K == 0 || K > 255 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    for (i, s) in shares.iter().enumerate() {
+        if *s.x() == 0 {
+            return Err(ShamirError::InvalidX);
+        }
+        if shares[..i].iter().any(|t| t.x() == s.x()) {
+            return Err(ShamirError::DuplicateX);
+        }
+    }
+
+    // The Lagrange coefficient at zero of each share is the product of x_j / (x_j - x_i) over
+    // the other shares.
+    let mut secret = F::ZERO;
+    for (i, si) in shares.iter().enumerate() {
+        let mut num = F::ONE;
+        let mut den = F::ONE;
+        for (j, sj) in shares.iter().enumerate() {
+            if i != j {
+                num = num * &F::from(*sj.x());
+                den = den * &F::from_diff(*sj.x(), *si.x());
+            }
+        }
+        secret += &(*si.y() * &num * &den.invert());
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shamir::{CompactShamir, Shamir};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    /// Maximal footprint of the embedded profile: the size of the stripped `embedded` example in
+    /// release mode, minus the size of the `embedded_baseline` example which has the same setup
+    /// without horcrux. The budget catches dependencies or tables that would be pulled into the
+    /// profile.
+    const FOOTPRINT_BUDGET: u64 = 32 * 1024;
+
+    fn check<F: EmbeddedField + core::fmt::Debug + core::fmt::Display>() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let secret = F::uniform(&mut rng);
+        let shares = split::<F, _, 3, 5>(&secret, &mut rng).unwrap();
+        assert_eq!(reconstruct(&[shares[0], shares[2], shares[4]]), Ok(secret));
+        assert_eq!(reconstruct(&[shares[3], shares[1], shares[0]]), Ok(secret));
+        // The shares are ordinary compact shares.
+        assert_eq!(CompactShamir::reconstruct(&shares, 3), Ok(secret));
+    }
+
+    #[test]
+    fn can_split_and_reconstruct() {
+        check::<GF8>();
+        check::<GF128>();
+    }
+
+    #[test]
+    fn checks_parameters() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let secret = GF8::from(42);
+        assert!(split::<_, _, 0, 2>(&secret, &mut rng).is_err());
+        assert!(split::<_, _, 3, 2>(&secret, &mut rng).is_err());
+        assert!(split::<_, _, 2, 256>(&secret, &mut rng).is_err());
+
+        let shares = split::<_, _, 2, 2>(&secret, &mut rng).unwrap();
+        assert_eq!(
+            reconstruct(&[shares[0], shares[0]]),
+            Err(ShamirError::DuplicateX)
+        );
+        assert_eq!(
+            reconstruct(&[shares[0], Share::new_unchecked(0, GF8::from(1))]),
+            Err(ShamirError::InvalidX)
+        );
+    }
+
+    #[test]
+    fn footprint_within_budget() {
+        let target_dir = std::env::temp_dir().join("horcrux-embedded-footprint");
+        let size = |example: &str| {
+            let status = std::process::Command::new(env!("CARGO"))
+                .current_dir(env!("CARGO_MANIFEST_DIR"))
+                .args(["build", "--release", "--example", example])
+                .args(["--no-default-features", "--features", "profile-embedded"])
+                .arg("--target-dir")
+                .arg(&target_dir)
+                .env("RUSTFLAGS", "-C strip=symbols")
+                .status()
+                .unwrap();
+            assert!(status.success());
+            let binary = target_dir.join(format!(
+                "release/examples/{}{}",
+                example,
+                std::env::consts::EXE_SUFFIX
+            ));
+            std::fs::metadata(binary).unwrap().len()
+        };
+
+        let footprint = size("embedded").saturating_sub(size("embedded_baseline"));
+        assert!(
+            footprint <= FOOTPRINT_BUDGET,
+            "The embedded profile takes {} bytes, over the budget of {} bytes",
+            footprint,
+            FOOTPRINT_BUDGET
+        );
+    }
+}
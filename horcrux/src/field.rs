@@ -1,11 +1,14 @@
 //! Trait for types that implement field arithmetic.
 
+use crate::ct;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::hash::Hash;
+use core::ops::{AddAssign, Mul, Sub};
 use rand::{CryptoRng, Rng};
-use std::hash::Hash;
-use std::ops::{AddAssign, Mul, Sub};
 
 /// Trait for types that implement field arithmetic.
-pub trait Field: Copy + Eq + Hash + From<u8> + Sub<Output = Self>
+pub trait Field: Copy + Eq + Hash + From<u8> + Sub<Output = Self> + Zeroizable
 where
     for<'a> Self: AddAssign<&'a Self>,
     for<'a> Self: Mul<&'a Self, Output = Self>,
@@ -26,4 +29,157 @@ where
     /// Parses a field element from a byte slice. Returns `None` if the parsing fails.
     #[cfg(feature = "parse")]
     fn from_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /// Returns the bytes of an element, in the canonical encoding parsed by `from_bytes()` and
+    /// printed in hexadecimal by the `Display` implementations, so that elements round-trip
+    /// without going through hexadecimal.
+    ///
+    /// Elements of prime fields are encoded as big-endian integers. Elements of binary fields are
+    /// encoded as their words in memory order (starting with the lowest powers of x), each word in
+    /// big-endian order. This is the big-endian encoding of the polynomial for fields with a single
+    /// word (e.g. `GF8` or `GF64`); `GF2n::to_canonical_bytes()` is big-endian for any word size.
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Multiplies each of the given elements by the same factor, in place. Implementations may
+    /// amortize the work that only depends on the factor over all the elements.
+    fn mul_many(values: &mut [Self], factor: &Self) {
+        for value in values {
+            *value = *value * factor;
+        }
+    }
+
+    /// Adds each element of `others` to the element of `values` at the same position, in place.
+    /// Panics if the slices don't have the same length.
+    fn add_many(values: &mut [Self], others: &[Self]) {
+        assert_eq!(values.len(), others.len());
+        for (value, other) in values.iter_mut().zip(others) {
+            *value += other;
+        }
+    }
+}
+
+/// Marker trait for values that can be wiped from memory, which requires the `zeroize` feature.
+#[cfg(feature = "zeroize")]
+pub trait Zeroizable: zeroize::DefaultIsZeroes {}
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::DefaultIsZeroes> Zeroizable for T {}
+
+/// Marker trait for values that can be wiped from memory, which requires the `zeroize` feature.
+#[cfg(not(feature = "zeroize"))]
+pub trait Zeroizable {}
+#[cfg(not(feature = "zeroize"))]
+impl<T> Zeroizable for T {}
+
+/// Decodes hexadecimal characters (in lower or upper case) into bytes. Returns `None` if the input
+/// contains other characters or has an odd length.
+pub(crate) fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    s.as_bytes()
+        .chunks_exact(2)
+        .map(|pair| Some((digit(pair[0])? << 4) | digit(pair[1])?))
+        .collect()
+}
+
+/// Writes bytes in hexadecimal, for the `LowerHex` and `UpperHex` implementations. The alternate
+/// flag (`{:#x}`) adds a `0x` prefix, as for integers.
+pub(crate) fn fmt_hex(
+    bytes: &[u8],
+    f: &mut core::fmt::Formatter,
+    upper: bool,
+) -> core::fmt::Result {
+    if f.alternate() {
+        f.write_str("0x")?;
+    }
+    write_hex(bytes, f, upper)
+}
+
+/// Writes bytes in hexadecimal without any prefix. The digits are read from a table with a fixed
+/// access pattern (see the `ct` module), so that the formatting of secrets doesn't leak them.
+pub(crate) fn write_hex(
+    bytes: &[u8],
+    f: &mut core::fmt::Formatter,
+    upper: bool,
+) -> core::fmt::Result {
+    let digits = match upper {
+        false => b"0123456789abcdef",
+        true => b"0123456789ABCDEF",
+    };
+    for &b in bytes {
+        f.write_char(char::from(ct::lookup(digits, (b >> 4) as usize)))?;
+        f.write_char(char::from(ct::lookup(digits, (b & 0xf) as usize)))?;
+    }
+    Ok(())
+}
+
+/// Writes the redacted `Debug` output of a secret-bearing value: its type followed by a
+/// placeholder, which also contains a keyed fingerprint of the value with the `redact-debug`
+/// feature.
+#[cfg(not(feature = "debug-values"))]
+pub(crate) fn fmt_redacted<T: Hash>(
+    f: &mut core::fmt::Formatter,
+    name: core::fmt::Arguments,
+    value: &T,
+) -> core::fmt::Result {
+    #[cfg(feature = "redact-debug")]
+    return write!(f, "{}<redacted:{:08x}>", name, redacted_fingerprint(value));
+    #[cfg(not(feature = "redact-debug"))]
+    {
+        let _ = value;
+        write!(f, "{}<redacted>", name)
+    }
+}
+
+/// Computes a truncated fingerprint of a value for redacted `Debug` implementations. The
+/// fingerprint is keyed with a random key generated once per process, so that it can be used to
+/// compare values within logs without revealing them.
+#[cfg(all(feature = "redact-debug", not(feature = "debug-values")))]
+pub(crate) fn redacted_fingerprint<T: Hash>(value: &T) -> u32 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::BuildHasher;
+    use std::sync::OnceLock;
+
+    static KEY: OnceLock<RandomState> = OnceLock::new();
+    KEY.get_or_init(RandomState::new).hash_one(value) as u32
+}
+
+/// Serde visitor for field elements, which are represented by their bytes in the format parsed by
+/// [`Field::from_bytes`]. Byte sequences are also accepted, for formats that don't support raw
+/// bytes.
+#[cfg(feature = "serde")]
+pub(crate) struct BytesVisitor<F>(pub(crate) core::marker::PhantomData<F>);
+
+#[cfg(feature = "serde")]
+impl<'de, F: Field> serde::de::Visitor<'de> for BytesVisitor<F>
+where
+    for<'a> F: AddAssign<&'a F>,
+    for<'a> F: Mul<&'a F, Output = F>,
+{
+    type Value = F;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("the bytes of a field element")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<F, E> {
+        F::from_bytes(v).ok_or_else(|| E::invalid_value(serde::de::Unexpected::Bytes(v), &self))
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<F, A::Error> {
+        let mut bytes = alloc::vec::Vec::new();
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        self.visit_bytes(&bytes)
+    }
 }
@@ -3,14 +3,20 @@
 //! This is based on the existence of a irreducible polynomial of the form
 //! `x^n + x^a + x^b + x^c + 1`, where `0 < c < b < a < n`.
 
-use crate::field::Field;
+use crate::backends::Backend;
+use crate::ct;
+use crate::field::{decode_hex, fmt_hex, write_hex, Field};
+use crate::shamir::ShamirError;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::{Debug, Display, LowerHex, UpperHex};
+use core::hash::{Hash, Hasher};
+use core::ops::{
+    Add, AddAssign, BitAnd, BitOr, BitXor, BitXorAssign, Mul, MulAssign, Not, Shl, Shr, Sub,
+};
+use core::str::FromStr;
 use rand::distributions::{Distribution, Standard};
 use rand::{CryptoRng, Rng};
-#[cfg(feature = "parse")]
-use std::convert::TryInto;
-use std::fmt::{Debug, Display};
-use std::hash::{Hash, Hasher};
-use std::ops::{Add, AddAssign, BitAnd, BitXor, BitXorAssign, Mul, MulAssign, Not, Shl, Shr, Sub};
 
 /// Trait for words that can be used for the representation of elements of GF(2^n).
 pub trait Word:
@@ -20,6 +26,7 @@ pub trait Word:
     + Debug
     + From<u8>
     + BitAnd<Output = Self>
+    + BitOr<Output = Self>
     + BitXorAssign
     + BitXor<Output = Self>
     + Not<Output = Self>
@@ -31,7 +38,7 @@ pub trait Word:
     /// One.
     const ONE: Self;
     /// Number of bytes in the size of the type.
-    const NBYTES: usize = std::mem::size_of::<Self>();
+    const NBYTES: usize = core::mem::size_of::<Self>();
     /// Number of bits in the size of the type.
     const NBITS: usize = 8 * Self::NBYTES;
     /// Base-2 logarithm of `NBITS`.
@@ -41,9 +48,15 @@ pub trait Word:
     #[cfg(test)]
     const MASK: usize = !(!1 << (Self::MASK_BITS - 1));
 
-    /// Parses a word from a byte slice. Panics if the slice length is not `NBYTES`.
-    #[cfg(feature = "parse")]
+    /// Returns all ones if the least significant bit of the word is set, and zero otherwise.
+    fn lsb_mask(self) -> Self;
+
+    /// Parses a word in big-endian order from a byte slice. Panics if the slice length is not
+    /// `NBYTES`.
     fn from_bytes(bytes: &[u8]) -> Self;
+    /// Writes the word in big-endian order to a byte slice. Panics if the slice length is not
+    /// `NBYTES`.
+    fn to_bytes(self, bytes: &mut [u8]);
 }
 
 // TODO: Make this implementation generic once const generics allow it.
@@ -53,11 +66,18 @@ impl Word for u128 {
     #[cfg(test)]
     const MASK_BITS: usize = 7;
 
-    #[cfg(feature = "parse")]
+    fn lsb_mask(self) -> Self {
+        (self & 1).wrapping_neg()
+    }
+
     fn from_bytes(bytes: &[u8]) -> Self {
         let array = bytes.try_into().unwrap();
         u128::from_be_bytes(array)
     }
+
+    fn to_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_be_bytes())
+    }
 }
 
 impl Word for u64 {
@@ -66,11 +86,18 @@ impl Word for u64 {
     #[cfg(test)]
     const MASK_BITS: usize = 6;
 
-    #[cfg(feature = "parse")]
+    fn lsb_mask(self) -> Self {
+        (self & 1).wrapping_neg()
+    }
+
     fn from_bytes(bytes: &[u8]) -> Self {
         let array = bytes.try_into().unwrap();
         u64::from_be_bytes(array)
     }
+
+    fn to_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_be_bytes())
+    }
 }
 
 impl Word for u32 {
@@ -79,11 +106,18 @@ impl Word for u32 {
     #[cfg(test)]
     const MASK_BITS: usize = 5;
 
-    #[cfg(feature = "parse")]
+    fn lsb_mask(self) -> Self {
+        (self & 1).wrapping_neg()
+    }
+
     fn from_bytes(bytes: &[u8]) -> Self {
         let array = bytes.try_into().unwrap();
         u32::from_be_bytes(array)
     }
+
+    fn to_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_be_bytes())
+    }
 }
 
 impl Word for u16 {
@@ -92,11 +126,18 @@ impl Word for u16 {
     #[cfg(test)]
     const MASK_BITS: usize = 4;
 
-    #[cfg(feature = "parse")]
+    fn lsb_mask(self) -> Self {
+        (self & 1).wrapping_neg()
+    }
+
     fn from_bytes(bytes: &[u8]) -> Self {
         let array = bytes.try_into().unwrap();
         u16::from_be_bytes(array)
     }
+
+    fn to_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_be_bytes())
+    }
 }
 
 impl Word for u8 {
@@ -105,11 +146,18 @@ impl Word for u8 {
     #[cfg(test)]
     const MASK_BITS: usize = 3;
 
-    #[cfg(feature = "parse")]
+    fn lsb_mask(self) -> Self {
+        (self & 1).wrapping_neg()
+    }
+
     fn from_bytes(bytes: &[u8]) -> Self {
         let array = bytes.try_into().unwrap();
         u8::from_be_bytes(array)
     }
+
+    fn to_bytes(self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_be_bytes())
+    }
 }
 
 /// Implementation of a binary field GF(2^n), with `W::NBYTES * NWORDS` bits, using the
@@ -158,9 +206,15 @@ pub type GF256 = GF2n<u64, 4, 10, 5, 2>;
 #[cfg(test)]
 pub type GF256u32 = GF2n<u32, 8, 10, 5, 2>;
 
+/// Finite field GF(2^160) implemented with 32-bit words and using the following irreducible
+/// polynomial: `x^160 + x^5 + x^3 + x^2 + 1`.
+pub type GF160 = GF2n<u32, 5, 5, 3, 2>;
 /// Finite field GF(2^192) implemented with 64-bit words and using the following irreducible
 /// polynomial: `x^192 + x^7 + x^2 + x + 1`.
 pub type GF192 = GF2n<u64, 3, 7, 2, 1>;
+/// Finite field GF(2^224) implemented with 32-bit words and using the following irreducible
+/// polynomial: `x^224 + x^9 + x^8 + x^3 + 1`.
+pub type GF224 = GF2n<u32, 7, 9, 8, 3>;
 /// Finite field GF(2^384) implemented with 64-bit words and using the following irreducible
 /// polynomial: `x^384 + x^12 + x^3 + x^2 + 1`.
 pub type GF384 = GF2n<u64, 6, 12, 3, 2>;
@@ -180,6 +234,8 @@ pub type GF1536 = GF2n<u64, 24, 21, 6, 2>;
 /// polynomial: `x^2048 + x^19 + x^14 + x^13 + 1`.
 pub type GF2048 = GF2n<u64, 32, 19, 14, 13>;
 
+/// `Debug` implementation printing the value, with the `debug-values` feature.
+#[cfg(feature = "debug-values")]
 impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> Debug
     for GF2n<W, NWORDS, A, B, C>
 {
@@ -195,21 +251,52 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
     }
 }
 
+/// Redacted `Debug` implementation, which only prints the size of the field (and a truncated
+/// fingerprint of the value with the `redact-debug` feature).
+#[cfg(not(feature = "debug-values"))]
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> Debug
+    for GF2n<W, NWORDS, A, B, C>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        crate::field::fmt_redacted(f, format_args!("GF2^{}", Self::NBITS), self)
+    }
+}
+
 impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> Display
     for GF2n<W, NWORDS, A, B, C>
 {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        for d in &self.words as &[W] {
-            match W::NBITS {
-                8 => f.write_fmt(format_args!("{d:02x?}"))?,
-                16 => f.write_fmt(format_args!("{d:04x?}"))?,
-                32 => f.write_fmt(format_args!("{d:08x?}"))?,
-                64 => f.write_fmt(format_args!("{d:016x?}"))?,
-                128 => f.write_fmt(format_args!("{d:032x?}"))?,
-                _ => unimplemented!(),
-            }
-        }
-        Ok(())
+        write_hex(&self.word_bytes(), f, false)
+    }
+}
+
+/// Parses an element from its hexadecimal representation, as printed by `Display`. Upper case
+/// digits are accepted too.
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> FromStr
+    for GF2n<W, NWORDS, A, B, C>
+{
+    type Err = ShamirError;
+
+    fn from_str(s: &str) -> Result<Self, ShamirError> {
+        decode_hex(s)
+            .and_then(|bytes| Self::from_word_bytes(&bytes))
+            .ok_or(ShamirError::Parse)
+    }
+}
+
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> LowerHex
+    for GF2n<W, NWORDS, A, B, C>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt_hex(&self.word_bytes(), f, false)
+    }
+}
+
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> UpperHex
+    for GF2n<W, NWORDS, A, B, C>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt_hex(&self.word_bytes(), f, true)
     }
 }
 
@@ -217,7 +304,12 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
     for GF2n<W, NWORDS, A, B, C>
 {
     fn eq(&self, other: &Self) -> bool {
-        &self.words as &[W] == &other.words as &[W]
+        // Compares all the words, rather than stopping at the first difference.
+        let mut diff = W::ZERO;
+        for (&x, &y) in self.words.iter().zip(&other.words as &[W]) {
+            diff = diff | (x ^ y);
+        }
+        ct::nonzero_mask(diff) == W::ZERO
     }
 }
 
@@ -234,10 +326,26 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
     }
 }
 
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> Default
+    for GF2n<W, NWORDS, A, B, C>
+{
+    fn default() -> Self {
+        Self::new_small(W::ZERO)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize>
+    zeroize::DefaultIsZeroes for GF2n<W, NWORDS, A, B, C>
+{
+}
+
+#[cfg(test)]
 trait FieldExt {
     type W;
 }
 
+#[cfg(test)]
 impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> FieldExt
     for GF2n<W, NWORDS, A, B, C>
 {
@@ -304,7 +412,7 @@ fn mul_clmul_u64<const NWORDS: usize, const A: usize, const B: usize, const C: u
     x: &GF2n<u64, NWORDS, A, B, C>,
     y: &GF2n<u64, NWORDS, A, B, C>,
 ) -> GF2n<u64, NWORDS, A, B, C> {
-    use std::arch::aarch64::vmull_p64;
+    use core::arch::aarch64::vmull_p64;
 
     // Note: we cannot create an array of `NWORDS * 2` elements:
     // error: constant expression depends on a generic parameter
@@ -339,17 +447,268 @@ fn mul_clmul_u64<const NWORDS: usize, const A: usize, const B: usize, const C: u
     GF2n::<u64, NWORDS, A, B, C>::propagate_carries(words, carry)
 }
 
+/// Returns whether the carry-less multiplication backend agrees with the portable implementation on
+/// this CPU. This is checked once, on first use, by multiplying a few random values with both
+/// implementations. On mismatch, a warning is printed and the portable implementation is used
+/// instead, to avoid silently corrupting shares on miscompiled binaries or exotic CPUs. Without the
+/// `std` feature, the values are derived from a fixed seed. The warning isn't printed without the
+/// `std` feature or with the `silent` feature.
+#[cfg(any(
+    all(
+        feature = "clmul",
+        target_arch = "x86_64",
+        target_feature = "sse2",
+        target_feature = "pclmulqdq"
+    ),
+    all(
+        feature = "clmul",
+        target_arch = "aarch64",
+        target_feature = "neon",
+        target_feature = "aes"
+    )
+))]
+fn clmul_is_sound() -> bool {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    const UNKNOWN: u8 = 0;
+    const SOUND: u8 = 1;
+    const UNSOUND: u8 = 2;
+
+    // Concurrent first uses may run the check several times, which is harmless.
+    static STATE: AtomicU8 = AtomicU8::new(UNKNOWN);
+    match STATE.load(Ordering::Relaxed) {
+        SOUND => true,
+        UNSOUND => false,
+        _ => {
+            // Check GF(2^64) and GF(2^256), which cover both the single-word and the multi-word
+            // reductions.
+            let sound = clmul_self_check::<1, 4, 3, 1>() && clmul_self_check::<4, 10, 5, 2>();
+            #[cfg(all(feature = "std", not(feature = "silent")))]
+            if !sound {
+                eprintln!(
+                    "Warning: carry-less multiplication is inconsistent on this CPU, falling back to the portable implementation"
+                );
+            }
+            STATE.store(if sound { SOUND } else { UNSOUND }, Ordering::Relaxed);
+            sound
+        }
+    }
+}
+
+#[cfg(any(
+    all(
+        feature = "clmul",
+        target_arch = "x86_64",
+        target_feature = "sse2",
+        target_feature = "pclmulqdq"
+    ),
+    all(
+        feature = "clmul",
+        target_arch = "aarch64",
+        target_feature = "neon",
+        target_feature = "aes"
+    )
+))]
+fn clmul_self_check<const NWORDS: usize, const A: usize, const B: usize, const C: usize>() -> bool {
+    #[cfg(feature = "std")]
+    let mut rng = rand::thread_rng();
+    #[cfg(not(feature = "std"))]
+    let mut rng = {
+        use rand::SeedableRng;
+        rand_chacha::ChaCha20Rng::seed_from_u64(0)
+    };
+    (0..16).all(|_| {
+        let x = GF2n::<u64, NWORDS, A, B, C>::uniform(&mut rng);
+        let y = GF2n::<u64, NWORDS, A, B, C>::uniform(&mut rng);
+        mul_clmul_u64(&x, &y) == x.mul_as_add(&y)
+    })
+}
+
+/// Returns the backend of the multiplication in binary fields with 64-bit words.
+pub(crate) fn u64_backend() -> Backend {
+    #[cfg(all(
+        feature = "clmul",
+        target_arch = "x86_64",
+        target_feature = "sse2",
+        target_feature = "pclmulqdq"
+    ))]
+    if clmul_is_sound() {
+        return Backend::ClmulX86;
+    }
+    #[cfg(all(
+        feature = "clmul",
+        target_arch = "aarch64",
+        target_feature = "neon",
+        target_feature = "aes"
+    ))]
+    if clmul_is_sound() {
+        return Backend::ClmulAarch64;
+    }
+    Backend::Portable
+}
+
+/// Multiplication in GF(2^8) with the irreducible polynomial `x^8 + x^a + x^b + x^c + 1`, without
+/// any secret-dependent branch or memory access.
+fn mul_gf8_ct<const A: usize, const B: usize, const C: usize>(mut x: u8, y: u8) -> u8 {
+    let reduction: u8 = 1 ^ (1 << A) ^ (1 << B) ^ (1 << C);
+    let mut result = 0;
+    for i in 0..8 {
+        result ^= ct::select((y >> i).lsb_mask(), x, 0);
+        x = (x << 1) ^ ct::select((x >> 7).lsb_mask(), reduction, 0);
+    }
+    result
+}
+
 impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize>
     GF2n<W, NWORDS, A, B, C>
 {
-    #[cfg(test)]
+    /// Returns the canonical byte form of this element: n / 8 bytes in big-endian order, i.e.
+    /// starting with the coefficients of the highest powers of x.
+    ///
+    /// The hexadecimal representation and `Field::from_bytes()` instead follow the order of the
+    /// words in memory, so they differ between representations of the same field with several
+    /// words (e.g. `GF128` and `GF128u32`). The canonical form doesn't depend on the word size, so
+    /// it's the one to exchange between such representations. It coincides with the hexadecimal
+    /// representation for fields with a single word.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = alloc::vec![0; Self::NBYTES];
+        for (word, chunk) in self.words.iter().rev().zip(bytes.chunks_mut(W::NBYTES)) {
+            word.to_bytes(chunk);
+        }
+        bytes
+    }
+
+    /// Parses an element from its canonical byte form (see `to_canonical_bytes()`). Returns
+    /// `None` if the length doesn't match the field.
+    pub fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::NBYTES {
+            return None;
+        }
+
+        let mut words = [W::ZERO; NWORDS];
+        for (word, chunk) in words.iter_mut().rev().zip(bytes.chunks(W::NBYTES)) {
+            *word = W::from_bytes(chunk);
+        }
+        Some(Self { words })
+    }
+
+    /// Converts this element into the same field with another word size, e.g. from `GF128` to
+    /// `GF128u32` or `GF128u128`. Both representations must have the same number of bits, which
+    /// is checked at runtime.
+    pub fn convert<V: Word, const MWORDS: usize>(&self) -> GF2n<V, MWORDS, A, B, C> {
+        assert_eq!(
+            Self::NBITS,
+            V::NBITS * MWORDS,
+            "Cannot convert between fields of different sizes"
+        );
+        GF2n::from_canonical_bytes(&self.to_canonical_bytes()).unwrap()
+    }
+}
+
+impl GF8 {
+    /// Returns the byte representing this element.
+    pub(crate) fn to_u8(self) -> u8 {
+        self.words[0]
+    }
+}
+
+/// Logarithm and exponential tables of GF(2^8) with the irreducible polynomial
+/// `x^8 + x^4 + x^3 + x + 1`, in base `x + 1`. The exponential table is doubled to avoid reducing
+/// the sum of logarithms modulo 255.
+#[cfg(any(test, feature = "gf8-tables"))]
+const GF8_TABLES: ([u8; 256], [u8; 510]) = {
+    let mut log = [0u8; 256];
+    let mut exp = [0u8; 510];
+    let mut x: u8 = 1;
+    let mut i = 0;
+    while i < 255 {
+        log[x as usize] = i as u8;
+        exp[i] = x;
+        exp[i + 255] = x;
+        // Multiply by x + 1.
+        let carry = if x & 0x80 != 0 { 0x1b } else { 0 };
+        x ^= (x << 1) ^ carry;
+        i += 1;
+    }
+    (log, exp)
+};
+
+/// Multiplication in GF(2^8) with the irreducible polynomial `x^8 + x^4 + x^3 + x + 1`, using
+/// logarithm and exponential tables. This is faster than `mul_gf8_ct()`, but the memory accesses
+/// depend on the operands, which can leak them through cache timings.
+#[cfg(any(test, feature = "gf8-tables"))]
+fn mul_gf8_tables(x: u8, y: u8) -> u8 {
+    if x == 0 || y == 0 {
+        return 0;
+    }
+    let (log, exp) = &GF8_TABLES;
+    exp[log[x as usize] as usize + log[y as usize] as usize]
+}
+
+/// Inversion in GF(2^8) with the same tables as `mul_gf8_tables()`, mapping zero to zero like
+/// `Field::invert()`.
+#[cfg(any(test, feature = "gf8-tables"))]
+fn invert_gf8_tables(x: u8) -> u8 {
+    if x == 0 {
+        return 0;
+    }
+    let (log, exp) = &GF8_TABLES;
+    exp[255 - log[x as usize] as usize]
+}
+
+/// Logarithm and exponential tables of GF(2^16) with the irreducible polynomial
+/// `x^16 + x^5 + x^3 + x + 1`, in base `x + 1`, laid out like `GF8_TABLES`. They take 384 KiB, so
+/// they are a static rather than a constant that could be copied at each use.
+#[cfg(any(test, feature = "gf16-tables"))]
+static GF16_TABLES: ([u16; 65536], [u16; 131070]) = {
+    let mut log = [0u16; 65536];
+    let mut exp = [0u16; 131070];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 65535 {
+        log[x as usize] = i as u16;
+        exp[i] = x;
+        exp[i + 65535] = x;
+        // Multiply by x + 1.
+        let carry = if x & 0x8000 != 0 { 0x2b } else { 0 };
+        x ^= (x << 1) ^ carry;
+        i += 1;
+    }
+    (log, exp)
+};
+
+/// Multiplication in GF(2^16) with the irreducible polynomial `x^16 + x^5 + x^3 + x + 1`, using
+/// logarithm and exponential tables. Like `mul_gf8_tables()`, the memory accesses depend on the
+/// operands.
+#[cfg(any(test, feature = "gf16-tables"))]
+fn mul_gf16_tables(x: u16, y: u16) -> u16 {
+    if x == 0 || y == 0 {
+        return 0;
+    }
+    let (log, exp) = &GF16_TABLES;
+    exp[log[x as usize] as usize + log[y as usize] as usize]
+}
+
+/// Inversion in GF(2^16) with the same tables as `mul_gf16_tables()`, mapping zero to zero.
+#[cfg(any(test, feature = "gf16-tables"))]
+fn invert_gf16_tables(x: u16) -> u16 {
+    if x == 0 {
+        return 0;
+    }
+    let (log, exp) = &GF16_TABLES;
+    exp[65535 - log[x as usize] as usize]
+}
+
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize>
+    GF2n<W, NWORDS, A, B, C>
+{
+    #[cfg(all(test, feature = "bench"))]
     const NWORDS: usize = NWORDS;
     const NBITS: usize = W::NBITS * NWORDS;
-    #[cfg(feature = "parse")]
     const NBYTES: usize = W::NBYTES * NWORDS;
 
     #[cfg(test)]
-    pub const fn new(words: [W; NWORDS]) -> Self {
+    const fn new(words: [W; NWORDS]) -> Self {
         Self { words }
     }
 
@@ -359,6 +718,30 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
         Self { words }
     }
 
+    /// Returns the bytes of the words in memory order, each in big-endian order. This is the
+    /// encoding of `Field::to_bytes()` and of the hexadecimal representation.
+    fn word_bytes(&self) -> Vec<u8> {
+        let mut bytes = alloc::vec![0; Self::NBYTES];
+        for (word, chunk) in self.words.iter().zip(bytes.chunks_mut(W::NBYTES)) {
+            word.to_bytes(chunk);
+        }
+        bytes
+    }
+
+    /// Creates an element from its bytes in the order of `Field::to_bytes()`. Returns `None` if
+    /// the length doesn't match the field.
+    fn from_word_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::NBYTES {
+            return None;
+        }
+
+        let mut words = [W::ZERO; NWORDS];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(W::NBYTES)) {
+            *word = W::from_bytes(chunk);
+        }
+        Some(Self { words })
+    }
+
     #[cfg(test)]
     fn get_nonzero_test_values() -> Vec<Self> {
         let all_ones = [!W::ZERO; NWORDS];
@@ -428,9 +811,8 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
             self.words[i] = (d << 1) ^ carry;
             carry = d >> (W::NBITS - 1);
         }
-        if carry != W::ZERO {
-            self.words[0] ^= W::ONE ^ (W::ONE << A) ^ (W::ONE << B) ^ (W::ONE << C);
-        }
+        let reduction = W::ONE ^ (W::ONE << A) ^ (W::ONE << B) ^ (W::ONE << C);
+        self.words[0] ^= ct::select(carry.lsb_mask(), reduction, W::ZERO);
     }
 
     #[cfg(test)]
@@ -487,8 +869,10 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
         };
         for &word in &other.words as &[W] {
             for i in 0..W::NBITS {
-                if word & (W::ONE << i) != W::ZERO {
-                    result += &self;
+                // Adds self if the i-th bit of the word is set, without branching on it.
+                let mask = (word >> i).lsb_mask();
+                for (r, &s) in result.words.iter_mut().zip(&self.words as &[W]) {
+                    *r ^= ct::select(mask, s, W::ZERO);
                 }
                 self.shl1();
             }
@@ -549,8 +933,10 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
     ))]
     fn propagate_carries(mut words: [W; NWORDS], carry: [W; NWORDS]) -> Self {
         if NWORDS == 1 {
+            // Each reduction lowers the degree of the carry by at least n - a, so a fixed number
+            // of reductions clears it, whatever its value (reducing a zero carry is a no-op).
             let mut c = carry[0];
-            while c != W::ZERO {
+            for _ in 0..W::NBITS.div_ceil(W::NBITS - A) {
                 words[0] ^= c ^ (c << A) ^ (c << B) ^ (c << C);
                 c = (c >> (W::NBITS - A)) ^ (c >> (W::NBITS - B)) ^ (c >> (W::NBITS - C));
             }
@@ -590,31 +976,128 @@ where
         Self { words }
     }
 
-    fn invert(mut self) -> Self {
-        // Compute x^(2^n - 2)
-        let mut result = Self::ONE;
-        for _ in 1..Self::NBITS {
-            self = self * &self;
-            result *= &self;
+    fn invert(self) -> Self {
+        #[cfg(feature = "gf8-tables")]
+        if W::NBITS == 8 && NWORDS == 1 && (A, B, C) == (4, 3, 1) {
+            // Safety: W == u8 when NBITS == 8.
+            let x: &GF2n<u8, NWORDS, A, B, C> = unsafe { core::mem::transmute(&self) };
+            return Self::new_small(W::from(invert_gf8_tables(x.words[0])));
         }
-        result
+        #[cfg(feature = "gf16-tables")]
+        if W::NBITS == 16 && NWORDS == 1 && (A, B, C) == (5, 3, 1) {
+            // Safety: W == u16 when NBITS == 16.
+            let x: &GF2n<u16, NWORDS, A, B, C> = unsafe { core::mem::transmute(&self) };
+            let tmp = GF2n::<u16, NWORDS, A, B, C>::new_small(invert_gf16_tables(x.words[0]));
+            // Safety: W == u16 when NBITS == 16.
+            let result: &Self = unsafe { core::mem::transmute(&tmp) };
+            return *result;
+        }
+
+        // Compute x^(2^n - 2) = (x^(2^(n-1) - 1))^2 with the Itoh-Tsujii algorithm, which follows
+        // the binary expansion of m = n - 1 to compute beta_e = x^(2^e - 1) with:
+        // - beta_2e = (beta_e)^(2^e) * beta_e,
+        // - beta_(e+1) = (beta_e)^2 * x.
+        // This takes n - 1 squarings but only O(log n) other multiplications, instead of n - 1
+        // squarings and n - 1 multiplications with square-and-multiply. The sequence of operations
+        // only depends on n.
+        let m = Self::NBITS - 1;
+        let mut beta = self;
+        let mut e = 1;
+        for bit in (0..usize::BITS - 1 - m.leading_zeros()).rev() {
+            let mut t = beta;
+            for _ in 0..e {
+                t = t * &t;
+            }
+            beta *= &t;
+            e *= 2;
+            if (m >> bit) & 1 != 0 {
+                beta = beta * &beta;
+                beta *= &self;
+                e += 1;
+            }
+        }
+        debug_assert_eq!(e, m);
+        beta * &beta
     }
 
     fn from_diff(lhs: u8, rhs: u8) -> Self {
         Self::from(lhs ^ rhs)
     }
 
-    #[cfg(feature = "parse")]
-    fn from_bytes(bytes: &[u8]) -> Option<Self> {
-        if bytes.len() != Self::NBYTES {
-            return None;
+    fn mul_many(values: &mut [Self], factor: &Self) {
+        // Carry-less multiplication and logarithm tables are faster than the table below, and so
+        // is the regular multiplication for fields larger than 128 bits.
+        if values.len() < 2
+            || Self::NBITS > 128
+            || (W::NBITS == 64 && u64_backend() != Backend::Portable)
+            || (cfg!(feature = "gf8-tables") && Self::NBITS == 8 && (A, B, C) == (4, 3, 1))
+            || (cfg!(feature = "gf16-tables") && Self::NBITS == 16 && (A, B, C) == (5, 3, 1))
+        {
+            for value in values {
+                *value *= factor;
+            }
+            return;
         }
 
-        let mut words = [W::ZERO; NWORDS];
-        for (i, word) in words.iter_mut().enumerate() {
-            *word = W::from_bytes(&bytes[i * W::NBYTES..(i + 1) * W::NBYTES]);
+        // Rows factor * x^j for each bit j, so that each product is the sum of the rows selected
+        // by the bits of the value. The rows are selected with masks rather than branches, in
+        // loops that the compiler can vectorize.
+        let mut rows = Vec::with_capacity(Self::NBITS);
+        let mut row = *factor;
+        for _ in 0..Self::NBITS {
+            rows.push(row);
+            row.shl1();
         }
-        Some(Self { words })
+        #[cfg(feature = "zeroize")]
+        let rows = zeroize::Zeroizing::new(rows);
+
+        for value in values {
+            let mut words = [W::ZERO; NWORDS];
+            for (&word, rows) in value.words.iter().zip(rows.chunks_exact(W::NBITS)) {
+                for (k, w) in words.iter_mut().enumerate() {
+                    let mut bits = word;
+                    let mut acc = W::ZERO;
+                    for row in rows {
+                        acc ^= row.words[k] & bits.lsb_mask();
+                        bits = bits >> 1;
+                    }
+                    *w ^= acc;
+                }
+            }
+            *value = Self { words };
+        }
+    }
+
+    #[cfg(feature = "parse")]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::from_word_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.word_bytes()
+    }
+}
+
+/// Serializes the element as its bytes, in the same order as the hexadecimal representation.
+#[cfg(feature = "serde")]
+impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize> serde::Serialize
+    for GF2n<W, NWORDS, A, B, C>
+where
+    Standard: Distribution<W>,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize>
+    serde::Deserialize<'de> for GF2n<W, NWORDS, A, B, C>
+where
+    Standard: Distribution<W>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(crate::field::BytesVisitor(core::marker::PhantomData))
     }
 }
 
@@ -686,14 +1169,37 @@ impl<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usiz
                 target_feature = "aes"
             )
         ))]
-        if W::NBITS == 64 {
+        if W::NBITS == 64 && clmul_is_sound() {
             // Safety: W == u64 when NBITS == 64.
-            let x: &GF2n<u64, NWORDS, A, B, C> = unsafe { std::mem::transmute(&self) };
+            let x: &GF2n<u64, NWORDS, A, B, C> = unsafe { core::mem::transmute(&self) };
             // Safety: W == u64 when NBITS == 64.
-            let y: &GF2n<u64, NWORDS, A, B, C> = unsafe { std::mem::transmute(other) };
+            let y: &GF2n<u64, NWORDS, A, B, C> = unsafe { core::mem::transmute(other) };
             let tmp: GF2n<u64, NWORDS, A, B, C> = mul_clmul_u64(x, y);
             // Safety: W == u64 when NBITS == 64.
-            let result: &Self = unsafe { std::mem::transmute(&tmp) };
+            let result: &Self = unsafe { core::mem::transmute(&tmp) };
+            return *result;
+        }
+        if W::NBITS == 8 && NWORDS == 1 {
+            // Safety: W == u8 when NBITS == 8.
+            let x: &GF2n<u8, NWORDS, A, B, C> = unsafe { core::mem::transmute(&self) };
+            // Safety: W == u8 when NBITS == 8.
+            let y: &GF2n<u8, NWORDS, A, B, C> = unsafe { core::mem::transmute(other) };
+            #[cfg(feature = "gf8-tables")]
+            if (A, B, C) == (4, 3, 1) {
+                return Self::new_small(W::from(mul_gf8_tables(x.words[0], y.words[0])));
+            }
+            return Self::new_small(W::from(mul_gf8_ct::<A, B, C>(x.words[0], y.words[0])));
+        }
+        #[cfg(feature = "gf16-tables")]
+        if W::NBITS == 16 && NWORDS == 1 && (A, B, C) == (5, 3, 1) {
+            // Safety: W == u16 when NBITS == 16.
+            let x: &GF2n<u16, NWORDS, A, B, C> = unsafe { core::mem::transmute(&self) };
+            // Safety: W == u16 when NBITS == 16.
+            let y: &GF2n<u16, NWORDS, A, B, C> = unsafe { core::mem::transmute(other) };
+            let tmp =
+                GF2n::<u16, NWORDS, A, B, C>::new_small(mul_gf16_tables(x.words[0], y.words[0]));
+            // Safety: W == u16 when NBITS == 16.
+            let result: &Self = unsafe { core::mem::transmute(&tmp) };
             return *result;
         }
         self.mul_as_add(other)
@@ -748,6 +1254,9 @@ mod test {
             for_field!(gf128, GF128, $($tests)*);
             for_field!(gf128u32, GF128u32, $($tests)*);
             for_field!(gf128u128, GF128u128, $($tests)*);
+            for_field!(gf160, GF160, $($tests)*);
+            for_field!(gf192, GF192, $($tests)*);
+            for_field!(gf224, GF224, $($tests)*);
             for_field!(gf256, GF256, $($tests)*);
             for_field!(gf256u32, GF256u32, $($tests)*);
             for_field!(gf256u128, GF256u128, $($tests)*);
@@ -874,6 +1383,142 @@ mod test {
         };
     }
 
+    #[test]
+    fn gf8_backends_are_equivalent() {
+        use crate::field::Field;
+        type F = super::GF8;
+        for x in 0..=255 {
+            for y in 0..=255 {
+                let expected = F::from(x).mul_as_add(&F::from(y));
+                assert_eq!(F::from(super::mul_gf8_ct::<4, 3, 1>(x, y)), expected);
+                assert_eq!(F::from(super::mul_gf8_tables(x, y)), expected);
+            }
+            assert_eq!(F::from(super::invert_gf8_tables(x)), F::from(x).invert());
+        }
+    }
+
+    #[test]
+    fn gf16_backends_are_equivalent() {
+        use crate::field::Field;
+        use rand::Rng;
+        type F = super::GF16;
+        let mut rng = rand::thread_rng();
+        for x in 0..=u16::MAX {
+            let y: u16 = rng.gen();
+            let expected = F::new_small(x).mul_as_add(&F::new_small(y));
+            assert_eq!(F::new_small(super::mul_gf16_tables(x, y)), expected);
+            assert_eq!(
+                F::new_small(super::invert_gf16_tables(x)),
+                F::new_small(x).invert()
+            );
+        }
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_mul_gf8_ct(b: &mut test::Bencher) {
+        b.iter(|| {
+            super::mul_gf8_ct::<4, 3, 1>(std::hint::black_box(0xff), std::hint::black_box(0xff))
+        });
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_mul_gf8_tables(b: &mut test::Bencher) {
+        b.iter(|| super::mul_gf8_tables(std::hint::black_box(0xff), std::hint::black_box(0xff)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_invert_gf8_tables(b: &mut test::Bencher) {
+        b.iter(|| super::invert_gf8_tables(std::hint::black_box(0xff)));
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_mul_gf16_tables(b: &mut test::Bencher) {
+        b.iter(|| {
+            super::mul_gf16_tables(std::hint::black_box(0xffff), std::hint::black_box(0xffff))
+        });
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_invert_gf16_tables(b: &mut test::Bencher) {
+        b.iter(|| super::invert_gf16_tables(std::hint::black_box(0xffff)));
+    }
+
+    #[test]
+    fn canonical_bytes_are_big_endian() {
+        let x64 = super::GF128::xn(64);
+        let mut expected = [0u8; 16];
+        expected[7] = 1;
+        assert_eq!(x64.to_canonical_bytes(), expected);
+        assert_eq!(x64.convert::<u32, 4>().to_canonical_bytes(), expected);
+        assert_eq!(x64.convert::<u128, 1>().to_canonical_bytes(), expected);
+        assert_eq!(super::GF128::from_canonical_bytes(&expected), Some(x64));
+        assert_eq!(super::GF128::from_canonical_bytes(&expected[1..]), None);
+
+        // The hexadecimal representation follows the words.
+        assert_eq!(
+            x64.convert::<u128, 1>().to_string(),
+            "00000000000000010000000000000000"
+        );
+        assert_eq!(x64.to_string(), "00000000000000000000000000000001");
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot convert between fields of different sizes")]
+    fn cannot_convert_to_another_size() {
+        super::GF128::xn(64).convert::<u64, 1>();
+    }
+
+    macro_rules! for_representations {
+        ( $mod:ident, $field:ident, $($others:ident),* ) => {
+            mod $mod {
+                use crate::field::Field;
+
+                type F = super::super::$field;
+
+                #[test]
+                fn convert_roundtrips() {
+                    let values = F::get_test_values();
+                    for x in &values {
+                        assert_eq!(F::from_canonical_bytes(&x.to_canonical_bytes()), Some(*x));
+                    }
+                    $({
+                        type G = super::super::$others;
+                        for x in &values {
+                            let y: G = x.convert();
+                            assert_eq!(y.to_canonical_bytes(), x.to_canonical_bytes());
+                            assert_eq!(y.convert(), *x);
+                        }
+                    })*
+                }
+
+                #[test]
+                fn convert_is_isomorphism() {
+                    let mut rng = rand::thread_rng();
+                    $({
+                        type G = super::super::$others;
+                        let convert = |x: &F| -> G { x.convert() };
+                        for _ in 0..100 {
+                            let x = F::uniform(&mut rng);
+                            let y = F::uniform(&mut rng);
+                            assert_eq!(convert(&(x * &y)), convert(&x) * &convert(&y));
+                            assert_eq!(convert(&(x + y)), convert(&x) + convert(&y));
+                            assert_eq!(convert(&x.invert()), convert(&x).invert());
+                        }
+                    })*
+                }
+            }
+        };
+    }
+
+    for_representations!(convert_gf064, GF64, GF64u32);
+    for_representations!(convert_gf128, GF128, GF128u32, GF128u128);
+    for_representations!(convert_gf256, GF256, GF256u32, GF256u128);
+
     for_all! {
         use crate::field::Field;
         use super::super::Word;
@@ -889,6 +1534,78 @@ mod test {
             }
         }
 
+        #[cfg(not(feature = "debug-values"))]
+        #[test]
+        fn debug_is_redacted() {
+            let values = F::get_test_values();
+            for &x in &values {
+                #[cfg(not(feature = "redact-debug"))]
+                let expected = format!("GF2^{}<redacted>", F::NBITS);
+                #[cfg(feature = "redact-debug")]
+                let expected = format!(
+                    "GF2^{}<redacted:{:08x}>",
+                    F::NBITS,
+                    crate::field::redacted_fingerprint(&x)
+                );
+                assert_eq!(format!("{x:?}"), expected);
+            }
+        }
+
+        #[test]
+        fn to_bytes() {
+            for x in F::get_test_values() {
+                let bytes = x.to_bytes();
+                assert_eq!(bytes.len(), F::NBYTES);
+                #[cfg(feature = "parse")]
+                {
+                    assert_eq!(F::from_bytes(&bytes), Some(x));
+                    assert_eq!(hex::encode(&bytes), x.to_string());
+                }
+            }
+        }
+
+        #[test]
+        fn from_str_round_trip() {
+            for x in F::get_test_values() {
+                let hex = format!("{x:x}");
+                assert_eq!(hex, x.to_string());
+                assert_eq!(hex.parse::<F>(), Ok(x));
+                assert_eq!(hex.to_uppercase(), format!("{x:X}"));
+                assert_eq!(format!("{x:X}").parse::<F>(), Ok(x));
+                assert_eq!(format!("{x:#x}"), format!("0x{x}"));
+            }
+            let hex = F::ONE.to_string();
+            assert!(hex[1..].parse::<F>().is_err());
+            assert!(format!("{hex}00").parse::<F>().is_err());
+            assert!(hex.replace('1', "g").parse::<F>().is_err());
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn serde() {
+            for x in F::get_test_values() {
+                let json = serde_json::to_string(&x).unwrap();
+                assert_eq!(serde_json::from_str::<F>(&json).unwrap(), x);
+            }
+            // Wrong length.
+            let json = serde_json::to_string(&vec![0u8; F::NBYTES + 1]).unwrap();
+            assert!(serde_json::from_str::<F>(&json).is_err());
+        }
+
+        #[cfg(feature = "zeroize")]
+        #[test]
+        fn zeroize() {
+            use zeroize::Zeroize;
+            let mut values = F::get_test_values();
+            values.zeroize();
+            assert!(values.is_empty());
+
+            for mut x in F::get_test_values() {
+                x.zeroize();
+                assert_eq!(x, F::ZERO);
+            }
+        }
+
         #[test]
         fn from_diff() {
             for i in 0..=255 {
@@ -999,6 +1716,41 @@ mod test {
             }
         }
 
+        #[cfg(not(debug_assertions))]
+        #[test]
+        fn invert_is_pow() {
+            // Square-and-multiply computation of x^(2^n - 2).
+            for &x in &F::get_test_values() {
+                let mut pow = x;
+                let mut expected = F::ONE;
+                for _ in 1..F::NBITS {
+                    pow = pow * &pow;
+                    expected *= &pow;
+                }
+                assert_eq!(x.invert(), expected);
+            }
+        }
+
+        #[test]
+        fn mul_many_is_mul() {
+            let values = F::get_test_values();
+            for y in &values {
+                let mut products = values.clone();
+                F::mul_many(&mut products, y);
+                for (x, product) in values.iter().zip(&products) {
+                    assert_eq!(*product, *x * y);
+                }
+            }
+        }
+
+        #[test]
+        fn add_many_is_add() {
+            let values = F::get_test_values();
+            let mut sums = values.clone();
+            F::add_many(&mut sums, &values);
+            assert!(sums.iter().all(|&sum| sum == F::ZERO));
+        }
+
         #[test]
         fn mul_as_add_is_mul_fused_carry() {
             let values = F::get_test_values();
@@ -1052,11 +1804,15 @@ mod test {
             }
         }
 
+        #[cfg(feature = "bench")]
         use test::Bencher;
+        #[cfg(feature = "bench")]
         use std::hint::black_box;
 
+        #[cfg(feature = "bench")]
         const TEST_VALUE: F = F::new([!W::ZERO; F::NWORDS]);
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_mul(b: &mut Bencher) {
             let x = TEST_VALUE;
@@ -1064,6 +1820,7 @@ mod test {
             b.iter(|| black_box(x) * &black_box(y));
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_mul_as_add(b: &mut Bencher) {
             let x = TEST_VALUE;
@@ -1071,6 +1828,7 @@ mod test {
             b.iter(|| black_box(x).mul_as_add(&black_box(y)));
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_mul_fused_carry(b: &mut Bencher) {
             let x = TEST_VALUE;
@@ -1078,18 +1836,29 @@ mod test {
             b.iter(|| black_box(x).mul_fused_carry(&black_box(y)));
         }
 
+        #[cfg(feature = "bench")]
+        #[bench]
+        fn bench_mul_many_255(b: &mut Bencher) {
+            let mut values = vec![TEST_VALUE; 255];
+            let y = TEST_VALUE;
+            b.iter(|| F::mul_many(black_box(&mut values), &black_box(y)));
+        }
+
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_invert(b: &mut Bencher) {
             let x = TEST_VALUE;
             b.iter(|| black_box(x).invert());
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_shl1(b: &mut Bencher) {
             let x = TEST_VALUE;
             b.iter(|| black_box(x).shl1_ret());
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_shlt(b: &mut Bencher) {
             let x = TEST_VALUE;
@@ -1127,6 +1896,11 @@ mod test {
         use super::super::Word;
         type W = <F as super::super::FieldExt>::W;
 
+        #[test]
+        fn clmul_is_sound() {
+            assert!(super::super::clmul_is_sound());
+        }
+
         #[test]
         fn mul_as_add_is_mul_clmul() {
             let values = F::get_test_values();
@@ -1137,11 +1911,15 @@ mod test {
             }
         }
 
+        #[cfg(feature = "bench")]
         use test::Bencher;
+        #[cfg(feature = "bench")]
         use std::hint::black_box;
 
+        #[cfg(feature = "bench")]
         const TEST_VALUE: F = F::new([!W::ZERO; F::NWORDS]);
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_mul_clmul(b: &mut Bencher) {
             let x = TEST_VALUE;
@@ -0,0 +1,558 @@
+//! Binary fields GF(2^n) whose size is only chosen at runtime.
+//!
+//! The `Field` trait requires `Copy` elements and constants that don't depend on the size of the
+//! field, so the elements of a field chosen at runtime can't implement it. This module instead
+//! implements the arithmetic of `GF2nDyn` elements directly, and the splitting and reconstruction
+//! of compact shares of such elements, so that applications can handle secrets of any of the
+//! supported sizes without a compile-time type for each size.
+//!
+//! Each size uses the same irreducible polynomial as the corresponding type of the `gf2n` module,
+//! and elements have the same hexadecimal and byte representations, so shares are interchangeable
+//! with compact shares of these types (e.g. `CompactShamir` over `GF256` for 256 bits).
+
+use crate::gf2n::GF2n;
+use crate::gf2n::Word;
+use crate::shamir::{check_split_parameters, ShamirError, Share};
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display};
+use core::hash::Hash;
+use core::ops::{Add, Mul};
+use rand::{CryptoRng, Rng};
+
+/// Supported sizes, as `(n, a, b, c, w)` for the irreducible polynomial
+/// `x^n + x^a + x^b + x^c + 1`, with w the number of bits of the words of the representation
+/// (the word size of the corresponding type of the `gf2n` module).
+const FIELDS: &[(usize, usize, usize, usize, usize)] = &[
+    (8, 4, 3, 1, 8),
+    (16, 5, 3, 1, 16),
+    (32, 7, 3, 2, 32),
+    (64, 4, 3, 1, 64),
+    (128, 7, 2, 1, 64),
+    (160, 5, 3, 2, 32),
+    (192, 7, 2, 1, 64),
+    (224, 9, 8, 3, 32),
+    (256, 10, 5, 2, 64),
+    (384, 12, 3, 2, 64),
+    (512, 8, 5, 2, 64),
+    (768, 19, 17, 4, 64),
+    (1024, 19, 6, 1, 64),
+    (1536, 21, 6, 2, 64),
+    (2048, 19, 14, 13, 64),
+];
+
+/// Binary field GF(2^n), with a size chosen at runtime among the supported ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DynField {
+    nbits: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+    word_bits: usize,
+}
+
+impl DynField {
+    /// Returns the field GF(2^n), or `None` if this size isn't supported.
+    pub fn new(nbits: usize) -> Option<Self> {
+        FIELDS
+            .iter()
+            .find(|&&(n, _, _, _, _)| n == nbits)
+            .map(|&(nbits, a, b, c, word_bits)| DynField {
+                nbits,
+                a,
+                b,
+                c,
+                word_bits,
+            })
+    }
+
+    /// Returns the supported sizes, in bits.
+    pub fn supported_sizes() -> impl Iterator<Item = usize> {
+        FIELDS.iter().map(|&(n, _, _, _, _)| n)
+    }
+
+    /// Returns the number of bits of the field, i.e. n for GF(2^n).
+    pub fn nbits(&self) -> usize {
+        self.nbits
+    }
+
+    /// Returns the number of bytes of an element.
+    pub fn nbytes(&self) -> usize {
+        self.nbits / 8
+    }
+
+    fn nwords(&self) -> usize {
+        self.nbits.div_ceil(64)
+    }
+
+    /// Returns the neutral element for addition.
+    pub fn zero(&self) -> GF2nDyn {
+        GF2nDyn {
+            field: *self,
+            words: alloc::vec![0; self.nwords()],
+        }
+    }
+
+    /// Returns the neutral element for multiplication.
+    pub fn one(&self) -> GF2nDyn {
+        self.from_u8(1)
+    }
+
+    /// Returns the element whose binary polynomial is represented by the given byte, like
+    /// `From<u8>` for the fields of the `gf2n` module.
+    pub fn from_u8(&self, x: u8) -> GF2nDyn {
+        let mut element = self.zero();
+        element.words[0] = x as u64;
+        element
+    }
+
+    /// Samples an element uniformly at random.
+    pub fn uniform<R: Rng + CryptoRng + ?Sized>(&self, rng: &mut R) -> GF2nDyn {
+        let mut element = self.zero();
+        for word in &mut element.words {
+            *word = rng.gen();
+        }
+        element.mask();
+        element
+    }
+
+    /// Parses an element from its bytes, in the same order as `Field::from_bytes()` for the
+    /// corresponding type of the `gf2n` module. Returns `None` if the length doesn't match.
+    pub fn from_bytes(&self, bytes: &[u8]) -> Option<GF2nDyn> {
+        if bytes.len() != self.nbytes() {
+            return None;
+        }
+        self.from_canonical_bytes(&self.swap_words(bytes))
+    }
+
+    /// Parses an element from its canonical byte form, i.e. in big-endian order (see
+    /// `GF2n::to_canonical_bytes()`). Returns `None` if the length doesn't match.
+    pub fn from_canonical_bytes(&self, bytes: &[u8]) -> Option<GF2nDyn> {
+        if bytes.len() != self.nbytes() {
+            return None;
+        }
+        let mut element = self.zero();
+        for (i, &byte) in bytes.iter().rev().enumerate() {
+            element.words[i / 8] |= (byte as u64) << (8 * (i % 8));
+        }
+        Some(element)
+    }
+
+    /// Converts between the canonical byte order and the order of the words of the
+    /// representation, which are the same bytes in reverse order of the words.
+    fn swap_words(&self, bytes: &[u8]) -> Vec<u8> {
+        bytes
+            .chunks(self.word_bits / 8)
+            .rev()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Parses an element in hexadecimal, in the same format as `Display`. Returns `None` if the
+    /// parsing fails.
+    #[cfg(feature = "parse")]
+    pub fn parse(&self, s: &str) -> Option<GF2nDyn> {
+        self.from_bytes(&hex::decode(s).ok()?)
+    }
+}
+
+/// Element of a binary field whose size is chosen at runtime (see `DynField`).
+///
+/// Operations between elements of different fields panic.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct GF2nDyn {
+    field: DynField,
+    /// Words of the binary polynomial, starting with the lowest powers of x.
+    words: Vec<u64>,
+}
+
+impl GF2nDyn {
+    /// Returns the field of this element.
+    pub fn field(&self) -> DynField {
+        self.field
+    }
+
+    /// Returns the bytes of this element, in the same order as the hexadecimal representation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.field.swap_words(&self.to_canonical_bytes())
+    }
+
+    /// Returns the canonical byte form of this element (see `GF2n::to_canonical_bytes()`).
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        (0..self.field.nbytes())
+            .rev()
+            .map(|i| (self.words[i / 8] >> (8 * (i % 8))) as u8)
+            .collect()
+    }
+
+    /// Converts an element of a field of the `gf2n` module. Returns `None` if its size isn't
+    /// supported, or if it uses another irreducible polynomial than the supported field of the same
+    /// size.
+    pub fn from_static<
+        W: Word,
+        const NWORDS: usize,
+        const A: usize,
+        const B: usize,
+        const C: usize,
+    >(
+        x: &GF2n<W, NWORDS, A, B, C>,
+    ) -> Option<Self> {
+        let bytes = x.to_canonical_bytes();
+        let field = DynField::new(8 * bytes.len())?;
+        if (field.a, field.b, field.c) != (A, B, C) {
+            return None;
+        }
+        field.from_canonical_bytes(&bytes)
+    }
+
+    /// Converts this element into the field of the `gf2n` module with the same size and the same
+    /// irreducible polynomial. Returns `None` if the field doesn't match.
+    pub fn to_static<
+        W: Word,
+        const NWORDS: usize,
+        const A: usize,
+        const B: usize,
+        const C: usize,
+    >(
+        &self,
+    ) -> Option<GF2n<W, NWORDS, A, B, C>> {
+        if (self.field.a, self.field.b, self.field.c) != (A, B, C) {
+            return None;
+        }
+        GF2n::from_canonical_bytes(&self.to_canonical_bytes())
+    }
+
+    /// Returns whether this element is zero.
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Inverts this element, mapping zero to zero.
+    pub fn invert(&self) -> Self {
+        // Same Itoh-Tsujii addition chain as `Field::invert()` for the fields of the `gf2n`
+        // module, computing x^(2^n - 2) = (x^(2^(n-1) - 1))^2.
+        let m = self.field.nbits - 1;
+        let mut beta = self.clone();
+        let mut e = 1;
+        for bit in (0..usize::BITS - 1 - m.leading_zeros()).rev() {
+            let mut t = beta.clone();
+            for _ in 0..e {
+                t = &t * &t;
+            }
+            beta = &beta * &t;
+            e *= 2;
+            if (m >> bit) & 1 != 0 {
+                beta = &(&beta * &beta) * self;
+                e += 1;
+            }
+        }
+        debug_assert_eq!(e, m);
+        &beta * &beta
+    }
+
+    /// Clears the bits beyond the size of the field.
+    fn mask(&mut self) {
+        let rem = self.field.nbits % 64;
+        if rem != 0 {
+            *self.words.last_mut().unwrap() &= (1 << rem) - 1;
+        }
+    }
+
+    /// Multiplies this element by x in place.
+    fn shl1(&mut self) {
+        let top = self.field.nbits - 1;
+        // All ones if the coefficient of x^(n-1) is set, all zeros otherwise.
+        let carry = 0u64.wrapping_sub((self.words[top / 64] >> (top % 64)) & 1);
+        for i in (1..self.words.len()).rev() {
+            self.words[i] = (self.words[i] << 1) | (self.words[i - 1] >> 63);
+        }
+        self.words[0] <<= 1;
+        self.mask();
+        let DynField { a, b, c, .. } = self.field;
+        self.words[0] ^= carry & (1 ^ (1 << a) ^ (1 << b) ^ (1 << c));
+    }
+
+    fn check_field(&self, other: &Self) {
+        assert_eq!(
+            self.field.nbits, other.field.nbits,
+            "Cannot combine elements of different fields"
+        );
+    }
+}
+
+impl Add for &GF2nDyn {
+    type Output = GF2nDyn;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, other: &GF2nDyn) -> GF2nDyn {
+        self.check_field(other);
+        let words = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(x, y)| x ^ y)
+            .collect();
+        GF2nDyn {
+            field: self.field,
+            words,
+        }
+    }
+}
+
+impl Mul for &GF2nDyn {
+    type Output = GF2nDyn;
+
+    fn mul(self, other: &GF2nDyn) -> GF2nDyn {
+        self.check_field(other);
+        // Double-and-add from the highest coefficient of the other element, selecting with masks
+        // rather than branches.
+        let mut result = self.field.zero();
+        for i in (0..self.field.nbits).rev() {
+            result.shl1();
+            let mask = 0u64.wrapping_sub((other.words[i / 64] >> (i % 64)) & 1);
+            for (r, x) in result.words.iter_mut().zip(&self.words) {
+                *r ^= x & mask;
+            }
+        }
+        result
+    }
+}
+
+impl Display for GF2nDyn {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for b in self.to_bytes() {
+            f.write_fmt(format_args!("{b:02x}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// `Debug` implementation printing the value, with the `debug-values` feature.
+#[cfg(feature = "debug-values")]
+impl Debug for GF2nDyn {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_fmt(format_args!("GF2^{}({})", self.field.nbits, self))
+    }
+}
+
+/// Redacted `Debug` implementation, which only prints the size of the field (and a truncated
+/// fingerprint of the value with the `redact-debug` feature).
+#[cfg(not(feature = "debug-values"))]
+impl Debug for GF2nDyn {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        crate::field::fmt_redacted(f, format_args!("GF2^{}", self.field.nbits), self)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for GF2nDyn {
+    fn zeroize(&mut self) {
+        self.words.zeroize();
+    }
+}
+
+/// Splits a secret into n compact shares at x = 1, 2, ..., n, with k shares being sufficient to
+/// reconstruct it, like `CompactShamir::split_with_rng()` in the field of the secret.
+pub fn split_with_rng<R: Rng + CryptoRng + ?Sized>(
+    secret: &GF2nDyn,
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<Share<u8, GF2nDyn>>, ShamirError> {
+    check_split_parameters(k, n)?;
+
+    let field = secret.field;
+    let polynom: Vec<GF2nDyn> = (1..k).map(|_| field.uniform(rng)).collect();
+    let shares = (1..=n as u8)
+        .map(|x| {
+            let xf = field.from_u8(x);
+            // Horner's method.
+            let mut y = field.zero();
+            for p in polynom.iter().rev() {
+                y = &(&y + p) * &xf;
+            }
+            Share::new_unchecked(x, &y + secret)
+        })
+        .collect();
+    Ok(shares)
+}
+
+/// Splits a secret into n compact shares, drawing the randomness from the thread-local RNG (see
+/// `split_with_rng()`).
+#[cfg(feature = "std")]
+pub fn split(secret: &GF2nDyn, k: usize, n: usize) -> Result<Vec<Share<u8, GF2nDyn>>, ShamirError> {
+    split_with_rng(secret, k, n, &mut rand::thread_rng())
+}
+
+/// Reconstructs a secret from the first k of the given compact shares, with Lagrange
+/// interpolation at zero.
+pub fn reconstruct(shares: &[Share<u8, GF2nDyn>], k: usize) -> Result<GF2nDyn, ShamirError> {
+    if k == 0 || k > 255 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    if shares.len() < k {
+        return Err(ShamirError::NotEnoughShares);
+    }
+    let shares = &shares[..k];
+    for (i, s) in shares.iter().enumerate() {
+        let (x, y) = (s.x(), s.y());
+        if *x == 0 {
+            return Err(ShamirError::InvalidX);
+        }
+        if shares[..i].iter().any(|t| t.x() == x) {
+            return Err(ShamirError::DuplicateX);
+        }
+        if y.field != shares[0].y().field {
+            return Err(ShamirError::DifferentFields);
+        }
+    }
+
+    let field = shares[0].y().field;
+    let mut secret = field.zero();
+    for (i, si) in shares.iter().enumerate() {
+        let (&xi, yi) = (si.x(), si.y());
+        // In characteristic 2, the Lagrange coefficient at zero is the product of
+        // x_j / (x_j + x_i) over the other shares.
+        let mut num = field.one();
+        let mut den = field.one();
+        for (j, sj) in shares.iter().enumerate() {
+            if i != j {
+                let xj = *sj.x();
+                num = &num * &field.from_u8(xj);
+                den = &den * &field.from_u8(xj ^ xi);
+            }
+        }
+        secret = &secret + &(yi * &(&num * &den.invert()));
+    }
+    Ok(secret)
+}
+
+/// Parses a compact share `x|y`, whose field is given by the length of y.
+#[cfg(feature = "parse")]
+pub fn parse_share(s: &str) -> Result<Share<u8, GF2nDyn>, ShamirError> {
+    let (x, y) = s.split_once('|').ok_or(ShamirError::Parse)?;
+    let x: u8 = crate::shamir::parse_decimal(x)?;
+    let field = DynField::new(4 * y.len()).ok_or(ShamirError::Parse)?;
+    let y = field.parse(y).ok_or(ShamirError::Parse)?;
+    Share::new(x, y)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::field::Field;
+    use crate::shamir::{CompactShamir, GetX, Shamir};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn check_static<W: Word, const NWORDS: usize, const A: usize, const B: usize, const C: usize>()
+    where
+        GF2n<W, NWORDS, A, B, C>: Field + Display,
+    {
+        type S = CompactShamir;
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        for _ in 0..10 {
+            let x = GF2n::<W, NWORDS, A, B, C>::uniform(&mut rng);
+            let y = GF2n::<W, NWORDS, A, B, C>::uniform(&mut rng);
+            let dx = GF2nDyn::from_static(&x).unwrap();
+            let dy = GF2nDyn::from_static(&y).unwrap();
+            assert_eq!(dx.to_string(), x.to_string());
+            assert_eq!(dx.field().from_bytes(&dx.to_bytes()), Some(dx.clone()));
+            assert_eq!((&dx * &dy).to_static(), Some(x * &y));
+            assert_eq!((&dx + &dy).to_static(), Some(x - y));
+            assert_eq!(dx.invert().to_static(), Some(x.invert()));
+        }
+
+        // Shares are interchangeable with compact shares of the static field.
+        let secret = GF2n::<W, NWORDS, A, B, C>::uniform(&mut rng);
+        let shares =
+            <S as Shamir<GF2n<W, NWORDS, A, B, C>>>::split_with_rng(&secret, 3, 5, &mut rng)
+                .unwrap();
+        let dyn_shares: Vec<_> = shares
+            .iter()
+            .map(|s| Share::new_unchecked(s.getx(), GF2nDyn::from_static(s.y()).unwrap()))
+            .collect();
+        assert_eq!(
+            reconstruct(&dyn_shares[2..], 3).unwrap().to_static(),
+            Some(secret)
+        );
+        let dyn_secret = GF2nDyn::from_static(&secret).unwrap();
+        let dyn_shares = split_with_rng(&dyn_secret, 3, 5, &mut rng).unwrap();
+        let shares: Vec<_> = dyn_shares
+            .iter()
+            .map(|s| {
+                let (&x, y) = (s.x(), s.y());
+                Share::<u8, _>::new(x, y.to_static().unwrap()).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            <S as Shamir<GF2n<W, NWORDS, A, B, C>>>::reconstruct(&shares[1..], 3),
+            Ok(secret)
+        );
+    }
+
+    #[test]
+    fn matches_static_fields() {
+        check_static::<u8, 1, 4, 3, 1>();
+        check_static::<u16, 1, 5, 3, 1>();
+        check_static::<u32, 1, 7, 3, 2>();
+        check_static::<u64, 1, 4, 3, 1>();
+        check_static::<u64, 2, 7, 2, 1>();
+        check_static::<u32, 5, 5, 3, 2>();
+        check_static::<u32, 7, 9, 8, 3>();
+        check_static::<u64, 4, 10, 5, 2>();
+        check_static::<u64, 32, 19, 14, 13>();
+        assert_eq!(
+            GF2nDyn::from_static(&crate::gf2n::GF64u32::ONE),
+            Some(DynField::new(64).unwrap().one())
+        );
+    }
+
+    #[test]
+    fn can_split_and_reconstruct() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        for nbits in DynField::supported_sizes() {
+            let field = DynField::new(nbits).unwrap();
+            let secret = field.uniform(&mut rng);
+            let shares = split_with_rng(&secret, 3, 5, &mut rng).unwrap();
+            assert_eq!(reconstruct(&shares, 3), Ok(secret.clone()));
+            assert_eq!(reconstruct(&shares[2..], 3), Ok(secret));
+            assert_eq!(reconstruct(&shares, 6), Err(ShamirError::NotEnoughShares));
+        }
+        assert_eq!(DynField::new(100), None);
+    }
+
+    #[test]
+    fn cannot_mix_fields() {
+        let a = DynField::new(64).unwrap().one();
+        let b = DynField::new(128).unwrap().one();
+        let shares = [
+            Share::new_unchecked(1, a.clone()),
+            Share::new_unchecked(2, b),
+        ];
+        assert_eq!(reconstruct(&shares, 2), Err(ShamirError::DifferentFields));
+        let shares = [
+            Share::new_unchecked(1, a.clone()),
+            Share::new_unchecked(1, a),
+        ];
+        assert_eq!(reconstruct(&shares, 2), Err(ShamirError::DuplicateX));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot combine elements of different fields")]
+    fn cannot_multiply_different_fields() {
+        let _ = &DynField::new(64).unwrap().one() * &DynField::new(128).unwrap().one();
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn can_parse_shares() {
+        let share = parse_share("3|00000000000000010000000000000002").unwrap();
+        let (&x, y) = (share.x(), share.y());
+        assert_eq!(x, 3);
+        assert_eq!(y.field().nbits(), 128);
+        assert_eq!(share.to_string(), "3|00000000000000010000000000000002");
+        assert_eq!(parse_share("0|01"), Err(ShamirError::InvalidX));
+        assert_eq!(parse_share("1|012"), Err(ShamirError::Parse));
+    }
+}
@@ -0,0 +1,748 @@
+//! Generic implementation of a prime field GF(p), using Montgomery multiplication.
+//!
+//! Elements are stored in Montgomery form, i.e. `x` is represented by `x * R mod p` where
+//! `R = 2^(64 * NLIMBS)`, as little-endian arrays of 64-bit limbs. The Montgomery constants are
+//! derived from the modulus at compile time, so that supporting a new prime field only requires
+//! implementing the `Modulus` trait.
+
+use crate::field::{decode_hex, fmt_hex, Field};
+use crate::shamir::ShamirError;
+use core::convert::TryInto;
+use core::fmt::{Debug, Display, LowerHex, UpperHex};
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Sub};
+use core::str::FromStr;
+use rand::{CryptoRng, Rng};
+
+/// Trait for odd prime moduli that fit in `NLIMBS` 64-bit limbs.
+pub trait Modulus<const NLIMBS: usize> {
+    /// The prime modulus, as little-endian 64-bit limbs.
+    const P: [u64; NLIMBS];
+}
+
+/// Order of the secp256k1 elliptic curve group, i.e. the modulus of secp256k1 private keys:
+/// `2^256 - 0x14551231950b75fc4402da1732fc9bebf`.
+pub struct Secp256k1Order;
+
+impl Modulus<4> for Secp256k1Order {
+    const P: [u64; 4] = [
+        0xbfd25e8cd0364141,
+        0xbaaedce6af48a03b,
+        0xfffffffffffffffe,
+        0xffffffffffffffff,
+    ];
+}
+
+/// Order of the Ed25519 prime-order subgroup, i.e. the modulus of Ed25519 scalars:
+/// `2^252 + 0x14def9dea2f79cd65812631a5cf5d3ed`.
+pub struct Ed25519Order;
+
+impl Modulus<4> for Ed25519Order {
+    const P: [u64; 4] = [
+        0x5812631a5cf5d3ed,
+        0x14def9dea2f79cd6,
+        0x0000000000000000,
+        0x1000000000000000,
+    ];
+}
+
+/// Implementation of a prime field GF(p), with the modulus `p` given by `M`.
+pub struct GFp<M: Modulus<NLIMBS>, const NLIMBS: usize> {
+    limbs: [u64; NLIMBS],
+    modulus: PhantomData<M>,
+}
+
+/// Scalar field of the secp256k1 elliptic curve.
+pub type Secp256k1Scalar = GFp<Secp256k1Order, 4>;
+/// Scalar field of the Ed25519 elliptic curve.
+pub type Ed25519Scalar = GFp<Ed25519Order, 4>;
+
+/// Returns whether `a < b`.
+const fn less_than<const NLIMBS: usize>(a: &[u64; NLIMBS], b: &[u64; NLIMBS]) -> bool {
+    let mut i = NLIMBS;
+    while i > 0 {
+        i -= 1;
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+/// Computes `a - b`, wrapping around `2^(64 * NLIMBS)`, and returns whether a borrow occurred.
+const fn sub_limbs<const NLIMBS: usize>(
+    mut a: [u64; NLIMBS],
+    b: &[u64; NLIMBS],
+) -> ([u64; NLIMBS], bool) {
+    let mut borrow = false;
+    let mut i = 0;
+    while i < NLIMBS {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow as u64);
+        a[i] = diff;
+        borrow = b1 | b2;
+        i += 1;
+    }
+    (a, borrow)
+}
+
+/// Computes `a + b`, wrapping around `2^(64 * NLIMBS)`, and returns whether a carry occurred.
+const fn add_limbs<const NLIMBS: usize>(
+    mut a: [u64; NLIMBS],
+    b: &[u64; NLIMBS],
+) -> ([u64; NLIMBS], bool) {
+    let mut carry = false;
+    let mut i = 0;
+    while i < NLIMBS {
+        let (sum, c1) = a[i].overflowing_add(b[i]);
+        let (sum, c2) = sum.overflowing_add(carry as u64);
+        a[i] = sum;
+        carry = c1 | c2;
+        i += 1;
+    }
+    (a, carry)
+}
+
+/// Computes `2 * a mod p`, assuming that `a < p`.
+const fn double_mod<const NLIMBS: usize>(a: [u64; NLIMBS], p: &[u64; NLIMBS]) -> [u64; NLIMBS] {
+    let (a, carry) = add_limbs(a, &a);
+    if carry || !less_than(&a, p) {
+        sub_limbs(a, p).0
+    } else {
+        a
+    }
+}
+
+/// Computes `2^shift * a mod p`, assuming that `a < p`.
+const fn shl_mod<const NLIMBS: usize>(
+    mut a: [u64; NLIMBS],
+    p: &[u64; NLIMBS],
+    shift: usize,
+) -> [u64; NLIMBS] {
+    let mut i = 0;
+    while i < shift {
+        a = double_mod(a, p);
+        i += 1;
+    }
+    a
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> GFp<M, NLIMBS> {
+    #[cfg(any(test, not(feature = "debug-values")))]
+    const NBITS: usize = 64 * NLIMBS - M::P[NLIMBS - 1].leading_zeros() as usize;
+    const NBYTES: usize = 8 * NLIMBS;
+
+    /// `-p^-1 mod 2^64`, computed by Newton iteration (each step doubles the number of correct
+    /// low bits).
+    const PINV: u64 = {
+        let p0 = M::P[0];
+        let mut inv: u64 = 1;
+        let mut i = 0;
+        while i < 6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(p0.wrapping_mul(inv)));
+            i += 1;
+        }
+        inv.wrapping_neg()
+    };
+    /// `R mod p`, i.e. the Montgomery form of one.
+    const R: [u64; NLIMBS] = {
+        let mut one = [0; NLIMBS];
+        one[0] = 1;
+        shl_mod(one, &M::P, 64 * NLIMBS)
+    };
+    /// `R^2 mod p`, used to convert into Montgomery form.
+    const R2: [u64; NLIMBS] = shl_mod(Self::R, &M::P, 64 * NLIMBS);
+
+    /// Creates an element from its canonical representation. Returns `None` if the value isn't
+    /// reduced modulo `p`.
+    pub(crate) fn from_limbs(limbs: [u64; NLIMBS]) -> Option<Self> {
+        if !less_than(&limbs, &M::P) {
+            return None;
+        }
+        Some(Self::from_montgomery(Self::mont_mul(&limbs, &Self::R2)))
+    }
+
+    /// Creates an element from its canonical big-endian bytes. Returns `None` if the length
+    /// doesn't match the field or if the value isn't reduced modulo `p`.
+    fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::NBYTES {
+            return None;
+        }
+
+        let mut limbs = [0u64; NLIMBS];
+        for (i, chunk) in bytes.chunks(8).rev().enumerate() {
+            limbs[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Self::from_limbs(limbs)
+    }
+
+    /// Returns the canonical representation of this element.
+    pub(crate) fn to_limbs(self) -> [u64; NLIMBS] {
+        let mut one = [0; NLIMBS];
+        one[0] = 1;
+        Self::mont_mul(&self.limbs, &one)
+    }
+
+    const fn from_montgomery(limbs: [u64; NLIMBS]) -> Self {
+        Self {
+            limbs,
+            modulus: PhantomData,
+        }
+    }
+
+    /// Computes `a * b * R^-1 mod p`, with the coarsely integrated operand scanning (CIOS)
+    /// method.
+    #[allow(clippy::needless_range_loop)]
+    fn mont_mul(a: &[u64; NLIMBS], b: &[u64; NLIMBS]) -> [u64; NLIMBS] {
+        let p = &M::P;
+        let mut t = [0u64; NLIMBS];
+        let mut t_hi: u64 = 0;
+        for i in 0..NLIMBS {
+            // t += a * b[i]
+            let mut carry: u64 = 0;
+            for j in 0..NLIMBS {
+                let uv = t[j] as u128 + (a[j] as u128) * (b[i] as u128) + carry as u128;
+                t[j] = uv as u64;
+                carry = (uv >> 64) as u64;
+            }
+            let uv = t_hi as u128 + carry as u128;
+            t_hi = uv as u64;
+            let t_top = (uv >> 64) as u64;
+
+            // t = (t + m * p) / 2^64, where m is chosen so that the division is exact.
+            let m = t[0].wrapping_mul(Self::PINV);
+            let uv = t[0] as u128 + (m as u128) * (p[0] as u128);
+            let mut carry = (uv >> 64) as u64;
+            for j in 1..NLIMBS {
+                let uv = t[j] as u128 + (m as u128) * (p[j] as u128) + carry as u128;
+                t[j - 1] = uv as u64;
+                carry = (uv >> 64) as u64;
+            }
+            let uv = t_hi as u128 + carry as u128;
+            t[NLIMBS - 1] = uv as u64;
+            t_hi = t_top + (uv >> 64) as u64;
+        }
+
+        if t_hi != 0 || !less_than(&t, p) {
+            t = sub_limbs(t, p).0;
+        }
+        t
+    }
+
+    #[cfg(test)]
+    fn get_nonzero_test_values() -> Vec<Self> {
+        let mut values = Vec::new();
+        values.push(Self::ONE);
+        values.push(Self::ZERO - Self::ONE);
+
+        let mut power = Self::ONE;
+        for _ in 0..Self::NBITS {
+            power = power + power;
+            values.push(power);
+            values.push(Self::ZERO - power);
+        }
+
+        values
+    }
+
+    #[cfg(test)]
+    fn get_test_values() -> Vec<Self> {
+        let mut values = Self::get_nonzero_test_values();
+        values.push(Self::ZERO);
+        values
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Clone for GFp<M, NLIMBS> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Copy for GFp<M, NLIMBS> {}
+
+/// `Debug` implementation printing the value, with the `debug-values` feature.
+#[cfg(feature = "debug-values")]
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Debug for GFp<M, NLIMBS> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_fmt(format_args!("{:016x?}", self.to_limbs()))
+    }
+}
+
+/// Redacted `Debug` implementation, which only prints the size of the field (and a truncated
+/// fingerprint of the value with the `redact-debug` feature).
+#[cfg(not(feature = "debug-values"))]
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Debug for GFp<M, NLIMBS> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        crate::field::fmt_redacted(f, format_args!("GFp{}", Self::NBITS), self)
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Display for GFp<M, NLIMBS> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for d in self.to_limbs().iter().rev() {
+            f.write_fmt(format_args!("{d:016x}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses an element from its hexadecimal representation, as printed by `Display`. Upper case
+/// digits are accepted too, but integers that aren't reduced modulo `p` are rejected.
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> FromStr for GFp<M, NLIMBS> {
+    type Err = ShamirError;
+
+    fn from_str(s: &str) -> Result<Self, ShamirError> {
+        decode_hex(s)
+            .and_then(|bytes| Self::from_be_bytes(&bytes))
+            .ok_or(ShamirError::Parse)
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> LowerHex for GFp<M, NLIMBS> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt_hex(&self.to_bytes(), f, false)
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> UpperHex for GFp<M, NLIMBS> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt_hex(&self.to_bytes(), f, true)
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> PartialEq for GFp<M, NLIMBS> {
+    fn eq(&self, other: &Self) -> bool {
+        self.limbs == other.limbs
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Eq for GFp<M, NLIMBS> {}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Hash for GFp<M, NLIMBS> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.limbs.hash(state)
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Default for GFp<M, NLIMBS> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> zeroize::DefaultIsZeroes for GFp<M, NLIMBS> {}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Field for GFp<M, NLIMBS> {
+    const ZERO: Self = Self::from_montgomery([0; NLIMBS]);
+    const ONE: Self = Self::from_montgomery(Self::R);
+
+    fn uniform<R: Rng + CryptoRng + ?Sized>(rng: &mut R) -> Self {
+        // Rejection sampling among values of the same bit length as p.
+        let mask = !0u64 >> M::P[NLIMBS - 1].leading_zeros();
+        loop {
+            let mut limbs = [0u64; NLIMBS];
+            for limb in limbs.iter_mut() {
+                *limb = rng.gen();
+            }
+            limbs[NLIMBS - 1] &= mask;
+            if let Some(x) = Self::from_limbs(limbs) {
+                return x;
+            }
+        }
+    }
+
+    fn invert(self) -> Self {
+        // Compute x^(p - 2)
+        let mut two = [0; NLIMBS];
+        two[0] = 2;
+        let exponent = sub_limbs(M::P, &two).0;
+
+        // Fixed 4-bit window: the 16 powers x^0, ..., x^15 are precomputed, and the exponent is
+        // processed 4 bits at a time. This takes one multiplication per 4 bits of the exponent,
+        // instead of one per set bit with square-and-multiply.
+        let mut powers = [Self::ONE; 16];
+        for i in 1..16 {
+            powers[i] = powers[i - 1] * &self;
+        }
+
+        let mut result = Self::ONE;
+        for limb in exponent.iter().rev() {
+            for i in (0..16).rev() {
+                for _ in 0..4 {
+                    result = result * &result;
+                }
+                result *= &powers[((limb >> (4 * i)) & 0xf) as usize];
+            }
+        }
+        result
+    }
+
+    fn from_diff(lhs: u8, rhs: u8) -> Self {
+        Self::from(lhs) - Self::from(rhs)
+    }
+
+    #[cfg(feature = "parse")]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::from_be_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> alloc::vec::Vec<u8> {
+        self.to_limbs()
+            .iter()
+            .rev()
+            .flat_map(|d| d.to_be_bytes())
+            .collect()
+    }
+}
+
+/// Serializes the element as its canonical big-endian bytes, in the same order as the hexadecimal
+/// representation.
+#[cfg(feature = "serde")]
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> serde::Serialize for GFp<M, NLIMBS> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, M: Modulus<NLIMBS>, const NLIMBS: usize> serde::Deserialize<'de> for GFp<M, NLIMBS> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(crate::field::BytesVisitor(PhantomData))
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> From<u8> for GFp<M, NLIMBS> {
+    fn from(word: u8) -> Self {
+        let mut limbs = [0; NLIMBS];
+        limbs[0] = word as u64;
+        Self::from_montgomery(Self::mont_mul(&limbs, &Self::R2))
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Add for GFp<M, NLIMBS> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (limbs, carry) = add_limbs(self.limbs, &other.limbs);
+        if carry || !less_than(&limbs, &M::P) {
+            Self::from_montgomery(sub_limbs(limbs, &M::P).0)
+        } else {
+            Self::from_montgomery(limbs)
+        }
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> AddAssign<&Self> for GFp<M, NLIMBS> {
+    fn add_assign(&mut self, other: &Self) {
+        *self = *self + *other;
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Sub for GFp<M, NLIMBS> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        let (limbs, borrow) = sub_limbs(self.limbs, &other.limbs);
+        if borrow {
+            Self::from_montgomery(add_limbs(limbs, &M::P).0)
+        } else {
+            Self::from_montgomery(limbs)
+        }
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> Mul<&Self> for GFp<M, NLIMBS> {
+    type Output = Self;
+
+    fn mul(self, other: &Self) -> Self {
+        Self::from_montgomery(Self::mont_mul(&self.limbs, &other.limbs))
+    }
+}
+
+impl<M: Modulus<NLIMBS>, const NLIMBS: usize> MulAssign<&Self> for GFp<M, NLIMBS> {
+    fn mul_assign(&mut self, other: &Self) {
+        *self = *self * other;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    macro_rules! for_field {
+        ( $mod:ident, $field:ident, $modulus:ident, $($tests:tt)* ) => {
+            mod $mod {
+                type F = super::super::$field;
+                type M = super::super::$modulus;
+                $($tests)*
+            }
+        }
+    }
+
+    macro_rules! for_all {
+        ( $($tests:tt)* ) => {
+            for_field!(secp256k1, Secp256k1Scalar, Secp256k1Order, $($tests)*);
+            for_field!(ed25519, Ed25519Scalar, Ed25519Order, $($tests)*);
+        };
+    }
+
+    /// Parses a big-endian hexadecimal string into little-endian limbs.
+    fn limbs<const NLIMBS: usize>(hex: &str) -> [u64; NLIMBS] {
+        assert_eq!(hex.len(), 16 * NLIMBS);
+        let mut limbs = [0; NLIMBS];
+        for (i, limb) in limbs.iter_mut().rev().enumerate() {
+            *limb = u64::from_str_radix(&hex[16 * i..16 * (i + 1)], 16).unwrap();
+        }
+        limbs
+    }
+
+    /// Checks `a * b` and `a^-1` against values computed with arbitrary-precision arithmetic.
+    fn check_known_answers<M: super::Modulus<4>>(expected: [&str; 4]) {
+        use crate::field::Field;
+
+        let a = super::GFp::<M, 4>::from_limbs(limbs(expected[0])).unwrap();
+        let b = super::GFp::<M, 4>::from_limbs(limbs(expected[1])).unwrap();
+        assert_eq!((a * &b).to_string(), expected[2]);
+        assert_eq!(a.invert().to_string(), expected[3]);
+    }
+
+    #[test]
+    fn secp256k1_known_answers() {
+        check_known_answers::<super::Secp256k1Order>([
+            "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210",
+            "a5393281d581eac38aa0b5b7a460398562c086099ee7fe5700c013d19c7b1d99",
+            "2b359de5cfb5937a5610d565dceaef2a760ceeaec96e68140757f0c8371534e0",
+        ]);
+    }
+
+    #[test]
+    fn ed25519_known_answers() {
+        check_known_answers::<super::Ed25519Order>([
+            "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+            "0edcba9876543210fedcba987654320fc5cc168ce9d20181d5c8ec0d03ecc72d",
+            "0e727e8ed54e7d1e9daab69447142133ff385bcb2e0092cd11907e19d2df78b8",
+            "0df129f4c628fe6addcf9d8b83ebdf2ac73f997b636e3251533d6172b8d7f668",
+        ]);
+    }
+
+    for_all! {
+        use crate::field::Field;
+        use crate::shamir::ShamirError;
+        use super::super::Modulus;
+
+        #[test]
+        fn modulus_is_not_reduced() {
+            assert_eq!(F::from_limbs(M::P), None);
+            let (p_minus_one, _) = super::super::sub_limbs(M::P, &[1, 0, 0, 0]);
+            assert_eq!(F::from_limbs(p_minus_one), Some(F::ZERO - F::ONE));
+        }
+
+        #[test]
+        fn limbs_round_trip() {
+            let values = F::get_test_values();
+            for &x in &values {
+                assert_eq!(F::from_limbs(x.to_limbs()), Some(x));
+            }
+        }
+
+        #[cfg(feature = "parse")]
+        #[test]
+        fn display_round_trip() {
+            let values = F::get_test_values();
+            for &x in &values {
+                let bytes = hex::decode(x.to_string()).unwrap();
+                assert_eq!(F::from_bytes(&bytes), Some(x));
+                assert_eq!(x.to_bytes(), bytes);
+            }
+        }
+
+        #[test]
+        fn from_str_round_trip() {
+            let values = F::get_test_values();
+            for &x in &values {
+                let hex = format!("{x:x}");
+                assert_eq!(hex, x.to_string());
+                assert_eq!(hex.parse::<F>(), Ok(x));
+                assert_eq!(format!("{x:X}").parse::<F>(), Ok(x));
+                assert_eq!(format!("{x:#x}"), format!("0x{x}"));
+            }
+            let p: String = M::P.iter().rev().map(|d| format!("{d:016x}")).collect();
+            assert_eq!(p.parse::<F>(), Err(ShamirError::Parse));
+            assert_eq!("00".parse::<F>(), Err(ShamirError::Parse));
+        }
+
+        #[cfg(not(feature = "debug-values"))]
+        #[test]
+        fn debug_is_redacted() {
+            let values = F::get_test_values();
+            for &x in &values {
+                #[cfg(not(feature = "redact-debug"))]
+                let expected = format!("GFp{}<redacted>", F::NBITS);
+                #[cfg(feature = "redact-debug")]
+                let expected = format!(
+                    "GFp{}<redacted:{:08x}>",
+                    F::NBITS,
+                    crate::field::redacted_fingerprint(&x)
+                );
+                assert_eq!(format!("{x:?}"), expected);
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn serde() {
+            for x in F::get_test_values() {
+                let json = serde_json::to_string(&x).unwrap();
+                assert_eq!(serde_json::from_str::<F>(&json).unwrap(), x);
+            }
+            // Wrong length.
+            let json = serde_json::to_string(&vec![0u8; F::NBYTES + 1]).unwrap();
+            assert!(serde_json::from_str::<F>(&json).is_err());
+        }
+
+        #[cfg(feature = "zeroize")]
+        #[test]
+        fn zeroize() {
+            use zeroize::Zeroize;
+            for mut x in F::get_test_values() {
+                x.zeroize();
+                assert_eq!(x, F::ZERO);
+            }
+        }
+
+        #[test]
+        fn from_u8() {
+            let mut x = F::ZERO;
+            for i in 0..=255 {
+                assert_eq!(F::from(i), x);
+                x += &F::ONE;
+            }
+        }
+
+        #[test]
+        fn from_diff() {
+            for i in 0..=255 {
+                for j in 0..=255 {
+                    assert_eq!(F::from_diff(i, j) + F::from(j), F::from(i));
+                }
+            }
+        }
+
+        #[test]
+        fn add_is_commutative() {
+            let values = F::get_test_values();
+            for &x in &values {
+                for &y in &values {
+                    assert_eq!(x + y, y + x);
+                }
+            }
+        }
+
+        #[test]
+        fn sub_is_add_inverse() {
+            let values = F::get_test_values();
+            for &x in &values {
+                for &y in &values {
+                    assert_eq!((x - y) + y, x);
+                }
+            }
+        }
+
+        #[test]
+        fn mul_is_commutative() {
+            let values = F::get_test_values();
+            for &x in &values {
+                for &y in &values {
+                    assert_eq!(x * &y, y * &x);
+                }
+            }
+        }
+
+        #[test]
+        fn mul_by_zero() {
+            let values = F::get_test_values();
+            for &x in &values {
+                assert_eq!(x * &F::ZERO, F::ZERO);
+                assert_eq!(F::ZERO * &x, F::ZERO);
+            }
+        }
+
+        #[test]
+        fn mul_by_one() {
+            let values = F::get_test_values();
+            for &x in &values {
+                assert_eq!(x * &F::ONE, x);
+                assert_eq!(F::ONE * &x, x);
+            }
+        }
+
+        #[test]
+        fn mul_many_is_mul() {
+            let values = F::get_test_values();
+            for y in &values {
+                let mut products = values.clone();
+                F::mul_many(&mut products, y);
+                for (x, product) in values.iter().zip(&products) {
+                    assert_eq!(*product, *x * y);
+                }
+                let mut sums = products.clone();
+                F::add_many(&mut sums, &values);
+                for ((x, product), sum) in values.iter().zip(&products).zip(&sums) {
+                    assert_eq!(*sum, *x + *product);
+                }
+            }
+        }
+
+        #[test]
+        fn mul_self_invert() {
+            let values = F::get_nonzero_test_values();
+            for &x in &values {
+                assert_eq!(x * &x.invert(), F::ONE);
+            }
+        }
+
+        #[cfg(not(debug_assertions))]
+        #[test]
+        fn mul_is_distributive() {
+            let values = F::get_test_values();
+            for &x in &values {
+                for &y in &values {
+                    for &z in &values {
+                        assert_eq!(x * &(y + z), x * &y + x * &z);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn uniform_is_reduced() {
+            let mut rng = rand::thread_rng();
+            for _ in 0..1000 {
+                let x = F::uniform(&mut rng);
+                assert_eq!(F::from_limbs(x.to_limbs()), Some(x));
+            }
+        }
+
+        #[cfg(feature = "bench")]
+        use test::Bencher;
+        #[cfg(feature = "bench")]
+        use std::hint::black_box;
+
+        #[cfg(feature = "bench")]
+        #[bench]
+        fn bench_mul(b: &mut Bencher) {
+            let x = F::ZERO - F::ONE;
+            let y = F::ZERO - F::ONE;
+            b.iter(|| black_box(x) * &black_box(y));
+        }
+
+        #[cfg(feature = "bench")]
+        #[bench]
+        fn bench_invert(b: &mut Bencher) {
+            let x = F::ZERO - F::ONE;
+            b.iter(|| black_box(x).invert());
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Two-level threshold sharing, where shares are organized into groups (like the groups of
+//! SLIP-39).
+//!
+//! The secret is first split into one group secret per group, so that a threshold of groups is
+//! needed to reconstruct it. Each group secret is then split among the members of the group, with
+//! a threshold of its own. The secret is reconstructed from enough members of enough groups, e.g.
+//! 2 members out of 3 in the first group and 3 members out of 5 in the second group.
+
+use crate::field::Field;
+use crate::shamir::{CompactShamir, GetX, Shamir, ShamirError, Share};
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand::{CryptoRng, Rng};
+
+/// Parameters of a group: the number of members and the threshold among them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Group {
+    /// Minimum number of members needed to reconstruct the group secret.
+    pub threshold: usize,
+    /// Number of members of the group.
+    pub count: usize,
+}
+
+/// Formats the group as `<threshold>of<count>`, e.g. `2of3`.
+impl Display for Group {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}of{}", self.threshold, self.count)
+    }
+}
+
+/// Parses a group in the `<threshold>of<count>` format, e.g. `2of3`.
+impl FromStr for Group {
+    type Err = ShamirError;
+
+    fn from_str(s: &str) -> Result<Self, ShamirError> {
+        let (threshold, count) = s.split_once("of").ok_or(ShamirError::Parse)?;
+        let parse = |s: &str| s.parse::<usize>().map_err(|_| ShamirError::Parse);
+        Ok(Group {
+            threshold: parse(threshold)?,
+            count: parse(count)?,
+        })
+    }
+}
+
+/// Share of a member of a group, with the index of the group (starting at 1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupShare<T> {
+    /// Index of the group, starting at 1.
+    pub group: u8,
+    /// Share of the group secret.
+    pub share: T,
+}
+
+/// Formats the share as the index of the group and the member's share, separated by a slash.
+impl<T: Display> Display for GroupShare<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}/{}", self.group, self.share)
+    }
+}
+
+/// Two-level Shamir's Secret Sharing, where the members of each group hold shares of type
+/// `S::Share`.
+pub struct GroupShamir;
+
+impl GroupShamir {
+    /// Splits a secret among the given groups, with `group_threshold` groups being sufficient to
+    /// reconstruct it. Returns the shares of the members, group after group, or an error if the
+    /// parameters are out of range.
+    #[cfg(feature = "std")]
+    pub fn split<F: Field + Debug + Display, S: Shamir<F> + ?Sized>(
+        secret: &F,
+        group_threshold: usize,
+        groups: &[Group],
+    ) -> Result<Vec<GroupShare<S::Share>>, ShamirError> {
+        Self::split_with_rng::<F, S, _>(secret, group_threshold, groups, &mut thread_rng())
+    }
+
+    /// Splits a secret among the given groups like `split()`, but drawing all the randomness from
+    /// the given random number generator.
+    pub fn split_with_rng<
+        F: Field + Debug + Display,
+        S: Shamir<F> + ?Sized,
+        R: Rng + CryptoRng + ?Sized,
+    >(
+        secret: &F,
+        group_threshold: usize,
+        groups: &[Group],
+        rng: &mut R,
+    ) -> Result<Vec<GroupShare<S::Share>>, ShamirError> {
+        // The group secrets are compact shares of the secret, at the indices of the groups.
+        let group_secrets =
+            CompactShamir::split_with_rng(secret, group_threshold, groups.len(), rng)?;
+        let mut shares = Vec::with_capacity(groups.iter().map(|g| g.count).sum());
+        for (group, group_secret) in groups.iter().zip(&group_secrets) {
+            let members = S::split_with_rng(group_secret.y(), group.threshold, group.count, rng)?;
+            shares.extend(members.into_iter().map(|share| GroupShare {
+                group: group_secret.getx(),
+                share,
+            }));
+        }
+        Ok(shares)
+    }
+
+    /// Reconstructs a secret from the shares of the members of the given groups. Returns an error
+    /// if fewer than `group_threshold` groups have enough members, or if a share belongs to an
+    /// unknown group.
+    pub fn reconstruct<F: Field + Debug + Display, S: Shamir<F> + ?Sized>(
+        shares: &[GroupShare<S::Share>],
+        group_threshold: usize,
+        groups: &[Group],
+    ) -> Result<F, ShamirError> {
+        if shares
+            .iter()
+            .any(|share| share.group == 0 || share.group as usize > groups.len())
+        {
+            return Err(ShamirError::InvalidX);
+        }
+
+        let mut group_secrets = Vec::with_capacity(group_threshold);
+        for (index, group) in (1..=groups.len() as u8).zip(groups) {
+            if group_secrets.len() == group_threshold {
+                break;
+            }
+            let members: Vec<S::Share> = shares
+                .iter()
+                .filter(|share| share.group == index)
+                .map(|share| share.share)
+                .collect();
+            if members.len() >= group.threshold {
+                let group_secret = S::reconstruct(&members, group.threshold)?;
+                group_secrets.push(Share::<u8, F>::new(index, group_secret)?);
+            }
+        }
+        CompactShamir::reconstruct(&group_secrets, group_threshold)
+    }
+
+    /// Parses the share of a member from a string. Returns an error if the parsing fails.
+    #[cfg(feature = "parse")]
+    pub fn parse_share<F: Field + Debug + Display, S: Shamir<F> + ?Sized>(
+        s: &str,
+    ) -> Result<GroupShare<S::Share>, ShamirError> {
+        let (group, share) = s.split_once('/').ok_or(ShamirError::Parse)?;
+        let group = group.parse::<u8>().map_err(|_| ShamirError::Parse)?;
+        if group == 0 {
+            return Err(ShamirError::InvalidX);
+        }
+        Ok(GroupShare {
+            group,
+            share: S::parse_share(share)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gf2n::GF256;
+    use crate::shamir::RandomShamir;
+
+    const GROUPS: [Group; 3] = [
+        Group {
+            threshold: 2,
+            count: 3,
+        },
+        Group {
+            threshold: 3,
+            count: 5,
+        },
+        Group {
+            threshold: 1,
+            count: 1,
+        },
+    ];
+
+    #[test]
+    fn can_parse_groups() {
+        for group in &GROUPS {
+            assert_eq!(group.to_string().parse::<Group>(), Ok(*group));
+        }
+        assert_eq!("2of3".parse::<Group>(), Ok(GROUPS[0]));
+        assert_eq!("2/3".parse::<Group>(), Err(ShamirError::Parse));
+        assert_eq!("of3".parse::<Group>(), Err(ShamirError::Parse));
+    }
+
+    #[test]
+    fn can_reconstruct_compact() {
+        can_reconstruct::<CompactShamir>();
+    }
+
+    #[test]
+    fn can_reconstruct_random() {
+        can_reconstruct::<RandomShamir>();
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn can_parse_compact() {
+        can_parse::<CompactShamir>();
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn can_parse_random() {
+        can_parse::<RandomShamir>();
+    }
+
+    #[test]
+    fn can_reject_invalid_parameters() {
+        let secret = GF256::uniform(&mut thread_rng());
+        assert_eq!(
+            GroupShamir::split::<GF256, CompactShamir>(&secret, 4, &GROUPS),
+            Err(ShamirError::InvalidThreshold)
+        );
+        let invalid = [Group {
+            threshold: 3,
+            count: 2,
+        }];
+        assert_eq!(
+            GroupShamir::split::<GF256, CompactShamir>(&secret, 1, &invalid),
+            Err(ShamirError::InvalidThreshold)
+        );
+    }
+
+    fn can_reconstruct<S: Shamir<GF256>>() {
+        let secret = GF256::uniform(&mut thread_rng());
+        let shares = GroupShamir::split::<GF256, S>(&secret, 2, &GROUPS).unwrap();
+        assert_eq!(shares.len(), 9);
+        let (first, rest) = shares.split_at(3);
+        let (second, third) = rest.split_at(5);
+
+        let reconstruct = |shares: &[GroupShare<S::Share>]| {
+            GroupShamir::reconstruct::<GF256, S>(shares, 2, &GROUPS)
+        };
+        // Any two groups with enough members are sufficient.
+        assert_eq!(
+            reconstruct(&[&first[1..], &second[..3]].concat()),
+            Ok(secret)
+        );
+        assert_eq!(reconstruct(&[&second[2..], third].concat()), Ok(secret));
+        assert_eq!(reconstruct(&[&first[..2], third].concat()), Ok(secret));
+        assert_eq!(reconstruct(&shares), Ok(secret));
+        // A single group isn't sufficient, even with all its members.
+        assert_eq!(reconstruct(second), Err(ShamirError::NotEnoughShares));
+        // Neither are groups below their threshold.
+        assert_eq!(
+            reconstruct(&[&first[..1], &second[..2], third].concat()),
+            Err(ShamirError::NotEnoughShares)
+        );
+
+        let mut unknown = shares[0];
+        unknown.group = 4;
+        assert_eq!(reconstruct(&[unknown]), Err(ShamirError::InvalidX));
+    }
+
+    #[cfg(feature = "parse")]
+    fn can_parse<S: Shamir<GF256>>()
+    where
+        S::Share: Display,
+    {
+        let secret = GF256::uniform(&mut thread_rng());
+        let shares = GroupShamir::split::<GF256, S>(&secret, 2, &GROUPS).unwrap();
+        for share in &shares {
+            let text = share.to_string();
+            assert_eq!(GroupShamir::parse_share::<GF256, S>(&text), Ok(*share));
+            let (_, member) = text.split_once('/').unwrap();
+            assert_eq!(
+                GroupShamir::parse_share::<GF256, S>(member),
+                Err(ShamirError::Parse)
+            );
+            assert_eq!(
+                GroupShamir::parse_share::<GF256, S>(&format!("0/{member}")),
+                Err(ShamirError::InvalidX)
+            );
+        }
+    }
+}
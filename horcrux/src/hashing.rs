@@ -0,0 +1,346 @@
+//! Hash functions behind the integrity features: fingerprints and commitments to secrets, digests of
+//! share sets, authentication tags of records, and the key derivations of sealed files.
+//!
+//! SHA-256 is always available, and BLAKE3 is available with the `blake3` feature. Artifacts that
+//! depend on a hash record the identifier of its algorithm (see `HashAlgorithm::id()`), so that they
+//! can be verified without knowing how they were created. Artifacts written before the identifiers
+//! were introduced don't have one and use SHA-256, which therefore remains the default algorithm.
+
+#[cfg(feature = "blake3")]
+use core::convert::TryInto;
+use core::fmt::{Display, Write};
+use core::str::FromStr;
+use hmac::digest::KeyInit;
+#[cfg(feature = "blake3")]
+use hmac::digest::{
+    consts::U32, crypto_common::KeySizeUser, FixedOutput, InvalidLength, Key, MacMarker, Output,
+    OutputSizeUser,
+};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+/// Length of the hashes, in bytes.
+pub const HASH_LEN: usize = 32;
+
+/// Hash algorithm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    /// SHA-256.
+    #[default]
+    Sha256,
+    /// BLAKE3, with 256-bit outputs.
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+/// Error returned when parsing the identifier of an algorithm that isn't supported, either because
+/// it's unknown or because its feature isn't enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedAlgorithm(pub String);
+
+impl Display for UnsupportedAlgorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_fmt(format_args!("unsupported hash algorithm: {}", self.0))
+    }
+}
+
+impl std::error::Error for UnsupportedAlgorithm {}
+
+impl HashAlgorithm {
+    /// All the supported algorithms.
+    pub const ALL: &'static [HashAlgorithm] = &[
+        HashAlgorithm::Sha256,
+        #[cfg(feature = "blake3")]
+        HashAlgorithm::Blake3,
+    ];
+
+    /// Returns the identifier of the algorithm in artifacts, i.e. `sha256` or `blake3`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Returns whether this is the default algorithm, whose identifier can be omitted.
+    pub fn is_default(&self) -> bool {
+        *self == HashAlgorithm::default()
+    }
+
+    /// Starts an incremental hash.
+    pub fn hasher(&self) -> Hasher {
+        Hasher(match self {
+            HashAlgorithm::Sha256 => HasherInner::Sha256(Sha256::new()),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => HasherInner::Blake3(Box::default()),
+        })
+    }
+
+    /// Hashes the given data.
+    pub fn digest(&self, data: &[u8]) -> [u8; HASH_LEN] {
+        self.hasher().chain_update(data).finalize()
+    }
+
+    /// Computes the authentication tag of the concatenation of the data with the given key:
+    /// HMAC-SHA256 (RFC 2104) for SHA-256, and the keyed mode of BLAKE3 for BLAKE3. Keys of any
+    /// length are supported, keys that don't fit the algorithm are hashed first.
+    pub fn mac(&self, key: &[u8], data: &[&[u8]]) -> [u8; HASH_LEN] {
+        match self {
+            HashAlgorithm::Sha256 => keyed::<Hmac<Sha256>>(key, data),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => keyed::<Blake3Mac>(key, data),
+        }
+    }
+
+    /// Derives a key from a password and a salt with PBKDF2 (RFC 8018), restricted to a single
+    /// output block, using `mac()` as the pseudo-random function.
+    pub fn pbkdf2(&self, password: &[u8], salt: &[u8], iterations: u32) -> [u8; HASH_LEN] {
+        let mut key = [0; HASH_LEN];
+        match self {
+            HashAlgorithm::Sha256 => {
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(password, salt, iterations, &mut key)
+            }
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => {
+                pbkdf2::pbkdf2::<Blake3Mac>(password, salt, iterations, &mut key)
+            }
+        }
+        .expect("Keys of any length are supported");
+        key
+    }
+}
+
+/// Computes the authentication tag of the concatenation of the data with the given MAC.
+fn keyed<M: Mac + KeyInit>(key: &[u8], data: &[&[u8]]) -> [u8; HASH_LEN] {
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("Keys of any length are supported");
+    for d in data {
+        Mac::update(&mut mac, d);
+    }
+    let mut tag = [0; HASH_LEN];
+    tag.copy_from_slice(&mac.finalize().into_bytes());
+    tag
+}
+
+/// The keyed mode of BLAKE3, as a MAC of the `hmac` crate for `pbkdf2`. Keys that aren't 32 bytes
+/// long are hashed first.
+#[cfg(feature = "blake3")]
+#[derive(Clone)]
+struct Blake3Mac(blake3::Hasher);
+
+#[cfg(feature = "blake3")]
+impl KeySizeUser for Blake3Mac {
+    type KeySize = U32;
+}
+
+#[cfg(feature = "blake3")]
+impl KeyInit for Blake3Mac {
+    fn new(key: &Key<Self>) -> Self {
+        Blake3Mac(blake3::Hasher::new_keyed(key.as_ref()))
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        let mut key: [u8; HASH_LEN] = match key.try_into() {
+            Ok(key) => key,
+            Err(_) => *blake3::hash(key).as_bytes(),
+        };
+        let mac = Blake3Mac(blake3::Hasher::new_keyed(&key));
+        wipe(&mut key);
+        Ok(mac)
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl hmac::digest::Update for Blake3Mac {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl OutputSizeUser for Blake3Mac {
+    type OutputSize = U32;
+}
+
+#[cfg(feature = "blake3")]
+impl FixedOutput for Blake3Mac {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(self.0.finalize().as_bytes());
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl MacMarker for Blake3Mac {}
+
+/// Formats the algorithm as its identifier.
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = UnsupportedAlgorithm;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HashAlgorithm::ALL
+            .iter()
+            .find(|algorithm| algorithm.id() == s)
+            .copied()
+            .ok_or_else(|| UnsupportedAlgorithm(s.to_owned()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for HashAlgorithm {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for HashAlgorithm {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Incremental hash, created by `HashAlgorithm::hasher()`. Values can also be formatted directly
+/// into the hash with `write!`, without intermediate copies.
+#[derive(Clone)]
+pub struct Hasher(HasherInner);
+
+/// The state of BLAKE3 is much larger than that of SHA-256, so it is boxed.
+#[derive(Clone)]
+enum HasherInner {
+    Sha256(Sha256),
+    #[cfg(feature = "blake3")]
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    /// Adds data to the hash.
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        match &mut self.0 {
+            HasherInner::Sha256(hasher) => hasher.update(data),
+            #[cfg(feature = "blake3")]
+            HasherInner::Blake3(hasher) => {
+                hasher.update(data.as_ref());
+            }
+        }
+    }
+
+    /// Adds data to the hash, and returns the hasher.
+    pub fn chain_update(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.update(data);
+        self
+    }
+
+    /// Returns the hash of the data added so far.
+    pub fn finalize(self) -> [u8; HASH_LEN] {
+        match self.0 {
+            HasherInner::Sha256(hasher) => hasher.finalize().into(),
+            #[cfg(feature = "blake3")]
+            HasherInner::Blake3(hasher) => *hasher.finalize().as_bytes(),
+        }
+    }
+}
+
+impl Write for Hasher {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.update(s);
+        Ok(())
+    }
+}
+
+/// Wipes intermediate keys.
+#[cfg(feature = "blake3")]
+fn wipe(bytes: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(bytes);
+    #[cfg(not(feature = "zeroize"))]
+    bytes.iter_mut().for_each(|b| *b = 0);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sha256_test_vectors() {
+        assert_eq!(
+            hex::encode(HashAlgorithm::Sha256.digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // RFC 4231, test cases 2 and 6.
+        assert_eq!(
+            hex::encode(
+                HashAlgorithm::Sha256.mac(b"Jefe", &[b"what do ya want ", b"for nothing?"])
+            ),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(HashAlgorithm::Sha256.mac(
+                &[0xaa; 131],
+                &[b"Test Using Larger Than Block-Size Key - Hash Key First"]
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+        // RFC 7914, section 11.
+        assert_eq!(
+            hex::encode(HashAlgorithm::Sha256.pbkdf2(b"passwd", b"salt", 1)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn blake3_test_vectors() {
+        assert_eq!(
+            hex::encode(HashAlgorithm::Blake3.digest(b"")),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        let key = *b"whats the Elvish word for friend";
+        assert_eq!(
+            HashAlgorithm::Blake3.mac(&key, &[b"ab", b"c"]),
+            *blake3::keyed_hash(&key, b"abc").as_bytes()
+        );
+        // Keys of other lengths are hashed first.
+        assert_eq!(
+            HashAlgorithm::Blake3.mac(b"key", &[b"abc"]),
+            *blake3::keyed_hash(blake3::hash(b"key").as_bytes(), b"abc").as_bytes()
+        );
+        // PBKDF2 with the keyed mode as the pseudo-random function.
+        let u1 = HashAlgorithm::Blake3.mac(b"passwd", &[b"salt", &[0, 0, 0, 1]]);
+        let u2 = HashAlgorithm::Blake3.mac(b"passwd", &[&u1]);
+        let mut expected = u1;
+        expected.iter_mut().zip(u2).for_each(|(x, y)| *x ^= y);
+        assert_eq!(
+            HashAlgorithm::Blake3.pbkdf2(b"passwd", b"salt", 2),
+            expected
+        );
+    }
+
+    #[test]
+    fn hasher_is_incremental() {
+        for &algorithm in HashAlgorithm::ALL {
+            let mut hasher = algorithm.hasher();
+            write!(hasher, "{}|abc", 42).unwrap();
+            assert_eq!(hasher.finalize(), algorithm.digest(b"42|abc"));
+        }
+    }
+
+    #[test]
+    fn can_parse_identifiers() {
+        for &algorithm in HashAlgorithm::ALL {
+            assert_eq!(algorithm.id().parse(), Ok(algorithm));
+            assert_eq!(algorithm.to_string(), algorithm.id());
+        }
+        assert!(HashAlgorithm::default().is_default());
+        assert_eq!(
+            "md5".parse::<HashAlgorithm>(),
+            Err(UnsupportedAlgorithm("md5".to_owned()))
+        );
+    }
+}
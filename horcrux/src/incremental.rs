@@ -0,0 +1,233 @@
+//! Incremental reconstruction, for shares that arrive one at a time (e.g. as QR codes are scanned
+//! or as custodians type them in).
+//!
+//! An `IncrementalReconstructor` doesn't keep the shares: it keeps the polynomial interpolated
+//! from the shares added so far in Newton form, i.e. as coefficients of the basis
+//! `1, (x - x_0), (x - x_0)(x - x_1), ...`, so that each share adds one coefficient without
+//! recomputing the others. Once the threshold is met, the secret is the value of this polynomial
+//! at x = 0, and further shares are checked against it.
+
+use crate::field::Field;
+use crate::secret::Secret;
+use crate::shamir::{Shamir, ShamirError};
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+/// Progress of an incremental reconstruction after adding a share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Progress {
+    /// The given number of shares are still needed to reach the threshold.
+    Remaining(usize),
+    /// The threshold is met, so the secret can be reconstructed with `finalize()`. Any share
+    /// added from now on is checked against the previous ones.
+    ThresholdMet,
+}
+
+/// Reconstructs a secret from shares added one at a time, given the threshold parameter k.
+pub struct IncrementalReconstructor<F: Field, S: Shamir<F>> {
+    k: usize,
+    /// x coordinates of all the shares added so far, to detect duplicates.
+    xs: Vec<F>,
+    /// Newton coefficients of the polynomial interpolated from the first k shares.
+    coefficients: Vec<F>,
+    _phantom: PhantomData<S>,
+}
+
+impl<F: Field, S: Shamir<F>> IncrementalReconstructor<F, S> {
+    /// Starts a reconstruction with the threshold parameter k. Returns an error if k is out of
+    /// range, i.e. not `1 <= k <= 255`.
+    pub fn new(k: usize) -> Result<Self, ShamirError> {
+        if k == 0 || k > 255 {
+            return Err(ShamirError::InvalidThreshold);
+        }
+        Ok(Self {
+            k,
+            xs: Vec::with_capacity(k),
+            coefficients: Vec::with_capacity(k),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the threshold parameter k.
+    pub fn threshold(&self) -> usize {
+        self.k
+    }
+
+    /// Returns the number of shares added so far.
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    /// Returns whether no share was added yet.
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    /// Returns the progress towards the threshold.
+    pub fn progress(&self) -> Progress {
+        match self.k - self.coefficients.len() {
+            0 => Progress::ThresholdMet,
+            remaining => Progress::Remaining(remaining),
+        }
+    }
+
+    /// Adds a share to the reconstruction. Returns an error if a share with the same x coordinate
+    /// was already added, or if the threshold is already met and the share is inconsistent with
+    /// the previous ones (the index in the error is the number of shares added before it). A
+    /// rejected share doesn't change the state of the reconstruction.
+    pub fn add(&mut self, share: &S::Share) -> Result<Progress, ShamirError> {
+        let (x, y) = S::point(share);
+        if self.xs.contains(&x) {
+            return Err(ShamirError::DuplicateX);
+        }
+
+        // Evaluates the polynomial at x, along with the product of the (x - x_i).
+        let mut value = F::ZERO;
+        let mut basis = F::ONE;
+        for (c, &xi) in self.coefficients.iter().zip(&self.xs) {
+            value += &(*c * &basis);
+            basis = basis * &(x - xi);
+        }
+
+        if self.coefficients.len() < self.k {
+            // The new coefficient makes the polynomial pass through (x, y). The basis is non-zero
+            // as the x coordinates are distinct.
+            self.coefficients.push((y - value) * &basis.invert());
+        } else if value != y {
+            return Err(ShamirError::InconsistentShares(alloc::vec![self.xs.len()]));
+        }
+        self.xs.push(x);
+        Ok(self.progress())
+    }
+
+    /// Reconstructs the secret, i.e. the value at x = 0 of the polynomial. Returns an error if
+    /// fewer shares than the threshold were added.
+    pub fn finalize(self) -> Result<Secret<F>, ShamirError> {
+        if self.coefficients.len() < self.k {
+            return Err(ShamirError::NotEnoughShares);
+        }
+        // Horner's method on the Newton form, starting from the highest coefficient.
+        let mut secret = F::ZERO;
+        for (c, &xi) in self.coefficients.iter().zip(&self.xs[..self.k]).rev() {
+            secret = secret * &(F::ZERO - xi);
+            secret += c;
+        }
+        Ok(Secret::new(secret))
+    }
+}
+
+/// Redacted `Debug` implementation, which only prints the progress of the reconstruction.
+impl<F: Field, S: Shamir<F>> Debug for IncrementalReconstructor<F, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("IncrementalReconstructor")
+            .field("k", &self.k)
+            .field("shares", &self.xs.len())
+            .finish()
+    }
+}
+
+impl<F: Field, S: Shamir<F>> Drop for IncrementalReconstructor<F, S> {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut self.coefficients);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gf2n::{GF256, GF64};
+    use crate::gfp::Ed25519Scalar;
+    use crate::shamir::{CompactShamir, RandomShamir};
+    use core::fmt::Display;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn check<F: Field + Debug + Display, S: Shamir<F>>() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let secret = F::uniform(&mut rng);
+        let shares = S::split_with_rng(&secret, 3, 5, &mut rng).unwrap();
+
+        let mut reconstructor = IncrementalReconstructor::<F, S>::new(3).unwrap();
+        assert_eq!(reconstructor.progress(), Progress::Remaining(3));
+        assert_eq!(reconstructor.add(&shares[4]), Ok(Progress::Remaining(2)));
+        assert_eq!(reconstructor.add(&shares[4]), Err(ShamirError::DuplicateX));
+        assert_eq!(reconstructor.add(&shares[1]), Ok(Progress::Remaining(1)));
+        assert_eq!(reconstructor.add(&shares[2]), Ok(Progress::ThresholdMet));
+        assert_eq!(reconstructor.add(&shares[0]), Ok(Progress::ThresholdMet));
+        assert_eq!(reconstructor.len(), 4);
+
+        let reconstructed = reconstructor.finalize().unwrap();
+        assert_eq!(*reconstructed.expose_secret(), secret);
+        assert_eq!(
+            *reconstructed.expose_secret(),
+            S::reconstruct(&shares, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn can_reconstruct() {
+        check::<GF64, CompactShamir>();
+        check::<GF256, CompactShamir>();
+        check::<GF256, RandomShamir>();
+        check::<Ed25519Scalar, CompactShamir>();
+        check::<Ed25519Scalar, RandomShamir>();
+    }
+
+    #[test]
+    fn can_reconstruct_with_threshold_one() {
+        let shares = CompactShamir::split(&GF64::from(42), 1, 2).unwrap();
+        let mut reconstructor = IncrementalReconstructor::<_, CompactShamir>::new(1).unwrap();
+        assert_eq!(reconstructor.add(&shares[1]), Ok(Progress::ThresholdMet));
+        assert_eq!(reconstructor.add(&shares[0]), Ok(Progress::ThresholdMet));
+        assert_eq!(
+            *reconstructor.finalize().unwrap().expose_secret(),
+            GF64::from(42)
+        );
+    }
+
+    #[test]
+    fn rejects_inconsistent_shares() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let mut shares = CompactShamir::split_with_rng(&GF64::from(42), 2, 4, &mut rng).unwrap();
+        shares[3] = shares[3].add(&shares[3]).unwrap();
+
+        let mut reconstructor = IncrementalReconstructor::<_, CompactShamir>::new(2).unwrap();
+        reconstructor.add(&shares[0]).unwrap();
+        reconstructor.add(&shares[1]).unwrap();
+        assert_eq!(
+            reconstructor.add(&shares[3]),
+            Err(ShamirError::InconsistentShares(alloc::vec![2]))
+        );
+        assert_eq!(reconstructor.add(&shares[2]), Ok(Progress::ThresholdMet));
+        assert_eq!(
+            *reconstructor.finalize().unwrap().expose_secret(),
+            GF64::from(42)
+        );
+    }
+
+    #[test]
+    fn checks_parameters() {
+        assert_eq!(
+            IncrementalReconstructor::<GF64, CompactShamir>::new(0).unwrap_err(),
+            ShamirError::InvalidThreshold
+        );
+        assert_eq!(
+            IncrementalReconstructor::<GF64, CompactShamir>::new(256).unwrap_err(),
+            ShamirError::InvalidThreshold
+        );
+
+        let shares = CompactShamir::split(&GF64::from(42), 2, 2).unwrap();
+        let mut reconstructor = IncrementalReconstructor::<_, CompactShamir>::new(2).unwrap();
+        reconstructor.add(&shares[0]).unwrap();
+        assert_eq!(
+            format!("{reconstructor:?}"),
+            "IncrementalReconstructor { k: 2, shares: 1 }"
+        );
+        assert_eq!(
+            reconstructor.finalize().unwrap_err(),
+            ShamirError::NotEnoughShares
+        );
+    }
+}
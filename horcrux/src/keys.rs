@@ -0,0 +1,122 @@
+//! Conversions between the key types of other crates and field elements of the same size, so that
+//! keys can be split and reconstructed without converting their bytes by hand.
+//!
+//! The bytes of a key map to the bytes of the field element in the same order as
+//! `Field::from_bytes()`, i.e. the hexadecimal representation of the element is the hexadecimal
+//! encoding of the key. A key split with the library can therefore be reconstructed with the
+//! command line tool, and conversely.
+//!
+//! With the `ed25519-dalek` feature, Ed25519 signing keys convert from and into `GF256`, the seed
+//! of the key being the secret. With the `generic-array` feature, byte arrays of 16, 24 and 32
+//! bytes convert from and into `GF128`, `GF192` and `GF256`. These arrays are the key types of the
+//! RustCrypto ciphers, e.g. `aes::Key<Aes256>`.
+
+#[cfg(feature = "generic-array")]
+use crate::gf2n::{GF128, GF192};
+use crate::{field::Field, gf2n::GF256};
+#[cfg(feature = "ed25519-dalek")]
+use core::convert::TryInto;
+#[cfg(feature = "generic-array")]
+use generic_array::{typenum, GenericArray};
+
+/// Wipes the temporary copy of the bytes of a key.
+fn wipe(bytes: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(bytes);
+    #[cfg(not(feature = "zeroize"))]
+    bytes.iter_mut().for_each(|b| *b = 0);
+}
+
+#[cfg(feature = "ed25519-dalek")]
+impl From<&ed25519_dalek::SigningKey> for GF256 {
+    fn from(key: &ed25519_dalek::SigningKey) -> Self {
+        GF256::from_bytes(key.as_bytes()).unwrap()
+    }
+}
+
+#[cfg(feature = "ed25519-dalek")]
+impl From<&GF256> for ed25519_dalek::SigningKey {
+    fn from(x: &GF256) -> Self {
+        let mut bytes = x.to_bytes();
+        let key = ed25519_dalek::SigningKey::from_bytes(bytes.as_slice().try_into().unwrap());
+        wipe(&mut bytes);
+        key
+    }
+}
+
+#[cfg(feature = "generic-array")]
+macro_rules! impl_generic_array {
+    ( $field:ty, $size:ty ) => {
+        impl From<&GenericArray<u8, $size>> for $field {
+            fn from(key: &GenericArray<u8, $size>) -> Self {
+                <$field>::from_bytes(key.as_slice()).unwrap()
+            }
+        }
+
+        impl From<&$field> for GenericArray<u8, $size> {
+            fn from(x: &$field) -> Self {
+                let mut bytes = x.to_bytes();
+                let key = GenericArray::clone_from_slice(&bytes);
+                wipe(&mut bytes);
+                key
+            }
+        }
+    };
+}
+
+#[cfg(feature = "generic-array")]
+impl_generic_array!(GF128, typenum::U16);
+#[cfg(feature = "generic-array")]
+impl_generic_array!(GF192, typenum::U24);
+#[cfg(feature = "generic-array")]
+impl_generic_array!(GF256, typenum::U32);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shamir::{CompactShamir, Shamir};
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    #[cfg(feature = "ed25519-dalek")]
+    #[test]
+    fn can_split_ed25519_keys() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let mut seed = [0; 32];
+        rng.fill_bytes(&mut seed);
+        let key = ed25519_dalek::SigningKey::from_bytes(&seed);
+
+        let secret = GF256::from(&key);
+        assert_eq!(secret.to_string(), hex::encode(seed));
+        let shares = CompactShamir::split_with_rng(&secret, 2, 3, &mut rng).unwrap();
+        let reconstructed = CompactShamir::reconstruct(&shares[1..], 2).unwrap();
+        let reconstructed = ed25519_dalek::SigningKey::from(&reconstructed);
+        assert_eq!(reconstructed.to_bytes(), key.to_bytes());
+        assert_eq!(reconstructed.verifying_key(), key.verifying_key());
+    }
+
+    #[cfg(feature = "generic-array")]
+    #[test]
+    fn can_split_generic_arrays() {
+        fn check<F, N: generic_array::ArrayLength<u8>>()
+        where
+            F: Field + core::fmt::Debug + core::fmt::Display,
+            for<'a> F: From<&'a GenericArray<u8, N>>,
+            for<'a> GenericArray<u8, N>: From<&'a F>,
+        {
+            let mut rng = ChaCha20Rng::seed_from_u64(42);
+            let mut key = GenericArray::<u8, N>::default();
+            rng.fill_bytes(&mut key);
+
+            let secret = F::from(&key);
+            assert_eq!(secret.to_string(), hex::encode(&key));
+            let shares = CompactShamir::split_with_rng(&secret, 2, 3, &mut rng).unwrap();
+            let reconstructed = CompactShamir::reconstruct(&shares[1..], 2).unwrap();
+            assert_eq!(GenericArray::<u8, N>::from(&reconstructed), key);
+        }
+
+        check::<GF128, typenum::U16>();
+        check::<GF192, typenum::U24>();
+        check::<GF256, typenum::U32>();
+    }
+}
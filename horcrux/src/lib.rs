@@ -1,11 +1,89 @@
-#![feature(test)]
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![deny(missing_docs)]
 
 //! Rust implementation of Shamir's Secret Sharing.
+//!
+//! Without the default `std` feature, the library only requires `alloc`. The operations that sample
+//! randomness from the thread-local RNG (e.g. `Shamir::split`) and the parsing functions are then
+//! unavailable, but shares can still be generated with `Shamir::split_with_rng`.
 
-#[cfg(test)]
+extern crate alloc;
+
+#[cfg(all(test, feature = "bench"))]
 extern crate test;
 
+// With the `silent` feature, any printing from the library code is a compile error.
+#[cfg(feature = "silent")]
+#[allow(unused_macros)]
+macro_rules! print {
+    ($($t:tt)*) => {
+        compile_error!("printing is disabled by the `silent` feature")
+    };
+}
+#[cfg(feature = "silent")]
+#[allow(unused_macros)]
+macro_rules! println {
+    ($($t:tt)*) => {
+        compile_error!("printing is disabled by the `silent` feature")
+    };
+}
+#[cfg(feature = "silent")]
+#[allow(unused_macros)]
+macro_rules! eprint {
+    ($($t:tt)*) => {
+        compile_error!("printing is disabled by the `silent` feature")
+    };
+}
+#[cfg(feature = "silent")]
+#[allow(unused_macros)]
+macro_rules! eprintln {
+    ($($t:tt)*) => {
+        compile_error!("printing is disabled by the `silent` feature")
+    };
+}
+#[cfg(feature = "silent")]
+#[allow(unused_macros)]
+macro_rules! dbg {
+    ($($t:tt)*) => {
+        compile_error!("printing is disabled by the `silent` feature")
+    };
+}
+
+pub mod access;
+pub mod backends;
+pub mod batch;
+pub mod checksum;
+pub mod ct;
+pub mod decoy;
+pub mod dispatch;
+#[cfg(feature = "dkg")]
+pub mod dkg;
+#[cfg(feature = "profile-embedded")]
+pub mod embedded;
 pub mod field;
 pub mod gf2n;
+pub mod gf2n_dyn;
+pub mod gfp;
+pub mod group;
+#[cfg(feature = "parse")]
+pub mod hashing;
+pub mod incremental;
+#[cfg(any(feature = "ed25519-dalek", feature = "generic-array"))]
+pub mod keys;
+#[cfg(feature = "dkg")]
+pub mod pedersen;
+pub mod polynomial;
+#[cfg(feature = "parse")]
+pub mod record;
+#[cfg(feature = "recovery")]
+pub mod recovery;
+pub mod rng;
+#[cfg(feature = "serde")]
+pub mod schema;
+pub mod secret;
 pub mod shamir;
+#[cfg(feature = "parse")]
+pub mod shareset;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
@@ -0,0 +1,276 @@
+//! Pedersen's verifiable secret sharing over the Ed25519 curve, whose commitments hide the secret.
+//!
+//! Feldman's commitments, as in the `dkg` module, are the multiples of the base point G by the
+//! coefficients of the polynomial, so the first one reveals the multiple of G by the secret, and
+//! anyone can check a guess of the secret against it. Pedersen's scheme draws a second, random
+//! blinding polynomial, and commits to each pair of coefficients `(a, b)` as `a * G + b * H`, where
+//! H is a second generator whose discrete logarithm in base G is unknown (see
+//! `blinding_generator()`). The commitments are then information-theoretically hiding: they are
+//! uniformly distributed whatever the secret. They remain binding as long as the discrete logarithm
+//! of H is unknown.
+//!
+//! Each custodian receives a `BlindedShare`, i.e. a share of the secret and the evaluation of the
+//! blinding polynomial at the same x coordinate, and checks it against the public `Commitment`.
+//! The shares of the secret are compact shares of the `Ed25519Scalar` field, so that the usual
+//! reconstruction functions apply.
+
+use crate::dkg::{evaluate, to_scalar};
+use crate::field::Field;
+use crate::gfp::Ed25519Scalar;
+use crate::shamir::{Coefficients, GetX, ShamirError, Share};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+use rand::{thread_rng, CryptoRng, Rng};
+use sha2::{Digest, Sha512};
+
+/// Domain separation of the hashes that derive the blinding generator.
+const GENERATOR_DOMAIN: &[u8] = b"horcrux-pedersen-generator";
+
+/// Public commitment to the polynomials of a split.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commitment {
+    /// Commitments `a * G + b * H` to the coefficients `a` of the polynomial and `b` of the
+    /// blinding polynomial, constant term first.
+    pub points: Vec<EdwardsPoint>,
+}
+
+/// Share of the secret, with the evaluation of the blinding polynomial needed to verify it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlindedShare {
+    /// Share of the secret.
+    pub share: Share<u8, Ed25519Scalar>,
+    /// Evaluation of the blinding polynomial at the x coordinate of the share.
+    pub blinding: Ed25519Scalar,
+}
+
+/// Returns the blinding generator H: the first point obtained by hashing a fixed domain and a
+/// counter, multiplied by the cofactor to be in the prime-order subgroup. As it's derived from a
+/// hash, nobody knows its discrete logarithm in base G.
+pub fn blinding_generator() -> EdwardsPoint {
+    (0u32..)
+        .find_map(|counter| {
+            let hash = Sha512::new()
+                .chain_update(GENERATOR_DOMAIN)
+                .chain_update(counter.to_le_bytes())
+                .finalize();
+            CompressedEdwardsY(hash[..32].try_into().unwrap())
+                .decompress()
+                .map(|point| point.mul_by_cofactor())
+                .filter(|point| !point.is_identity())
+        })
+        .unwrap()
+}
+
+/// Splits a secret into n blinded shares with a threshold k, and returns the commitment to
+/// publish along with the shares.
+pub fn split(
+    secret: &Ed25519Scalar,
+    k: usize,
+    n: usize,
+) -> Result<(Commitment, Vec<BlindedShare>), ShamirError> {
+    split_with_rng(secret, k, n, &mut thread_rng())
+}
+
+/// Splits a secret like `split()`, but drawing the polynomials from the given random number
+/// generator.
+pub fn split_with_rng<R: Rng + CryptoRng + ?Sized>(
+    secret: &Ed25519Scalar,
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<(Commitment, Vec<BlindedShare>), ShamirError> {
+    if k == 0 || k > n || n > 255 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    let mut polynom = Coefficients::from(Vec::with_capacity(k));
+    polynom.push(*secret);
+    polynom.extend((1..k).map(|_| Ed25519Scalar::uniform(rng)));
+    let blinding: Vec<Ed25519Scalar> = (0..k).map(|_| Ed25519Scalar::uniform(rng)).collect();
+    let blinding = Coefficients::from(blinding);
+
+    let h = blinding_generator();
+    let commitment = Commitment {
+        points: polynom
+            .iter()
+            .zip(blinding.iter())
+            .map(|(&a, &b)| EdwardsPoint::mul_base(&to_scalar(a)) + h * to_scalar(b))
+            .collect(),
+    };
+    let shares = (1..=n as u8)
+        .map(|x| BlindedShare {
+            share: Share::<u8, _>::new(x, evaluate_scalars(&polynom, x)).unwrap(),
+            blinding: evaluate_scalars(&blinding, x),
+        })
+        .collect();
+    Ok((commitment, shares))
+}
+
+impl Commitment {
+    /// Returns whether the share is consistent with the commitment.
+    pub fn verify(&self, share: &BlindedShare) -> bool {
+        EdwardsPoint::mul_base(&to_scalar(*share.share.y()))
+            + blinding_generator() * to_scalar(share.blinding)
+            == evaluate(&self.points, share.share.getx())
+    }
+
+    /// Verifies all the shares at once, with a single multi-scalar multiplication over a random
+    /// linear combination of their verification equations. Returns an error listing the indices
+    /// of the shares that are inconsistent with the commitment, if any.
+    pub fn verify_shares(&self, shares: &[BlindedShare]) -> Result<(), ShamirError> {
+        self.verify_shares_with_rng(shares, &mut thread_rng())
+    }
+
+    /// Verifies all the shares like `verify_shares()`, but drawing the coefficients of the linear
+    /// combination from the given random number generator.
+    pub fn verify_shares_with_rng<R: Rng + CryptoRng + ?Sized>(
+        &self,
+        shares: &[BlindedShare],
+        rng: &mut R,
+    ) -> Result<(), ShamirError> {
+        // The sum over the shares of w * (y * G + blinding * H - sum_j x^j * C_j) is the identity
+        // if all the shares are consistent, and otherwise with negligible probability over the
+        // random weights w.
+        let mut y = Scalar::ZERO;
+        let mut blinding = Scalar::ZERO;
+        let mut powers = alloc::vec![Scalar::ZERO; self.points.len()];
+        for share in shares {
+            let weight = to_scalar(Ed25519Scalar::uniform(rng));
+            y += weight * to_scalar(*share.share.y());
+            blinding += weight * to_scalar(share.blinding);
+            let x = Scalar::from(share.share.getx());
+            let mut power = weight;
+            for p in powers.iter_mut() {
+                *p -= power;
+                power *= x;
+            }
+        }
+        let combined = EdwardsPoint::vartime_multiscalar_mul(
+            [y, blinding].iter().chain(&powers),
+            [ED25519_BASEPOINT_POINT, blinding_generator()]
+                .iter()
+                .chain(&self.points),
+        );
+        if combined.is_identity() {
+            return Ok(());
+        }
+        let inconsistent: Vec<usize> = shares
+            .iter()
+            .enumerate()
+            .filter(|(_, share)| !self.verify(share))
+            .map(|(i, _)| i)
+            .collect();
+        Err(ShamirError::InconsistentShares(inconsistent))
+    }
+
+    /// Returns whether the secret and the constant term of the blinding polynomial open the
+    /// commitment, e.g. after reconstructing both from the blinded shares.
+    pub fn verify_secret(&self, secret: &Ed25519Scalar, blinding: &Ed25519Scalar) -> bool {
+        self.points.first()
+            == Some(
+                &(EdwardsPoint::mul_base(&to_scalar(*secret))
+                    + blinding_generator() * to_scalar(*blinding)),
+            )
+    }
+}
+
+impl BlindedShare {
+    /// Returns the share of the constant term of the blinding polynomial, which reconstructs like
+    /// the share of the secret.
+    pub fn blinding_share(&self) -> Share<u8, Ed25519Scalar> {
+        Share::<u8, _>::new(self.share.getx(), self.blinding).unwrap()
+    }
+}
+
+/// Evaluates the polynomial of the given coefficients (constant term first) at x, with Horner's
+/// method.
+fn evaluate_scalars(polynom: &[Ed25519Scalar], x: u8) -> Ed25519Scalar {
+    let mut y = Ed25519Scalar::ZERO;
+    for c in polynom.iter().rev() {
+        y *= &Ed25519Scalar::from(x);
+        y += c;
+    }
+    y
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::shamir::{CompactShamir, Shamir};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn blinding_generator_is_independent() {
+        let h = blinding_generator();
+        assert!(h.is_torsion_free());
+        assert!(!h.is_identity());
+        assert_ne!(h, ED25519_BASEPOINT_POINT);
+        assert_eq!(h, blinding_generator());
+    }
+
+    #[test]
+    fn can_split_and_verify() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let secret = Ed25519Scalar::uniform(&mut rng);
+        let (commitment, shares) = split_with_rng(&secret, 3, 5, &mut rng).unwrap();
+        assert_eq!(commitment.points.len(), 3);
+        assert!(shares.iter().all(|share| commitment.verify(share)));
+        assert_eq!(commitment.verify_shares(&shares), Ok(()));
+
+        let secret_shares: Vec<_> = shares.iter().map(|s| s.share).collect();
+        assert_eq!(
+            CompactShamir::reconstruct(&secret_shares[2..], 3),
+            Ok(secret)
+        );
+        let blinding_shares: Vec<_> = shares.iter().map(|s| s.blinding_share()).collect();
+        let blinding = CompactShamir::reconstruct(&blinding_shares[..3], 3).unwrap();
+        assert!(commitment.verify_secret(&secret, &blinding));
+        let mut wrong = secret;
+        wrong += &Ed25519Scalar::ONE;
+        assert!(!commitment.verify_secret(&wrong, &blinding));
+    }
+
+    #[test]
+    fn commitment_hides_the_secret() {
+        // Unlike Feldman's commitments, the constant commitment isn't the multiple of the base
+        // point by the secret, and commitments to the same secret differ.
+        let secret = Ed25519Scalar::from(42);
+        let (first, _) = split(&secret, 2, 3).unwrap();
+        let (second, _) = split(&secret, 2, 3).unwrap();
+        assert_ne!(first.points[0], EdwardsPoint::mul_base(&to_scalar(secret)));
+        assert_ne!(first.points[0], second.points[0]);
+    }
+
+    #[test]
+    fn can_detect_inconsistent_shares() {
+        let secret = Ed25519Scalar::from(42);
+        let (commitment, mut shares) = split(&secret, 3, 5).unwrap();
+        shares[1].blinding += &Ed25519Scalar::ONE;
+        let mut y = *shares[3].share.y();
+        y += &Ed25519Scalar::ONE;
+        shares[3].share = Share::<u8, _>::new(4, y).unwrap();
+        assert!(!commitment.verify(&shares[1]));
+        assert!(!commitment.verify(&shares[3]));
+        assert_eq!(
+            commitment.verify_shares(&shares),
+            Err(ShamirError::InconsistentShares(vec![1, 3]))
+        );
+        assert_eq!(commitment.verify_shares(&shares[2..3]), Ok(()));
+
+        // A share verified against the commitment of another split.
+        let (other, _) = split(&secret, 3, 5).unwrap();
+        assert!(!other.verify(&shares[0]));
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let secret = Ed25519Scalar::from(42);
+        assert_eq!(split(&secret, 0, 3), Err(ShamirError::InvalidThreshold));
+        assert_eq!(split(&secret, 4, 3), Err(ShamirError::InvalidThreshold));
+        assert_eq!(split(&secret, 2, 256), Err(ShamirError::InvalidThreshold));
+    }
+}
@@ -0,0 +1,221 @@
+//! Polynomials over a field, as used in Shamir's Secret Sharing.
+
+use crate::field::Field;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+/// Polynomial over a field, represented by its coefficients (constant term first).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polynomial<F> {
+    coefficients: Vec<F>,
+}
+
+impl<F: Field> Polynomial<F> {
+    /// Creates a polynomial from its coefficients, starting with the constant term.
+    pub fn new(coefficients: Vec<F>) -> Self {
+        Self { coefficients }
+    }
+
+    /// Computes the polynomial of degree at most m-1 passing through the given m points, with
+    /// Lagrange interpolation. The x coordinates must be distinct.
+    pub fn interpolate(points: &[(F, F)]) -> Self {
+        // Master polynomial: (x - x_0) * ... * (x - x_{m-1}).
+        let mut master = vec![F::ONE];
+        for &(xj, _) in points {
+            master.push(F::ZERO);
+            for t in (0..master.len() - 1).rev() {
+                let c = master[t];
+                master[t + 1] += &c;
+                master[t] = F::ZERO - c * &xj;
+            }
+        }
+
+        let xs: Vec<F> = points.iter().map(|&(x, _)| x).collect();
+        let weights = barycentric_weights(&xs);
+
+        let mut coefficients = vec![F::ZERO; points.len()];
+        for (&(xi, yi), w) in points.iter().zip(&weights) {
+            let scale = yi * w;
+
+            // Divide the master polynomial by (x - x_i).
+            let mut q = F::ZERO;
+            for t in (0..points.len()).rev() {
+                q = q * &xi;
+                q += &master[t + 1];
+                coefficients[t] += &(q * &scale);
+            }
+        }
+
+        Self { coefficients }
+    }
+
+    /// Returns the coefficients, starting with the constant term.
+    pub fn coefficients(&self) -> &[F] {
+        &self.coefficients
+    }
+
+    /// Returns the degree of the polynomial, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.iter().rposition(|&c| c != F::ZERO)
+    }
+
+    /// Evaluates the polynomial at x, with Horner's method.
+    pub fn evaluate(&self, x: F) -> F {
+        let mut y = F::ZERO;
+        for c in self.coefficients.iter().rev() {
+            y = y * &x;
+            y += c;
+        }
+        y
+    }
+
+    /// Parses a polynomial from a comma-separated list of coefficients in hexadecimal (constant
+    /// term first). Returns `None` if the parsing fails.
+    #[cfg(feature = "parse")]
+    pub fn parse(s: &str) -> Option<Self> {
+        let coefficients = s
+            .split(',')
+            .map(|c| F::from_bytes(&hex::decode(c).ok()?))
+            .collect::<Option<Vec<F>>>()?;
+        Some(Self { coefficients })
+    }
+}
+
+/// Serializes the polynomial as a comma-separated list of coefficients (constant term first).
+impl<F: Display> Display for Polynomial<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for (i, c) in self.coefficients.iter().enumerate() {
+            if i != 0 {
+                f.write_str(",")?;
+            }
+            f.write_fmt(format_args!("{c}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the barycentric weights `1 / prod_{j != i} (x_i - x_j)` of the given x coordinates,
+/// which must be distinct.
+///
+/// The number of field operations only depends on the number of coordinates, and there is no
+/// branch on their values, so that the timing doesn't reveal which shares are interpolated.
+pub(crate) fn barycentric_weights<F: Field>(xs: &[F]) -> Vec<F> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, &xi)| {
+            let mut denom = F::ONE;
+            for (j, &xj) in xs.iter().enumerate() {
+                // The factor for j = i is 0 + 1, i.e. a neutral factor.
+                let mut factor = xi - xj;
+                factor += &F::from((j == i) as u8);
+                denom = denom * &factor;
+            }
+            denom.invert()
+        })
+        .collect()
+}
+
+/// Returns the Lagrange coefficients `w_i * prod_{j != i} (x - x_j)` of the given x coordinates
+/// at x, given their barycentric weights, so that the value at x of the polynomial interpolated
+/// from the points `(x_i, y_i)` is the sum of the `y_i` times these coefficients. The products
+/// are computed from prefix and suffix products, in a fixed number of operations, even if x is
+/// one of the coordinates.
+pub(crate) fn lagrange_coefficients<F: Field>(xs: &[F], weights: &[F], x: &F) -> Vec<F> {
+    let mut suffixes = vec![F::ONE; xs.len() + 1];
+    for (i, &xi) in xs.iter().enumerate().rev() {
+        suffixes[i] = suffixes[i + 1] * &(*x - xi);
+    }
+    let mut prefix = F::ONE;
+    xs.iter()
+        .zip(weights)
+        .zip(&suffixes[1..])
+        .map(|((&xi, w), suffix)| {
+            let coefficient = prefix * suffix * w;
+            prefix = prefix * &(*x - xi);
+            coefficient
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Polynomial;
+    use crate::field::Field;
+    use crate::gf2n::GF256;
+    use rand::thread_rng;
+
+    fn random_polynomial(len: usize) -> Polynomial<GF256> {
+        let mut rng = thread_rng();
+        Polynomial::new((0..len).map(|_| GF256::uniform(&mut rng)).collect())
+    }
+
+    #[test]
+    fn degree() {
+        assert_eq!(Polynomial::<GF256>::new(vec![]).degree(), None);
+        assert_eq!(Polynomial::new(vec![GF256::ZERO; 3]).degree(), None);
+        assert_eq!(Polynomial::new(vec![GF256::ONE]).degree(), Some(0));
+        let polynom = Polynomial::new(vec![GF256::ONE, GF256::ZERO, GF256::ONE, GF256::ZERO]);
+        assert_eq!(polynom.degree(), Some(2));
+    }
+
+    #[test]
+    fn evaluate() {
+        let polynom = random_polynomial(5);
+        let c = polynom.coefficients();
+        assert_eq!(polynom.evaluate(GF256::ZERO), c[0]);
+        for i in 0..=255 {
+            let x = GF256::from(i);
+            let mut expected = GF256::ZERO;
+            let mut xn = GF256::ONE;
+            for ci in c {
+                expected += &(xn * ci);
+                xn *= &x;
+            }
+            assert_eq!(polynom.evaluate(x), expected);
+        }
+    }
+
+    #[test]
+    fn interpolate() {
+        for len in 1..10 {
+            let polynom = random_polynomial(len);
+            let points: Vec<(GF256, GF256)> = (1..=len as u8)
+                .map(|i| (GF256::from(i), polynom.evaluate(GF256::from(i))))
+                .collect();
+            assert_eq!(Polynomial::interpolate(&points), polynom);
+        }
+    }
+
+    #[test]
+    fn lagrange_coefficients() {
+        let polynom = random_polynomial(6);
+        let xs: Vec<GF256> = [3u8, 17, 42, 99, 200, 255]
+            .iter()
+            .map(|&x| GF256::from(x))
+            .collect();
+        let weights = super::barycentric_weights(&xs);
+        for i in 0..=255 {
+            let x = GF256::from(i);
+            let mut value = GF256::ZERO;
+            for (&xi, l) in xs
+                .iter()
+                .zip(super::lagrange_coefficients(&xs, &weights, &x))
+            {
+                value += &(polynom.evaluate(xi) * &l);
+            }
+            assert_eq!(value, polynom.evaluate(x));
+        }
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn parse_display() {
+        for len in 1..10 {
+            let polynom = random_polynomial(len);
+            assert_eq!(Polynomial::parse(&polynom.to_string()), Some(polynom));
+        }
+        assert_eq!(Polynomial::<GF256>::parse(""), None);
+        assert_eq!(Polynomial::<GF256>::parse("00,"), None);
+    }
+}
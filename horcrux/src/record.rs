@@ -0,0 +1,593 @@
+//! Records of shares, to split secrets larger than a field element.
+//!
+//! The secret is cut into field elements, which are split independently, and each custodian
+//! receives a single record containing their shares of all the elements, at the same x coordinate.
+//! A 1 KiB secret therefore still yields exactly n records, rather than one share per element and
+//! per custodian.
+//!
+//! Records are as large as the secret, which can be prohibitive for secrets of several kilobytes.
+//! With a ramp scheme (see `Ramp` and `split_ramp()`), the elements are instead packed L at a time
+//! into polynomials of degree k-1, so that records are L times smaller, but only up to k - L
+//! records reveal nothing about the secret. The last group of elements is padded with zeros.
+//!
+//! Each record also contains the length of the secret in bytes, so that the padding of the last
+//! element can be removed, and an authentication tag keyed with the secret. Once the secret is
+//! reconstructed, the tags of all the records are checked against it, which detects records that
+//! were corrupted or that belong to another secret. Like witness shares, the tags can be inverted
+//! by brute force for low-entropy secrets. The tags are computed with SHA-256 by default, or with
+//! another algorithm of the `hashing` module, whose identifier is then appended to the records.
+
+use crate::field::Field;
+use crate::hashing::HashAlgorithm;
+use crate::secret::Secret;
+use crate::shamir::{parse_decimal, GetX, Ramp, Shamir, ShamirError};
+use core::convert::TryInto;
+use core::fmt::{Display, Write};
+use rand::{thread_rng, CryptoRng, Rng};
+
+/// Length of the authentication tag of a record, in bytes.
+pub const MAC_LEN: usize = 16;
+
+/// Domain separation of the key derived from the secret.
+const KEY_DOMAIN: &str = "horcrux-record-key-v1";
+/// Domain separation of the authentication tags.
+const MAC_DOMAIN: &str = "horcrux-record-mac-v1";
+
+/// Record of a custodian, containing their shares of all the elements of the secret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record<T> {
+    /// Shares of the elements of the secret, in order, all at the same x coordinate.
+    pub shares: Vec<T>,
+    /// Length of the secret in bytes.
+    pub len: usize,
+    /// Number of elements of the secret packed into each share, i.e. 1 for ordinary records and L
+    /// for records of a ramp scheme.
+    pub packing: usize,
+    /// Authentication tag of the record, keyed with the secret.
+    pub mac: [u8; MAC_LEN],
+    /// Hash algorithm of the authentication tag.
+    pub hash: HashAlgorithm,
+}
+
+impl<T: Display> Record<T> {
+    /// Returns the record in text format without its tag, i.e. `<x>|<y1>,...,<ym>|<len>`, or
+    /// `<x>|<y1>,...,<ym>|<len>/<packing>` for records of a ramp scheme.
+    fn body(&self) -> String {
+        let mut body = String::new();
+        for (i, share) in self.shares.iter().enumerate() {
+            let text = share.to_string();
+            match i {
+                0 => body.push_str(&text),
+                _ => {
+                    body.push(',');
+                    body.push_str(text.split_once('|').map_or(&text[..], |(_, y)| y));
+                }
+            }
+        }
+        write!(body, "|{}", self.len).unwrap();
+        if self.packing != 1 {
+            write!(body, "/{}", self.packing).unwrap();
+        }
+        body
+    }
+}
+
+/// Formats the record as the x coordinate, the y coordinates separated by commas, the length of
+/// the secret (followed by the packing of ramp schemes) and the tag in hexadecimal, separated by
+/// pipes: `<x>|<y1>,...,<ym>|<len>|<mac>` or `<x>|<y1>,...,<ym>|<len>/<packing>|<mac>`.
+/// Tags that aren't computed with SHA-256 are followed by the identifier of their algorithm, e.g.
+/// `<x>|<y1>,...,<ym>|<len>|<mac>|blake3`.
+impl<T: Display> Display for Record<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}|{}", self.body(), hex::encode(self.mac))?;
+        if !self.hash.is_default() {
+            write!(f, "|{}", self.hash)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a secret made of several field elements into n records, with k of them being sufficient
+/// to reconstruct it. The length `len` of the secret in bytes is stored in the records. Returns an
+/// error if the parameters are out of range, or if the secret is empty.
+pub fn split<F: Field + Display, S: Shamir<F> + ?Sized>(
+    secret: &[F],
+    len: usize,
+    k: usize,
+    n: usize,
+) -> Result<Vec<Record<S::Share>>, ShamirError>
+where
+    S::Share: Display,
+{
+    split_with_rng::<F, S, _>(secret, len, k, n, &mut thread_rng())
+}
+
+/// Splits a secret into n records like `split()`, but drawing all the randomness from the given
+/// random number generator.
+pub fn split_with_rng<F: Field + Display, S: Shamir<F> + ?Sized, R: Rng + CryptoRng + ?Sized>(
+    secret: &[F],
+    len: usize,
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<Record<S::Share>>, ShamirError>
+where
+    S::Share: Display,
+{
+    split_with_hash::<F, S, R>(secret, len, k, n, HashAlgorithm::default(), rng)
+}
+
+/// Splits a secret into n records with a ramp scheme, packing `ramp.packing()` elements into each
+/// share: any `ramp.reconstruction()` records reconstruct the secret, and up to `ramp.privacy()`
+/// records reveal nothing about it. Returns an error if the parameters are out of range (see
+/// `Shamir::split_many()`), or if the secret is empty.
+pub fn split_ramp<F: Field + Display, S: Shamir<F> + ?Sized>(
+    secret: &[F],
+    len: usize,
+    ramp: Ramp,
+    n: usize,
+) -> Result<Vec<Record<S::Share>>, ShamirError>
+where
+    S::Share: Display,
+{
+    split_ramp_with_hash::<F, S, _>(
+        secret,
+        len,
+        ramp,
+        n,
+        HashAlgorithm::default(),
+        &mut thread_rng(),
+    )
+}
+
+/// Splits a secret into n records with a ramp scheme like `split_ramp()`, but computing the tags
+/// with the given hash algorithm and drawing all the randomness from the given random number
+/// generator.
+pub fn split_ramp_with_hash<
+    F: Field + Display,
+    S: Shamir<F> + ?Sized,
+    R: Rng + CryptoRng + ?Sized,
+>(
+    secret: &[F],
+    len: usize,
+    ramp: Ramp,
+    n: usize,
+    hash: HashAlgorithm,
+    rng: &mut R,
+) -> Result<Vec<Record<S::Share>>, ShamirError>
+where
+    S::Share: Display,
+{
+    split_packed::<F, S, R>(
+        secret,
+        len,
+        ramp.reconstruction(),
+        ramp.packing(),
+        n,
+        hash,
+        rng,
+    )
+}
+
+/// Splits a secret into n records like `split_with_rng()`, but computing the tags with the given
+/// hash algorithm.
+pub fn split_with_hash<F: Field + Display, S: Shamir<F> + ?Sized, R: Rng + CryptoRng + ?Sized>(
+    secret: &[F],
+    len: usize,
+    k: usize,
+    n: usize,
+    hash: HashAlgorithm,
+    rng: &mut R,
+) -> Result<Vec<Record<S::Share>>, ShamirError>
+where
+    S::Share: Display,
+{
+    split_packed::<F, S, R>(secret, len, k, 1, n, hash, rng)
+}
+
+/// Splits a secret into n records, packing the given number of elements into each share.
+fn split_packed<F: Field + Display, S: Shamir<F> + ?Sized, R: Rng + CryptoRng + ?Sized>(
+    secret: &[F],
+    len: usize,
+    k: usize,
+    packing: usize,
+    n: usize,
+    hash: HashAlgorithm,
+    rng: &mut R,
+) -> Result<Vec<Record<S::Share>>, ShamirError>
+where
+    S::Share: Display,
+{
+    if secret.is_empty() {
+        return Err(ShamirError::InvalidRecord);
+    }
+    let padding = vec![F::ZERO; (packing - secret.len() % packing) % packing];
+    let mut groups = secret.chunks(packing);
+    let mut split_group = |group: &[F]| -> Result<Vec<S::Share>, ShamirError> {
+        let group = Secret::new([group, &padding[..packing - group.len()]].concat());
+        match packing {
+            1 => S::split_with_rng(&group.expose_secret()[0], k, n, rng),
+            _ => S::split_many_with_rng(group.expose_secret(), k, n, rng),
+        }
+    };
+    let mut records: Vec<Record<S::Share>> = split_group(groups.next().unwrap())?
+        .into_iter()
+        .map(|share| Record {
+            shares: vec![share],
+            len,
+            packing,
+            mac: [0; MAC_LEN],
+            hash,
+        })
+        .collect();
+    for group in groups {
+        let shares = split_group(group)?;
+        // Randomized shares of the different splits have different x coordinates, so the other
+        // polynomials are evaluated at the x coordinates of the first split.
+        for (record, share) in records.iter_mut().zip(&shares) {
+            let x = record.shares[0].getx();
+            let share = match share.getx() == x {
+                true => *share,
+                false => S::reconstruct_at(&shares[..k], k, x)?,
+            };
+            record.shares.push(share);
+        }
+    }
+    for record in &mut records {
+        record.mac = compute_mac(hash, secret.iter().chain(&padding), &record.body());
+    }
+    Ok(records)
+}
+
+/// Reconstructs a secret from a set of records, given the threshold k (i.e. the reconstruction
+/// threshold of ramp schemes). Returns the elements of the secret (including the zero padding of
+/// ramp schemes), wrapped in a `Secret`, and its length in bytes, or an error if reconstruction
+/// failed, if some records don't have the same number of elements, length, packing or hash
+/// algorithm as the first one, or if the tag of a record doesn't match the reconstructed secret.
+pub fn reconstruct<F: Field + Display, S: Shamir<F> + ?Sized>(
+    records: &[Record<S::Share>],
+    k: usize,
+) -> Result<(Secret<Vec<F>>, usize), ShamirError>
+where
+    S::Share: Display,
+{
+    let first = records.first().ok_or(ShamirError::NotEnoughShares)?;
+    if first.shares.is_empty() {
+        return Err(ShamirError::InvalidRecord);
+    }
+    let inconsistent: Vec<usize> = records
+        .iter()
+        .enumerate()
+        .filter(|(_, record)| {
+            record.shares.len() != first.shares.len()
+                || record.len != first.len
+                || record.packing != first.packing
+                || record.hash != first.hash
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if !inconsistent.is_empty() {
+        return Err(ShamirError::InconsistentShares(inconsistent));
+    }
+
+    let mut secret = Secret::new(Vec::with_capacity(first.shares.len() * first.packing));
+    for i in 0..first.shares.len() {
+        let shares: Vec<S::Share> = records.iter().map(|record| record.shares[i]).collect();
+        match first.packing {
+            1 => secret.expose_secret_mut().push(S::reconstruct(&shares, k)?),
+            packing => {
+                let group = Secret::new(S::reconstruct_many(&shares, k, packing)?);
+                secret
+                    .expose_secret_mut()
+                    .extend_from_slice(group.expose_secret());
+            }
+        }
+    }
+    if records.iter().any(|record| {
+        record.mac != compute_mac(record.hash, secret.expose_secret(), &record.body())
+    }) {
+        return Err(ShamirError::InvalidRecord);
+    }
+    Ok((secret, first.len))
+}
+
+/// Parses a record from a string. Returns an error if the parsing fails.
+pub fn parse_record<F: Field, S: Shamir<F> + ?Sized>(
+    s: &str,
+) -> Result<Record<S::Share>, ShamirError> {
+    let parts: Vec<&str> = s.split('|').collect();
+    let hash = match parts.len() {
+        4 => HashAlgorithm::default(),
+        5 => parts[4].parse().map_err(|_| ShamirError::Parse)?,
+        _ => return Err(ShamirError::Parse),
+    };
+    let shares = parts[1]
+        .split(',')
+        .map(|y| S::parse_share(&format!("{}|{}", parts[0], y)))
+        .collect::<Result<Vec<S::Share>, ShamirError>>()?;
+    let (len, packing) = match parts[2].split_once('/') {
+        None => (parts[2], 1),
+        Some((len, packing)) => match parse_decimal(packing)? {
+            0 | 1 => return Err(ShamirError::Parse),
+            packing => (len, packing),
+        },
+    };
+    let mac = hex::decode(parts[3])
+        .ok()
+        .and_then(|mac| mac.try_into().ok())
+        .ok_or(ShamirError::Parse)?;
+    Ok(Record {
+        shares,
+        len: parse_decimal(len)?,
+        packing,
+        mac,
+        hash,
+    })
+}
+
+/// Computes the tag of a record body, keyed with the secret.
+fn compute_mac<'a, F: Display + 'a>(
+    hash: HashAlgorithm,
+    secret: impl IntoIterator<Item = &'a F>,
+    body: &str,
+) -> [u8; MAC_LEN] {
+    // The elements are formatted directly into the hash, without intermediate copies of the secret.
+    let mut key = hash.hasher().chain_update(KEY_DOMAIN);
+    for element in secret {
+        write!(key, "{},", element).unwrap();
+    }
+    let digest = hash
+        .hasher()
+        .chain_update(MAC_DOMAIN)
+        .chain_update(key.finalize())
+        .chain_update(body)
+        .finalize();
+    digest[..MAC_LEN].try_into().unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gf2n::GF64;
+    use crate::shamir::{CompactShamir, RandomShamir};
+
+    #[test]
+    fn can_reconstruct_compact() {
+        can_reconstruct::<CompactShamir>();
+    }
+
+    #[test]
+    fn can_reconstruct_random() {
+        can_reconstruct::<RandomShamir>();
+    }
+
+    #[test]
+    fn can_parse_compact() {
+        can_parse::<CompactShamir>();
+    }
+
+    #[test]
+    fn can_parse_random() {
+        can_parse::<RandomShamir>();
+    }
+
+    #[test]
+    fn can_reconstruct_ramp_compact() {
+        can_reconstruct_ramp::<CompactShamir>();
+    }
+
+    #[test]
+    fn can_reconstruct_ramp_random() {
+        can_reconstruct_ramp::<RandomShamir>();
+    }
+
+    #[test]
+    fn can_reject_empty_secret() {
+        assert_eq!(
+            split::<GF64, CompactShamir>(&[], 0, 2, 3),
+            Err(ShamirError::InvalidRecord)
+        );
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn can_split_with_blake3() {
+        let secret = random_secret(3);
+        let records = split_with_hash::<GF64, CompactShamir, _>(
+            &secret,
+            20,
+            2,
+            3,
+            HashAlgorithm::Blake3,
+            &mut thread_rng(),
+        )
+        .unwrap();
+        for record in &records {
+            let text = record.to_string();
+            assert!(text.ends_with("|blake3"));
+            assert_eq!(
+                parse_record::<GF64, CompactShamir>(&text),
+                Ok(record.clone())
+            );
+        }
+        assert_eq!(
+            reconstruct_exposed::<CompactShamir>(&records[1..], 2),
+            Ok((secret, 20))
+        );
+
+        // The tags don't verify with another algorithm.
+        let mut relabeled = records[..2].to_vec();
+        for record in &mut relabeled {
+            record.hash = HashAlgorithm::Sha256;
+        }
+        assert_eq!(
+            reconstruct_exposed::<CompactShamir>(&relabeled, 2),
+            Err(ShamirError::InvalidRecord)
+        );
+        relabeled[0].hash = HashAlgorithm::Blake3;
+        assert_eq!(
+            reconstruct_exposed::<CompactShamir>(&relabeled, 2),
+            Err(ShamirError::InconsistentShares(vec![1]))
+        );
+    }
+
+    fn random_secret(len: usize) -> Vec<GF64> {
+        let mut rng = thread_rng();
+        (0..len).map(|_| GF64::uniform(&mut rng)).collect()
+    }
+
+    fn reconstruct_exposed<S: Shamir<GF64>>(
+        records: &[Record<S::Share>],
+        k: usize,
+    ) -> Result<(Vec<GF64>, usize), ShamirError>
+    where
+        S::Share: Display,
+    {
+        reconstruct::<GF64, S>(records, k)
+            .map(|(secret, len)| (secret.expose_secret().clone(), len))
+    }
+
+    fn can_reconstruct<S: Shamir<GF64>>()
+    where
+        S::Share: Display,
+    {
+        let secret = random_secret(5);
+        let records = split::<GF64, S>(&secret, 37, 3, 5).unwrap();
+        assert_eq!(records.len(), 5);
+        for record in &records {
+            assert_eq!(record.shares.len(), 5);
+            assert!(record
+                .shares
+                .iter()
+                .all(|s| s.getx() == record.shares[0].getx()));
+            assert_eq!(record.len, 37);
+        }
+        assert_eq!(
+            reconstruct_exposed::<S>(&records[..3], 3),
+            Ok((secret.clone(), 37))
+        );
+        assert_eq!(
+            reconstruct_exposed::<S>(&records[2..], 3),
+            Ok((secret.clone(), 37))
+        );
+        assert_eq!(
+            reconstruct_exposed::<S>(&records[..2], 3),
+            Err(ShamirError::NotEnoughShares)
+        );
+
+        // Records of different secrets or lengths can't be mixed.
+        let other = split::<GF64, S>(&random_secret(4), 37, 3, 5).unwrap();
+        assert_eq!(
+            reconstruct_exposed::<S>(&[&records[..2], &other[2..3]].concat(), 3),
+            Err(ShamirError::InconsistentShares(vec![2]))
+        );
+        let mut truncated = records[1].clone();
+        truncated.len = 36;
+        assert_eq!(
+            reconstruct_exposed::<S>(&[records[0].clone(), truncated, records[2].clone()], 3),
+            Err(ShamirError::InconsistentShares(vec![1]))
+        );
+
+        // A corrupted share within the threshold yields a wrong secret, which the tags detect.
+        let mut corrupted = records[..3].to_vec();
+        corrupted[1].shares[2] = other[1].shares[2];
+        assert_eq!(
+            reconstruct_exposed::<S>(&corrupted, 3),
+            Err(ShamirError::InvalidRecord)
+        );
+        let mut corrupted = records[..3].to_vec();
+        corrupted[0].mac[0] ^= 1;
+        assert_eq!(
+            reconstruct_exposed::<S>(&corrupted, 3),
+            Err(ShamirError::InvalidRecord)
+        );
+    }
+
+    fn can_reconstruct_ramp<S: Shamir<GF64>>()
+    where
+        S::Share: Display,
+    {
+        // 7 elements are packed 3 at a time, and padded with 2 zeros.
+        let secret = random_secret(7);
+        let mut padded = secret.clone();
+        padded.extend([GF64::ZERO; 2]);
+        let ramp = Ramp::new(2, 5).unwrap();
+        let records = split_ramp::<GF64, S>(&secret, 53, ramp, 6).unwrap();
+        assert_eq!(records.len(), 6);
+        for record in &records {
+            assert_eq!(record.shares.len(), 3);
+            assert_eq!(record.packing, 3);
+            assert!(record
+                .shares
+                .iter()
+                .all(|s| s.getx() == record.shares[0].getx()));
+            let text = record.to_string();
+            assert!(text.contains("|53/3|"));
+            assert_eq!(parse_record::<GF64, S>(&text), Ok(record.clone()));
+            for packing in ["0", "1", "03"] {
+                assert_eq!(
+                    parse_record::<GF64, S>(&text.replace("|53/3|", &format!("|53/{}|", packing))),
+                    Err(ShamirError::Parse)
+                );
+            }
+        }
+        assert_eq!(
+            reconstruct_exposed::<S>(&records[..5], 5),
+            Ok((padded.clone(), 53))
+        );
+        assert_eq!(reconstruct_exposed::<S>(&records[1..], 5), Ok((padded, 53)));
+        assert_eq!(
+            reconstruct_exposed::<S>(&records[..4], 5),
+            Err(ShamirError::NotEnoughShares)
+        );
+
+        // Records with another packing can't be mixed.
+        let mut relabeled = records[..5].to_vec();
+        relabeled[4].packing = 1;
+        assert_eq!(
+            reconstruct_exposed::<S>(&relabeled, 5),
+            Err(ShamirError::InconsistentShares(vec![4]))
+        );
+
+        // A single element per share yields ordinary records.
+        let ramp = Ramp::new(2, 3).unwrap();
+        let records = split_ramp::<GF64, S>(&secret, 53, ramp, 4).unwrap();
+        assert_eq!(records[0].to_string().matches('|').count(), 3);
+        assert_eq!(
+            reconstruct_exposed::<S>(&records[1..], 3),
+            Ok((secret.clone(), 53))
+        );
+
+        // The secrets of a ramp scheme are stored at x coordinates that can't be shares.
+        let ramp = Ramp::new(1, 3).unwrap();
+        assert_eq!(
+            split_ramp::<GF64, S>(&secret, 53, ramp, 255),
+            Err(ShamirError::InvalidThreshold)
+        );
+    }
+
+    fn can_parse<S: Shamir<GF64>>()
+    where
+        S::Share: Display,
+    {
+        let secret = random_secret(3);
+        let records = split::<GF64, S>(&secret, 20, 2, 3).unwrap();
+        for record in &records {
+            let text = record.to_string();
+            assert_eq!(text.matches('|').count(), 3);
+            assert_eq!(text.matches(',').count(), 2);
+            assert_eq!(parse_record::<GF64, S>(&text), Ok(record.clone()));
+
+            let (body, _) = text.rsplit_once('|').unwrap();
+            assert_eq!(parse_record::<GF64, S>(body), Err(ShamirError::Parse));
+            assert_eq!(
+                parse_record::<GF64, S>(&format!("{}|00", body)),
+                Err(ShamirError::Parse)
+            );
+            assert_eq!(
+                parse_record::<GF64, S>(&text.replacen(",", ",,", 1)),
+                Err(ShamirError::Parse)
+            );
+        }
+    }
+}
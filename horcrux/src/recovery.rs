@@ -0,0 +1,477 @@
+//! Reconstruction of an Ed25519 scalar from shares re-encrypted by their custodians to the key of a
+//! recovery session, so that the coordinator collecting them never sees the raw shares.
+//!
+//! 1. The requester starts a recovery session by generating a `SessionKey`, and publishes its
+//!    `SessionPublicKey`.
+//! 2. Each custodian locally transforms its share into a `Response` bound to this public key: the
+//!    share is masked with a scalar derived from a Diffie-Hellman exchange with the session key,
+//!    together with the commitment to the mask and a Schnorr proof of knowledge of the mask.
+//! 3. The coordinator (or anyone) verifies each response against the public share of its
+//!    custodian, i.e. the multiple of the base point by the share, which is published when the
+//!    secret is split (see `public_share()` and `dkg::public_share()`). A valid response proves
+//!    that the custodian knows its share, without revealing it.
+//! 4. The requester unmasks the responses with the session key, and reconstructs the secret.
+//!
+//! The coordinator can't unmask the responses, and a response can't be replayed to another
+//! session, as both the mask and the proof depend on the session public key. A custodian that
+//! derives its mask incorrectly passes the public verification, but is detected when the requester
+//! opens its response: this only denies the recovery, and the custodian is reported like one whose
+//! response is invalid.
+//!
+//! The masks are derived from the Diffie-Hellman secret with SHA-512, and the responses have a text
+//! format: `<x>|<masked share>|<ephemeral point>|<mask point>|<proof point>|<proof scalar>`, with
+//! scalars in big-endian hexadecimal like the shares, and points in the compressed encoding of
+//! Ed25519 in hexadecimal.
+
+use crate::dkg::{parse_point, to_scalar};
+use crate::gfp::Ed25519Scalar;
+use crate::shamir::{parse_decimal, CompactShamir, GetX, Shamir, ShamirError, Share};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::Display;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::{thread_rng, CryptoRng, Rng};
+use sha2::{Digest, Sha512};
+
+/// Domain separation string of the masks of the shares.
+const MASK_DOMAIN: &[u8] = b"horcrux-recovery-mask-v1";
+/// Domain separation string of the challenges of the proofs.
+const PROOF_DOMAIN: &[u8] = b"horcrux-recovery-proof-v1";
+
+/// Secret key of a recovery session, held by the requester.
+pub struct SessionKey {
+    secret: Scalar,
+    public: SessionPublicKey,
+}
+
+/// Public key of a recovery session, to which the custodians re-encrypt their shares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionPublicKey(pub EdwardsPoint);
+
+/// Share re-encrypted by its custodian to the key of a recovery session, with a proof that the
+/// custodian knows the share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    /// Index of the custodian, i.e. the x coordinate of its share.
+    pub x: u8,
+    /// Share plus the mask.
+    pub masked: Ed25519Scalar,
+    /// Ephemeral point of the Diffie-Hellman exchange with the session key.
+    pub ephemeral: EdwardsPoint,
+    /// Multiple of the base point by the mask.
+    pub mask_point: EdwardsPoint,
+    /// Commitment of the Schnorr proof of knowledge of the mask.
+    pub proof_point: EdwardsPoint,
+    /// Response of the Schnorr proof of knowledge of the mask.
+    pub proof_scalar: Ed25519Scalar,
+}
+
+impl SessionKey {
+    /// Generates the key of a new recovery session.
+    pub fn generate() -> Self {
+        Self::generate_with_rng(&mut thread_rng())
+    }
+
+    /// Generates the key of a new recovery session, with the given random number generator.
+    pub fn generate_with_rng<R: Rng + CryptoRng + ?Sized>(rng: &mut R) -> Self {
+        let secret = random_scalar(rng);
+        SessionKey {
+            secret,
+            public: SessionPublicKey(EdwardsPoint::mul_base(&secret)),
+        }
+    }
+
+    /// Returns the public key of the session, to send to the custodians.
+    pub fn public_key(&self) -> SessionPublicKey {
+        self.public
+    }
+
+    /// Verifies a response against the public share of its custodian, and unmasks it into the
+    /// share of the custodian. Returns `ShamirError::InvalidContributions` with the index of the
+    /// custodian if the response is invalid or if its mask wasn't derived from the session key.
+    pub fn open(
+        &self,
+        response: &Response,
+        public_share: &EdwardsPoint,
+    ) -> Result<Share<u8, Ed25519Scalar>, ShamirError> {
+        let invalid = || ShamirError::InvalidContributions(vec![response.x]);
+        if !response.verify(&self.public, public_share) {
+            return Err(invalid());
+        }
+        let mask = mask(
+            &self.public,
+            response.x,
+            &response.ephemeral,
+            &(response.ephemeral * self.secret),
+        );
+        if EdwardsPoint::mul_base(&mask) != response.mask_point {
+            return Err(invalid());
+        }
+        Share::<u8, _>::new(response.x, response.masked - from_scalar(mask))
+    }
+
+    /// Opens the responses with `open()`, given a function returning the public share of each
+    /// custodian, and reconstructs the secret with the threshold k. Returns an error listing the
+    /// custodians whose responses are invalid, or the error of the reconstruction.
+    pub fn reconstruct(
+        &self,
+        responses: &[Response],
+        public_share: impl Fn(u8) -> EdwardsPoint,
+        k: usize,
+    ) -> Result<Ed25519Scalar, ShamirError> {
+        let mut shares = Vec::with_capacity(responses.len());
+        let mut invalid = Vec::new();
+        for response in responses {
+            match self.open(response, &public_share(response.x)) {
+                Ok(share) => shares.push(share),
+                Err(_) => invalid.push(response.x),
+            }
+        }
+        if !invalid.is_empty() {
+            return Err(ShamirError::InvalidContributions(invalid));
+        }
+        CompactShamir::reconstruct(&shares, k)
+    }
+}
+
+impl Drop for SessionKey {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut self.secret);
+    }
+}
+
+/// Returns the public share corresponding to a share, i.e. the multiple of the base point by the
+/// share, which is published when the secret is split to verify the responses of its custodian.
+pub fn public_share(share: &Share<u8, Ed25519Scalar>) -> EdwardsPoint {
+    EdwardsPoint::mul_base(&to_scalar(*share.y()))
+}
+
+/// Re-encrypts a share to the key of a recovery session. The custodian can send the response to
+/// the coordinator of the session without revealing its share.
+pub fn respond(share: &Share<u8, Ed25519Scalar>, session: &SessionPublicKey) -> Response {
+    respond_with_rng(share, session, &mut thread_rng())
+}
+
+/// Re-encrypts a share like `respond()`, with the given random number generator.
+pub fn respond_with_rng<R: Rng + CryptoRng + ?Sized>(
+    share: &Share<u8, Ed25519Scalar>,
+    session: &SessionPublicKey,
+    rng: &mut R,
+) -> Response {
+    let x = share.getx();
+    let ephemeral_secret = random_scalar(rng);
+    let ephemeral = EdwardsPoint::mul_base(&ephemeral_secret);
+    let mask = mask(session, x, &ephemeral, &(session.0 * ephemeral_secret));
+    let masked = *share.y() + from_scalar(mask);
+    let mask_point = EdwardsPoint::mul_base(&mask);
+
+    let nonce = random_scalar(rng);
+    let proof_point = EdwardsPoint::mul_base(&nonce);
+    let challenge = challenge(session, x, &masked, &ephemeral, &mask_point, &proof_point);
+    Response {
+        x,
+        masked,
+        ephemeral,
+        mask_point,
+        proof_point,
+        proof_scalar: from_scalar(nonce + challenge * mask),
+    }
+}
+
+impl Response {
+    /// Verifies the response against the session public key and the public share of its
+    /// custodian, i.e. that the masked share matches the public share and the commitment to the
+    /// mask, and that the custodian knows the mask. This doesn't require the session key, so the
+    /// coordinator can filter out invalid responses before forwarding them to the requester.
+    pub fn verify(&self, session: &SessionPublicKey, public_share: &EdwardsPoint) -> bool {
+        if self.x == 0
+            || EdwardsPoint::mul_base(&to_scalar(self.masked)) != public_share + self.mask_point
+        {
+            return false;
+        }
+        let challenge = challenge(
+            session,
+            self.x,
+            &self.masked,
+            &self.ephemeral,
+            &self.mask_point,
+            &self.proof_point,
+        );
+        EdwardsPoint::mul_base(&to_scalar(self.proof_scalar))
+            == self.proof_point + self.mask_point * challenge
+    }
+
+    /// Parses a response from a string. Returns an error if the parsing fails, or if a point isn't
+    /// in the prime-order subgroup of the curve.
+    pub fn parse(s: &str) -> Result<Self, ShamirError> {
+        let parts: Vec<&str> = s.split('|').collect();
+        let [x, masked, ephemeral, mask_point, proof_point, proof_scalar] = parts[..] else {
+            return Err(ShamirError::Parse);
+        };
+        let x = match parse_decimal(x)? {
+            0 => return Err(ShamirError::InvalidX),
+            x => x,
+        };
+        Ok(Response {
+            x,
+            masked: masked.parse()?,
+            ephemeral: parse_point(ephemeral)?,
+            mask_point: parse_point(mask_point)?,
+            proof_point: parse_point(proof_point)?,
+            proof_scalar: proof_scalar.parse()?,
+        })
+    }
+}
+
+/// Formats the response as `<x>|<masked share>|<ephemeral point>|<mask point>|<proof point>|<proof
+/// scalar>`.
+impl Display for Response {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}|{}", self.x, self.masked)?;
+        for point in [&self.ephemeral, &self.mask_point, &self.proof_point] {
+            write!(f, "|{}", hex::encode(point.compress().as_bytes()))?;
+        }
+        write!(f, "|{}", self.proof_scalar)
+    }
+}
+
+impl SessionPublicKey {
+    /// Parses a session public key from its compressed encoding in hexadecimal. Returns an error
+    /// if the parsing fails, or if the point isn't in the prime-order subgroup of the curve.
+    pub fn parse(s: &str) -> Result<Self, ShamirError> {
+        parse_point(s).map(SessionPublicKey)
+    }
+}
+
+/// Formats the session public key in its compressed encoding in hexadecimal.
+impl Display for SessionPublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(&hex::encode(self.0.compress().as_bytes()))
+    }
+}
+
+/// Converts a scalar of the curve to a field element, which have the same modulus (the converse
+/// of `dkg::to_scalar()`).
+fn from_scalar(x: Scalar) -> Ed25519Scalar {
+    let bytes = x.to_bytes();
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    Ed25519Scalar::from_limbs(limbs).unwrap()
+}
+
+fn random_scalar<R: Rng + CryptoRng + ?Sized>(rng: &mut R) -> Scalar {
+    let mut bytes = [0u8; 64];
+    rng.fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+/// Hashes the given points and scalars into a scalar, with a domain separation string.
+fn hash_to_scalar(
+    domain: &[u8],
+    x: u8,
+    points: &[&EdwardsPoint],
+    scalars: &[&Ed25519Scalar],
+) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(domain);
+    hasher.update([x]);
+    for point in points {
+        hasher.update(point.compress().as_bytes());
+    }
+    for scalar in scalars {
+        hasher.update(crate::field::Field::to_bytes(*scalar));
+    }
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+/// Derives the mask of the share at x from the Diffie-Hellman secret shared with the session key.
+fn mask(session: &SessionPublicKey, x: u8, ephemeral: &EdwardsPoint, dh: &EdwardsPoint) -> Scalar {
+    hash_to_scalar(MASK_DOMAIN, x, &[&session.0, ephemeral, dh], &[])
+}
+
+/// Computes the Fiat-Shamir challenge of the proof of knowledge of the mask, which binds the
+/// proof to the session and to the whole response.
+fn challenge(
+    session: &SessionPublicKey,
+    x: u8,
+    masked: &Ed25519Scalar,
+    ephemeral: &EdwardsPoint,
+    mask_point: &EdwardsPoint,
+    proof_point: &EdwardsPoint,
+) -> Scalar {
+    hash_to_scalar(
+        PROOF_DOMAIN,
+        x,
+        &[&session.0, ephemeral, mask_point, proof_point],
+        &[masked],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::field::Field;
+
+    /// Splits a random secret with the threshold k among n custodians, and returns the secret,
+    /// the shares and the public shares.
+    fn split(
+        k: usize,
+        n: usize,
+    ) -> (
+        Ed25519Scalar,
+        Vec<Share<u8, Ed25519Scalar>>,
+        Vec<EdwardsPoint>,
+    ) {
+        let secret = Ed25519Scalar::uniform(&mut thread_rng());
+        let shares = CompactShamir::split(&secret, k, n).unwrap();
+        let public_shares = shares.iter().map(public_share).collect();
+        (secret, shares, public_shares)
+    }
+
+    #[test]
+    fn can_reconstruct_from_responses() {
+        let (secret, shares, public_shares) = split(3, 5);
+        let session = SessionKey::generate();
+        let responses: Vec<Response> = shares
+            .iter()
+            .map(|share| respond(share, &session.public_key()))
+            .collect();
+        for (response, (share, public)) in responses.iter().zip(shares.iter().zip(&public_shares)) {
+            assert!(response.verify(&session.public_key(), public));
+            assert_ne!(response.masked, *share.y());
+            assert_eq!(session.open(response, public), Ok(*share));
+        }
+
+        let lookup = |x: u8| public_shares[x as usize - 1];
+        assert_eq!(session.reconstruct(&responses[..3], lookup, 3), Ok(secret));
+        assert_eq!(session.reconstruct(&responses[2..], lookup, 3), Ok(secret));
+        assert_eq!(
+            session.reconstruct(&responses[..2], lookup, 3),
+            Err(ShamirError::NotEnoughShares)
+        );
+    }
+
+    #[test]
+    fn can_reconstruct_generated_key() {
+        use crate::dkg::{self, Commitment, Participant};
+
+        let participants: Vec<Participant> = (1..=3)
+            .map(|i| Participant::new(i, 2, 3).unwrap())
+            .collect();
+        let commitments: Vec<Commitment> = participants.iter().map(|p| p.commitment()).collect();
+        let private_shares: Vec<_> = participants
+            .iter()
+            .flat_map(|p| p.private_shares())
+            .collect();
+        let outputs: Vec<_> = participants
+            .into_iter()
+            .map(|p| p.finish(&commitments, &private_shares).unwrap())
+            .collect();
+
+        let session = SessionKey::generate();
+        let responses: Vec<Response> = outputs[1..]
+            .iter()
+            .map(|o| respond(&o.share, &session.public_key()))
+            .collect();
+        let secret = session
+            .reconstruct(&responses, |x| dkg::public_share(&commitments, x), 2)
+            .unwrap();
+        assert_eq!(
+            EdwardsPoint::mul_base(&to_scalar(secret)),
+            outputs[0].public_key
+        );
+    }
+
+    #[test]
+    fn can_detect_invalid_responses() {
+        let (_, shares, public_shares) = split(2, 3);
+        let session = SessionKey::generate();
+        let public_key = session.public_key();
+        let response = respond(&shares[0], &public_key);
+        let public = &public_shares[0];
+        let invalid = Err(ShamirError::InvalidContributions(vec![1]));
+
+        // Wrong public share, and tampered response.
+        assert!(!response.verify(&public_key, &public_shares[1]));
+        let mut tampered = response.clone();
+        tampered.masked += &Ed25519Scalar::ONE;
+        assert!(!tampered.verify(&public_key, public));
+        assert_eq!(session.open(&tampered, public), invalid);
+        let mut tampered = response.clone();
+        tampered.x = 2;
+        assert!(!tampered.verify(&public_key, &public_shares[1]));
+
+        // A response to another session can't be replayed, nor opened with another key.
+        let other = SessionKey::generate();
+        assert!(!response.verify(&other.public_key(), public));
+        assert_eq!(other.open(&response, public), invalid);
+
+        // A custodian that doesn't derive its mask from the session key passes the public
+        // verification, but is detected by the requester.
+        let mut rng = thread_rng();
+        let mask = random_scalar(&mut rng);
+        let masked = *shares[0].y() + from_scalar(mask);
+        let mask_point = EdwardsPoint::mul_base(&mask);
+        let nonce = random_scalar(&mut rng);
+        let proof_point = EdwardsPoint::mul_base(&nonce);
+        let c = challenge(
+            &public_key,
+            1,
+            &masked,
+            &response.ephemeral,
+            &mask_point,
+            &proof_point,
+        );
+        let dishonest = Response {
+            x: 1,
+            masked,
+            ephemeral: response.ephemeral,
+            mask_point,
+            proof_point,
+            proof_scalar: from_scalar(nonce + c * mask),
+        };
+        assert!(dishonest.verify(&public_key, public));
+        assert_eq!(session.open(&dishonest, public), invalid);
+
+        let responses = [dishonest, respond(&shares[1], &public_key), tampered];
+        assert_eq!(
+            session.reconstruct(&responses, |x| public_shares[x as usize - 1], 2),
+            Err(ShamirError::InvalidContributions(vec![1, 2]))
+        );
+    }
+
+    #[test]
+    fn can_parse_responses() {
+        let (_, shares, public_shares) = split(2, 2);
+        let session = SessionKey::generate();
+        let public_key = SessionPublicKey::parse(&session.public_key().to_string()).unwrap();
+        assert_eq!(public_key, session.public_key());
+
+        let response = respond(&shares[1], &public_key);
+        let text = response.to_string();
+        assert_eq!(text.matches('|').count(), 5);
+        let parsed = Response::parse(&text).unwrap();
+        assert_eq!(parsed, response);
+        assert!(parsed.verify(&public_key, &public_shares[1]));
+
+        assert_eq!(
+            Response::parse(&text.replacen("2|", "0|", 1)),
+            Err(ShamirError::InvalidX)
+        );
+        assert_eq!(
+            Response::parse(&text[..text.len() - 2]),
+            Err(ShamirError::Parse)
+        );
+        assert_eq!(
+            Response::parse(text.rsplit_once('|').unwrap().0),
+            Err(ShamirError::Parse)
+        );
+        // The point (0, -1) has order 2, outside of the prime-order subgroup.
+        let torsion = format!("ec{}7f", "ff".repeat(30));
+        assert_eq!(SessionPublicKey::parse(&torsion), Err(ShamirError::Parse));
+    }
+}
@@ -0,0 +1,98 @@
+//! Combination of random number generators, so that the randomness of a split doesn't rely on a
+//! single source.
+
+use rand::{CryptoRng, Error, RngCore};
+
+/// RNG whose output is the XOR of the outputs of two independent RNGs. The output is uniformly
+/// random as long as one of the sources is, even if the other one is malicious, provided that the
+/// sources don't observe each other's output.
+pub struct XorRng<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: RngCore, B: RngCore> XorRng<A, B> {
+    /// Combines the two given RNGs.
+    pub fn new(a: A, b: B) -> Self {
+        XorRng { a, b }
+    }
+
+    /// Returns the two underlying RNGs.
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: RngCore, B: RngCore> RngCore for XorRng<A, B> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.a.try_fill_bytes(dest)?;
+        let mut other = [0u8; 64];
+        for chunk in dest.chunks_mut(other.len()) {
+            let other = &mut other[..chunk.len()];
+            self.b.try_fill_bytes(other)?;
+            for (x, y) in chunk.iter_mut().zip(other.iter()) {
+                *x ^= y;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A: CryptoRng, B: CryptoRng> CryptoRng for XorRng<A, B> {}
+
+#[cfg(test)]
+mod test {
+    use super::XorRng;
+    use crate::field::Field;
+    use crate::gf2n::GF256;
+    use crate::shamir::{CompactShamir, Shamir};
+    use rand::rngs::mock::StepRng;
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn xor_with_zero_is_identity() {
+        let mut rng = XorRng::new(ChaCha20Rng::seed_from_u64(42), StepRng::new(0, 0));
+        let mut expected = ChaCha20Rng::seed_from_u64(42);
+
+        let mut bytes = [0u8; 200];
+        let mut expected_bytes = [0u8; 200];
+        rng.fill_bytes(&mut bytes);
+        expected.fill_bytes(&mut expected_bytes);
+        assert_eq!(bytes, expected_bytes);
+        assert_eq!(rng.next_u64(), expected.next_u64());
+    }
+
+    #[test]
+    fn xor_combines_both_sources() {
+        let mut rng = XorRng::new(ChaCha20Rng::seed_from_u64(1), ChaCha20Rng::seed_from_u64(2));
+        let (mut a, mut b) = (ChaCha20Rng::seed_from_u64(1), ChaCha20Rng::seed_from_u64(2));
+        for _ in 0..10 {
+            assert_eq!(rng.next_u32(), a.next_u32() ^ b.next_u32());
+        }
+    }
+
+    #[test]
+    fn can_split_with_xor_rng() {
+        let mut rng = XorRng::new(ChaCha20Rng::seed_from_u64(1), ChaCha20Rng::seed_from_u64(2));
+        let secret = GF256::uniform(&mut rng);
+        let shares = CompactShamir::split_with_rng(&secret, 3, 5, &mut rng).unwrap();
+        assert_eq!(CompactShamir::reconstruct(&shares, 3), Ok(secret));
+    }
+}
@@ -0,0 +1,675 @@
+//! Versioned schemas of the artifacts written by horcrux, so that other implementations can read
+//! and write them.
+//!
+//! Two artifacts are covered:
+//! - share lines, i.e. a share in text format (`x|y`, optionally followed by a `|`-separated
+//!   checksum), optionally prefixed by a header with the parameters of the split, e.g.
+//!   `horcrux1:compact:gf256:3:5f1c0a2e:1|...`,
+//! - split manifests, i.e. the JSON output of a split.
+//!
+//! The JSON Schema of each artifact is published in `SHARE_JSON_SCHEMA` and
+//! `MANIFEST_JSON_SCHEMA`.
+
+use crate::hashing::HashAlgorithm;
+use core::fmt::Display;
+use core::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Version of the share header format, which starts each header.
+pub const SHARE_VERSION: &str = "horcrux1";
+/// Version of the manifest format.
+pub const MANIFEST_VERSION: u32 = 1;
+/// Prefix of the messages signed by the operators of a split, before the manifest itself (see
+/// `SplitManifest::signatures`).
+pub const MANIFEST_SIGNATURE_DOMAIN: &str = "horcrux-manifest-signature-v1\n";
+
+/// JSON Schema (draft 2020-12) of a share line, represented as a JSON string.
+pub const SHARE_JSON_SCHEMA: &str = r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "$id": "urn:horcrux:share:v1",
+  "title": "horcrux share",
+  "description": "Share in text format: an optional header `horcrux1:<scheme>:<field>:<threshold>:<set id>:`, the x coordinate (decimal for compact shares, hexadecimal for random shares), `|`, the y coordinate in hexadecimal, and an optional checksum of 4 hexadecimal characters after another `|`.",
+  "type": "string",
+  "pattern": "^(horcrux1:(compact|random):[a-z0-9]+:[1-9][0-9]*:[A-Za-z0-9_-]+:)?[0-9a-fA-F]+\\|[0-9a-fA-F]+(\\|[0-9a-fA-F]{4})?$"
+}
+"#;
+
+/// JSON Schema (draft 2020-12) of a split manifest.
+pub const MANIFEST_JSON_SCHEMA: &str = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "$id": "urn:horcrux:manifest:v1",
+  "title": "horcrux split manifest",
+  "description": "JSON output of a split. Manifests written before the version field was introduced don't have it, and are of version 1.",
+  "type": "object",
+  "properties": {
+    "version": { "const": 1 },
+    "scheme": { "enum": ["compact", "random"] },
+    "field": { "enum": ["binary", "secp256k1", "ed25519"] },
+    "bitsize": { "type": "integer", "minimum": 8 },
+    "threshold": { "type": "integer", "minimum": 1, "maximum": 255 },
+    "group_threshold": { "type": "integer", "minimum": 1, "maximum": 255 },
+    "nshares": { "type": "integer", "minimum": 1, "maximum": 255 },
+    "groups": { "type": "array", "items": { "type": "string" } },
+    "access": { "type": "string" },
+    "set_id": { "type": "string", "pattern": "^[A-Za-z0-9_-]+$" },
+    "x_key": { "$ref": "#/$defs/hex" },
+    "secret_at": { "type": "string" },
+    "secret_len": { "type": "integer", "minimum": 1 },
+    "privacy_threshold": { "type": "integer", "minimum": 1, "maximum": 254 },
+    "decoy_threshold": { "type": "integer", "minimum": 1, "maximum": 255 },
+    "decoy": { "$ref": "#/$defs/hex" },
+    "secret": { "$ref": "#/$defs/hex" },
+    "hash": {
+      "description": "Hash algorithm of the commitments, digests and verification codes. Manifests without this field use sha256.",
+      "enum": ["sha256", "blake3"]
+    },
+    "secret_commitment": { "$ref": "#/$defs/hex" },
+    "polynom": { "type": "array", "items": { "$ref": "#/$defs/hex" } },
+    "shares": { "type": "array", "items": { "type": "string" } },
+    "shares_digest": { "$ref": "#/$defs/hex" },
+    "rng_commitments": {
+      "type": "object",
+      "properties": {
+        "os": { "$ref": "#/$defs/hex" },
+        "second": { "$ref": "#/$defs/hex" }
+      },
+      "required": ["os", "second"],
+      "additionalProperties": false
+    },
+    "verification_codes": { "type": "array", "items": { "type": "string" } },
+    "share_macs": {
+      "description": "MACs of the shares under the verification key of the custodians, in the same order.",
+      "type": "array",
+      "items": { "type": "string", "pattern": "^([a-z0-9]+:)?[0-9a-f]{64}$" }
+    },
+    "witness_shares": { "type": "array", "items": { "type": "string" } },
+    "policy_override": {
+      "type": "object",
+      "properties": {
+        "violations": { "type": "array", "items": { "type": "string" } },
+        "justification": { "type": "string" }
+      },
+      "required": ["violations", "justification"],
+      "additionalProperties": false
+    },
+    "signatures": {
+      "description": "Ed25519 signatures of the operators, over `horcrux-manifest-signature-v1` and a newline, followed by the manifest without this field, as JSON with sorted keys and without whitespace.",
+      "type": "array",
+      "items": {
+        "type": "object",
+        "properties": {
+          "operator": { "type": "string" },
+          "public_key": { "type": "string", "pattern": "^[0-9a-f]{64}$" },
+          "signature": { "type": "string", "pattern": "^[0-9a-f]{128}$" }
+        },
+        "required": ["operator", "public_key", "signature"],
+        "additionalProperties": false
+      }
+    }
+  },
+  "required": ["scheme", "field", "bitsize", "shares"],
+  "additionalProperties": false,
+  "$defs": {
+    "hex": { "type": "string", "pattern": "^[0-9a-fA-F]*$" }
+  }
+}
+"##;
+
+/// Errors returned when parsing an artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// The header has an unsupported version.
+    UnsupportedVersion(String),
+    /// The header or share is malformed.
+    Malformed,
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SchemaError::UnsupportedVersion(version) => {
+                f.write_fmt(format_args!("unsupported version: {version}"))
+            }
+            SchemaError::Malformed => f.write_str("malformed share"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Type of the shares of a split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scheme {
+    /// Compact shares, at x = 1, 2, ..., n (see `CompactShamir`).
+    Compact,
+    /// Shares at random x coordinates (see `RandomShamir`).
+    Random,
+}
+
+impl Scheme {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Scheme::Compact => "compact",
+            Scheme::Random => "random",
+        }
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self, SchemaError> {
+        match s {
+            "compact" => Ok(Scheme::Compact),
+            "random" => Ok(Scheme::Random),
+            _ => Err(SchemaError::Malformed),
+        }
+    }
+}
+
+/// Parameters of a split, embedded in each of its shares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareHeader {
+    /// Type of the shares.
+    pub scheme: Scheme,
+    /// Name of the field: `gf<bitsize>` for binary fields, or the name of the elliptic curve, in
+    /// lowercase.
+    pub field: String,
+    /// Number of shares needed to reconstruct the secret.
+    pub threshold: usize,
+    /// Identifier of the split, made of alphanumeric characters, '-' and '_'.
+    pub set_id: String,
+}
+
+impl Display for ShareHeader {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}:{}:{}:{}:{}:",
+            SHARE_VERSION,
+            self.scheme.as_str(),
+            self.field,
+            self.threshold,
+            self.set_id
+        ))
+    }
+}
+
+/// Share in text format, optionally prefixed by a header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareLine {
+    /// Header with the parameters of the split, if any.
+    pub header: Option<ShareHeader>,
+    /// Share without the header, i.e. `x|y` and an optional checksum, which aren't interpreted.
+    pub share: String,
+}
+
+impl FromStr for ShareLine {
+    type Err = SchemaError;
+
+    fn from_str(line: &str) -> Result<Self, SchemaError> {
+        let (header, share) = if line.starts_with("horcrux") {
+            let parts: Vec<&str> = line.splitn(6, ':').collect();
+            if parts[0] != SHARE_VERSION {
+                return Err(SchemaError::UnsupportedVersion(parts[0].to_owned()));
+            }
+            if parts.len() != 6 {
+                return Err(SchemaError::Malformed);
+            }
+            let threshold =
+                crate::shamir::parse_decimal(parts[3]).map_err(|_| SchemaError::Malformed)?;
+            let field_ok = !parts[2].is_empty()
+                && parts[2]
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+            let set_id_ok = !parts[4].is_empty()
+                && parts[4]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if threshold == 0 || !field_ok || !set_id_ok {
+                return Err(SchemaError::Malformed);
+            }
+            let header = ShareHeader {
+                scheme: parts[1].parse()?,
+                field: parts[2].to_owned(),
+                threshold,
+                set_id: parts[4].to_owned(),
+            };
+            (Some(header), parts[5])
+        } else {
+            (None, line)
+        };
+
+        let fields: Vec<&str> = share.split('|').collect();
+        let is_hex = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit());
+        let valid = match fields.as_slice() {
+            [x, y] => is_hex(x) && is_hex(y),
+            [x, y, checksum] => is_hex(x) && is_hex(y) && is_hex(checksum) && checksum.len() == 4,
+            _ => false,
+        };
+        if !valid {
+            return Err(SchemaError::Malformed);
+        }
+        Ok(ShareLine {
+            header,
+            share: share.to_owned(),
+        })
+    }
+}
+
+impl Display for ShareLine {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if let Some(header) = &self.header {
+            header.fmt(f)?;
+        }
+        f.write_str(&self.share)
+    }
+}
+
+/// Share lines are represented by strings in JSON.
+impl Serialize for ShareLine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ShareLine {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Field in which a secret is split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    /// Binary field GF(2^bitsize).
+    Binary,
+    /// Scalar field of the secp256k1 curve.
+    Secp256k1,
+    /// Scalar field of the Ed25519 curve.
+    Ed25519,
+}
+
+/// Commitments to the contributions of the two sources of randomness of a split.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RngCommitments {
+    /// Commitment to the contribution of the OS RNG, in hexadecimal.
+    pub os: String,
+    /// Commitment to the contribution of the second source, in hexadecimal.
+    pub second: String,
+}
+
+/// Policy violations accepted by the operator of a split.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyOverride {
+    /// Rules of the policy that the split violates.
+    pub violations: Vec<String>,
+    /// Justification given by the operator.
+    pub justification: String,
+}
+
+/// Signature of a manifest by an operator of the split.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestSignature {
+    /// Name of the operator.
+    pub operator: String,
+    /// Ed25519 public key of the operator, in hexadecimal.
+    pub public_key: String,
+    /// Ed25519 signature, in hexadecimal.
+    pub signature: String,
+}
+
+fn default_version() -> u32 {
+    MANIFEST_VERSION
+}
+
+/// JSON output of a split. Optional fields are only present for the corresponding options of the
+/// split.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SplitManifest {
+    /// Version of the manifest format.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Type of the shares.
+    pub scheme: Scheme,
+    /// Field in which the secret is split.
+    pub field: FieldKind,
+    /// Size of the binary field in bits, also given for the curve fields.
+    pub bitsize: usize,
+    /// Threshold of a single-level split.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<usize>,
+    /// Number of groups needed to reconstruct a split into groups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_threshold: Option<usize>,
+    /// Number of shares.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nshares: Option<usize>,
+    /// Shares of the secret among the groups, for a split into groups.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<String>>,
+    /// Access policy over named custodians, for a split according to an access policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<String>,
+    /// Identifier of the split, given in the share headers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_id: Option<String>,
+    /// Key deriving the x coordinates of compressed shares, in hexadecimal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_key: Option<String>,
+    /// x coordinate at which the secret is stored, in hexadecimal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_at: Option<String>,
+    /// Length in bytes of a secret split into records.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_len: Option<usize>,
+    /// Privacy threshold of a secret split into records with a ramp scheme, below the threshold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy_threshold: Option<usize>,
+    /// Number of decoy envelopes needed to reconstruct the decoy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoy_threshold: Option<usize>,
+    /// Decoy secret, in hexadecimal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoy: Option<String>,
+    /// Secret, in hexadecimal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Hash algorithm of the commitments, digests and verification codes of the split. Manifests
+    /// without it use SHA-256.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<HashAlgorithm>,
+    /// Commitment to the secret in hexadecimal, i.e. a random 32-byte salt followed by the MAC of
+    /// the secret keyed by the salt, or the plain hash of the secret in earlier manifests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_commitment: Option<String>,
+    /// Coefficients of the polynomial of the split, in hexadecimal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polynom: Option<Vec<String>>,
+    /// Shares in text format. Shares of records, decoy envelopes, shares of groups and shares of
+    /// custodians have their own formats, so they aren't parsed as share lines.
+    pub shares: Vec<String>,
+    /// Digest of the canonical set of shares, in hexadecimal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shares_digest: Option<String>,
+    /// Commitments to the sources of randomness of a split with two sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rng_commitments: Option<RngCommitments>,
+    /// Verification codes of the shares, in the same order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_codes: Option<Vec<String>>,
+    /// MACs of the shares under the verification key of the custodians, in the same order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_macs: Option<Vec<String>>,
+    /// Shares of the secret held by the witnesses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness_shares: Option<Vec<String>>,
+    /// Policy violations accepted by the operator, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_override: Option<PolicyOverride>,
+    /// Signatures of the operators of the split. Each signature is over `MANIFEST_SIGNATURE_DOMAIN`
+    /// followed by the manifest without this field, serialized as JSON with sorted keys and
+    /// without whitespace, so that operators can sign in any order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatures: Option<Vec<ManifestSignature>>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::field::Field;
+    use crate::gf2n::GF256;
+    use crate::shamir::{append_checksum, CompactShamir, RandomShamir, Shamir};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use serde_json::{json, Value};
+    use std::collections::BTreeSet;
+
+    fn full_manifest() -> SplitManifest {
+        SplitManifest {
+            version: 1,
+            scheme: Scheme::Random,
+            field: FieldKind::Binary,
+            bitsize: 128,
+            threshold: Some(2),
+            group_threshold: Some(2),
+            nshares: Some(3),
+            groups: Some(vec!["1|00".into()]),
+            access: Some("alice and bob".into()),
+            set_id: Some("5f1c0a2e".into()),
+            x_key: Some("00".into()),
+            secret_at: Some("01".into()),
+            secret_len: Some(16),
+            privacy_threshold: Some(1),
+            decoy_threshold: Some(2),
+            decoy: Some("00".into()),
+            secret: Some("00".into()),
+            hash: Some(HashAlgorithm::Sha256),
+            secret_commitment: Some("00".into()),
+            polynom: Some(vec!["00".into()]),
+            shares: vec!["01|00".into()],
+            shares_digest: Some("00".into()),
+            rng_commitments: Some(RngCommitments {
+                os: "00".into(),
+                second: "00".into(),
+            }),
+            verification_codes: Some(vec!["abc-def".into()]),
+            share_macs: Some(vec![format!("blake3:{}", "00".repeat(32))]),
+            witness_shares: Some(vec!["1|00".into()]),
+            policy_override: Some(PolicyOverride {
+                violations: vec!["threshold too low".into()],
+                justification: "test".into(),
+            }),
+            signatures: Some(vec![ManifestSignature {
+                operator: "alice".into(),
+                public_key: "00".repeat(32),
+                signature: "00".repeat(64),
+            }]),
+        }
+    }
+
+    fn minimal_manifest() -> SplitManifest {
+        SplitManifest {
+            version: 1,
+            scheme: Scheme::Compact,
+            field: FieldKind::Ed25519,
+            bitsize: 256,
+            threshold: None,
+            group_threshold: None,
+            nshares: None,
+            groups: None,
+            access: None,
+            set_id: None,
+            x_key: None,
+            secret_at: None,
+            secret_len: None,
+            privacy_threshold: None,
+            decoy_threshold: None,
+            decoy: None,
+            secret: None,
+            hash: None,
+            secret_commitment: None,
+            polynom: None,
+            shares: vec![],
+            shares_digest: None,
+            rng_commitments: None,
+            verification_codes: None,
+            share_macs: None,
+            witness_shares: None,
+            policy_override: None,
+            signatures: None,
+        }
+    }
+
+    fn keys(value: &Value) -> BTreeSet<String> {
+        value.as_object().unwrap().keys().cloned().collect()
+    }
+
+    #[test]
+    fn schemas_are_valid_json() {
+        for schema in [SHARE_JSON_SCHEMA, MANIFEST_JSON_SCHEMA] {
+            let schema: Value = serde_json::from_str(schema).unwrap();
+            assert_eq!(
+                schema["$schema"],
+                "https://json-schema.org/draft/2020-12/schema"
+            );
+        }
+    }
+
+    #[test]
+    fn manifest_schema_matches_type() {
+        let schema: Value = serde_json::from_str(MANIFEST_JSON_SCHEMA).unwrap();
+        // Every field of the type is described by the schema, and conversely.
+        let full = serde_json::to_value(full_manifest()).unwrap();
+        assert_eq!(keys(&full), keys(&schema["properties"]));
+        let required: BTreeSet<String> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|k| k.as_str().unwrap().to_owned())
+            .collect();
+        let mut minimal = serde_json::to_value(minimal_manifest()).unwrap();
+        minimal.as_object_mut().unwrap().remove("version");
+        assert_eq!(keys(&minimal), required);
+        for name in ["rng_commitments", "policy_override"] {
+            assert_eq!(
+                keys(&full[name]),
+                keys(&schema["properties"][name]["properties"])
+            );
+        }
+        assert_eq!(schema["properties"]["version"]["const"], MANIFEST_VERSION);
+        for algorithm in HashAlgorithm::ALL {
+            assert!(schema["properties"]["hash"]["enum"]
+                .as_array()
+                .unwrap()
+                .contains(&Value::from(algorithm.id())));
+        }
+    }
+
+    #[test]
+    fn manifest_roundtrips() {
+        for manifest in [full_manifest(), minimal_manifest()] {
+            let json = serde_json::to_string(&manifest).unwrap();
+            assert_eq!(
+                serde_json::from_str::<SplitManifest>(&json).unwrap(),
+                manifest
+            );
+        }
+    }
+
+    #[test]
+    fn manifest_is_strict() {
+        // Manifests without a version are of version 1.
+        let manifest: SplitManifest = serde_json::from_value(json!({
+            "scheme": "compact",
+            "field": "binary",
+            "bitsize": 128,
+            "threshold": 2,
+            "shares": ["1|00"],
+        }))
+        .unwrap();
+        assert_eq!(manifest.version, 1);
+
+        let invalid = [
+            json!({ "scheme": "compact", "field": "binary", "bitsize": 128 }),
+            json!({ "scheme": "other", "field": "binary", "bitsize": 128, "shares": [] }),
+            json!({ "scheme": "compact", "field": "gf2", "bitsize": 128, "shares": [] }),
+            json!({ "scheme": "compact", "field": "binary", "bitsize": -1, "shares": [] }),
+            json!({ "scheme": "compact", "field": "binary", "bitsize": 128, "shares": [], "extra": 1 }),
+            json!({ "scheme": "compact", "field": "binary", "bitsize": 128, "shares": [], "hash": "md5" }),
+            json!({
+                "scheme": "compact", "field": "binary", "bitsize": 128, "shares": [],
+                "rng_commitments": { "os": "00" },
+            }),
+            json!({
+                "scheme": "compact", "field": "binary", "bitsize": 128, "shares": [],
+                "policy_override": { "violations": [], "justification": "", "extra": 1 },
+            }),
+            json!({
+                "scheme": "compact", "field": "binary", "bitsize": 128, "shares": [],
+                "signatures": [{ "operator": "alice", "public_key": "00" }],
+            }),
+        ];
+        for value in invalid {
+            assert!(serde_json::from_value::<SplitManifest>(value).is_err());
+        }
+    }
+
+    #[test]
+    fn share_lines_roundtrip() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let secret = GF256::from_bytes(&[0x42; 32]).unwrap();
+        let compact = CompactShamir::split_with_rng(&secret, 2, 3, &mut rng).unwrap();
+        let random = RandomShamir::split_with_rng(&secret, 2, 3, &mut rng).unwrap();
+        let shares: Vec<String> = compact
+            .iter()
+            .map(|s| s.to_string())
+            .chain(random.iter().map(|s| s.to_string()))
+            .collect();
+
+        let pattern: Value = serde_json::from_str(SHARE_JSON_SCHEMA).unwrap();
+        let pattern = regex::Regex::new(pattern["pattern"].as_str().unwrap()).unwrap();
+        let header = ShareHeader {
+            scheme: Scheme::Compact,
+            field: "gf256".into(),
+            threshold: 2,
+            set_id: "5f1c0a2e".into(),
+        };
+        for share in &shares {
+            for share in [share.clone(), append_checksum(share).unwrap()] {
+                for header in [None, Some(header.clone())] {
+                    let line = ShareLine {
+                        header,
+                        share: share.clone(),
+                    };
+                    let text = line.to_string();
+                    assert!(pattern.is_match(&text), "{}", text);
+                    assert_eq!(text.parse::<ShareLine>(), Ok(line.clone()));
+                    let json = serde_json::to_value(&line).unwrap();
+                    assert_eq!(json, Value::String(text));
+                    assert_eq!(serde_json::from_value::<ShareLine>(json).unwrap(), line);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_share_lines() {
+        let pattern: Value = serde_json::from_str(SHARE_JSON_SCHEMA).unwrap();
+        let pattern = regex::Regex::new(pattern["pattern"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            "horcrux2:compact:gf8:2:id:1|00".parse::<ShareLine>(),
+            Err(SchemaError::UnsupportedVersion("horcrux2".into()))
+        );
+        for line in [
+            "horcrux1:compact:gf8:2:id",
+            "horcrux1:other:gf8:2:id:1|00",
+            "horcrux1:compact:gf8:x:id:1|00",
+            "horcrux1:compact:gf8:0:id:1|00",
+            "horcrux1:compact:gf8:02:id:1|00",
+            "horcrux1:compact:GF8:2:id:1|00",
+            "horcrux1:compact::2:id:1|00",
+            "horcrux1:compact:gf8:2:i.d:1|00",
+            "1",
+            "1|",
+            "|00",
+            "1|0g",
+            "1|00|123",
+            "1|00|1234|5678",
+        ] {
+            assert_eq!(
+                line.parse::<ShareLine>(),
+                Err(SchemaError::Malformed),
+                "{}",
+                line
+            );
+            assert!(!pattern.is_match(line), "{}", line);
+        }
+    }
+}
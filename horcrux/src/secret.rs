@@ -0,0 +1,106 @@
+//! Wrapper of reconstructed secrets, so that downstream code handles them safely by default.
+//!
+//! A `Secret` only gives access to its value through an explicit call to `expose_secret()`, which
+//! keeps the uses of the secret easy to audit. It doesn't implement `Display`, its `Debug` output
+//! is redacted, and with the `zeroize` feature its value is wiped from memory when it's dropped.
+
+use core::fmt::Debug;
+
+/// Marker trait for values that a `Secret` can wipe from memory, which requires the `zeroize`
+/// feature.
+#[cfg(feature = "zeroize")]
+pub trait Wipe: zeroize::Zeroize {}
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize> Wipe for T {}
+
+/// Marker trait for values that a `Secret` can wipe from memory, which requires the `zeroize`
+/// feature.
+#[cfg(not(feature = "zeroize"))]
+pub trait Wipe {}
+#[cfg(not(feature = "zeroize"))]
+impl<T> Wipe for T {}
+
+/// Secret value, such as a reconstructed secret.
+pub struct Secret<T: Wipe>(T);
+
+impl<T: Wipe> Secret<T> {
+    /// Wraps a secret value.
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// Returns a reference to the secret value.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns a mutable reference to the secret value.
+    pub fn expose_secret_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Wipe> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+/// Redacted `Debug` implementation, which never prints the value.
+impl<T: Wipe> Debug for Secret<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+impl<T: Wipe> Drop for Secret<T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gf2n::GF64;
+    use crate::shamir::{CompactShamir, Shamir, ShamirError};
+    use alloc::format;
+    use alloc::vec;
+
+    #[test]
+    fn can_expose() {
+        let secret = Secret::new(GF64::from(42));
+        assert_eq!(*secret.expose_secret(), GF64::from(42));
+        let secret = Secret::from(vec![GF64::from(1), GF64::from(2)]);
+        assert_eq!(secret.expose_secret().len(), 2);
+    }
+
+    #[test]
+    fn can_reconstruct_secret() {
+        let secret = GF64::from(42);
+        let shares = CompactShamir::split(&secret, 2, 3).unwrap();
+        let reconstructed = CompactShamir::reconstruct_secret(&shares[1..], 2).unwrap();
+        assert_eq!(*reconstructed.expose_secret(), secret);
+        assert_eq!(
+            CompactShamir::reconstruct_secret(&shares[..1], 2).unwrap_err(),
+            ShamirError::NotEnoughShares
+        );
+    }
+
+    #[test]
+    fn debug_is_redacted() {
+        let secret = Secret::new(vec![0x42u8; 16]);
+        assert_eq!(format!("{:?}", secret), "Secret(<redacted>)");
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn wipes_on_drop() {
+        let mut secret = core::mem::ManuallyDrop::new(Secret::new([0x42u8; 16]));
+        // Safety: the array has no drop glue and stays in place, so its bytes can still be read
+        // after the secret is dropped.
+        unsafe { core::mem::ManuallyDrop::drop(&mut secret) };
+        assert_eq!(secret.0, [0; 16]);
+    }
+}
@@ -1,10 +1,25 @@
 //! Implementation of the Shamir's Secret Sharing scheme.
 
-use crate::field::Field;
+use crate::checksum::{self, Mismatch};
+use crate::ct;
+use crate::field::{decode_hex, Field};
+use crate::polynomial::{barycentric_weights, lagrange_coefficients, Polynomial};
+use crate::secret::Secret;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use core::convert::TryFrom;
+use core::convert::TryInto;
+use core::fmt::{Debug, Display, LowerHex, UpperHex};
+use core::str::FromStr;
+#[cfg(feature = "std")]
 use rand::thread_rng;
+use rand::{CryptoRng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 #[cfg(feature = "parse")]
 use regex::Regex;
-use std::fmt::{Debug, Display};
+#[cfg(feature = "parse")]
+use std::io::{BufRead, Read};
 
 /// Trait to obtain the x coordinate of a share.
 pub trait GetX<X: Copy> {
@@ -15,27 +30,241 @@ pub trait GetX<X: Copy> {
 /// Trait for types implementing Shamir's Secret Sharing.
 pub trait Shamir<F: Field> {
     /// Type for the x coordinate of shares.
-    type X: Copy + From<u8>;
+    type X: Copy + PartialEq + From<u8>;
     /// Type for shares split from the secret.
     type Share: Copy + Debug + PartialEq + GetX<Self::X>;
 
-    /// Splits a secret into n shares, with k shares being sufficient to reconstruct it.
-    fn split(secret: &F, k: usize, n: usize) -> Vec<Self::Share>;
+    /// Splits a secret into n shares, with k shares being sufficient to reconstruct it. Returns an
+    /// error if the parameters are out of range.
+    #[cfg(feature = "std")]
+    fn split(secret: &F, k: usize, n: usize) -> Result<Vec<Self::Share>, ShamirError> {
+        Self::split_with_rng(secret, k, n, &mut thread_rng())
+    }
+
+    /// Splits a secret into n shares like `split()`, but drawing all the randomness from the given
+    /// random number generator, e.g. a seeded RNG to generate reproducible test vectors, or an RNG
+    /// backed by a hardware security module.
+    fn split_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError>;
+
+    /// Splits a secret into n shares, with k shares being sufficient to reconstruct it. The secret
+    /// is stored at the x coordinate c rather than at zero. No share is issued at c, nor at zero
+    /// (which shares can't have), so at most 254 shares can be issued when c isn't zero.
+    #[cfg(feature = "std")]
+    fn split_at(
+        secret: &F,
+        k: usize,
+        n: usize,
+        c: Self::X,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        Self::split_at_with_rng(secret, k, n, c, &mut thread_rng())
+    }
+
+    /// Splits a secret at the x coordinate c like `split_at()`, but drawing all the randomness
+    /// from the given random number generator.
+    fn split_at_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        c: Self::X,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError>;
+
+    /// Splits L secrets into n shares of a single polynomial, with a ramp scheme (a.k.a. packed
+    /// secret sharing): k shares are sufficient to reconstruct all the secrets, but only up to
+    /// k - L shares reveal nothing about them. Shares are as large as shares of a single secret, so
+    /// the total size of the shares is divided by L, at the price of a lower privacy threshold.
+    ///
+    /// The first secret is stored at x = 0 and the i-th one at x = 256 - i, so that splitting a
+    /// single secret is an ordinary split. Returns an error if the parameters are out of range, i.e.
+    /// not `1 <= L <= k <= n <= 256 - L`.
+    #[cfg(feature = "std")]
+    fn split_many(secrets: &[F], k: usize, n: usize) -> Result<Vec<Self::Share>, ShamirError> {
+        Self::split_many_with_rng(secrets, k, n, &mut thread_rng())
+    }
+
+    /// Splits L secrets with a ramp scheme like `split_many()`, but drawing all the randomness from
+    /// the given random number generator.
+    fn split_many_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secrets: &[F],
+        k: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError>;
+
+    /// Reconstructs a secret from a set of shares, given the threshold parameter k. Returns an
+    /// error if reconstruction failed, or if some shares are inconsistent with the others.
+    fn reconstruct(shares: &[Self::Share], k: usize) -> Result<F, ShamirError>;
+
+    /// Reconstructs a secret like `reconstruct()`, but wrapped in a `Secret`, which doesn't leak
+    /// it through `Debug` and wipes it from memory when dropped (with the `zeroize` feature).
+    fn reconstruct_secret(shares: &[Self::Share], k: usize) -> Result<Secret<F>, ShamirError> {
+        Self::reconstruct(shares, k).map(Secret::new)
+    }
+
+    /// Reconstructs the L secrets of a ramp scheme (see `split_many()`) from a set of shares, given
+    /// the threshold parameter k. Returns an error if reconstruction failed, or if some shares are
+    /// inconsistent with the others.
+    fn reconstruct_many(shares: &[Self::Share], k: usize, l: usize) -> Result<Vec<F>, ShamirError> {
+        if l == 0 || l > k {
+            return Err(ShamirError::InvalidThreshold);
+        }
+        let polynom = Self::interpolate(shares, k)?;
+        Ok((0..l)
+            .map(|i| polynom.evaluate(F::from(ramp_x(i))))
+            .collect())
+    }
+
+    /// Reconstructs a secret from the first k shares, and checks the remaining shares against the
+    /// interpolated polynomial. Returns an error if reconstruction failed.
+    fn reconstruct_verified(shares: &[Self::Share], k: usize) -> Result<Verified<F>, ShamirError>;
+
+    /// Interpolates the full polynomial of degree k-1 from the first k shares, given the threshold
+    /// parameter k. Returns an error if reconstruction failed, or if the remaining shares are
+    /// inconsistent with the polynomial.
+    fn interpolate(shares: &[Self::Share], k: usize) -> Result<Polynomial<F>, ShamirError>;
 
-    /// Reconstructs a secret from a set of shares, given the threshold parameter k. Returns `None`
-    /// if reconstruction failed.
-    fn reconstruct(shares: &[Self::Share], k: usize) -> Option<F>;
+    /// Returns the coordinates of a share as a point of the polynomial, i.e. with its x
+    /// coordinate converted to a field element.
+    fn point(share: &Self::Share) -> (F, F);
 
     /// Reconstructs a share at some x coordinate, given a set of shares and the threshold parameter
-    /// k. Returns `None` if reconstruction failed.
-    fn reconstruct_at(shares: &[Self::Share], k: usize, x: Self::X) -> Option<Self::Share>;
+    /// k. Returns an error if reconstruction failed, if some shares are inconsistent with the
+    /// others, or if x = 0: the share there would be the secret itself. For a secret stored at
+    /// another x coordinate (see `split_at()`), use `reconstruct_share_at()` instead.
+    fn reconstruct_at(
+        shares: &[Self::Share],
+        k: usize,
+        x: Self::X,
+    ) -> Result<Self::Share, ShamirError>;
+
+    /// Reconstructs a share at some x coordinate like `reconstruct_at()`, for a secret stored at
+    /// the x coordinate c (see `split_at()`). Returns an error if x = c, where the share would be
+    /// the secret itself, or if x = 0 (and c isn't zero), where shares can't be issued.
+    fn reconstruct_share_at(
+        shares: &[Self::Share],
+        k: usize,
+        x: Self::X,
+        c: Self::X,
+    ) -> Result<Self::Share, ShamirError> {
+        if x == c {
+            return Err(ShamirError::SecretPoint);
+        }
+        if x == Self::X::from(0) {
+            return Err(ShamirError::InvalidX);
+        }
+        Self::reconstruct_at(shares, k, x)
+    }
+
+    /// Derives additional shares of the same sharing at the new x coordinates, from a set of
+    /// existing shares and the threshold parameter k, for a secret stored at the x coordinate c
+    /// (zero for an ordinary split, see `reconstruct_share_at()`). Returns an error if a new x
+    /// coordinate collides with an existing share or another new coordinate, if it's the secret
+    /// point c, or if reconstruction failed.
+    fn derive_new_shares(
+        existing: &[Self::Share],
+        k: usize,
+        new_xs: &[Self::X],
+        c: Self::X,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        for (i, x) in new_xs.iter().enumerate() {
+            if existing.iter().any(|s| s.getx() == *x) || new_xs[..i].contains(x) {
+                return Err(ShamirError::DuplicateX);
+            }
+        }
+        new_xs
+            .iter()
+            .map(|&x| Self::reconstruct_share_at(existing, k, x, c))
+            .collect()
+    }
+
+    /// Re-randomizes a set of shares of a secret stored at the x coordinate c (zero for an
+    /// ordinary split, see `split_at()`), by adding to them a random polynomial of degree k-1 which
+    /// is zero at c. The refreshed shares reconstruct the same secret, but cannot be combined with
+    /// the old shares.
+    #[cfg(feature = "std")]
+    fn refresh(
+        shares: &[Self::Share],
+        k: usize,
+        c: Self::X,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        Self::refresh_with_rng(shares, k, c, &mut thread_rng())
+    }
 
-    /// Parses a share's x coordinate from a string. Returns `None` if the parsing fails.
+    /// Re-randomizes a set of shares like `refresh()`, but drawing all the randomness from the
+    /// given random number generator.
+    fn refresh_with_rng<R: Rng + CryptoRng + ?Sized>(
+        shares: &[Self::Share],
+        k: usize,
+        c: Self::X,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError>;
+
+    /// Converts a sharing with threshold k into a new sharing of the same secret, with threshold
+    /// new_k and new_n shares, without reconstructing the secret. Each of the first k shares is
+    /// split into sub-shares for the new holders, who combine them with Lagrange coefficients.
+    ///
+    /// The secret is stored at the x coordinate c (zero for an ordinary split, see `split_at()`),
+    /// in both the old and the new sharing, so no new share is issued at c.
+    #[cfg(feature = "std")]
+    fn reshare(
+        shares: &[Self::Share],
+        k: usize,
+        new_k: usize,
+        new_n: usize,
+        c: Self::X,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        Self::reshare_with_rng(shares, k, new_k, new_n, c, &mut thread_rng())
+    }
+
+    /// Converts a sharing into a new sharing like `reshare()`, but drawing all the randomness from
+    /// the given random number generator.
+    fn reshare_with_rng<R: Rng + CryptoRng + ?Sized>(
+        shares: &[Self::Share],
+        k: usize,
+        new_k: usize,
+        new_n: usize,
+        c: Self::X,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError>;
+
+    /// Reconstructs a secret stored at the x coordinate c (see `split_at()`), given a set of shares
+    /// and the threshold parameter k. Returns an error if reconstruction failed.
+    fn reconstruct_secret_at(
+        shares: &[Self::Share],
+        k: usize,
+        c: Self::X,
+    ) -> Result<F, ShamirError>;
+
+    /// Reconstructs a secret from a set of m >= k shares, tolerating up to (m - k) / 2 corrupted
+    /// shares thanks to Berlekamp-Welch decoding. Returns the secret and the indices of the
+    /// corrupted shares, or an error if there were too many corrupted shares to decode.
+    fn reconstruct_robust(shares: &[Self::Share], k: usize)
+        -> Result<(F, Vec<usize>), ShamirError>;
+
+    /// Parses a share's x coordinate from a string. Returns an error if the parsing fails.
     #[cfg(feature = "parse")]
-    fn parse_x(s: &str) -> Option<Self::X>;
-    /// Parses a share from a string. Returns `None` if the parsing fails.
+    fn parse_x(s: &str) -> Result<Self::X, ShamirError>;
+    /// Parses a share from a string. Returns an error if the parsing fails, if the encoding isn't
+    /// canonical, or if the x coordinate is out of range (see `Share::new`).
     #[cfg(feature = "parse")]
-    fn parse_share(s: &str) -> Option<Self::Share>;
+    fn parse_share(s: &str) -> Result<Self::Share, ShamirError>;
+
+    /// Reads at most `max_shares` shares from a reader, one per line, and stops reading there.
+    /// Surrounding whitespace, empty lines and comment lines starting with `#` are ignored.
+    /// Lines longer than `MAX_LINE_LEN` bytes are rejected without being buffered entirely.
+    /// Returns the shares, and whether the input contained more shares than the limit.
+    #[cfg(feature = "parse")]
+    fn read_shares<R: BufRead>(
+        reader: R,
+        max_shares: usize,
+    ) -> Result<(Vec<Self::Share>, bool), ReadError> {
+        read_lines(reader, max_shares, Self::parse_share)
+    }
 }
 
 /// Instance of `Shamir` using compact shares.
@@ -43,13 +272,344 @@ pub struct CompactShamir;
 /// Instance of `Shamir` using randomized shares.
 pub struct RandomShamir;
 
+/// Errors returned by the `Shamir` operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShamirError {
+    /// The threshold or the number of shares is out of range, i.e. not `1 <= k <= n <= 255`.
+    InvalidThreshold,
+    /// Fewer shares than the threshold were supplied.
+    NotEnoughShares,
+    /// Several shares have the same x coordinate.
+    DuplicateX,
+    /// The shares at the given indices are inconsistent with the polynomial interpolated from the
+    /// first k shares.
+    InconsistentShares(Vec<usize>),
+    /// There were too many corrupted shares to correct them.
+    TooManyCorruptedShares,
+    /// A share or x coordinate couldn't be parsed.
+    Parse,
+    /// The x coordinate of a share is out of range (i.e. zero).
+    InvalidX,
+    /// A share was requested at x = 0, where the secret is stored.
+    SecretPoint,
+    /// The checksum of a share in text format doesn't match the share (see `append_checksum`).
+    Checksum(Mismatch),
+    /// A record is empty, or doesn't authenticate the reconstructed secret (see the `record`
+    /// module).
+    InvalidRecord,
+    /// Shares at different x coordinates were combined (see `add_sharings`).
+    DifferentX,
+    /// Shares of secrets in fields of different sizes were combined (see the `gf2n_dyn` module).
+    DifferentFields,
+    /// The contributions of the participants at the given indices are missing or invalid (see the
+    /// `dkg` and `recovery` modules).
+    InvalidContributions(Vec<u8>),
+}
+
+impl Display for ShamirError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ShamirError::InvalidThreshold => f.write_str("invalid threshold or number of shares"),
+            ShamirError::NotEnoughShares => f.write_str("fewer shares than the threshold"),
+            ShamirError::DuplicateX => f.write_str("several shares have the same x coordinate"),
+            ShamirError::InconsistentShares(indices) => {
+                f.write_fmt(format_args!("inconsistent shares at indices {indices:?}"))
+            }
+            ShamirError::TooManyCorruptedShares => f.write_str("too many corrupted shares"),
+            ShamirError::Parse => f.write_str("invalid share format"),
+            ShamirError::InvalidX => f.write_str("invalid x coordinate"),
+            ShamirError::SecretPoint => {
+                f.write_str("the share at this x coordinate would be the secret itself")
+            }
+            ShamirError::Checksum(Mismatch::Data(i)) => f.write_fmt(format_args!(
+                "checksum mismatch, probably a typo around character {}",
+                i + 1
+            )),
+            ShamirError::Checksum(Mismatch::Checksum(_)) => {
+                f.write_str("checksum mismatch, probably a typo in the checksum")
+            }
+            ShamirError::Checksum(Mismatch::Unknown) => {
+                f.write_str("checksum mismatch, several characters are wrong")
+            }
+            ShamirError::InvalidRecord => {
+                f.write_str("invalid record, or records of another secret")
+            }
+            ShamirError::DifferentX => {
+                f.write_str("shares at different x coordinates cannot be combined")
+            }
+            ShamirError::DifferentFields => {
+                f.write_str("shares in fields of different sizes cannot be combined")
+            }
+            ShamirError::InvalidContributions(senders) => f.write_fmt(format_args!(
+                "missing or invalid contributions from participants {senders:?}"
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShamirError {}
+
+impl ShamirError {
+    /// Returns the category of the error.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ShamirError::SecretPoint => ErrorCategory::Usage,
+            ShamirError::Parse | ShamirError::InvalidX | ShamirError::Checksum(_) => {
+                ErrorCategory::Parse
+            }
+            ShamirError::InvalidThreshold | ShamirError::NotEnoughShares => {
+                ErrorCategory::Threshold
+            }
+            ShamirError::DuplicateX
+            | ShamirError::InconsistentShares(_)
+            | ShamirError::TooManyCorruptedShares
+            | ShamirError::DifferentX
+            | ShamirError::DifferentFields => ErrorCategory::Inconsistency,
+            ShamirError::InvalidRecord | ShamirError::InvalidContributions(_) => {
+                ErrorCategory::Crypto
+            }
+        }
+    }
+
+    /// Returns the numeric code of the error, which is the code of its category plus the index of
+    /// the error within the category. Codes are stable: new errors get new codes, and the codes
+    /// of removed errors aren't reused.
+    pub fn code(&self) -> u8 {
+        self.category().code()
+            + match self {
+                ShamirError::SecretPoint => 0,
+                ShamirError::Parse => 0,
+                ShamirError::InvalidX => 1,
+                ShamirError::Checksum(_) => 2,
+                ShamirError::InvalidThreshold => 0,
+                ShamirError::NotEnoughShares => 1,
+                ShamirError::DuplicateX => 0,
+                ShamirError::InconsistentShares(_) => 1,
+                ShamirError::TooManyCorruptedShares => 2,
+                ShamirError::DifferentX => 3,
+                ShamirError::DifferentFields => 4,
+                ShamirError::InvalidRecord => 0,
+                ShamirError::InvalidContributions(_) => 1,
+            }
+    }
+}
+
+/// Categories of errors, with stable numeric codes that applications can report (e.g. as exit
+/// codes). The codes of the errors of a category start at the code of the category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// An operation was requested with invalid parameters.
+    Usage,
+    /// A share or a value couldn't be parsed.
+    Parse,
+    /// The threshold or the number of shares is out of range.
+    Threshold,
+    /// The shares are inconsistent with each other.
+    Inconsistency,
+    /// A record or a contribution doesn't authenticate.
+    Crypto,
+    /// Reading or writing failed.
+    Io,
+}
+
+impl ErrorCategory {
+    /// Returns the numeric code of the category: 10 for usage errors, 20 for parse errors, 30 for
+    /// threshold errors, 40 for inconsistencies, 50 for cryptographic errors and 60 for I/O
+    /// errors.
+    pub fn code(&self) -> u8 {
+        match self {
+            ErrorCategory::Usage => 10,
+            ErrorCategory::Parse => 20,
+            ErrorCategory::Threshold => 30,
+            ErrorCategory::Inconsistency => 40,
+            ErrorCategory::Crypto => 50,
+            ErrorCategory::Io => 60,
+        }
+    }
+}
+
+/// Maximal length of a line read by `Shamir::read_shares`, which is enough for shares in the
+/// largest supported fields.
+#[cfg(feature = "parse")]
+pub const MAX_LINE_LEN: usize = 4096;
+
+/// Errors returned when reading shares from a reader.
+#[cfg(feature = "parse")]
+#[derive(Debug)]
+pub enum ReadError {
+    /// The underlying reader failed.
+    Io(std::io::Error),
+    /// The line at the given number (starting at 1) is longer than `MAX_LINE_LEN`.
+    LineTooLong(usize),
+    /// The line at the given number (starting at 1) isn't a valid share, with the parsing error.
+    Parse(usize, ShamirError),
+}
+
+#[cfg(feature = "parse")]
+impl Display for ReadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ReadError::Io(e) => f.write_fmt(format_args!("read error: {e}")),
+            ReadError::LineTooLong(line) => f.write_fmt(format_args!(
+                "line {line} is longer than {MAX_LINE_LEN} bytes"
+            )),
+            ReadError::Parse(line, e) => {
+                f.write_fmt(format_args!("invalid share at line {line}: {e}"))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl std::error::Error for ReadError {}
+
+#[cfg(feature = "parse")]
+impl ReadError {
+    /// Returns the numeric code of the error, in the scheme of `ShamirError::code()`: the code of
+    /// the parsing error, 23 for lines that are too long, and 60 for I/O errors.
+    pub fn code(&self) -> u8 {
+        match self {
+            ReadError::Io(_) => ErrorCategory::Io.code(),
+            ReadError::LineTooLong(_) => ErrorCategory::Parse.code() + 3,
+            ReadError::Parse(_, e) => e.code(),
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl From<std::io::Error> for ReadError {
+    fn from(e: std::io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+/// Secret reconstructed from the first k shares, together with the result of checking the remaining
+/// shares.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Verified<F> {
+    /// The reconstructed secret.
+    pub secret: F,
+    /// Indices of the shares beyond the first k that are inconsistent with the secret's
+    /// polynomial.
+    pub inconsistent: Vec<usize>,
+}
+
+/// Thresholds of a ramp scheme (see `Shamir::split_many()`), which trades privacy for smaller
+/// shares. The polynomial has degree `reconstruction - 1` and carries `reconstruction - privacy`
+/// secrets, so that any `privacy` shares reveal nothing about the secrets, any `reconstruction`
+/// shares reconstruct all of them, and sets of shares in between reveal partial information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ramp {
+    privacy: usize,
+    reconstruction: usize,
+}
+
+impl Ramp {
+    /// Creates the thresholds of a ramp scheme. Returns an error unless
+    /// `1 <= privacy < reconstruction`.
+    pub fn new(privacy: usize, reconstruction: usize) -> Result<Self, ShamirError> {
+        if privacy == 0 || privacy >= reconstruction {
+            return Err(ShamirError::InvalidThreshold);
+        }
+        Ok(Ramp {
+            privacy,
+            reconstruction,
+        })
+    }
+
+    /// Returns the privacy threshold, i.e. the number of shares that reveal nothing about the
+    /// secrets.
+    pub fn privacy(&self) -> usize {
+        self.privacy
+    }
+
+    /// Returns the reconstruction threshold, i.e. the number of shares that reconstruct all the
+    /// secrets.
+    pub fn reconstruction(&self) -> usize {
+        self.reconstruction
+    }
+
+    /// Returns the number of secrets carried by each polynomial, i.e. the factor by which the
+    /// shares are smaller than with ordinary sharings of each secret.
+    pub fn packing(&self) -> usize {
+        self.reconstruction - self.privacy
+    }
+}
+
 /// Representation of a share.
+///
+/// Shares can only be built by splitting a secret, by parsing them, or with `Share::new`, which all
+/// check the x coordinate, so that a share at x = 0 (which would directly contain the secret)
+/// can't be fed into the library.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "UncheckedShare<X, Y>",
+        bound(
+            deserialize = "X: serde::Deserialize<'de>, Y: serde::Deserialize<'de>, \
+                             Share<X, Y>: core::convert::TryFrom<UncheckedShare<X, Y>, Error = ShamirError>"
+        )
+    )
+)]
 pub struct Share<X, Y> {
     x: X,
     y: Y,
 }
 
+impl<X: PartialEq + From<u8>, Y> Share<X, Y> {
+    /// Creates a share from its coordinates, e.g. received over an application's own channel.
+    /// Returns an error if x is zero, i.e. out of the range `1..=255` for compact shares.
+    pub fn new(x: X, y: Y) -> Result<Self, ShamirError> {
+        if x == X::from(0) {
+            return Err(ShamirError::InvalidX);
+        }
+        Ok(Share { x, y })
+    }
+}
+
+/// Share as deserialized, before checking its x coordinate.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[doc(hidden)]
+pub struct UncheckedShare<X, Y> {
+    x: X,
+    y: Y,
+}
+
+#[cfg(feature = "serde")]
+impl<F: Field> TryFrom<UncheckedShare<u8, F>> for Share<u8, F> {
+    type Error = ShamirError;
+
+    fn try_from(share: UncheckedShare<u8, F>) -> Result<Self, ShamirError> {
+        Self::new(share.x, share.y)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<F: Field> TryFrom<UncheckedShare<F, F>> for Share<F, F> {
+    type Error = ShamirError;
+
+    fn try_from(share: UncheckedShare<F, F>) -> Result<Self, ShamirError> {
+        Self::new(share.x, share.y)
+    }
+}
+
+/// Any index is valid for a compressed share.
+#[cfg(feature = "serde")]
+impl<F: Field> TryFrom<UncheckedShare<u32, F>> for Share<u32, F> {
+    type Error = ShamirError;
+
+    fn try_from(share: UncheckedShare<u32, F>) -> Result<Self, ShamirError> {
+        Ok(Share {
+            x: share.x,
+            y: share.y,
+        })
+    }
+}
+
 impl<X, Y> Display for Share<X, Y>
 where
     X: Display,
@@ -60,140 +620,883 @@ where
     }
 }
 
+/// Parses a compact share in the text format printed by `Display`, i.e. `x|y` with x in decimal
+/// and y in hexadecimal, optionally followed by a checksum (see `append_checksum`).
+impl<F: Field + FromStr> FromStr for Share<u8, F> {
+    type Err = ShamirError;
+
+    fn from_str(s: &str) -> Result<Self, ShamirError> {
+        let (x, y) = strip_checksum(s)?
+            .split_once('|')
+            .ok_or(ShamirError::Parse)?;
+        if !x.bytes().all(|c| c.is_ascii_digit()) || (x.len() > 1 && x.starts_with('0')) {
+            return Err(ShamirError::Parse);
+        }
+        let x = x.parse().map_err(|_| ShamirError::Parse)?;
+        let y = y.parse().map_err(|_| ShamirError::Parse)?;
+        Self::new(x, y)
+    }
+}
+
+/// Parses a randomized share in the text format printed by `Display`, i.e. `x|y` with both
+/// coordinates in hexadecimal, optionally followed by a checksum (see `append_checksum`).
+impl<F: Field + FromStr> FromStr for Share<F, F> {
+    type Err = ShamirError;
+
+    fn from_str(s: &str) -> Result<Self, ShamirError> {
+        let (x, y) = strip_checksum(s)?
+            .split_once('|')
+            .ok_or(ShamirError::Parse)?;
+        let x = x.parse().map_err(|_| ShamirError::Parse)?;
+        let y = y.parse().map_err(|_| ShamirError::Parse)?;
+        Self::new(x, y)
+    }
+}
+
+/// Formats a compact share as `x|y`, with x in decimal and y in lower case hexadecimal.
+impl<F: LowerHex> LowerHex for Share<u8, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}|", self.x)?;
+        LowerHex::fmt(&self.y, f)
+    }
+}
+
+/// Formats a compact share as `x|y`, with x in decimal and y in upper case hexadecimal.
+impl<F: UpperHex> UpperHex for Share<u8, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}|", self.x)?;
+        UpperHex::fmt(&self.y, f)
+    }
+}
+
+/// Formats a randomized share as `x|y`, with both coordinates in lower case hexadecimal.
+impl<F: Field + LowerHex> LowerHex for Share<F, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        LowerHex::fmt(&self.x, f)?;
+        f.write_str("|")?;
+        LowerHex::fmt(&self.y, f)
+    }
+}
+
+/// Formats a randomized share as `x|y`, with both coordinates in upper case hexadecimal.
+impl<F: Field + UpperHex> UpperHex for Share<F, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        UpperHex::fmt(&self.x, f)?;
+        f.write_str("|")?;
+        UpperHex::fmt(&self.y, f)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<X: zeroize::Zeroize, Y: zeroize::Zeroize> zeroize::Zeroize for Share<X, Y> {
+    fn zeroize(&mut self) {
+        self.x.zeroize();
+        self.y.zeroize();
+    }
+}
+
 impl<X: Copy, Y> GetX<X> for Share<X, Y> {
     fn getx(self) -> X {
         self.x
     }
 }
 
+impl<X, Y> Share<X, Y> {
+    /// Creates a share from coordinates that the caller knows to be valid, i.e. with a non-zero x.
+    pub(crate) fn new_unchecked(x: X, y: Y) -> Self {
+        Share { x, y }
+    }
+
+    /// Returns the x coordinate of the share.
+    pub fn x(&self) -> &X {
+        &self.x
+    }
+
+    /// Returns the y coordinate of the share.
+    pub fn y(&self) -> &Y {
+        &self.y
+    }
+
+    /// Returns the coordinates of the share, consuming it.
+    pub fn into_parts(self) -> (X, Y) {
+        (self.x, self.y)
+    }
+}
+
+impl<X: Copy + PartialEq, F: Field> Share<X, F> {
+    /// Adds two shares at the same x coordinate, which yields the share of the sum of their secrets
+    /// at this x coordinate. Returns an error if the x coordinates differ.
+    pub fn add(&self, other: &Self) -> Result<Self, ShamirError> {
+        let mut sum = *self;
+        sum.add_assign_share(other)?;
+        Ok(sum)
+    }
+
+    /// Adds another share at the same x coordinate to this one, in place, e.g. a share of a sharing
+    /// of zero to re-randomize this share. Only shares of the same type, i.e. of the same field and
+    /// with the same kind of x coordinates, can be added. Returns an error if the x coordinates
+    /// differ, in which case this share is unchanged.
+    pub fn add_assign_share(&mut self, other: &Self) -> Result<(), ShamirError> {
+        if self.x != other.x {
+            return Err(ShamirError::DifferentX);
+        }
+        self.y += &other.y;
+        Ok(())
+    }
+}
+
+/// Adds two sharings share by share, which yields a sharing of the sum of the secrets without
+/// reconstructing them. The threshold of the sum is the largest of the two thresholds.
+///
+/// The shares at the same index must have the same x coordinate, e.g. compact shares of splits
+/// with the same number of shares. Randomized shares of different splits have different x
+/// coordinates, so one of the sharings must first be evaluated at the x coordinates of the other
+/// (see `Shamir::reconstruct_at`). Returns an error if the sharings don't have the same length or
+/// the same x coordinates.
+pub fn add_sharings<X: Copy + PartialEq, F: Field>(
+    a: &[Share<X, F>],
+    b: &[Share<X, F>],
+) -> Result<Vec<Share<X, F>>, ShamirError> {
+    if a.len() != b.len() {
+        return Err(ShamirError::DifferentX);
+    }
+    a.iter().zip(b).map(|(a, b)| a.add(b)).collect()
+}
+
 type CompactShare<F> = Share<u8, F>;
 type RandomShare<F> = Share<F, F>;
+/// Randomized share whose x coordinate is compressed into a 32-bit index (see `XKey`).
+pub type CompressedShare<F> = Share<u32, F>;
+
+/// Key of the PRF that derives the x coordinates of randomized shares from 32-bit indices, so that
+/// these shares can be emitted in a compact `index|y` form. The indices are random, so compressed
+/// shares don't reveal how many shares were issued.
+///
+/// The key isn't secret and is meant to be stored in the public metadata of the ceremony, but it
+/// is needed to expand compressed shares back into regular randomized shares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XKey([u8; 32]);
+
+impl XKey {
+    /// Generates a random key.
+    #[cfg(feature = "std")]
+    pub fn random() -> Self {
+        Self::random_with_rng(&mut thread_rng())
+    }
+
+    /// Generates a random key from the given random number generator.
+    pub fn random_with_rng<R: Rng + CryptoRng + ?Sized>(rng: &mut R) -> Self {
+        Self(rng.gen())
+    }
+
+    /// Derives the x coordinate of the given index, with ChaCha20 keyed by this key and using the
+    /// index as stream number.
+    pub fn derive_x<F: Field>(&self, index: u32) -> F {
+        let mut rng = ChaCha20Rng::from_seed(self.0);
+        rng.set_stream(index as u64);
+        F::uniform(&mut rng)
+    }
 
-fn check_split_parameters(k: usize, n: usize) {
-    debug_assert!(k != 0);
-    debug_assert!(n != 0);
-    debug_assert!(k <= n);
-    debug_assert!(n < 256);
+    /// Parses a key from 64 hexadecimal characters. Returns an error if the parsing fails.
+    #[cfg(feature = "parse")]
+    pub fn parse(s: &str) -> Result<Self, ShamirError> {
+        let mut key = [0; 32];
+        hex::decode_to_slice(s, &mut key).map_err(|_| ShamirError::Parse)?;
+        Ok(Self(key))
+    }
+}
+
+impl Display for XKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for b in &self.0 {
+            f.write_fmt(format_args!("{b:02x}"))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn check_split_parameters(k: usize, n: usize) -> Result<(), ShamirError> {
+    if k == 0 || k > n || n > 255 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    Ok(())
+}
+
+/// Checks the parameters of `Shamir::split_at()`: when the secret isn't at zero, neither zero nor
+/// its coordinate can be issued, which leaves 254 x coordinates in GF(2^8).
+fn check_split_at_parameters(k: usize, n: usize, zero: bool) -> Result<(), ShamirError> {
+    check_split_parameters(k, n)?;
+    if !zero && n > 254 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    Ok(())
+}
+
+fn check_ramp_parameters(l: usize, k: usize, n: usize) -> Result<(), ShamirError> {
+    check_split_parameters(k, n)?;
+    if l == 0 || l > k || n + l > 256 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    Ok(())
+}
+
+/// Returns the x coordinate of the i-th secret of a ramp scheme: 0 for the first secret, and
+/// 256 - i for the others, away from the compact shares issued at 1, 2, ...
+fn ramp_x(i: usize) -> u8 {
+    ((256 - i) % 256) as u8
 }
 
-fn check_reconstruct_parameters<X, Y>(shares: &[Share<X, Y>], k: usize)
+fn check_reconstruct_parameters<X, Y>(shares: &[Share<X, Y>], k: usize) -> Result<(), ShamirError>
 where
-    X: Debug + PartialEq,
-    Y: Debug,
+    X: PartialEq,
 {
-    debug_assert!(k != 0);
-    debug_assert!(k < 256);
-    debug_assert!(shares.len() >= k);
+    if k == 0 || k > 255 {
+        return Err(ShamirError::InvalidThreshold);
+    }
+    if shares.len() < k {
+        return Err(ShamirError::NotEnoughShares);
+    }
     for (i, s) in shares.iter().enumerate() {
-        for (j, t) in shares.iter().enumerate() {
-            if i != j {
-                debug_assert!(s.x != t.x);
-            }
+        if shares[..i].iter().any(|t| s.x == t.x) {
+            return Err(ShamirError::DuplicateX);
         }
     }
+    Ok(())
 }
 
-fn generate_polynom<F: Field + Debug + Display>(secret: &F, k: usize) -> Vec<F> {
-    let mut rng = thread_rng();
+/// Appends a checksum to a share in text format, as a third `|`-separated field of 4 hexadecimal
+/// characters (see the `checksum` module). The parsing functions verify the checksum when present.
+/// Returns `None` if the share is too long.
+#[cfg(feature = "parse")]
+pub fn append_checksum(share: &str) -> Option<String> {
+    let checksum = checksum::compute(share.as_bytes())?;
+    Some(format!("{}|{}", share, hex::encode(checksum)))
+}
 
-    let mut polynom = Vec::with_capacity(k);
-    println!("Polynom = {secret}");
-    for i in 1..k {
-        polynom.push(F::uniform(&mut rng));
-        println!("    + {} x^{i}", polynom.last().unwrap());
+/// Verifies and removes the checksum of a share in text format, if any.
+fn strip_checksum(s: &str) -> Result<&str, ShamirError> {
+    if s.matches('|').count() != 2 {
+        return Ok(s);
     }
+    let (share, sum) = s.rsplit_once('|').unwrap();
+    let expected = decode_hex(sum)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ShamirError::Parse)?;
+    checksum::verify(share.as_bytes(), expected).map_err(ShamirError::Checksum)?;
+    Ok(share)
+}
 
-    polynom
+/// Parses an integer in canonical decimal form, i.e. without leading zeros.
+#[cfg(feature = "parse")]
+pub(crate) fn parse_decimal<T: core::str::FromStr>(s: &str) -> Result<T, ShamirError> {
+    if s.len() > 1 && s.starts_with('0') {
+        return Err(ShamirError::Parse);
+    }
+    s.parse().map_err(|_| ShamirError::Parse)
 }
 
-impl<F: Field + Debug + Display> Shamir<F> for CompactShamir {
-    type X = u8;
-    type Share = CompactShare<F>;
+/// Parses a field element from hexadecimal characters. Field implementations reject encodings
+/// that aren't canonical (e.g. integers not reduced modulo a prime).
+#[cfg(feature = "parse")]
+pub(crate) fn parse_field<F: Field>(s: &str) -> Result<F, ShamirError> {
+    hex::decode(s)
+        .ok()
+        .and_then(|bytes| F::from_bytes(&bytes))
+        .ok_or(ShamirError::Parse)
+}
 
-    fn split(secret: &F, k: usize, n: usize) -> Vec<Self::Share> {
-        check_split_parameters(k, n);
+/// Parses at most `max_items` lines of the reader, and stops reading there. Surrounding whitespace,
+/// empty lines and comment lines starting with `#` are ignored, and lines are limited to
+/// `MAX_LINE_LEN` bytes. Returns the parsed items, and whether the input contained more items.
+///
+/// This is the reader behind `Shamir::read_shares`, exposed for custom share formats.
+#[cfg(feature = "parse")]
+pub fn read_lines<T, R: BufRead>(
+    mut reader: R,
+    max_items: usize,
+    mut parse: impl FnMut(&str) -> Result<T, ShamirError>,
+) -> Result<(Vec<T>, bool), ReadError> {
+    let mut items = Vec::new();
+    let mut line = Vec::new();
+    for number in 1.. {
+        line.clear();
+        let len = Read::take(&mut reader, MAX_LINE_LEN as u64 + 1).read_until(b'\n', &mut line)?;
+        if len == 0 {
+            break;
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        } else if line.len() > MAX_LINE_LEN {
+            return Err(ReadError::LineTooLong(number));
+        }
+        let text = core::str::from_utf8(&line)
+            .map_err(|_| ReadError::Parse(number, ShamirError::Parse))?
+            .trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        if items.len() == max_items {
+            return Ok((items, true));
+        }
+        let item = parse(text).map_err(|e| ReadError::Parse(number, e))?;
+        items.push(item);
+    }
+    Ok((items, false))
+}
 
-        let polynom = generate_polynom(secret, k);
+/// Random coefficients of a polynomial, which are wiped from memory on drop if the `zeroize`
+/// feature is enabled.
+#[cfg(feature = "zeroize")]
+pub(crate) type Coefficients<F> = zeroize::Zeroizing<Vec<F>>;
+#[cfg(not(feature = "zeroize"))]
+pub(crate) type Coefficients<F> = Vec<F>;
+
+/// Samples the k-1 random non-constant coefficients of a polynomial.
+fn generate_polynom<F: Field, R: Rng + CryptoRng + ?Sized>(
+    k: usize,
+    rng: &mut R,
+) -> Coefficients<F> {
+    let polynom: Vec<F> = (1..k).map(|_| F::uniform(rng)).collect();
+    Coefficients::from(polynom)
+}
 
-        let mut shares: Vec<Self::Share> = Vec::with_capacity(n);
-        for i in 1..=(n as u8) {
-            let x = F::from(i);
+/// Evaluates the polynomial `secret + polynom[0] * x + polynom[1] * x^2 + ...` at all the given
+/// points, with Horner's method. Each step processes all the points, in independent
+/// multiplications that the compiler can vectorize.
+fn evaluate_many<F: Field>(secret: &F, polynom: &[F], xs: &[F]) -> Coefficients<F> {
+    let mut ys = Coefficients::from(vec![F::ZERO; xs.len()]);
+    for p in polynom.iter().rev() {
+        for (y, x) in ys.iter_mut().zip(xs) {
+            *y += p;
+            *y = *y * x;
+        }
+    }
+    for y in ys.iter_mut() {
+        *y += secret;
+    }
+    ys
+}
 
-            let mut y = *secret;
-            let mut xn = x;
-            for p in &polynom {
-                y += &(xn * p);
-                xn = xn * &x;
-            }
+/// Polynomial of degree k-1 of a ramp scheme, which takes the values of the L secrets at their x
+/// coordinates: the polynomial interpolated from the secrets, plus a random polynomial of degree
+/// k-L-1 times the polynomial vanishing at the x coordinates of the secrets.
+struct RampPolynom<F: Field> {
+    secrets: Polynomial<F>,
+    points: Vec<F>,
+    random: Coefficients<F>,
+}
 
-            shares.push(Self::Share { x: i, y })
+impl<F: Field> RampPolynom<F> {
+    fn new<R: Rng + CryptoRng + ?Sized>(secrets: &[F], k: usize, rng: &mut R) -> Self {
+        let points: Vec<F> = (0..secrets.len()).map(|i| F::from(ramp_x(i))).collect();
+        let values: Vec<(F, F)> = points
+            .iter()
+            .copied()
+            .zip(secrets.iter().copied())
+            .collect();
+        RampPolynom {
+            secrets: Polynomial::interpolate(&values),
+            points,
+            random: generate_polynom(k - secrets.len() + 1, rng),
         }
+    }
 
-        shares
+    fn evaluate(&self, x: F) -> F {
+        let mut vanishing = F::ONE;
+        for &p in &self.points {
+            vanishing = vanishing * &(x - p);
+        }
+        let mut random = F::ZERO;
+        for c in self.random.iter().rev() {
+            random = random * &x;
+            random += c;
+        }
+        let mut y = self.secrets.evaluate(x);
+        y += &(vanishing * &random);
+        y
     }
+}
+
+/// Returns the indices of the points beyond the first k that aren't on the polynomial interpolated
+/// from the first k points.
+fn inconsistent_points<F: Field>(points: &[(F, F)], k: usize) -> Vec<usize> {
+    let (base, rest) = points.split_at(k);
+    let xs: Vec<F> = base.iter().map(|&(x, _)| x).collect();
+    let weights = barycentric_weights(&xs);
+
+    rest.iter()
+        .enumerate()
+        .filter(|(_, &(x, y))| interpolate_with_weights(base, &xs, &weights, &x) != y)
+        .map(|(i, _)| k + i)
+        .collect()
+}
+
+/// Returns the value at x of the polynomial interpolated from the given points, whose x
+/// coordinates must be distinct. The number of field operations only depends on the number of
+/// points, so that the timing doesn't reveal which shares are used.
+fn interpolate_at<F: Field>(points: &[(F, F)], x: &F) -> F {
+    let xs: Vec<F> = points.iter().map(|&(x, _)| x).collect();
+    interpolate_with_weights(points, &xs, &barycentric_weights(&xs), x)
+}
 
-    fn reconstruct(shares: &[Self::Share], k: usize) -> Option<F> {
-        check_reconstruct_parameters(shares, k);
+/// Returns the value at x of the polynomial interpolated from the given points, given their x
+/// coordinates and barycentric weights.
+fn interpolate_with_weights<F: Field>(points: &[(F, F)], xs: &[F], weights: &[F], x: &F) -> F {
+    let mut y = F::ZERO;
+    for (&(_, yi), l) in points.iter().zip(lagrange_coefficients(xs, weights, x)) {
+        y += &(yi * &l);
+    }
+    y
+}
 
-        let gfx: Vec<F> = shares.iter().map(|share| F::from(share.x)).collect();
+/// Interpolates the polynomial passing through the first k points, and checks that the remaining
+/// points are on it.
+fn interpolate_points<F: Field>(points: &[(F, F)], k: usize) -> Result<Polynomial<F>, ShamirError> {
+    let polynom = Polynomial::interpolate(&points[..k]);
+    let inconsistent: Vec<usize> = (k..points.len())
+        .filter(|&i| polynom.evaluate(points[i].0) != points[i].1)
+        .collect();
+    if !inconsistent.is_empty() {
+        return Err(ShamirError::InconsistentShares(inconsistent));
+    }
+    Ok(polynom)
+}
 
-        let mut secret = F::ZERO;
-        for (i, si) in shares.iter().take(k).enumerate() {
-            let mut lagrange = F::ONE;
-            let mut denom = F::ONE;
-            let xi = si.x;
-            for (j, sj) in shares.iter().take(k).enumerate() {
-                if j != i {
-                    let xj = sj.x;
-                    lagrange = lagrange * &gfx[j];
-                    denom = denom * &F::from_diff(xj, xi);
+/// Finds a solution of a linear system given by its augmented matrix, with Gauss-Jordan
+/// elimination. Free variables are set to zero. Returns `None` if the system is inconsistent.
+///
+/// The pivot of each column is moved into place with conditional swaps of all the candidate rows
+/// (see the `ct` module), so that the memory accesses don't reveal which entries are zero. Only
+/// whether a column has a pivot, i.e. the rank of the system, affects the control flow.
+fn solve_linear_system<F: Field>(rows: &mut [Vec<F>], nvars: usize) -> Option<Vec<F>> {
+    let mut pivots = Vec::new();
+    for col in 0..nvars {
+        let r = pivots.len();
+        if r == rows.len() {
+            break;
+        }
+        // Swaps the first row with a non-zero entry in this column into row r.
+        let mut found = 0u8;
+        let (top, rest) = rows.split_at_mut(r + 1);
+        let pivot_row = &mut top[r];
+        found |= ct::nonzero_field_mask(&pivot_row[col]);
+        for row in rest {
+            let take = !found & ct::nonzero_field_mask(&row[col]);
+            for (a, b) in pivot_row.iter_mut().zip(row.iter_mut()) {
+                ct::swap_field(take, a, b);
+            }
+            found |= take;
+        }
+        if found == 0 {
+            continue;
+        }
+
+        let inv = rows[r][col].invert();
+        for v in rows[r].iter_mut() {
+            *v = *v * &inv;
+        }
+        let pivot_row = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != r {
+                let factor = row[col];
+                for (v, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *v = *v - factor * p;
                 }
             }
-            secret += &(lagrange * &si.y * &denom.invert());
         }
+        pivots.push(col);
+    }
+
+    if rows[pivots.len()..].iter().any(|row| row[nvars] != F::ZERO) {
+        return None;
+    }
+    let mut solution = vec![F::ZERO; nvars];
+    for (i, &col) in pivots.iter().enumerate() {
+        solution[col] = rows[i][nvars];
+    }
+    Some(solution)
+}
+
+/// Decodes the polynomial of degree k-1 passing through all but at most (m - k) / 2 of the given m
+/// points, with the Berlekamp-Welch algorithm. Returns the secret (the constant term) and the
+/// indices of the points that are not on the polynomial.
+fn berlekamp_welch<F: Field>(points: &[(F, F)], k: usize) -> Result<(F, Vec<usize>), ShamirError> {
+    let e = (points.len() - k) / 2;
+    let nq = k + e;
+
+    // Find an error locator polynomial E (monic of degree e) and a polynomial Q (of degree
+    // k + e - 1) such that Q(x) = y * E(x) for all points. The corrupted points are roots of E.
+    let mut rows: Vec<Vec<F>> = points
+        .iter()
+        .map(|&(x, y)| {
+            let mut row = Vec::with_capacity(nq + e + 1);
+            let mut xn = F::ONE;
+            for _ in 0..nq {
+                row.push(xn);
+                xn = xn * &x;
+            }
+            let mut xn = F::ONE;
+            for _ in 0..e {
+                row.push(F::ZERO - y * &xn);
+                xn = xn * &x;
+            }
+            row.push(y * &xn);
+            row
+        })
+        .collect();
+    let solution =
+        solve_linear_system(&mut rows, nq + e).ok_or(ShamirError::TooManyCorruptedShares)?;
+
+    // The polynomial is Q / E.
+    let mut remainder = solution[..nq].to_vec();
+    let mut locator = solution[nq..].to_vec();
+    locator.push(F::ONE);
+    let mut coefficients = vec![F::ZERO; k];
+    for i in (0..k).rev() {
+        let c = remainder[i + e];
+        coefficients[i] = c;
+        for (j, l) in locator.iter().enumerate() {
+            remainder[i + j] = remainder[i + j] - c * l;
+        }
+    }
+    if remainder.iter().any(|&r| r != F::ZERO) {
+        return Err(ShamirError::TooManyCorruptedShares);
+    }
+    let polynom = Polynomial::new(coefficients);
+
+    let corrupted: Vec<usize> = points
+        .iter()
+        .enumerate()
+        .filter(|(_, &(x, y))| polynom.evaluate(x) != y)
+        .map(|(i, _)| i)
+        .collect();
+    if corrupted.len() > e {
+        return Err(ShamirError::TooManyCorruptedShares);
+    }
+    Ok((polynom.coefficients()[0], corrupted))
+}
+
+impl CompactShamir {
+    /// Evaluates the polynomial interpolated from the first k shares at x, checking the remaining
+    /// shares against it.
+    fn evaluate_at<F: Field>(
+        shares: &[CompactShare<F>],
+        k: usize,
+        x: u8,
+    ) -> Result<F, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+
+        let points: Vec<(F, F)> = shares.iter().map(|s| (F::from(s.x), s.y)).collect();
+        let y = interpolate_at(&points[..k], &F::from(x));
+        let inconsistent = inconsistent_points(&points, k);
+        if !inconsistent.is_empty() {
+            return Err(ShamirError::InconsistentShares(inconsistent));
+        }
+
+        Ok(y)
+    }
+}
+
+impl<F: Field + Debug + Display> Shamir<F> for CompactShamir {
+    type X = u8;
+    type Share = CompactShare<F>;
+
+    fn split_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        Self::split_at_with_rng(secret, k, n, 0, rng)
+    }
+
+    fn split_at_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        c: u8,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_split_at_parameters(k, n, c == 0)?;
+
+        let polynom = generate_polynom(k, rng);
+
+        // Shares are issued at 1, 2, ..., 255, skipping the secret's coordinate.
+        let xs: Vec<u8> = (1..=255).filter(|&i| i != c).take(n).collect();
+        // The polynomial is expressed in powers of (x - c).
+        let ds: Vec<F> = xs.iter().map(|&i| F::from_diff(i, c)).collect();
+        let ys = evaluate_many(secret, &polynom, &ds);
+
+        let shares: Vec<CompactShare<F>> = xs
+            .iter()
+            .zip(ys.iter())
+            .map(|(&x, &y)| Share { x, y })
+            .collect();
+
+        Ok(shares)
+    }
+
+    fn split_many_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secrets: &[F],
+        k: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_ramp_parameters(secrets.len(), k, n)?;
+
+        let polynom = RampPolynom::new(secrets, k, rng);
+        Ok((1..=n as u8)
+            .map(|x| Share {
+                x,
+                y: polynom.evaluate(F::from(x)),
+            })
+            .collect())
+    }
+
+    fn reconstruct(shares: &[Self::Share], k: usize) -> Result<F, ShamirError> {
+        let result = Self::reconstruct_verified(shares, k)?;
+        if !result.inconsistent.is_empty() {
+            return Err(ShamirError::InconsistentShares(result.inconsistent));
+        }
+        Ok(result.secret)
+    }
+
+    fn reconstruct_verified(shares: &[Self::Share], k: usize) -> Result<Verified<F>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
 
-        // TODO: Verify the remaining shares.
+        let points: Vec<(F, F)> = shares.iter().map(|s| (F::from(s.x), s.y)).collect();
+        Ok(Verified {
+            secret: interpolate_at(&points[..k], &F::ZERO),
+            inconsistent: inconsistent_points(&points, k),
+        })
+    }
+
+    fn interpolate(shares: &[Self::Share], k: usize) -> Result<Polynomial<F>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+
+        let points: Vec<(F, F)> = shares.iter().map(|s| (F::from(s.x), s.y)).collect();
+        interpolate_points(&points, k)
+    }
+
+    fn point(share: &Self::Share) -> (F, F) {
+        (F::from(share.x), share.y)
+    }
 
-        Some(secret)
+    fn reconstruct_at(shares: &[Self::Share], k: usize, x: u8) -> Result<Self::Share, ShamirError> {
+        if x == 0 {
+            return Err(ShamirError::SecretPoint);
+        }
+        let y = Self::evaluate_at(shares, k, x)?;
+        Ok(Self::Share { x, y })
     }
 
-    fn reconstruct_at(shares: &[Self::Share], k: usize, x: u8) -> Option<Self::Share> {
-        check_reconstruct_parameters(shares, k);
+    fn refresh_with_rng<R: Rng + CryptoRng + ?Sized>(
+        shares: &[Self::Share],
+        k: usize,
+        c: u8,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+
+        let polynom = generate_polynom(k, rng);
+
+        // The polynomial is expressed in powers of (x - c), so that it's zero at c.
+        let ds: Vec<F> = shares
+            .iter()
+            .map(|share| F::from_diff(share.x, c))
+            .collect();
+        let zeros = evaluate_many(&F::ZERO, &polynom, &ds);
+
+        Ok(shares
+            .iter()
+            .zip(zeros.iter())
+            .map(|(share, zero)| {
+                let mut y = share.y;
+                y += zero;
+                Self::Share { x: share.x, y }
+            })
+            .collect())
+    }
 
-        let mut y = F::ZERO;
-        for (i, si) in shares.iter().take(k).enumerate() {
-            let mut lagrange = F::ONE;
-            let mut denom = F::ONE;
-            let xi = si.x;
-            for (j, sj) in shares.iter().take(k).enumerate() {
-                if j != i {
-                    let xj = sj.x;
-                    lagrange = lagrange * &F::from_diff(xj, x);
-                    denom = denom * &F::from_diff(xj, xi);
+    fn reshare_with_rng<R: Rng + CryptoRng + ?Sized>(
+        shares: &[Self::Share],
+        k: usize,
+        new_k: usize,
+        new_n: usize,
+        c: u8,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+        check_split_at_parameters(new_k, new_n, c == 0)?;
+
+        let old_shares = &shares[..k];
+        // New shares are issued at 1, 2, ..., 255, skipping the secret's coordinate.
+        let new_xs: Vec<u8> = (1..=255).filter(|&i| i != c).take(new_n).collect();
+        let new_ds: Vec<F> = new_xs.iter().map(|&x| F::from_diff(x, c)).collect();
+
+        // Each old holder splits their share into sub-shares for the new holders, with
+        // polynomials expressed in powers of (x - c).
+        let sub_shares: Vec<Coefficients<F>> = old_shares
+            .iter()
+            .map(|si| {
+                let polynom: Coefficients<F> =
+                    Coefficients::from((1..new_k).map(|_| F::uniform(rng)).collect::<Vec<F>>());
+                evaluate_many(&si.y, &polynom, &new_ds)
+            })
+            .collect();
+
+        // Lagrange coefficients of the old shares, for an interpolation at the secret point.
+        let old_xs: Vec<F> = old_shares.iter().map(|s| F::from(s.x)).collect();
+        let lagrange = lagrange_coefficients(&old_xs, &barycentric_weights(&old_xs), &F::from(c));
+
+        // Each new holder combines the sub-shares they received.
+        Ok(new_xs
+            .iter()
+            .enumerate()
+            .map(|(j, &x)| {
+                let mut y = F::ZERO;
+                for (i, l) in lagrange.iter().enumerate() {
+                    y += &(sub_shares[i][j] * l);
                 }
+                Self::Share { x, y }
+            })
+            .collect())
+    }
+
+    fn reconstruct_secret_at(shares: &[Self::Share], k: usize, c: u8) -> Result<F, ShamirError> {
+        Self::evaluate_at(shares, k, c)
+    }
+
+    fn reconstruct_robust(
+        shares: &[Self::Share],
+        k: usize,
+    ) -> Result<(F, Vec<usize>), ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+
+        let points: Vec<(F, F)> = shares.iter().map(|s| (F::from(s.x), s.y)).collect();
+        berlekamp_welch(&points, k)
+    }
+
+    #[cfg(feature = "parse")]
+    fn parse_x(s: &str) -> Result<Self::X, ShamirError> {
+        parse_decimal(s)
+    }
+
+    #[cfg(feature = "parse")]
+    fn parse_share(s: &str) -> Result<Self::Share, ShamirError> {
+        let regex = Regex::new(r"^([0-9]+)\|([0-9a-fA-F]+)$").unwrap();
+        let captures = regex
+            .captures(strip_checksum(s)?)
+            .ok_or(ShamirError::Parse)?;
+
+        let x: u8 = parse_decimal(&captures[1])?;
+        let y = parse_field(&captures[2])?;
+
+        CompactShare::new(x, y)
+    }
+}
+
+impl RandomShamir {
+    /// Evaluates the polynomial interpolated from the first k shares at x, checking the remaining
+    /// shares against it.
+    fn evaluate_at<F: Field>(shares: &[RandomShare<F>], k: usize, x: F) -> Result<F, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+
+        let points: Vec<(F, F)> = shares.iter().map(|s| (s.x, s.y)).collect();
+        let y = interpolate_at(&points[..k], &x);
+        let inconsistent = inconsistent_points(&points, k);
+        if !inconsistent.is_empty() {
+            return Err(ShamirError::InconsistentShares(inconsistent));
+        }
+
+        Ok(y)
+    }
+
+    /// Splits a secret into n randomized shares, with k shares being sufficient to reconstruct it.
+    /// The x coordinates are derived from random indices with the given key, and the shares are
+    /// returned in compressed form.
+    #[cfg(feature = "std")]
+    pub fn split_compressed<F: Field + Debug + Display>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        key: &XKey,
+    ) -> Result<Vec<CompressedShare<F>>, ShamirError> {
+        Self::split_compressed_with_rng(secret, k, n, key, &mut thread_rng())
+    }
+
+    /// Splits a secret into n compressed shares like `split_compressed()`, but drawing all the
+    /// randomness from the given random number generator.
+    pub fn split_compressed_with_rng<F: Field + Debug + Display, R: Rng + CryptoRng + ?Sized>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        key: &XKey,
+        rng: &mut R,
+    ) -> Result<Vec<CompressedShare<F>>, ShamirError> {
+        check_split_parameters(k, n)?;
+
+        let polynom = generate_polynom(k, rng);
+
+        let mut xs: Vec<F> = Vec::with_capacity(n);
+        let mut indices: Vec<u32> = Vec::with_capacity(n);
+        while indices.len() < n {
+            let index: u32 = rng.gen();
+            let x: F = key.derive_x(index);
+            if x == F::ZERO || xs.contains(&x) {
+                continue;
             }
-            y += &(lagrange * &si.y * &denom.invert());
+            xs.push(x);
+            indices.push(index);
         }
+        let ys = evaluate_many(secret, &polynom, &xs);
 
-        // TODO: Verify the remaining shares.
+        let shares: Vec<CompressedShare<F>> = indices
+            .iter()
+            .zip(ys.iter())
+            .map(|(&x, &y)| Share { x, y })
+            .collect();
 
-        Some(Self::Share { x, y })
+        Ok(shares)
     }
 
-    #[cfg(feature = "parse")]
-    fn parse_x(s: &str) -> Option<Self::X> {
-        s.parse::<u8>().ok()
+    /// Expands a compressed share into a regular randomized share, given the key used to split it.
+    /// Returns an error if the index of the share derives to the x coordinate zero, which
+    /// `split_compressed()` never issues (see `Share::new`).
+    pub fn expand_share<F: Field>(
+        share: &CompressedShare<F>,
+        key: &XKey,
+    ) -> Result<RandomShare<F>, ShamirError> {
+        Share::new(key.derive_x(share.x), share.y)
     }
 
+    /// Parses a compressed share from a string, and expands it with the given key. Returns an
+    /// error if the parsing fails.
     #[cfg(feature = "parse")]
-    fn parse_share(s: &str) -> Option<Self::Share> {
+    pub fn parse_compressed_share<F: Field>(
+        s: &str,
+        key: &XKey,
+    ) -> Result<RandomShare<F>, ShamirError> {
         let regex = Regex::new(r"^([0-9]+)\|([0-9a-fA-F]+)$").unwrap();
-        let captures = regex.captures(s)?;
+        let captures = regex
+            .captures(strip_checksum(s)?)
+            .ok_or(ShamirError::Parse)?;
+
+        let x: u32 = parse_decimal(&captures[1])?;
+        let y = parse_field(&captures[2])?;
 
-        let x: u8 = captures[1].parse().ok()?;
-        let y = F::from_bytes(&hex::decode(&captures[2]).ok()?)?;
+        Self::expand_share(&Share { x, y }, key)
+    }
 
-        Some(Self::Share { x, y })
+    /// Reads at most `max_shares` compressed shares from a reader, one per line, and expands them
+    /// with the given key. See `Shamir::read_shares` for the limits.
+    #[cfg(feature = "parse")]
+    pub fn read_compressed_shares<F: Field, R: BufRead>(
+        reader: R,
+        max_shares: usize,
+        key: &XKey,
+    ) -> Result<(Vec<RandomShare<F>>, bool), ReadError> {
+        read_lines(reader, max_shares, |s| Self::parse_compressed_share(s, key))
     }
 }
 
@@ -201,115 +1504,243 @@ impl<F: Field + Debug + Display> Shamir<F> for RandomShamir {
     type X = F;
     type Share = RandomShare<F>;
 
-    fn split(secret: &F, k: usize, n: usize) -> Vec<Self::Share> {
-        check_split_parameters(k, n);
+    fn split_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        Self::split_at_with_rng(secret, k, n, F::ZERO, rng)
+    }
 
-        let polynom = generate_polynom(secret, k);
-        let mut rng = thread_rng();
+    fn split_many_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secrets: &[F],
+        k: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_ramp_parameters(secrets.len(), k, n)?;
+
+        let polynom = RampPolynom::new(secrets, k, rng);
+        let mut shares: Vec<RandomShare<F>> = Vec::with_capacity(n);
+        for _ in 0..n {
+            let x = loop {
+                let x = F::uniform(rng);
+                if !polynom.points.contains(&x) && shares.iter().all(|s| s.x != x) {
+                    break x;
+                }
+            };
+            shares.push(Share {
+                x,
+                y: polynom.evaluate(x),
+            });
+        }
+        Ok(shares)
+    }
+
+    fn split_at_with_rng<R: Rng + CryptoRng + ?Sized>(
+        secret: &F,
+        k: usize,
+        n: usize,
+        c: F,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_split_at_parameters(k, n, c == F::ZERO)?;
+
+        let polynom = generate_polynom(k, rng);
 
-        let mut shares: Vec<Self::Share> = Vec::with_capacity(n);
+        let mut xs: Vec<F> = Vec::with_capacity(n);
         for _ in 0..n {
             let x = 'retry: loop {
-                let x = F::uniform(&mut rng);
-                if x == F::ZERO {
+                let x = F::uniform(rng);
+                if x == F::ZERO || x == c || xs.contains(&x) {
                     continue 'retry;
                 }
-                for s in &shares {
-                    if x == s.x {
-                        continue 'retry;
-                    }
-                }
                 break x;
             };
+            xs.push(x);
+        }
+        // The polynomial is expressed in powers of (x - c).
+        let ds: Vec<F> = xs.iter().map(|&x| x - c).collect();
+        let ys = evaluate_many(secret, &polynom, &ds);
 
-            let mut y = *secret;
-            let mut xn = x;
-            for p in &polynom {
-                y += &(xn * p);
-                xn = xn * &x;
-            }
+        let shares: Vec<RandomShare<F>> = xs
+            .iter()
+            .zip(ys.iter())
+            .map(|(&x, &y)| Share { x, y })
+            .collect();
+
+        Ok(shares)
+    }
 
-            shares.push(Self::Share { x, y })
+    fn reconstruct(shares: &[Self::Share], k: usize) -> Result<F, ShamirError> {
+        let result = Self::reconstruct_verified(shares, k)?;
+        if !result.inconsistent.is_empty() {
+            return Err(ShamirError::InconsistentShares(result.inconsistent));
         }
+        Ok(result.secret)
+    }
+
+    fn reconstruct_verified(shares: &[Self::Share], k: usize) -> Result<Verified<F>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
 
-        shares
+        let points: Vec<(F, F)> = shares.iter().map(|s| (s.x, s.y)).collect();
+        Ok(Verified {
+            secret: interpolate_at(&points[..k], &F::ZERO),
+            inconsistent: inconsistent_points(&points, k),
+        })
     }
 
-    fn reconstruct(shares: &[Self::Share], k: usize) -> Option<F> {
-        check_reconstruct_parameters(shares, k);
+    fn interpolate(shares: &[Self::Share], k: usize) -> Result<Polynomial<F>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
 
-        let mut secret = F::ZERO;
-        for (i, si) in shares.iter().take(k).enumerate() {
-            let mut lagrange = F::ONE;
-            let mut denom = F::ONE;
-            let xi = si.x;
-            for (j, sj) in shares.iter().take(k).enumerate() {
-                if j != i {
-                    let xj = &sj.x;
-                    lagrange = lagrange * xj;
-                    denom = denom * &(*xj - xi);
-                }
-            }
-            secret += &(lagrange * &si.y * &denom.invert());
-        }
+        let points: Vec<(F, F)> = shares.iter().map(|s| (s.x, s.y)).collect();
+        interpolate_points(&points, k)
+    }
 
-        // TODO: Verify the remaining shares.
+    fn point(share: &Self::Share) -> (F, F) {
+        (share.x, share.y)
+    }
 
-        Some(secret)
+    fn reconstruct_at(shares: &[Self::Share], k: usize, x: F) -> Result<Self::Share, ShamirError> {
+        if x == F::ZERO {
+            return Err(ShamirError::SecretPoint);
+        }
+        let y = Self::evaluate_at(shares, k, x)?;
+        Ok(Self::Share { x, y })
     }
 
-    fn reconstruct_at(shares: &[Self::Share], k: usize, x: F) -> Option<Self::Share> {
-        check_reconstruct_parameters(shares, k);
+    fn refresh_with_rng<R: Rng + CryptoRng + ?Sized>(
+        shares: &[Self::Share],
+        k: usize,
+        c: F,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+
+        let polynom = generate_polynom(k, rng);
+
+        // The polynomial is expressed in powers of (x - c), so that it's zero at c.
+        let ds: Vec<F> = shares.iter().map(|share| share.x - c).collect();
+        let zeros = evaluate_many(&F::ZERO, &polynom, &ds);
+
+        Ok(shares
+            .iter()
+            .zip(zeros.iter())
+            .map(|(share, zero)| {
+                let mut y = share.y;
+                y += zero;
+                Self::Share { x: share.x, y }
+            })
+            .collect())
+    }
 
-        let mut y = F::ZERO;
-        for (i, si) in shares.iter().take(k).enumerate() {
-            let mut lagrange = F::ONE;
-            let mut denom = F::ONE;
-            let xi = si.x;
-            for (j, sj) in shares.iter().take(k).enumerate() {
-                if j != i {
-                    let xj = sj.x;
-                    lagrange = lagrange * &(xj - x);
-                    denom = denom * &(xj - xi);
+    fn reshare_with_rng<R: Rng + CryptoRng + ?Sized>(
+        shares: &[Self::Share],
+        k: usize,
+        new_k: usize,
+        new_n: usize,
+        c: F,
+        rng: &mut R,
+    ) -> Result<Vec<Self::Share>, ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
+        check_split_at_parameters(new_k, new_n, c == F::ZERO)?;
+
+        let old_shares = &shares[..k];
+
+        let mut new_xs: Vec<F> = Vec::with_capacity(new_n);
+        for _ in 0..new_n {
+            let x = 'retry: loop {
+                let x = F::uniform(rng);
+                if x == F::ZERO || x == c || new_xs.contains(&x) {
+                    continue 'retry;
                 }
-            }
-            y += &(lagrange * &si.y * &denom.invert());
+                break x;
+            };
+            new_xs.push(x);
         }
+        let new_ds: Vec<F> = new_xs.iter().map(|&x| x - c).collect();
+
+        // Each old holder splits their share into sub-shares for the new holders, with
+        // polynomials expressed in powers of (x - c).
+        let sub_shares: Vec<Coefficients<F>> = old_shares
+            .iter()
+            .map(|si| {
+                let polynom: Coefficients<F> =
+                    Coefficients::from((1..new_k).map(|_| F::uniform(rng)).collect::<Vec<F>>());
+                evaluate_many(&si.y, &polynom, &new_ds)
+            })
+            .collect();
+
+        // Lagrange coefficients of the old shares, for an interpolation at the secret point.
+        let old_xs: Vec<F> = old_shares.iter().map(|s| s.x).collect();
+        let lagrange = lagrange_coefficients(&old_xs, &barycentric_weights(&old_xs), &c);
+
+        // Each new holder combines the sub-shares they received.
+        Ok(new_xs
+            .iter()
+            .enumerate()
+            .map(|(j, &x)| {
+                let mut y = F::ZERO;
+                for (i, l) in lagrange.iter().enumerate() {
+                    y += &(sub_shares[i][j] * l);
+                }
+                Self::Share { x, y }
+            })
+            .collect())
+    }
+
+    fn reconstruct_secret_at(shares: &[Self::Share], k: usize, c: F) -> Result<F, ShamirError> {
+        Self::evaluate_at(shares, k, c)
+    }
 
-        // TODO: Verify the remaining shares.
+    fn reconstruct_robust(
+        shares: &[Self::Share],
+        k: usize,
+    ) -> Result<(F, Vec<usize>), ShamirError> {
+        check_reconstruct_parameters(shares, k)?;
 
-        Some(Self::Share { x, y })
+        let points: Vec<(F, F)> = shares.iter().map(|s| (s.x, s.y)).collect();
+        berlekamp_welch(&points, k)
     }
 
     #[cfg(feature = "parse")]
-    fn parse_x(s: &str) -> Option<Self::X> {
-        F::from_bytes(&hex::decode(s).ok()?)
+    fn parse_x(s: &str) -> Result<Self::X, ShamirError> {
+        parse_field(s)
     }
 
     #[cfg(feature = "parse")]
-    fn parse_share(s: &str) -> Option<Self::Share> {
+    fn parse_share(s: &str) -> Result<Self::Share, ShamirError> {
         let regex = Regex::new(r"^([0-9a-fA-F]+)\|([0-9a-fA-F]+)$").unwrap();
-        let captures = regex.captures(s)?;
+        let captures = regex
+            .captures(strip_checksum(s)?)
+            .ok_or(ShamirError::Parse)?;
 
-        let x = F::from_bytes(&hex::decode(&captures[1]).ok()?)?;
-        let y = F::from_bytes(&hex::decode(&captures[2]).ok()?)?;
+        let x = parse_field(&captures[1])?;
+        let y = parse_field(&captures[2])?;
 
-        Some(Self::Share { x, y })
+        RandomShare::new(x, y)
     }
 }
 
 #[cfg(test)]
 mod test {
+    #[cfg(feature = "parse")]
+    use super::append_checksum;
     use super::GetX;
-    use super::Shamir;
+    use super::{CompactShamir, Ramp, RandomShamir, Shamir, ShamirError, Share, XKey};
+    #[cfg(feature = "parse")]
+    use crate::checksum::{self, Mismatch};
     use crate::field::Field;
-    use rand::thread_rng;
-    use std::fmt::Debug;
+    use rand::{thread_rng, Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use std::fmt::{Debug, Display, LowerHex, UpperHex};
+    use std::str::FromStr;
 
     macro_rules! for_shamir {
-        ( $field:ident, $mod:ident, $shamir:ident, $($tests:tt)* ) => {
+        ( $field:ty, $mod:ident, $shamir:ident, $($tests:tt)* ) => {
             mod $mod {
-                type F = crate::gf2n::$field;
+                type F = $field;
                 type S = super::super::super::$shamir;
                 $($tests)*
             }
@@ -318,6 +1749,9 @@ mod test {
 
     macro_rules! for_field {
         ( $mod:ident, $field:ident, $($tests:tt)* ) => {
+            for_field!($mod, crate::gf2n::$field, $($tests)*);
+        };
+        ( $mod:ident, $field:ty, $($tests:tt)* ) => {
             mod $mod {
                 for_shamir!($field, compact, CompactShamir, $($tests)*);
                 for_shamir!($field, random, RandomShamir, $($tests)*);
@@ -341,6 +1775,8 @@ mod test {
             for_field!(gf512, GF512, $($tests)*);
             for_field!(gf1024, GF1024, $($tests)*);
             for_field!(gf2048, GF2048, $($tests)*);
+            for_field!(secp256k1, crate::gfp::Secp256k1Scalar, $($tests)*);
+            for_field!(ed25519, crate::gfp::Ed25519Scalar, $($tests)*);
         };
     }
 
@@ -367,6 +1803,11 @@ mod test {
             super::super::can_split::<F, S>();
         }
 
+        #[test]
+        fn can_split_with_rng() {
+            super::super::can_split_with_rng::<F, S>();
+        }
+
         #[cfg(not(debug_assertions))]
         #[test]
         fn can_split_big() {
@@ -410,72 +1851,184 @@ mod test {
             super::super::can_reconstruct_at_pairs::<F, S>();
         }
 
+        #[test]
+        fn can_derive_new_shares() {
+            super::super::can_derive_new_shares::<F, S>();
+        }
+
+        #[test]
+        fn can_reconstruct_many() {
+            super::super::can_reconstruct_many::<F, S>();
+        }
+
+        #[test]
+        fn can_interpolate() {
+            super::super::can_interpolate::<F, S>();
+        }
+
+        #[test]
+        fn can_reconstruct_secret_at() {
+            super::super::can_reconstruct_secret_at::<F, S>();
+        }
+
+        #[test]
+        fn can_reconstruct_refreshed() {
+            super::super::can_reconstruct_refreshed::<F, S>();
+        }
+
+        #[test]
+        fn can_reconstruct_reshared() {
+            super::super::can_reconstruct_reshared::<F, S>();
+        }
+
+        #[test]
+        fn can_reconstruct_reshared_at() {
+            super::super::can_reconstruct_reshared_at::<F, S>();
+        }
+
+        #[test]
+        fn can_detect_inconsistent_shares() {
+            super::super::can_detect_inconsistent_shares::<F, S>();
+        }
+
+        #[test]
+        fn can_reconstruct_robust() {
+            super::super::can_reconstruct_robust::<F, S>();
+        }
+
+        #[test]
+        fn can_report_errors() {
+            super::super::can_report_errors::<F, S>();
+        }
+
+        #[cfg(feature = "parse")]
+        #[test]
+        fn can_parse_shares() {
+            super::super::can_parse_shares::<F, S>();
+        }
+
+        #[cfg(feature = "parse")]
+        #[test]
+        fn can_parse_shares_split_at() {
+            super::super::can_parse_shares_split_at::<F, S>();
+        }
+
+        #[cfg(feature = "parse")]
+        #[test]
+        fn can_read_shares() {
+            super::super::can_read_shares::<F, S>();
+        }
+
+        #[cfg(feature = "parse")]
+        #[test]
+        fn can_parse_checksummed_shares() {
+            super::super::can_parse_checksummed_shares::<F, S>();
+        }
+
+        #[test]
+        fn can_parse_shares_from_str() {
+            super::super::can_parse_shares_from_str::<F, S>();
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn can_serialize_shares() {
+            super::super::can_serialize_shares::<F, S>();
+        }
+
+        #[cfg(feature = "bench")]
         use test::Bencher;
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_split_10(b: &mut Bencher) {
             super::super::bench_split::<F, S>(b, 10, 10);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_10(b: &mut Bencher) {
             super::super::bench_reconstruct::<F, S>(b, 10, 10);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_at_10(b: &mut Bencher) {
             super::super::bench_reconstruct_at::<F, S>(b, 10, 10);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_split_10_20(b: &mut Bencher) {
             super::super::bench_split::<F, S>(b, 10, 20);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_10_20(b: &mut Bencher) {
             super::super::bench_reconstruct::<F, S>(b, 10, 20);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_at_10_20(b: &mut Bencher) {
             super::super::bench_reconstruct_at::<F, S>(b, 10, 20);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_10_20_arbitrary(b: &mut Bencher) {
             super::super::bench_reconstruct_arbitrary::<F, S>(b, 10, 20);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_at_10_20_arbitrary(b: &mut Bencher) {
             super::super::bench_reconstruct_at_arbitrary::<F, S>(b, 10, 20);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_split_big_triple(b: &mut Bencher) {
             super::super::bench_split::<F, S>(b, 3, 255);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_big_triple(b: &mut Bencher) {
             super::super::bench_reconstruct::<F, S>(b, 3, 255);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_at_big_triple(b: &mut Bencher) {
             super::super::bench_reconstruct_at::<F, S>(b, 3, 255);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_big_triple_arbitrary(b: &mut Bencher) {
             super::super::bench_reconstruct_arbitrary::<F, S>(b, 3, 255);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_at_big_triple_arbitrary(b: &mut Bencher) {
             super::super::bench_reconstruct_at_arbitrary::<F, S>(b, 3, 255);
         }
+
+        #[cfg(feature = "bench")]
+        #[bench]
+        fn bench_format_share(b: &mut Bencher) {
+            super::super::bench_format_share::<F, S>(b);
+        }
+
+        #[cfg(feature = "parse")]
+        #[cfg(feature = "bench")]
+        #[bench]
+        fn bench_parse_share(b: &mut Bencher) {
+            super::super::bench_parse_share::<F, S>(b);
+        }
     }
 
     for_all_fast! {
@@ -484,18 +2037,22 @@ mod test {
             super::super::can_reconstruct_big_triples::<F, S>();
         }
 
+        #[cfg(feature = "bench")]
         use test::Bencher;
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_split_big_all(b: &mut Bencher) {
             super::super::bench_split::<F, S>(b, 255, 255);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_big_all(b: &mut Bencher) {
             super::super::bench_reconstruct::<F, S>(b, 255, 255);
         }
 
+        #[cfg(feature = "bench")]
         #[bench]
         fn bench_reconstruct_at_big_all(b: &mut Bencher) {
             super::super::bench_reconstruct_at::<F, S>(b, 255, 255);
@@ -511,12 +2068,50 @@ mod test {
         let secret = F::uniform(&mut rng);
         for k in 1..KMAX {
             for n in k..=255 {
-                let shares = S::split(&secret, k, n);
+                let shares = S::split(&secret, k, n).unwrap();
                 assert_eq!(shares.len(), n);
             }
         }
     }
 
+    fn can_split_with_rng<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let seed: [u8; 32] = rng.gen();
+
+        // The same seed yields the same shares.
+        let seeded = || ChaCha20Rng::from_seed(seed);
+        let shares = S::split_with_rng(&secret, 3, 10, &mut seeded()).unwrap();
+        let again = S::split_with_rng(&secret, 3, 10, &mut seeded()).unwrap();
+        assert_eq!(shares, again);
+        assert_eq!(S::reconstruct(&shares, 3), Ok(secret));
+
+        // Likewise for all the other randomized operations.
+        let c = S::X::from(42);
+        let shares_at = S::split_at_with_rng(&secret, 3, 10, c, &mut seeded()).unwrap();
+        assert_eq!(
+            S::split_at_with_rng(&secret, 3, 10, c, &mut seeded()),
+            Ok(shares_at)
+        );
+        let secrets = [secret, F::uniform(&mut rng)];
+        let packed = S::split_many_with_rng(&secrets, 3, 10, &mut seeded()).unwrap();
+        assert_eq!(
+            S::split_many_with_rng(&secrets, 3, 10, &mut seeded()),
+            Ok(packed)
+        );
+        let refreshed = S::refresh_with_rng(&shares, 3, S::X::from(0), &mut seeded()).unwrap();
+        assert_eq!(
+            S::refresh_with_rng(&shares, 3, S::X::from(0), &mut seeded()),
+            Ok(refreshed)
+        );
+        let reshared = S::reshare_with_rng(&shares, 3, 4, 6, S::X::from(0), &mut seeded()).unwrap();
+        assert_eq!(
+            S::reshare_with_rng(&shares, 3, 4, 6, S::X::from(0), &mut seeded()),
+            Ok(reshared.clone())
+        );
+        assert_eq!(S::reconstruct(&reshared, 4), Ok(secret));
+    }
+
     fn can_reconstruct<F: Field + Debug, S: Shamir<F> + ?Sized>() {
         #[cfg(not(debug_assertions))]
         const NMAX: usize = 10;
@@ -526,9 +2121,9 @@ mod test {
         let secret = F::uniform(&mut rng);
         for k in 1..NMAX {
             for n in k..NMAX {
-                let shares = S::split(&secret, k, n);
+                let shares = S::split(&secret, k, n).unwrap();
                 let reconstructed = S::reconstruct(&shares, k);
-                assert_eq!(reconstructed, Some(secret));
+                assert_eq!(reconstructed, Ok(secret));
             }
         }
     }
@@ -566,9 +2161,9 @@ mod test {
         let secret = F::uniform(&mut rng);
         for k in 1..NMAX {
             for n in k..NMAX {
-                let shares = S::split(&secret, k, n);
+                let shares = S::split(&secret, k, n).unwrap();
                 let reconstructed = S::reconstruct(&shares, n);
-                assert_eq!(reconstructed, Some(secret));
+                assert_eq!(reconstructed, Ok(secret));
             }
         }
     }
@@ -585,11 +2180,821 @@ mod test {
         }
     }
 
+    fn can_derive_new_shares<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let shares = S::split(&secret, 3, 5).unwrap();
+        let new_xs: Vec<S::X> = (6..=255)
+            .map(S::X::from)
+            .filter(|&x| shares.iter().all(|s| s.getx() != x))
+            .take(3)
+            .collect();
+
+        let derived = S::derive_new_shares(&shares[..3], 3, &new_xs, S::X::from(0)).unwrap();
+        assert_eq!(derived.len(), 3);
+        for (share, &x) in derived.iter().zip(&new_xs) {
+            assert!(share.getx() == x);
+        }
+        // The new shares are interchangeable with the existing ones.
+        let mixed = [&shares[3..], &derived[..1]].concat();
+        assert_eq!(S::reconstruct(&mixed, 3), Ok(secret));
+        assert_eq!(S::reconstruct(&derived, 3), Ok(secret));
+
+        assert_eq!(
+            S::derive_new_shares(&shares[..3], 3, &[shares[1].getx()], S::X::from(0)),
+            Err(ShamirError::DuplicateX)
+        );
+        assert_eq!(
+            S::derive_new_shares(&shares[..3], 3, &[new_xs[0], new_xs[0]], S::X::from(0)),
+            Err(ShamirError::DuplicateX)
+        );
+        assert_eq!(
+            S::derive_new_shares(&shares[..3], 3, &[S::X::from(0)], S::X::from(0)),
+            Err(ShamirError::SecretPoint)
+        );
+
+        // With the secret stored at c, the share at c would be the secret, and shares can't be
+        // issued at zero.
+        let c = new_xs[2];
+        let shares = S::split_at(&secret, 3, 5, c).unwrap();
+        let new_xs: Vec<S::X> = (6..=255)
+            .map(S::X::from)
+            .filter(|&x| x != c && shares.iter().all(|s| s.getx() != x))
+            .take(2)
+            .collect();
+        let derived = S::derive_new_shares(&shares[..3], 3, &new_xs, c).unwrap();
+        assert_eq!(
+            S::reconstruct_secret_at(&[&shares[3..], &derived[..1]].concat(), 3, c),
+            Ok(secret)
+        );
+        assert_eq!(
+            S::derive_new_shares(&shares[..3], 3, &[c], c),
+            Err(ShamirError::SecretPoint)
+        );
+        assert_eq!(
+            S::derive_new_shares(&shares[..3], 3, &[S::X::from(0)], c),
+            Err(ShamirError::InvalidX)
+        );
+    }
+
+    fn can_reconstruct_many<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        let mut rng = thread_rng();
+        let secrets: Vec<F> = (0..4).map(|_| F::uniform(&mut rng)).collect();
+        for l in 1..=3 {
+            for k in l..=4 {
+                let shares = S::split_many(&secrets[..l], k, 6).unwrap();
+                assert_eq!(shares.len(), 6);
+                assert_eq!(
+                    S::reconstruct_many(&shares, k, l),
+                    Ok(secrets[..l].to_vec())
+                );
+                assert_eq!(
+                    S::reconstruct_many(&shares[6 - k..], k, l),
+                    Ok(secrets[..l].to_vec())
+                );
+                assert_eq!(
+                    S::reconstruct_many(&shares[..k - 1], k, l),
+                    Err(ShamirError::NotEnoughShares)
+                );
+                // The first secret is stored at x = 0, like an ordinary split.
+                assert_eq!(S::reconstruct(&shares, k), Ok(secrets[0]));
+            }
+        }
+
+        assert_eq!(S::split_many(&[], 2, 3), Err(ShamirError::InvalidThreshold));
+        assert_eq!(
+            S::split_many(&secrets[..3], 2, 3),
+            Err(ShamirError::InvalidThreshold)
+        );
+        assert_eq!(
+            S::split_many(&secrets[..2], 2, 255),
+            Err(ShamirError::InvalidThreshold)
+        );
+        assert_eq!(
+            S::split_many(&secrets[..2], 2, 254).map(|s| s.len()),
+            Ok(254)
+        );
+        let shares = S::split_many(&secrets[..2], 2, 3).unwrap();
+        assert_eq!(
+            S::reconstruct_many(&shares, 2, 3),
+            Err(ShamirError::InvalidThreshold)
+        );
+    }
+
+    fn can_interpolate<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        #[cfg(not(debug_assertions))]
+        const NMAX: usize = 10;
+        #[cfg(debug_assertions)]
+        const NMAX: usize = 5;
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        for k in 1..NMAX {
+            for n in k..NMAX {
+                let shares = S::split(&secret, k, n).unwrap();
+                let polynom = S::interpolate(&shares[n - k..], k).unwrap();
+                assert_eq!(polynom.coefficients().len(), k);
+                assert!(polynom.degree().unwrap() < k);
+                assert_eq!(polynom.coefficients()[0], secret);
+                assert_eq!(S::interpolate(&shares, k), Ok(polynom));
+            }
+        }
+    }
+
+    fn can_reconstruct_secret_at<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        #[cfg(not(debug_assertions))]
+        const NMAX: usize = 10;
+        #[cfg(debug_assertions)]
+        const NMAX: usize = 5;
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        for c in [0, 1, 2, 255] {
+            let c = S::X::from(c);
+            for k in 1..NMAX {
+                for n in k..NMAX {
+                    let shares = S::split_at(&secret, k, n, c).unwrap();
+                    let reconstructed = S::reconstruct_secret_at(&shares, k, c);
+                    assert_eq!(reconstructed, Ok(secret));
+                }
+            }
+        }
+        let shares = S::split_at(&secret, 2, 254, S::X::from(255)).unwrap();
+        assert_eq!(shares.len(), 254);
+        assert!(shares.iter().all(|s| s.getx() != S::X::from(0)));
+        let reconstructed = S::reconstruct_secret_at(&shares, 2, S::X::from(255));
+        assert_eq!(reconstructed, Ok(secret));
+        // The share at the secret's coordinate would be the secret itself, and shares can't be at
+        // x = 0.
+        let c = S::X::from(255);
+        assert_eq!(
+            S::reconstruct_share_at(&shares[..2], 2, shares[2].getx(), c),
+            Ok(shares[2])
+        );
+        assert_eq!(
+            S::reconstruct_share_at(&shares, 2, c, c),
+            Err(ShamirError::SecretPoint)
+        );
+        assert_eq!(
+            S::reconstruct_share_at(&shares, 2, S::X::from(0), c),
+            Err(ShamirError::InvalidX)
+        );
+        // Neither zero nor the secret's coordinate can be issued.
+        assert_eq!(
+            S::split_at(&secret, 2, 255, S::X::from(255)),
+            Err(ShamirError::InvalidThreshold)
+        );
+        assert_eq!(
+            S::split_at(&secret, 2, 255, S::X::from(0)).map(|shares| shares.len()),
+            Ok(255)
+        );
+
+        // The share at x = 0 would be the secret itself.
+        let shares = S::split(&secret, 2, 3).unwrap();
+        assert_eq!(
+            S::reconstruct_at(&shares, 2, S::X::from(0)),
+            Err(ShamirError::SecretPoint)
+        );
+        assert_eq!(
+            S::reconstruct_share_at(&shares, 2, S::X::from(0), S::X::from(0)),
+            Err(ShamirError::SecretPoint)
+        );
+        assert_eq!(
+            S::reconstruct_secret_at(&shares, 2, S::X::from(0)),
+            Ok(secret)
+        );
+    }
+
+    fn can_reconstruct_refreshed<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        #[cfg(not(debug_assertions))]
+        const NMAX: usize = 10;
+        #[cfg(debug_assertions)]
+        const NMAX: usize = 5;
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        for k in 1..NMAX {
+            for n in k..NMAX {
+                let shares = S::split(&secret, k, n).unwrap();
+                let refreshed = S::refresh(&shares, k, S::X::from(0)).unwrap();
+                assert_eq!(refreshed.len(), n);
+                let reconstructed = S::reconstruct(&refreshed[n - k..], k);
+                assert_eq!(reconstructed, Ok(secret));
+
+                // The secret stays where it was stored.
+                let c = S::X::from(200);
+                let shares = S::split_at(&secret, k, n, c).unwrap();
+                let refreshed = S::refresh(&shares, k, c).unwrap();
+                let reconstructed = S::reconstruct_secret_at(&refreshed[n - k..], k, c);
+                assert_eq!(reconstructed, Ok(secret));
+            }
+        }
+    }
+
+    fn can_reconstruct_reshared<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        #[cfg(not(debug_assertions))]
+        const NMAX: usize = 7;
+        #[cfg(debug_assertions)]
+        const NMAX: usize = 4;
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        for k in 1..NMAX {
+            let shares = S::split(&secret, k, NMAX).unwrap();
+            for new_k in 1..NMAX {
+                for new_n in new_k..NMAX {
+                    let reshared =
+                        S::reshare(&shares[NMAX - k..], k, new_k, new_n, S::X::from(0)).unwrap();
+                    assert_eq!(reshared.len(), new_n);
+                    let reconstructed = S::reconstruct(&reshared[new_n - new_k..], new_k);
+                    assert_eq!(reconstructed, Ok(secret));
+                }
+            }
+        }
+    }
+
+    fn can_reconstruct_reshared_at<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        // The new shares would be issued at 1, 2, 3 and 4, so c must be skipped.
+        let c = S::X::from(2);
+        let shares = S::split_at(&secret, 3, 5, c).unwrap();
+        let reshared = S::reshare(&shares[2..], 3, 2, 4, c).unwrap();
+        assert_eq!(reshared.len(), 4);
+        assert!(reshared.iter().all(|s| s.getx() != c));
+        assert_eq!(S::reconstruct_secret_at(&reshared[2..], 2, c), Ok(secret));
+        assert_eq!(S::reconstruct_secret_at(&reshared[..2], 2, c), Ok(secret));
+        assert_eq!(
+            S::reshare(&shares, 3, 2, 255, c),
+            Err(ShamirError::InvalidThreshold)
+        );
+    }
+
+    fn can_detect_inconsistent_shares<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        #[cfg(not(debug_assertions))]
+        const NMAX: usize = 10;
+        #[cfg(debug_assertions)]
+        const NMAX: usize = 6;
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        for k in 1..NMAX {
+            for n in k..NMAX {
+                let shares = S::split(&secret, k, n).unwrap();
+                let refreshed = S::refresh(&shares, k, S::X::from(0)).unwrap();
+                for i in k..n {
+                    let mut received = shares.clone();
+                    received[i] = refreshed[i];
+                    let inconsistent: Vec<usize> = if refreshed[i] != shares[i] {
+                        vec![i]
+                    } else {
+                        vec![]
+                    };
+                    let expected = if inconsistent.is_empty() {
+                        Ok(secret)
+                    } else {
+                        Err(ShamirError::InconsistentShares(inconsistent.clone()))
+                    };
+
+                    let verified = S::reconstruct_verified(&received, k).unwrap();
+                    assert_eq!(verified.secret, secret);
+                    assert_eq!(verified.inconsistent, inconsistent);
+                    assert_eq!(S::reconstruct(&received, k), expected);
+                    assert_eq!(
+                        S::reconstruct_at(&received, k, shares[0].getx()).is_ok(),
+                        expected.is_ok()
+                    );
+                }
+            }
+        }
+    }
+
+    fn can_reconstruct_robust<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        #[cfg(not(debug_assertions))]
+        const NMAX: usize = 12;
+        #[cfg(debug_assertions)]
+        const NMAX: usize = 8;
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        for k in 1..NMAX {
+            for n in k..NMAX {
+                let shares = S::split(&secret, k, n).unwrap();
+                // Refreshed shares are at the same x coordinates, but are inconsistent with the
+                // original shares (except in the rare case of a collision).
+                let refreshed = S::refresh(&shares, k, S::X::from(0)).unwrap();
+                let mut corrupted: Vec<usize> = (0..n).collect();
+                corrupted.shuffle(&mut rng);
+                corrupted.truncate((n - k) / 2);
+                corrupted.sort_unstable();
+
+                let mut received = shares.clone();
+                for &i in &corrupted {
+                    received[i] = refreshed[i];
+                }
+                corrupted.retain(|&i| refreshed[i] != shares[i]);
+
+                let reconstructed = S::reconstruct_robust(&received, k);
+                assert_eq!(reconstructed, Ok((secret, corrupted)));
+            }
+        }
+    }
+
+    fn can_report_errors<F: Field + Debug, S: Shamir<F> + ?Sized>() {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        assert_eq!(S::split(&secret, 0, 5), Err(ShamirError::InvalidThreshold));
+        assert_eq!(S::split(&secret, 6, 5), Err(ShamirError::InvalidThreshold));
+        assert_eq!(
+            S::split(&secret, 2, 256),
+            Err(ShamirError::InvalidThreshold)
+        );
+
+        let shares = S::split(&secret, 3, 5).unwrap();
+        assert_eq!(
+            S::reconstruct(&shares, 0),
+            Err(ShamirError::InvalidThreshold)
+        );
+        assert_eq!(
+            S::reconstruct(&shares[..2], 3),
+            Err(ShamirError::NotEnoughShares)
+        );
+        assert_eq!(
+            S::reconstruct(&[shares[0], shares[1], shares[0]], 3),
+            Err(ShamirError::DuplicateX)
+        );
+    }
+
+    #[cfg(feature = "parse")]
+    fn can_parse_shares<F: Field + Debug, S: Shamir<F> + ?Sized>()
+    where
+        S::Share: Display,
+    {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let shares = S::split(&secret, 3, 10).unwrap();
+        for share in &shares {
+            let parsed = S::parse_share(&share.to_string());
+            assert_eq!(parsed, Ok(*share));
+        }
+    }
+
+    #[cfg(feature = "parse")]
+    fn can_parse_shares_split_at<F: Field + Debug, S: Shamir<F> + ?Sized>()
+    where
+        S::Share: Display,
+    {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        for c in [1, 7, 255] {
+            let c = S::X::from(c);
+            let shares = S::split_at(&secret, 3, 254, c).unwrap();
+            let parsed: Vec<S::Share> = shares
+                .iter()
+                .map(|share| S::parse_share(&share.to_string()).unwrap())
+                .collect();
+            assert_eq!(parsed, shares);
+            assert_eq!(S::reconstruct_secret_at(&parsed[251..], 3, c), Ok(secret));
+            assert_eq!(
+                S::split_at(&secret, 3, 255, c),
+                Err(ShamirError::InvalidThreshold)
+            );
+        }
+    }
+
+    #[cfg(feature = "parse")]
+    fn can_parse_checksummed_shares<F: Field + Debug, S: Shamir<F> + ?Sized>()
+    where
+        S::Share: Display,
+    {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let shares = S::split(&secret, 3, 10).unwrap();
+        for share in &shares {
+            let text = share.to_string();
+            let checksummed = match append_checksum(&text) {
+                Some(checksummed) => checksummed,
+                None => {
+                    assert!(text.len() > checksum::MAX_LEN);
+                    continue;
+                }
+            };
+            assert_eq!(S::parse_share(&checksummed), Ok(*share));
+
+            // Flipping a hexadecimal digit of y is caught and located.
+            let i = text.len() - 1;
+            let mut typo = checksummed.clone().into_bytes();
+            typo[i] = if typo[i] == b'0' { b'1' } else { b'0' };
+            let typo = String::from_utf8(typo).unwrap();
+            assert_eq!(
+                S::parse_share(&typo),
+                Err(ShamirError::Checksum(Mismatch::Data(i)))
+            );
+
+            let mut typo = checksummed.into_bytes();
+            let last = typo.len() - 1;
+            typo[last] = if typo[last] == b'0' { b'1' } else { b'0' };
+            let typo = String::from_utf8(typo).unwrap();
+            assert_eq!(
+                S::parse_share(&typo),
+                Err(ShamirError::Checksum(Mismatch::Checksum(1)))
+            );
+        }
+    }
+
+    fn can_parse_shares_from_str<F: Field + Debug, S: Shamir<F> + ?Sized>()
+    where
+        S::Share: Display + LowerHex + UpperHex + FromStr<Err = ShamirError>,
+    {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let shares = S::split(&secret, 3, 10).unwrap();
+        for share in &shares {
+            let text = share.to_string();
+            assert_eq!(format!("{share:x}"), text);
+            assert_eq!(text.parse(), Ok(*share));
+            assert_eq!(format!("{share:X}").parse(), Ok(*share));
+            #[cfg(feature = "parse")]
+            if let Some(checksummed) = append_checksum(&text) {
+                assert_eq!(checksummed.parse(), Ok(*share));
+            }
+        }
+        assert_eq!("".parse::<S::Share>(), Err(ShamirError::Parse));
+        assert_eq!(
+            format!("{}", shares[0])
+                .replace('|', ":")
+                .parse::<S::Share>(),
+            Err(ShamirError::Parse)
+        );
+    }
+
+    #[test]
+    fn ramp_thresholds() {
+        let ramp = Ramp::new(2, 5).unwrap();
+        assert_eq!(ramp.privacy(), 2);
+        assert_eq!(ramp.reconstruction(), 5);
+        assert_eq!(ramp.packing(), 3);
+        assert_eq!(Ramp::new(1, 2).map(|r| r.packing()), Ok(1));
+        assert_eq!(Ramp::new(0, 3), Err(ShamirError::InvalidThreshold));
+        assert_eq!(Ramp::new(3, 3), Err(ShamirError::InvalidThreshold));
+        assert_eq!(Ramp::new(4, 3), Err(ShamirError::InvalidThreshold));
+    }
+
+    #[test]
+    fn can_destructure_shares() {
+        use crate::gf2n::GF64;
+        let shares = CompactShamir::split(&GF64::from(42), 2, 3).unwrap();
+        for share in shares {
+            let (x, y) = (*share.x(), *share.y());
+            assert_eq!(share.into_parts(), (x, y));
+            assert_eq!(Share::new(x, y), Ok(share));
+        }
+
+        let shares = RandomShamir::split(&GF64::from(42), 2, 3).unwrap();
+        let parts: Vec<_> = shares.iter().map(|s| s.into_parts()).collect();
+        let shares: Vec<_> = parts
+            .into_iter()
+            .map(|(x, y)| Share::new(x, y).unwrap())
+            .collect();
+        assert_eq!(RandomShamir::reconstruct(&shares, 2), Ok(GF64::from(42)));
+    }
+
+    #[test]
+    fn error_codes_are_distinct() {
+        let errors = [
+            ShamirError::InvalidThreshold,
+            ShamirError::NotEnoughShares,
+            ShamirError::DuplicateX,
+            ShamirError::InconsistentShares(vec![1]),
+            ShamirError::TooManyCorruptedShares,
+            ShamirError::Parse,
+            ShamirError::InvalidX,
+            ShamirError::SecretPoint,
+            ShamirError::Checksum(Mismatch::Unknown),
+            ShamirError::InvalidRecord,
+            ShamirError::DifferentX,
+            ShamirError::DifferentFields,
+            ShamirError::InvalidContributions(vec![1]),
+        ];
+        let codes: std::collections::BTreeSet<u8> = errors.iter().map(|e| e.code()).collect();
+        assert_eq!(codes.len(), errors.len());
+        for e in &errors {
+            let category = e.category().code();
+            assert!((category..category + 10).contains(&e.code()));
+        }
+        assert_eq!(ShamirError::InvalidThreshold.code(), 30);
+        assert_eq!(ShamirError::InconsistentShares(vec![]).code(), 41);
+        assert_eq!(ShamirError::InvalidRecord.code(), 50);
+    }
+
+    #[cfg(not(any(feature = "debug-values", feature = "redact-debug")))]
+    #[test]
+    fn debug_is_redacted() {
+        use crate::gf2n::GF64;
+        use crate::gfp::Ed25519Scalar;
+        let share = Share::<u8, _>::new(3, GF64::from(42)).unwrap();
+        assert_eq!(format!("{share:?}"), "Share { x: 3, y: GF2^64<redacted> }");
+        let share = Share::new(Ed25519Scalar::from(3), Ed25519Scalar::from(42)).unwrap();
+        assert_eq!(
+            format!("{share:?}"),
+            "Share { x: GFp253<redacted>, y: GFp253<redacted> }"
+        );
+    }
+
+    #[test]
+    fn compact_shares_from_str_are_canonical() {
+        type Share = super::CompactShare<crate::gf2n::GF8>;
+        assert_eq!(
+            "1|aB".parse::<Share>(),
+            Share::new(1, crate::gf2n::GF8::from(0xab))
+        );
+        assert_eq!("0|ab".parse::<Share>(), Err(ShamirError::InvalidX));
+        for s in ["+1|ab", "01|ab", "256|ab", "1|abc", "1|ab|", "1|", "|ab"] {
+            assert_eq!(s.parse::<Share>(), Err(ShamirError::Parse));
+        }
+    }
+
+    #[cfg(feature = "parse")]
+    fn can_read_shares<F: Field + Debug, S: Shamir<F> + ?Sized>()
+    where
+        S::Share: Display,
+    {
+        use super::{ReadError, MAX_LINE_LEN};
+
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let shares = S::split(&secret, 3, 10).unwrap();
+        let text: String = shares.iter().map(|s| format!("{s}\n")).collect();
+
+        let (read, truncated) = S::read_shares(text.as_bytes(), 10).unwrap();
+        assert_eq!(read, shares);
+        assert!(!truncated);
+        let (read, truncated) = S::read_shares(text.as_bytes(), 4).unwrap();
+        assert_eq!(read, &shares[..4]);
+        assert!(truncated);
+        // The last line doesn't need a terminating newline.
+        let (read, _) = S::read_shares(text.trim_end().as_bytes(), 10).unwrap();
+        assert_eq!(read, shares);
+
+        let invalid = format!("{}\n{}\nfoo\n", shares[0], shares[1]);
+        assert!(matches!(
+            S::read_shares(invalid.as_bytes(), 10),
+            Err(ReadError::Parse(3, ShamirError::Parse))
+        ));
+        // Nothing after the limit is parsed.
+        assert!(S::read_shares(invalid.as_bytes(), 2).unwrap().1);
+
+        let long = format!("{}\n{}", shares[0], "0".repeat(10 * MAX_LINE_LEN));
+        assert!(matches!(
+            S::read_shares(long.as_bytes(), 10),
+            Err(ReadError::LineTooLong(2))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    fn can_serialize_shares<F: Field + Debug, S: Shamir<F> + ?Sized>()
+    where
+        S::Share: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let shares = S::split(&secret, 3, 10).unwrap();
+        let json = serde_json::to_string(&shares).unwrap();
+        let parsed: Vec<S::Share> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, shares);
+    }
+
+    mod compressed {
+        #[test]
+        fn can_reconstruct_compressed() {
+            super::can_reconstruct_compressed::<crate::gf2n::GF8>();
+            super::can_reconstruct_compressed::<crate::gf2n::GF256>();
+            super::can_reconstruct_compressed::<crate::gfp::Secp256k1Scalar>();
+        }
+
+        #[cfg(feature = "parse")]
+        #[test]
+        fn can_parse_compressed_shares() {
+            super::can_parse_compressed_shares::<crate::gf2n::GF8>();
+            super::can_parse_compressed_shares::<crate::gf2n::GF256>();
+            super::can_parse_compressed_shares::<crate::gfp::Secp256k1Scalar>();
+        }
+    }
+
+    mod homomorphic {
+        use super::super::{add_sharings, CompactShamir, RandomShamir, Shamir, ShamirError};
+        use crate::field::Field;
+        use crate::gf2n::GF64;
+        use rand::thread_rng;
+
+        #[test]
+        fn can_add_compact_sharings() {
+            let mut rng = thread_rng();
+            let (a, b) = (GF64::uniform(&mut rng), GF64::uniform(&mut rng));
+            let mut sum = a;
+            sum += &b;
+
+            let shares_a = CompactShamir::split(&a, 3, 5).unwrap();
+            let shares_b = CompactShamir::split(&b, 2, 5).unwrap();
+            let shares = add_sharings(&shares_a, &shares_b).unwrap();
+            assert_eq!(CompactShamir::reconstruct(&shares, 3), Ok(sum));
+            assert_eq!(CompactShamir::reconstruct(&shares[2..], 3), Ok(sum));
+            assert_eq!(shares_a[1].add(&shares_b[1]), Ok(shares[1]));
+
+            assert_eq!(
+                add_sharings(&shares_a[1..], &shares_b[..4]),
+                Err(ShamirError::DifferentX)
+            );
+            assert_eq!(
+                add_sharings(&shares_a, &shares_b[..4]),
+                Err(ShamirError::DifferentX)
+            );
+            assert_eq!(shares_a[0].add(&shares_b[1]), Err(ShamirError::DifferentX));
+        }
+
+        #[test]
+        fn can_add_assign_share() {
+            let mut rng = thread_rng();
+            let (a, b) = (GF64::uniform(&mut rng), GF64::uniform(&mut rng));
+            let shares_a = CompactShamir::split(&a, 2, 3).unwrap();
+            let shares_b = CompactShamir::split(&b, 2, 3).unwrap();
+
+            let mut share = shares_a[0];
+            assert_eq!(share.add_assign_share(&shares_b[0]), Ok(()));
+            assert_eq!(Ok(share), shares_a[0].add(&shares_b[0]));
+
+            let before = share;
+            assert_eq!(
+                share.add_assign_share(&shares_b[1]),
+                Err(ShamirError::DifferentX)
+            );
+            assert_eq!(share, before);
+        }
+
+        #[test]
+        fn can_add_random_sharings() {
+            let mut rng = thread_rng();
+            let (a, b) = (GF64::uniform(&mut rng), GF64::uniform(&mut rng));
+            let mut sum = a;
+            sum += &b;
+
+            let shares_a = RandomShamir::split(&a, 3, 5).unwrap();
+            let shares_b = RandomShamir::split(&b, 3, 5).unwrap();
+            assert_eq!(
+                add_sharings(&shares_a, &shares_b),
+                Err(ShamirError::DifferentX)
+            );
+
+            // The second sharing is evaluated at the x coordinates of the first one.
+            let aligned = shares_a
+                .iter()
+                .map(|s| RandomShamir::reconstruct_at(&shares_b, 3, s.x))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let shares = add_sharings(&shares_a, &aligned).unwrap();
+            assert_eq!(RandomShamir::reconstruct(&shares, 3), Ok(sum));
+        }
+    }
+
+    mod checked {
+        #[cfg(feature = "parse")]
+        use super::super::{CompactShamir, RandomShamir, Shamir};
+        use super::super::{ShamirError, Share};
+        use crate::field::Field;
+        use crate::gf2n::GF64;
+        #[cfg(feature = "parse")]
+        use crate::gfp::Secp256k1Scalar;
+
+        #[test]
+        fn can_reject_zero_x() {
+            let y = GF64::from(42);
+            assert!(Share::<u8, GF64>::new(1, y).is_ok());
+            assert!(Share::<u8, GF64>::new(255, y).is_ok());
+            assert_eq!(Share::<u8, GF64>::new(0, y), Err(ShamirError::InvalidX));
+            assert!(Share::<GF64, GF64>::new(GF64::ONE, y).is_ok());
+            assert_eq!(
+                Share::<GF64, GF64>::new(GF64::ZERO, y),
+                Err(ShamirError::InvalidX)
+            );
+        }
+
+        #[cfg(feature = "parse")]
+        #[test]
+        fn can_reject_invalid_shares() {
+            let y = "0123456789abcdef";
+            assert!(<CompactShamir as Shamir<GF64>>::parse_share(&format!("7|{y}")).is_ok());
+            assert_eq!(
+                <CompactShamir as Shamir<GF64>>::parse_share(&format!("0|{y}")),
+                Err(ShamirError::InvalidX)
+            );
+            assert_eq!(
+                <CompactShamir as Shamir<GF64>>::parse_share(&format!("07|{y}")),
+                Err(ShamirError::Parse)
+            );
+            assert_eq!(
+                <CompactShamir as Shamir<GF64>>::parse_share(&format!("256|{y}")),
+                Err(ShamirError::Parse)
+            );
+            assert_eq!(
+                <RandomShamir as Shamir<GF64>>::parse_share(&format!("0000000000000000|{y}")),
+                Err(ShamirError::InvalidX)
+            );
+
+            // Integers that aren't reduced modulo the order aren't canonical encodings.
+            let order = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+            assert_eq!(
+                <CompactShamir as Shamir<Secp256k1Scalar>>::parse_share(&format!("1|{order}")),
+                Err(ShamirError::Parse)
+            );
+            assert_eq!(
+                <RandomShamir as Shamir<Secp256k1Scalar>>::parse_share(&format!("{order}|{order}")),
+                Err(ShamirError::Parse)
+            );
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn can_reject_deserialized_zero_x() {
+            let y = GF64::from(42);
+            let share = Share::<u8, GF64>::new(1, y).unwrap();
+            let json = serde_json::to_string(&share).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Share<u8, GF64>>(&json).unwrap(),
+                share
+            );
+            let zero = json.replace("\"x\":1", "\"x\":0");
+            assert!(serde_json::from_str::<Share<u8, GF64>>(&zero).is_err());
+        }
+    }
+
+    fn can_reconstruct_compressed<F: Field + Debug + Display>() {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let key = XKey::random();
+        // All the non-zero x coordinates are needed in GF(2^8).
+        let shares = RandomShamir::split_compressed(&secret, 10, 255, &key).unwrap();
+        let expanded: Vec<_> = shares
+            .iter()
+            .map(|s| RandomShamir::expand_share(s, &key).unwrap())
+            .collect();
+        assert_eq!(RandomShamir::reconstruct(&expanded, 10), Ok(secret));
+        assert_eq!(RandomShamir::reconstruct(&expanded[245..], 10), Ok(secret));
+
+        // The same seed yields the same key and shares.
+        let seed: [u8; 32] = rng.gen();
+        let seeded = || ChaCha20Rng::from_seed(seed);
+        let key = XKey::random_with_rng(&mut seeded());
+        assert_eq!(XKey::random_with_rng(&mut seeded()), key);
+        let seeded_shares =
+            RandomShamir::split_compressed_with_rng(&secret, 3, 10, &key, &mut seeded()).unwrap();
+        assert_eq!(
+            RandomShamir::split_compressed_with_rng(&secret, 3, 10, &key, &mut seeded()),
+            Ok(seeded_shares)
+        );
+
+        // Another key yields unrelated x coordinates, skipping the indices that it derives to zero.
+        let other_key = XKey::random();
+        let other: Vec<_> = shares
+            .iter()
+            .filter_map(|s| RandomShamir::expand_share(s, &other_key).ok())
+            .take(10)
+            .collect();
+        assert_ne!(RandomShamir::reconstruct(&other, 10), Ok(secret));
+    }
+
+    #[cfg(feature = "parse")]
+    fn can_parse_compressed_shares<F: Field + Debug + Display>() {
+        let mut rng = thread_rng();
+        let secret = F::uniform(&mut rng);
+        let key = XKey::random();
+        assert_eq!(XKey::parse(&key.to_string()), Ok(key));
+
+        let shares = RandomShamir::split_compressed(&secret, 3, 10, &key).unwrap();
+        for share in &shares {
+            let parsed = RandomShamir::parse_compressed_share(&share.to_string(), &key);
+            assert_eq!(parsed, RandomShamir::expand_share(share, &key));
+            let checksummed = append_checksum(&share.to_string()).unwrap();
+            let parsed = RandomShamir::parse_compressed_share(&checksummed, &key);
+            assert_eq!(parsed, RandomShamir::expand_share(share, &key));
+        }
+
+        let text: String = shares.iter().map(|s| format!("{s}\n")).collect();
+        let (read, truncated) =
+            RandomShamir::read_compressed_shares::<F, _>(text.as_bytes(), 5, &key).unwrap();
+        assert!(truncated);
+        for (read, share) in read.iter().zip(&shares) {
+            assert_eq!(Ok(*read), RandomShamir::expand_share(share, &key));
+        }
+
+        // An index whose x coordinate would be zero is rejected, which is only likely to be found
+        // in small fields.
+        if let Some(index) = (0..4096).find(|&i| key.derive_x::<F>(i) == F::ZERO) {
+            assert_eq!(
+                RandomShamir::parse_compressed_share::<F>(&format!("{}|{}", index, F::ZERO), &key),
+                Err(ShamirError::InvalidX)
+            );
+        }
+    }
+
     #[cfg(not(debug_assertions))]
     fn can_split_big<F: Field + Debug, S: Shamir<F> + ?Sized>() {
         let mut rng = thread_rng();
         let secret = F::uniform(&mut rng);
-        let shares = S::split(&secret, 255, 255);
+        let shares = S::split(&secret, 255, 255).unwrap();
         assert_eq!(shares.len(), 255);
     }
 
@@ -597,9 +3002,9 @@ mod test {
     fn can_reconstruct_big<F: Field + Debug, S: Shamir<F> + ?Sized>() {
         let mut rng = thread_rng();
         let secret = F::uniform(&mut rng);
-        let shares = S::split(&secret, 255, 255);
+        let shares = S::split(&secret, 255, 255).unwrap();
         let reconstructed = S::reconstruct(&shares, 255);
-        assert_eq!(reconstructed, Some(secret));
+        assert_eq!(reconstructed, Ok(secret));
     }
 
     #[cfg(not(debug_assertions))]
@@ -617,102 +3022,131 @@ mod test {
     }
 
     fn reconstruct_pairs<F: Field + Debug, S: Shamir<F> + ?Sized>(secret: F, n: usize) {
-        let shares = S::split(&secret, 2, n);
+        let shares = S::split(&secret, 2, n).unwrap();
         for a in 0..n {
             for b in 0..a {
                 let reconstructed = S::reconstruct(&[shares[a], shares[b]], 2);
-                assert_eq!(reconstructed, Some(secret));
+                assert_eq!(reconstructed, Ok(secret));
             }
         }
     }
 
     fn reconstruct_triples<F: Field + Debug, S: Shamir<F> + ?Sized>(secret: F, n: usize) {
-        let shares = S::split(&secret, 3, n);
+        let shares = S::split(&secret, 3, n).unwrap();
         for a in 0..n {
             for b in 0..a {
                 for c in 0..b {
                     let reconstructed = S::reconstruct(&[shares[a], shares[b], shares[c]], 3);
-                    assert_eq!(reconstructed, Some(secret));
+                    assert_eq!(reconstructed, Ok(secret));
                 }
             }
         }
     }
 
     fn reconstruct_at_pairs<F: Field + Debug, S: Shamir<F> + ?Sized>(secret: F, n: usize) {
-        let shares = S::split(&secret, 2, n);
+        let shares = S::split(&secret, 2, n).unwrap();
         for a in 0..n {
             for b in 0..a {
                 for c in 0..b {
                     let reconstructed =
                         S::reconstruct_at(&[shares[a], shares[b]], 2, shares[c].getx());
-                    assert_eq!(reconstructed, Some(shares[c]));
+                    assert_eq!(reconstructed, Ok(shares[c]));
                 }
             }
         }
     }
 
+    #[cfg(feature = "bench")]
     use rand::rngs::SmallRng;
     use rand::seq::SliceRandom;
-    use rand::SeedableRng;
+    #[cfg(feature = "bench")]
     use std::hint::black_box;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
+    #[cfg(feature = "bench")]
     fn bench_split<F: Field + Debug, S: Shamir<F> + ?Sized>(b: &mut Bencher, k: usize, n: usize) {
         let secret = F::uniform(&mut thread_rng());
         b.iter(|| S::split(black_box(&secret), k, n));
     }
 
+    #[cfg(feature = "bench")]
     fn bench_reconstruct<F: Field + Debug, S: Shamir<F> + ?Sized>(
         b: &mut Bencher,
         k: usize,
         n: usize,
     ) {
         let secret = F::uniform(&mut thread_rng());
-        let shares = S::split(&secret, k, n);
+        let shares = S::split(&secret, k, n).unwrap();
         b.iter(|| S::reconstruct(black_box(&shares), k));
     }
 
+    #[cfg(feature = "bench")]
     fn bench_reconstruct_arbitrary<F: Field + std::fmt::Debug, S: Shamir<F> + ?Sized>(
         b: &mut Bencher,
         k: usize,
         n: usize,
     ) {
         let secret = F::uniform(&mut thread_rng());
-        let mut shares = S::split(&secret, k, n);
+        let mut shares = S::split(&secret, k, n).unwrap();
         // Choose a fast rng to limit the impact on the measurements.
         let mut rng = SmallRng::from_entropy();
         b.iter(|| {
             // Pick arbitrary values on each run.
             let (chosen, _) = shares.partial_shuffle(&mut rng, k);
-            S::reconstruct(black_box(&chosen), k)
+            S::reconstruct(black_box(chosen), k)
         });
     }
 
+    #[cfg(feature = "bench")]
     fn bench_reconstruct_at<F: Field + Debug, S: Shamir<F> + ?Sized>(
         b: &mut Bencher,
         k: usize,
         n: usize,
     ) {
         let secret = F::uniform(&mut thread_rng());
-        let shares = S::split(&secret, k, n);
+        let shares = S::split(&secret, k, n).unwrap();
         let x = S::X::from(42);
         b.iter(|| S::reconstruct_at(black_box(&shares), k, black_box(x)));
     }
 
+    #[cfg(feature = "bench")]
     fn bench_reconstruct_at_arbitrary<F: Field + std::fmt::Debug, S: Shamir<F> + ?Sized>(
         b: &mut Bencher,
         k: usize,
         n: usize,
     ) {
         let secret = F::uniform(&mut thread_rng());
-        let mut shares = S::split(&secret, k, n);
+        let mut shares = S::split(&secret, k, n).unwrap();
         // Choose a fast rng to limit the impact on the measurements.
         let mut rng = SmallRng::from_entropy();
         let x = S::X::from(42);
         b.iter(|| {
             // Pick arbitrary values on each run.
             let (chosen, _) = shares.partial_shuffle(&mut rng, k);
-            S::reconstruct_at(black_box(&chosen), k, black_box(x))
+            S::reconstruct_at(black_box(chosen), k, black_box(x))
         });
     }
+
+    #[cfg(feature = "bench")]
+    fn bench_format_share<F: Field + Debug, S: Shamir<F> + ?Sized>(b: &mut Bencher)
+    where
+        S::Share: Display,
+    {
+        let secret = F::uniform(&mut thread_rng());
+        let shares = S::split(&secret, 10, 10).unwrap();
+        b.iter(|| black_box(&shares[0]).to_string());
+    }
+
+    #[cfg(feature = "parse")]
+    #[cfg(feature = "bench")]
+    fn bench_parse_share<F: Field + Debug, S: Shamir<F> + ?Sized>(b: &mut Bencher)
+    where
+        S::Share: Display,
+    {
+        let secret = F::uniform(&mut thread_rng());
+        let shares = S::split(&secret, 10, 10).unwrap();
+        let share = shares[0].to_string();
+        b.iter(|| S::parse_share(black_box(&share)));
+    }
 }
@@ -0,0 +1,213 @@
+//! Canonical form of a set of shares, so that the same logical set of shares always has the same
+//! representation and digest, regardless of the order of the shares and of the formatting of the
+//! input.
+
+use crate::field::Field;
+use crate::hashing::HashAlgorithm;
+use crate::shamir::{GetX, ReadError, Shamir, ShamirError, Share};
+use core::fmt::Display;
+use core::marker::PhantomData;
+use rand::{thread_rng, CryptoRng, Rng};
+use std::io::BufRead;
+
+/// Set of shares of a Shamir scheme.
+pub struct ShareSet<F: Field, S: Shamir<F> + ?Sized> {
+    shares: Vec<S::Share>,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: Field, S: Shamir<F> + ?Sized> ShareSet<F, S>
+where
+    S::Share: Display,
+{
+    /// Creates a set from the given shares, in their current order.
+    pub fn new(shares: Vec<S::Share>) -> Self {
+        ShareSet {
+            shares,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Reads at most `max_shares` shares from a reader, with the same rules as `Shamir::read_shares`.
+    /// Returns the set, and whether the input contained more shares than the limit.
+    pub fn read<R: BufRead>(reader: R, max_shares: usize) -> Result<(Self, bool), ReadError> {
+        let (shares, truncated) = S::read_shares(reader, max_shares)?;
+        Ok((Self::new(shares), truncated))
+    }
+
+    /// Returns the shares, in their current order.
+    pub fn shares(&self) -> &[S::Share] {
+        &self.shares
+    }
+
+    /// Sorts the shares by x coordinate and removes duplicate shares. Shares with the same x
+    /// coordinate but different y coordinates are kept, ordered by y.
+    pub fn canonicalize(&mut self) {
+        // The x coordinates are formatted in decimal for compact shares and in fixed-width
+        // hexadecimal for random shares, so sorting the formatted shares by the length of x and
+        // then lexicographically sorts them by x.
+        self.shares.sort_by_cached_key(|share| {
+            let text = share.to_string();
+            let x_len = text.find('|').unwrap_or(text.len());
+            (x_len, text)
+        });
+        self.shares.dedup();
+    }
+
+    /// Returns the SHA-256 digest of the canonical form of the set, i.e. of the canonicalized shares
+    /// in their text format, one per line.
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest_with(HashAlgorithm::default())
+    }
+
+    /// Returns the digest of the canonical form of the set like `digest()`, but with the given hash
+    /// algorithm.
+    pub fn digest_with(&self, hash: HashAlgorithm) -> [u8; 32] {
+        let mut canonical = Self::new(self.shares.clone());
+        canonical.canonicalize();
+        hash.digest(canonical.to_string().as_bytes())
+    }
+}
+
+impl<X, F, S> ShareSet<F, S>
+where
+    X: Copy + PartialEq + From<u8>,
+    F: Field,
+    S: Shamir<F, X = X, Share = Share<X, F>> + ?Sized,
+{
+    /// Adds a fresh random sharing of zero with threshold k to the shares, in place. The shares
+    /// still reconstruct the same secret with the same threshold, but can no longer be combined
+    /// with shares from before the call, as in `Shamir::refresh()`. Returns an error if k is out
+    /// of range.
+    ///
+    /// Shares with the same x coordinate receive the same share of zero, so that duplicates stay
+    /// consistent.
+    pub fn add_zero_sharing(&mut self, k: usize) -> Result<(), ShamirError> {
+        self.add_zero_sharing_with_rng(k, &mut thread_rng())
+    }
+
+    /// Adds a random sharing of zero like `add_zero_sharing()`, but drawing the randomness from the
+    /// given random number generator.
+    pub fn add_zero_sharing_with_rng<R: Rng + CryptoRng + ?Sized>(
+        &mut self,
+        k: usize,
+        rng: &mut R,
+    ) -> Result<(), ShamirError> {
+        // Any k shares of zero define the polynomial, which is then evaluated at the x coordinates
+        // of the set.
+        let zero = S::split_with_rng(&F::ZERO, k, k, rng)?;
+        for share in self.shares.iter_mut() {
+            let delta = S::reconstruct_at(&zero, k, share.getx())?;
+            share.add_assign_share(&delta)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the shares in their current order, one per line.
+impl<F: Field, S: Shamir<F> + ?Sized> Display for ShareSet<F, S>
+where
+    S::Share: Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for share in &self.shares {
+            writeln!(f, "{}", share)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ShareSet;
+    use crate::field::Field;
+    use crate::gf2n::GF128;
+    use crate::shamir::{CompactShamir, GetX, RandomShamir, Shamir, ShamirError, Share};
+    use rand::seq::SliceRandom;
+    use rand::thread_rng;
+    use std::fmt::{Debug, Display};
+
+    #[test]
+    fn can_canonicalize_compact() {
+        can_canonicalize::<CompactShamir>();
+    }
+
+    #[test]
+    fn can_canonicalize_random() {
+        can_canonicalize::<RandomShamir>();
+    }
+
+    fn can_canonicalize<S: Shamir<GF128>>()
+    where
+        S::Share: Display,
+    {
+        let mut rng = thread_rng();
+        let secret = GF128::uniform(&mut rng);
+        let shares = S::split(&secret, 3, 20).unwrap();
+        let digest = ShareSet::<GF128, S>::new(shares.clone()).digest();
+
+        let mut shuffled = shares.clone();
+        shuffled.shuffle(&mut rng);
+        shuffled.push(shuffled[0]);
+        let mut text = String::from("# Shares of the test secret\n\n");
+        for share in &shuffled {
+            text += &format!("  {}\t\n", share);
+        }
+        let (mut set, truncated) = ShareSet::<GF128, S>::read(text.as_bytes(), 255).unwrap();
+        assert!(!truncated);
+        assert_eq!(set.shares().len(), 21);
+        assert_eq!(set.digest(), digest);
+
+        set.canonicalize();
+        assert_eq!(set.shares().len(), 20);
+        let mut canonical = ShareSet::<GF128, S>::new(shares.clone());
+        canonical.canonicalize();
+        assert_eq!(set.to_string(), canonical.to_string());
+        for pair in set.shares().windows(2) {
+            assert!(x_of(&pair[0]) < x_of(&pair[1]));
+        }
+
+        let other = ShareSet::<GF128, S>::new(shares[1..].to_vec());
+        assert_ne!(other.digest(), digest);
+    }
+
+    #[test]
+    fn can_add_zero_sharing_compact() {
+        can_add_zero_sharing::<_, CompactShamir>();
+    }
+
+    #[test]
+    fn can_add_zero_sharing_random() {
+        can_add_zero_sharing::<_, RandomShamir>();
+    }
+
+    fn can_add_zero_sharing<X, S>()
+    where
+        X: Copy + PartialEq + From<u8> + Debug + Display,
+        S: Shamir<GF128, X = X, Share = Share<X, GF128>>,
+    {
+        let secret = GF128::uniform(&mut thread_rng());
+        let shares = S::split(&secret, 3, 5).unwrap();
+        let mut set = ShareSet::<GF128, S>::new(shares.clone());
+        set.add_zero_sharing(3).unwrap();
+
+        let refreshed = set.shares();
+        assert_eq!(S::reconstruct(&refreshed[..3], 3), Ok(secret));
+        assert_eq!(S::reconstruct(&refreshed[2..], 3), Ok(secret));
+        for (old, new) in shares.iter().zip(refreshed) {
+            assert_eq!(old.getx(), new.getx());
+            assert_ne!(old, new);
+        }
+        let mixed = [shares[0], shares[1], refreshed[2]];
+        assert_ne!(S::reconstruct(&mixed, 3), Ok(secret));
+
+        assert_eq!(set.add_zero_sharing(0), Err(ShamirError::InvalidThreshold));
+    }
+
+    /// Returns the x coordinate of a share in a comparable form.
+    fn x_of<X: Display>(share: &X) -> (usize, String) {
+        let text = share.to_string();
+        let x = text.split('|').next().unwrap().to_owned();
+        (x.len(), x)
+    }
+}
@@ -0,0 +1,170 @@
+//! JavaScript bindings of the splitting and reconstruction of byte secrets, so that the library can
+//! power browser-based tools that split secrets offline.
+//!
+//! The bindings don't need the `std` feature, and are built for `wasm32-unknown-unknown` without
+//! it: `thread_rng()` relies on `getrandom`, which needs its `js` feature on this target. The
+//! caller instead provides a 32-byte seed from its own source of randomness (e.g.
+//! `crypto.getRandomValues()` in a browser), from which ChaCha20 derives all the randomness of the
+//! split. A seed must never be reused, as it determines the shares.
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --crate-type cdylib \
+//!     --no-default-features --features wasm-bindgen
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/horcrux.wasm
+//! ```
+//!
+//! Secrets and shares are byte arrays. A secret of n bits is split into compact shares in GF(2^n)
+//! (see the `gf2n_dyn` module for the supported sizes, e.g. 16 or 32 bytes), and each share is its
+//! x coordinate followed by the bytes of its y coordinate, i.e. one byte more than the secret.
+//! Several shares are passed as the concatenation of their bytes. The y coordinates have the same
+//! bytes as the hexadecimal representation of the shares, so `x|hex(y)` is the share in text
+//! format, which the command line tool reconstructs with `--bitsize n`.
+
+use crate::gf2n_dyn::{self, DynField, GF2nDyn};
+use crate::shamir::Share;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use rand::{CryptoRng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use wasm_bindgen::prelude::*;
+
+/// Length of the seed of the random number generator, in bytes.
+pub const SEED_LEN: usize = 32;
+
+/// Splits a secret into `nshares` shares, with `threshold` of them being sufficient to reconstruct
+/// it. The randomness is derived from the given 32-byte seed, which must be uniformly random and
+/// never reused. Returns the concatenation of the shares, of one byte more than the secret each.
+#[wasm_bindgen]
+pub fn split(
+    secret: &[u8],
+    threshold: usize,
+    nshares: usize,
+    seed: &[u8],
+) -> Result<Vec<u8>, JsError> {
+    let seed: [u8; SEED_LEN] = seed
+        .try_into()
+        .map_err(|_| JsError::new("the seed must be 32 bytes long"))?;
+    split_with_rng(
+        secret,
+        threshold,
+        nshares,
+        &mut ChaCha20Rng::from_seed(seed),
+    )
+    .map_err(|e| JsError::new(&e))
+}
+
+/// Reconstructs a secret of `secret_len` bytes from the concatenation of at least `threshold`
+/// shares, as returned by `split()`.
+#[wasm_bindgen]
+pub fn reconstruct(shares: &[u8], secret_len: usize, threshold: usize) -> Result<Vec<u8>, JsError> {
+    reconstruct_bytes(shares, secret_len, threshold).map_err(|e| JsError::new(&e))
+}
+
+/// Returns the field of a secret of the given length in bytes.
+fn field_of_len(len: usize) -> Result<DynField, String> {
+    DynField::new(8 * len).ok_or_else(|| {
+        let sizes: Vec<String> = DynField::supported_sizes()
+            .map(|n| (n / 8).to_string())
+            .collect();
+        alloc::format!(
+            "unsupported secret length of {} bytes, expected one of {}",
+            len,
+            sizes.join(", ")
+        )
+    })
+}
+
+fn split_with_rng<R: Rng + CryptoRng + ?Sized>(
+    secret: &[u8],
+    threshold: usize,
+    nshares: usize,
+    rng: &mut R,
+) -> Result<Vec<u8>, String> {
+    let secret = field_of_len(secret.len())?.from_bytes(secret).unwrap();
+    let shares =
+        gf2n_dyn::split_with_rng(&secret, threshold, nshares, rng).map_err(|e| e.to_string())?;
+    let mut bytes = Vec::with_capacity(shares.len() * (1 + secret.field().nbytes()));
+    for share in &shares {
+        bytes.push(*share.x());
+        bytes.extend_from_slice(&share.y().to_bytes());
+    }
+    Ok(bytes)
+}
+
+fn reconstruct_bytes(
+    shares: &[u8],
+    secret_len: usize,
+    threshold: usize,
+) -> Result<Vec<u8>, String> {
+    let field = field_of_len(secret_len)?;
+    if !shares.len().is_multiple_of(1 + secret_len) {
+        return Err(alloc::format!(
+            "the shares must be {} bytes long each",
+            1 + secret_len
+        ));
+    }
+    let shares = shares
+        .chunks(1 + secret_len)
+        .map(|share| Share::new(share[0], field.from_bytes(&share[1..]).unwrap()))
+        .collect::<Result<Vec<Share<u8, GF2nDyn>>, _>>()
+        .map_err(|e| e.to_string())?;
+    let secret = gf2n_dyn::reconstruct(&shares, threshold).map_err(|e| e.to_string())?;
+    Ok(secret.to_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::field::Field;
+    use crate::gf2n::GF256;
+    use crate::shamir::{CompactShamir, Shamir};
+
+    #[test]
+    fn split_reconstruct() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        for len in [1, 2, 4, 8, 16, 20, 24, 28, 32, 64] {
+            let secret: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let shares = split_with_rng(&secret, 3, 5, &mut rng).unwrap();
+            assert_eq!(shares.len(), 5 * (1 + len));
+            // Any 3 shares reconstruct the secret.
+            let subset = &shares[(1 + len)..];
+            assert_eq!(reconstruct_bytes(subset, len, 3).unwrap(), secret);
+            // Fewer shares don't.
+            assert_eq!(
+                reconstruct_bytes(&shares[..2 * (1 + len)], len, 3),
+                Err("fewer shares than the threshold".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn shares_match_compact_shamir() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let secret = GF256::uniform(&mut rng);
+        let shares = CompactShamir::split_with_rng(&secret, 2, 3, &mut rng).unwrap();
+        // Compact shares in GF(2^256) are interchangeable with the shares of a 32-byte secret.
+        let mut bytes = Vec::new();
+        for share in &shares[1..] {
+            bytes.push(*share.x());
+            bytes.extend_from_slice(&share.y().to_bytes());
+        }
+        assert_eq!(reconstruct_bytes(&bytes, 32, 2).unwrap(), secret.to_bytes());
+    }
+
+    #[test]
+    fn rejects_invalid_lengths() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        assert!(split_with_rng(&[0; 3], 2, 3, &mut rng)
+            .unwrap_err()
+            .starts_with("unsupported secret length of 3 bytes"));
+        assert_eq!(
+            reconstruct_bytes(&[0; 50], 16, 2),
+            Err("the shares must be 17 bytes long each".to_string())
+        );
+        assert_eq!(
+            split_with_rng(&[0; 16], 4, 3, &mut rng),
+            Err("invalid threshold or number of shares".to_string())
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Share files generated by each version of horcrux, which the current code must keep reading.
+//!
+//! The files of `corpus/v1` were generated by horcrux 0.1.0, whose plain `x|y` shares are the
+//! formats of era v1 (see `split --era`). The files of `corpus/v2` were generated after 0.1.0,
+//! with the share headers, checksums, compressed x coordinates, records, elliptic curve fields and
+//! manifests of era v2. Each file starts with comments giving the command that generated it and
+//! the secret, and the files must never be regenerated: a change that breaks one of these tests
+//! breaks the shares that custodians already hold.
+
+#![cfg(feature = "parse")]
+
+use horcrux::field::Field;
+use horcrux::gf2n::{GF128, GF16, GF160, GF256, GF32, GF64, GF8};
+use horcrux::gfp::{Ed25519Scalar, Secp256k1Scalar};
+use horcrux::record;
+use horcrux::shamir::{read_lines, CompactShamir, RandomShamir, Shamir, XKey};
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// Threshold of all the splits of the corpus.
+const THRESHOLD: usize = 3;
+/// Number of shares of all the splits of the corpus.
+const NSHARES: usize = 5;
+
+/// Reads a file of the corpus, and returns the value of its `# <name> = <value>` comment and its
+/// other lines.
+fn read(path: &str, name: &str) -> (String, Vec<String>) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "corpus", path]
+        .iter()
+        .collect();
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+    let prefix = format!("# {} = ", name);
+    let value = contents
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix))
+        .unwrap_or_else(|| panic!("{} has no {} comment", path.display(), name))
+        .to_owned();
+    let (lines, truncated) =
+        read_lines(contents.as_bytes(), NSHARES, |line| Ok(line.to_owned())).unwrap();
+    assert!(!truncated);
+    assert_eq!(lines.len(), NSHARES);
+    (value, lines)
+}
+
+/// Removes the header of a share line, checking it against the expected one.
+fn strip_header<'a>(line: &'a str, header: &str) -> &'a str {
+    line.strip_prefix(header)
+        .unwrap_or_else(|| panic!("Share {} doesn't start with {}", line, header))
+}
+
+/// Checks that the shares of a file reconstruct its secret, after removing the given header.
+fn check_shares<F: Field + Display, S: Shamir<F>>(path: &str, header: &str) {
+    let (secret, lines) = read(path, "Secret");
+    let shares: Vec<S::Share> = lines
+        .iter()
+        .map(|line| S::parse_share(strip_header(line, header)).unwrap())
+        .collect();
+    assert_eq!(
+        S::reconstruct(&shares, THRESHOLD).unwrap().to_string(),
+        secret
+    );
+    // Any subset of the threshold reconstructs the secret as well.
+    let subset = &shares[NSHARES - THRESHOLD..];
+    assert_eq!(
+        S::reconstruct(subset, THRESHOLD).unwrap().to_string(),
+        secret
+    );
+}
+
+/// Checks that the records of a file reconstruct its secret.
+fn check_records<F: Field + Display, S: Shamir<F>>(path: &str)
+where
+    S::Share: Display,
+{
+    let (secret, lines) = read(path, "Secret");
+    let records: Vec<_> = lines
+        .iter()
+        .map(|line| record::parse_record::<F, S>(line).unwrap())
+        .collect();
+    let (elements, len) = record::reconstruct::<F, S>(&records, THRESHOLD).unwrap();
+    let bytes: Vec<u8> = elements
+        .expose_secret()
+        .iter()
+        .flat_map(|element| element.to_bytes())
+        .take(len)
+        .collect();
+    assert_eq!(hex::encode(bytes), secret);
+}
+
+#[test]
+fn v1_compact_shares() {
+    check_shares::<GF8, CompactShamir>("v1/compact-gf8.txt", "");
+    check_shares::<GF16, CompactShamir>("v1/compact-gf16.txt", "");
+    check_shares::<GF32, CompactShamir>("v1/compact-gf32.txt", "");
+    check_shares::<GF64, CompactShamir>("v1/compact-gf64.txt", "");
+    check_shares::<GF128, CompactShamir>("v1/compact-gf128.txt", "");
+    check_shares::<GF256, CompactShamir>("v1/compact-gf256.txt", "");
+}
+
+#[test]
+fn v1_random_shares() {
+    check_shares::<GF8, RandomShamir>("v1/random-gf8.txt", "");
+    check_shares::<GF16, RandomShamir>("v1/random-gf16.txt", "");
+    check_shares::<GF32, RandomShamir>("v1/random-gf32.txt", "");
+    check_shares::<GF64, RandomShamir>("v1/random-gf64.txt", "");
+    check_shares::<GF128, RandomShamir>("v1/random-gf128.txt", "");
+    check_shares::<GF256, RandomShamir>("v1/random-gf256.txt", "");
+}
+
+#[test]
+fn v2_headers_and_checksums() {
+    check_shares::<GF256, CompactShamir>(
+        "v2/compact-gf256-header.txt",
+        "horcrux1:compact:gf256:3:corpus:",
+    );
+    check_shares::<GF64, CompactShamir>(
+        "v2/compact-gf64-header-checksum.txt",
+        "horcrux1:compact:gf64:3:corpus:",
+    );
+    check_shares::<GF128, RandomShamir>("v2/random-gf128-checksum.txt", "");
+}
+
+#[test]
+fn v2_fields() {
+    check_shares::<GF160, CompactShamir>("v2/compact-gf160.txt", "");
+    check_shares::<Ed25519Scalar, CompactShamir>("v2/compact-ed25519.txt", "");
+    check_shares::<Secp256k1Scalar, RandomShamir>("v2/random-secp256k1.txt", "");
+}
+
+#[test]
+fn v2_compressed_shares() {
+    let path = "v2/random-gf128-compressed.txt";
+    let (secret, lines) = read(path, "Secret");
+    let key = XKey::parse(&read(path, "X key").0).unwrap();
+    let shares: Vec<_> = lines
+        .iter()
+        .map(|line| RandomShamir::parse_compressed_share::<GF128>(line, &key).unwrap())
+        .collect();
+    assert_eq!(
+        RandomShamir::reconstruct(&shares, THRESHOLD)
+            .unwrap()
+            .to_string(),
+        secret
+    );
+}
+
+#[test]
+fn v2_records() {
+    check_records::<GF128, CompactShamir>("v2/records-gf128.txt");
+}
+
+#[cfg(feature = "blake3")]
+#[test]
+fn v2_records_blake3() {
+    check_records::<GF128, CompactShamir>("v2/records-gf128-blake3.txt");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn v2_manifest() {
+    use horcrux::schema::{FieldKind, Scheme, ShareLine, SplitManifest};
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "tests",
+        "corpus",
+        "v2",
+        "manifest-compact-gf256.json",
+    ]
+    .iter()
+    .collect();
+    let manifest: SplitManifest =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(manifest.version, 1);
+    assert_eq!(manifest.scheme, Scheme::Compact);
+    assert_eq!(manifest.field, FieldKind::Binary);
+    assert_eq!(manifest.bitsize, 256);
+    assert_eq!(manifest.threshold, Some(THRESHOLD));
+    assert_eq!(manifest.nshares, Some(NSHARES));
+    assert_eq!(manifest.set_id.as_deref(), Some("corpus"));
+
+    let shares: Vec<_> = manifest
+        .shares
+        .iter()
+        .map(|line| {
+            let line: ShareLine = line.parse().unwrap();
+            assert_eq!(line.header.unwrap().set_id, "corpus");
+            CompactShamir::parse_share(&line.share).unwrap()
+        })
+        .collect();
+    let secret: GF256 = CompactShamir::reconstruct(&shares, THRESHOLD).unwrap();
+    assert_eq!(Some(secret.to_string()), manifest.secret);
+}
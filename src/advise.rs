@@ -0,0 +1,155 @@
+//! Recommendation of the Shamir parameters (threshold and number of shares) for given
+//! availability and secrecy requirements.
+//!
+//! With n custodians each holding one share and a threshold k, the secret remains available as long
+//! as at most n - k shares are lost, and remains secret as long as at most k - 1 custodians collude
+//! (or have their share compromised). Tolerating the loss of L shares and the compromise of C
+//! custodians therefore requires C + 1 <= k <= n - L, which is only possible if n >= L + C + 1.
+
+/// Requirements, as given on the command line.
+pub struct Requirements {
+    pub custodians: usize,
+    pub tolerate_loss: usize,
+    pub tolerate_compromise: usize,
+}
+
+impl Requirements {
+    /// Explains the suitable thresholds and recommends one, or explains why the requirements can't
+    /// be met.
+    pub fn advise(&self) -> Result<String, String> {
+        let n = self.custodians;
+        let loss = self.tolerate_loss;
+        let compromise = self.tolerate_compromise;
+
+        if n == 0 || n > 255 {
+            return Err("The number of custodians must be between 1 and 255".to_owned());
+        }
+        let min_k = compromise
+            .checked_add(1)
+            .ok_or("The number of tolerated compromises is out of range")?;
+        let max_k = n.saturating_sub(loss);
+        if min_k > max_k {
+            let needed = loss
+                .checked_add(min_k)
+                .ok_or("The number of tolerated losses is out of range")?;
+            let mut explanation = format!(
+                "Tolerating the compromise of {compromise} custodians requires a threshold of at least {min_k}, but tolerating the loss of {loss} shares requires a threshold of at most {n} - {loss} = {}.\n",
+                n as i128 - loss as i128
+            );
+            if needed <= 255 {
+                explanation += &format!(
+                    "These requirements need at least {loss} + {compromise} + 1 = {needed} custodians: add custodians, or relax one of the requirements."
+                );
+            } else {
+                explanation += &format!(
+                    "These requirements need at least {needed} custodians, which is more than the maximum of 255 shares: relax one of the requirements."
+                );
+            }
+            return Err(explanation);
+        }
+
+        // Split the slack evenly between both requirements, favoring secrecy.
+        let recommended = min_k + (max_k - min_k).div_ceil(2);
+
+        let mut advice = format!(
+            "With {n} custodians, tolerating the loss of {loss} shares requires a threshold of at most {max_k}, and tolerating the compromise of {compromise} custodians requires a threshold of at least {min_k}.\n\nSuitable thresholds:\n"
+        );
+        for k in min_k..=max_k {
+            advice += &format!(
+                "  --threshold {k}: tolerates the loss of up to {} shares and the compromise of up to {} custodians{}\n",
+                n - k,
+                k - 1,
+                if k == recommended { " (recommended)" } else { "" }
+            );
+        }
+        advice += &format!("\nRecommended parameters: --nshares {n} --threshold {recommended}\n");
+        Ok(advice)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Requirements;
+
+    fn advise(custodians: usize, loss: usize, compromise: usize) -> Result<String, String> {
+        Requirements {
+            custodians,
+            tolerate_loss: loss,
+            tolerate_compromise: compromise,
+        }
+        .advise()
+    }
+
+    fn recommended(advice: &str) -> &str {
+        advice.lines().last().unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_custodians() {
+        assert!(advise(0, 0, 0).is_err());
+        assert!(advise(256, 0, 0).is_err());
+        assert!(advise(1, 0, 0).is_ok());
+        assert!(advise(255, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn can_advise_at_boundaries() {
+        let advice = advise(1, 0, 0).unwrap();
+        assert_eq!(
+            recommended(&advice),
+            "Recommended parameters: --nshares 1 --threshold 1"
+        );
+
+        // Exactly enough custodians: a single suitable threshold.
+        let advice = advise(6, 2, 3).unwrap();
+        assert_eq!(advice.matches("--threshold").count(), 2);
+        assert_eq!(
+            recommended(&advice),
+            "Recommended parameters: --nshares 6 --threshold 4"
+        );
+
+        let advice = advise(255, 254, 0).unwrap();
+        assert!(advice.contains("--threshold 1: tolerates the loss of up to 254 shares"));
+        let advice = advise(255, 0, 254).unwrap();
+        assert!(advice.contains("--threshold 255: tolerates the loss of up to 0 shares"));
+    }
+
+    #[test]
+    fn can_recommend_middle_threshold() {
+        let advice = advise(10, 2, 2).unwrap();
+        for k in 3..=8 {
+            assert!(advice.contains(&format!("--threshold {k}:")));
+        }
+        assert!(!advice.contains("--threshold 2:"));
+        assert!(!advice.contains("--threshold 9:"));
+        assert!(advice.contains("--threshold 6: tolerates the loss of up to 4 shares and the compromise of up to 5 custodians (recommended)"));
+
+        let advice = advise(5, 0, 0).unwrap();
+        assert_eq!(
+            recommended(&advice),
+            "Recommended parameters: --nshares 5 --threshold 3"
+        );
+    }
+
+    #[test]
+    fn explains_impossible_requirements() {
+        // One custodian short.
+        let explanation = advise(5, 2, 3).unwrap_err();
+        assert!(explanation.contains("at least 2 + 3 + 1 = 6 custodians"));
+
+        let explanation = advise(3, 5, 0).unwrap_err();
+        assert!(explanation.contains("at most 3 - 5 = -2"));
+
+        let explanation = advise(255, 255, 0).unwrap_err();
+        assert!(explanation.contains("at least 256 custodians, which is more than the maximum"));
+    }
+
+    #[test]
+    fn rejects_overflowing_requirements() {
+        assert!(advise(10, 0, usize::MAX).is_err());
+        assert!(advise(10, usize::MAX, 0).is_err());
+        assert!(advise(10, usize::MAX - 1, 1).is_err());
+        let explanation = advise(10, usize::MAX, 0).unwrap_err();
+        assert!(explanation.contains("out of range"));
+    }
+}
@@ -0,0 +1,255 @@
+//! Experimental audio transfer of shares, for air-gapped machines without USB, network or camera.
+//!
+//! Shares are modulated with Bell 202 style FSK (1200 baud, mark at 1200 Hz, space at 2200 Hz), and
+//! each byte is framed like on a serial line (one start bit, 8 data bits LSB first, one stop bit).
+//! A transmission consists of a mark preamble followed by a frame: a magic prefix, the length of
+//! the share in text format, the share and a CRC-16 of the share. The signal is stored as a mono
+//! 16-bit PCM WAV file, which can be played on one machine and recorded on the other, e.g. with
+//! `aplay` and `arecord -f S16_LE -r 48000`.
+
+use std::convert::TryInto;
+use std::f64::consts::PI;
+
+const SAMPLE_RATE: u32 = 48000;
+const BAUD: f64 = 1200.0;
+const MARK_HZ: f64 = 1200.0;
+const SPACE_HZ: f64 = 2200.0;
+const AMPLITUDE: f64 = 16384.0;
+
+/// Durations of the silence around the transmission, of the mark preamble and of the mark
+/// postamble, in seconds.
+const SILENCE: f64 = 0.1;
+const PREAMBLE: f64 = 0.2;
+const POSTAMBLE: f64 = 0.05;
+
+const MAGIC: &[u8] = b"HX";
+
+/// Modulates a share in text format into a WAV file. Returns `None` if the share is too large for
+/// an audio frame.
+pub fn encode(share: &str) -> Option<Vec<u8>> {
+    let share = share.as_bytes();
+    let mut frame = MAGIC.to_vec();
+    frame.push(share.len().try_into().ok()?);
+    frame.extend_from_slice(share);
+    frame.extend_from_slice(&crc16(share).to_be_bytes());
+
+    let mut bits = Vec::new();
+    for byte in frame {
+        bits.push(false);
+        bits.extend((0..8).map(|i| (byte >> i) & 1 != 0));
+        bits.push(true);
+    }
+
+    let rate = SAMPLE_RATE as f64;
+    let mut samples = vec![0i16; (SILENCE * rate) as usize];
+    let mut phase = 0.0f64;
+    let mut tone = |samples: &mut Vec<i16>, freq: f64, count: usize| {
+        for _ in 0..count {
+            samples.push((AMPLITUDE * phase.sin()) as i16);
+            phase = (phase + 2.0 * PI * freq / rate) % (2.0 * PI);
+        }
+    };
+    tone(&mut samples, MARK_HZ, (PREAMBLE * rate) as usize);
+    let samples_per_bit = rate / BAUD;
+    for (i, &bit) in bits.iter().enumerate() {
+        // Round the bit boundaries to avoid accumulating a drift.
+        let count = ((i + 1) as f64 * samples_per_bit).round() as usize
+            - (i as f64 * samples_per_bit).round() as usize;
+        tone(&mut samples, if bit { MARK_HZ } else { SPACE_HZ }, count);
+    }
+    tone(&mut samples, MARK_HZ, (POSTAMBLE * rate) as usize);
+    samples.resize(samples.len() + (SILENCE * rate) as usize, 0);
+
+    Some(write_wav(&samples))
+}
+
+/// Demodulates a share in text format from a WAV file. Returns `None` if the file isn't a supported
+/// WAV file or if no valid frame was found.
+pub fn decode(wav: &[u8]) -> Option<String> {
+    let (rate, samples) = read_wav(wav)?;
+    let bytes = demodulate(rate as f64, &samples);
+
+    // Look for the first frame with a valid CRC.
+    (0..bytes.len()).find_map(|start| {
+        let frame = bytes[start..].strip_prefix(MAGIC)?;
+        let (&len, rest) = frame.split_first()?;
+        let (share, rest) = rest.split_at_checked(len as usize)?;
+        let crc = rest.get(..2)?;
+        if crc != crc16(share).to_be_bytes() {
+            return None;
+        }
+        String::from_utf8(share.to_vec()).ok()
+    })
+}
+
+/// Decodes the bytes of an FSK signal.
+fn demodulate(rate: f64, samples: &[f64]) -> Vec<u8> {
+    let samples_per_bit = rate / BAUD;
+    let window = samples_per_bit.round() as usize;
+    if samples.len() < 10 * window {
+        return Vec::new();
+    }
+    let detector = Detector::new(rate, samples, window);
+    let bit_at =
+        |i: usize, j: usize| detector.bit((i as f64 + j as f64 * samples_per_bit) as usize);
+
+    let mut bytes = Vec::new();
+    let last = samples.len() - ((10.0 * samples_per_bit).ceil() as usize + window);
+    let mut i = 0;
+    while i <= last {
+        // Windows starting half a bit before the start bit are mostly filled with it already.
+        if detector.bit(i) != Some(false) {
+            i += 1;
+            continue;
+        }
+        let start = i + window / 2;
+        if bit_at(start, 0) != Some(false) || bit_at(start, 9) != Some(true) {
+            i += 1;
+            continue;
+        }
+        let bits: Option<Vec<bool>> = (1..9).map(|j| bit_at(start, j)).collect();
+        match bits {
+            Some(bits) => {
+                let byte = bits
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (j, &bit)| byte | ((bit as u8) << j));
+                bytes.push(byte);
+                // Resume in the middle of the stop bit.
+                i = start + (9.5 * samples_per_bit) as usize;
+            }
+            None => i += 1,
+        }
+    }
+    bytes
+}
+
+/// Measures the energy of the mark and space frequencies over sliding windows of one bit, using
+/// prefix sums of the signal multiplied by the reference tones.
+struct Detector {
+    window: usize,
+    /// Prefix sums of s*cos(mark), s*sin(mark), s*cos(space), s*sin(space) and s^2.
+    sums: Vec<[f64; 5]>,
+}
+
+impl Detector {
+    fn new(rate: f64, samples: &[f64], window: usize) -> Self {
+        let mut sums = Vec::with_capacity(samples.len() + 1);
+        let mut acc = [0.0; 5];
+        sums.push(acc);
+        for (n, &s) in samples.iter().enumerate() {
+            let mark = 2.0 * PI * MARK_HZ * n as f64 / rate;
+            let space = 2.0 * PI * SPACE_HZ * n as f64 / rate;
+            acc[0] += s * mark.cos();
+            acc[1] += s * mark.sin();
+            acc[2] += s * space.cos();
+            acc[3] += s * space.sin();
+            acc[4] += s * s;
+            sums.push(acc);
+        }
+        Self { window, sums }
+    }
+
+    /// Returns the bit carried by the window starting at the given sample (mark = 1, space = 0),
+    /// or `None` if the window doesn't contain a clear tone.
+    fn bit(&self, i: usize) -> Option<bool> {
+        let (a, b) = (self.sums.get(i)?, self.sums.get(i + self.window)?);
+        let d: Vec<f64> = (0..5).map(|t| b[t] - a[t]).collect();
+        let mark = d[0] * d[0] + d[1] * d[1];
+        let space = d[2] * d[2] + d[3] * d[3];
+        let energy = d[4];
+
+        // For a pure tone, the energy at its frequency is window/2 times the signal energy.
+        let threshold = 0.25 * self.window as f64 * energy;
+        if energy < self.window as f64 * 1e-4 || mark.max(space) < threshold {
+            return None;
+        }
+        Some(mark > space)
+    }
+}
+
+/// CRC-16/CCITT-FALSE.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn write_wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = 2 * samples.len() as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(2 * SAMPLE_RATE).to_le_bytes()); // Byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // Block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        wav.extend_from_slice(&s.to_le_bytes());
+    }
+    wav
+}
+
+/// Parses a PCM WAV file with 8 or 16 bits per sample, returning the sample rate and the samples of
+/// the first channel, normalized to [-1, 1].
+fn read_wav(wav: &[u8]) -> Option<(u32, Vec<f64>)> {
+    if wav.get(..4)? != b"RIFF" || wav.get(8..12)? != b"WAVE" {
+        return None;
+    }
+
+    let mut format = None;
+    let mut chunks = &wav[12..];
+    while chunks.len() >= 8 {
+        let id = &chunks[..4];
+        let len = u32::from_le_bytes(chunks[4..8].try_into().unwrap()) as usize;
+        let body = chunks.get(8..)?;
+        let body = body.get(..len).unwrap_or(body);
+        match id {
+            b"fmt " => {
+                let field = |i: usize| u16::from_le_bytes(body[i..i + 2].try_into().unwrap());
+                if body.len() < 16 || field(0) != 1 || field(2) == 0 {
+                    return None;
+                }
+                let rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                if rate == 0 {
+                    return None;
+                }
+                format = Some((field(2) as usize, rate, field(14)));
+            }
+            b"data" => {
+                let (channels, rate, bits) = format?;
+                let samples = match bits {
+                    8 => body
+                        .chunks_exact(channels)
+                        .map(|s| (s[0] as f64 - 128.0) / 128.0)
+                        .collect(),
+                    16 => body
+                        .chunks_exact(2 * channels)
+                        .map(|s| i16::from_le_bytes([s[0], s[1]]) as f64 / 32768.0)
+                        .collect(),
+                    _ => return None,
+                };
+                return Some((rate, samples));
+            }
+            _ => (),
+        }
+        // Chunks are padded to an even length.
+        chunks = chunks.get(8 + len + (len & 1)..)?;
+    }
+    None
+}
@@ -0,0 +1,72 @@
+//! Generation of key ceremony checklists, tailored to the parameters of a split.
+
+/// Parameters of the ceremony, as given on the command line.
+pub struct Ceremony<'a> {
+    pub shamir_type: &'a str,
+    pub bitsize: &'a str,
+    pub nshares: usize,
+    pub threshold: usize,
+    pub witnesses: Option<usize>,
+}
+
+impl Ceremony<'_> {
+    fn flags(&self) -> String {
+        format!(
+            "--type {} --bitsize {} --nshares {} --threshold {}",
+            self.shamir_type, self.bitsize, self.nshares, self.threshold
+        )
+    }
+
+    /// Generates an ordered runbook for the ceremony, in Markdown format.
+    pub fn checklist(&self) -> String {
+        let flags = self.flags();
+        let n = self.nshares;
+        let k = self.threshold;
+
+        let mut steps: Vec<String> = vec![
+            "Prepare an offline machine with a freshly built `horcrux` binary, and make sure that no network interface is enabled.".to_owned(),
+            format!("Gather the {n} custodians, and make sure that each of them has a way to store their share (paper, metal plate, etc.)."),
+            format!("Write the secret in hexadecimal ({} bits) to a file named `secret.txt` on the offline machine.", self.bitsize),
+        ];
+
+        let mut split = format!("horcrux {flags} split --secret secret.txt");
+        if let Some(w) = self.witnesses {
+            split += &format!(" --witnesses {w}");
+        }
+        steps.push(format!(
+            "Split the secret:\n\n   ```\n   $ {split} > shares.txt\n   ```"
+        ));
+        steps.push(format!(
+            "Hand over exactly one share line to each of the {n} custodians, and have each custodian copy it back for comparison."
+        ));
+        if let Some(w) = self.witnesses {
+            steps.push(format!(
+                "Hand over one `witness:` line to each of the {w} auditors. Witness shares must never be given to custodians."
+            ));
+        }
+        steps.push(format!(
+            "Verify the split: pick {k} shares at random, write them to `check.txt` and run:\n\n   ```\n   $ horcrux {flags} reconstruct --shares check.txt\n   ```\n\n   Check that the output matches the original secret."
+        ));
+        steps.push(format!(
+            "Remind the custodians that any {k} of them can reconstruct the secret: up to {} shares can be lost, and up to {} custodians can collude without learning anything.",
+            n - k,
+            k - 1
+        ));
+        steps.push(
+            "Destroy `secret.txt`, `shares.txt` and `check.txt` (overwrite them, then wipe or destroy the storage medium), and power off the offline machine.".to_owned(),
+        );
+        steps.push(
+            "Record the date, the participants and the parameters above in the ceremony log."
+                .to_owned(),
+        );
+
+        let mut checklist = format!(
+            "# Key ceremony checklist ({k}-of-{n}, {} shares, {} bits)\n\n",
+            self.shamir_type, self.bitsize
+        );
+        for (i, step) in steps.iter().enumerate() {
+            checklist += &format!("{}. [ ] {}\n", i + 1, step);
+        }
+        checklist
+    }
+}
@@ -0,0 +1,45 @@
+//! Short verification codes of shares, to catch transcription errors when shares are handed over
+//! to their custodians.
+//!
+//! When a custodian has recorded their share, the operator reads the code of the share aloud, and
+//! the custodian checks it against their copy (with `info --expect-code`). The code only depends on
+//! the share itself (not on its header or checksum), so it can be checked at any time later.
+
+use horcrux::hashing::HashAlgorithm;
+use std::convert::TryInto;
+
+/// Key of the hash, separating verification codes from the other hashes of shares.
+const KEY: &str = "horcrux-verification-code-v1";
+
+/// Returns the 6-digit verification code of a share in canonical text format.
+pub fn verification_code(hash: HashAlgorithm, share: &str) -> String {
+    let digest = hash
+        .hasher()
+        .chain_update(KEY)
+        .chain_update(share)
+        .finalize();
+    let value = u64::from_be_bytes(digest[..8].try_into().unwrap());
+    format!("{:06}", value % 1_000_000)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn codes_have_six_digits() {
+        for share in ["", "1|00", "2|ffff", "3|0123456789abcdef"] {
+            let code = verification_code(HashAlgorithm::default(), share);
+            assert_eq!(code.len(), 6);
+            assert!(code.bytes().all(|b| b.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn codes_are_deterministic() {
+        let code = verification_code(HashAlgorithm::default(), "1|abcd");
+        assert_eq!(verification_code(HashAlgorithm::default(), "1|abcd"), code);
+        assert_ne!(verification_code(HashAlgorithm::default(), "2|abcd"), code);
+        assert_ne!(verification_code(HashAlgorithm::Blake3, "1|abcd"), code);
+    }
+}
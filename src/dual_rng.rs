@@ -0,0 +1,112 @@
+//! Dual-control randomness: the secret and the polynomial are sampled from the XOR of the OS RNG
+//! and of a second, independent source (e.g. a hardware RNG device or a file of dice rolls), so
+//! that a single backdoored source can't choose them. The bytes contributed by each source are
+//! committed to with a hash (SHA-256 by default), so that the contributions can later be audited.
+
+use crate::exit::OrExit;
+use horcrux::hashing::{HashAlgorithm, Hasher};
+use horcrux::rng::XorRng;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, Error, RngCore};
+use std::fs::File;
+use std::io::Read;
+
+/// RNG that hashes all its output, as a commitment to the randomness it contributed.
+pub struct Committed<R> {
+    rng: R,
+    hasher: Hasher,
+}
+
+impl<R> Committed<R> {
+    fn new(rng: R, hash: HashAlgorithm) -> Self {
+        Committed {
+            rng,
+            hasher: hash.hasher(),
+        }
+    }
+
+    /// Returns the hash of the output so far, in hexadecimal.
+    pub fn commitment(&self) -> String {
+        hex::encode(self.hasher.clone().finalize())
+    }
+}
+
+impl<R: RngCore> RngCore for Committed<R> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).unwrap()
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)?;
+        self.hasher.update(&*dest);
+        Ok(())
+    }
+}
+
+impl<R: CryptoRng> CryptoRng for Committed<R> {}
+
+/// Random source reading bytes from a file or device, which must contain enough of them.
+pub struct FileRng {
+    filename: String,
+    file: File,
+}
+
+impl RngCore for FileRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.file.read_exact(dest).or_exit(format_args!(
+            "Could not read enough random bytes from {}",
+            self.filename
+        ))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The second source is trusted to be a CSPRNG, but the combination remains secure as long as one
+/// of the sources is.
+impl CryptoRng for FileRng {}
+
+pub type DualRng = XorRng<Committed<OsRng>, Committed<FileRng>>;
+
+/// Combines the OS RNG with the random bytes of the given file or device, committing to their
+/// contributions with the given hash algorithm.
+pub fn open(filename: &str, hash: HashAlgorithm) -> DualRng {
+    let file = File::open(filename).or_exit(format_args!("Could not open {}", filename));
+    XorRng::new(
+        Committed::new(OsRng, hash),
+        Committed::new(
+            FileRng {
+                filename: filename.to_owned(),
+                file,
+            },
+            hash,
+        ),
+    )
+}
@@ -0,0 +1,140 @@
+//! Health tests of the random number generators used to split secrets.
+//!
+//! These are lightweight versions of the continuous health tests of NIST SP 800-90B (repetition
+//! count and adaptive proportion, assuming full entropy per byte), together with the monobit, runs
+//! and long run tests of FIPS 140-2. They can't prove that a source is good, but they catch
+//! grossly broken sources (stuck outputs, heavily biased bits), e.g. on exotic or embedded hardware.
+//!
+//! On a good source, the SP 800-90B tests fail with a probability of about 2^-20, and the FIPS
+//! 140-2 tests with a probability of about 10^-4, in which case the tests can simply be re-run.
+
+use rand::rngs::OsRng;
+use rand::{thread_rng, RngCore};
+
+/// Number of bytes sampled from each source (20000 bits, as in FIPS 140-2).
+const SAMPLE_BYTES: usize = 2500;
+
+/// Cutoff of the repetition count test, for a claimed entropy of 8 bits per byte and a false
+/// positive probability of 2^-20: `1 + ceil(20 / 8)`.
+const REPETITION_CUTOFF: usize = 4;
+/// Window size of the adaptive proportion test, for non-binary samples.
+const ADAPTIVE_WINDOW: usize = 512;
+/// Cutoff of the adaptive proportion test, for a claimed entropy of 8 bits per byte and a false
+/// positive probability of 2^-20.
+const ADAPTIVE_CUTOFF: usize = 13;
+
+/// Result of the health tests on one source.
+pub struct Report {
+    pub source: &'static str,
+    pub results: Vec<(&'static str, bool)>,
+}
+
+impl Report {
+    /// Returns whether the source passed all the tests.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|&(_, ok)| ok)
+    }
+}
+
+/// Samples all the random sources used by horcrux, and runs the health tests on them.
+pub fn test_sources() -> Vec<Report> {
+    vec![
+        test_source("OsRng", &mut OsRng),
+        test_source("thread_rng", &mut thread_rng()),
+    ]
+}
+
+fn test_source<R: RngCore>(source: &'static str, rng: &mut R) -> Report {
+    let mut sample = [0u8; SAMPLE_BYTES];
+    rng.fill_bytes(&mut sample);
+    Report {
+        source,
+        results: vec![
+            ("repetition count", repetition_count(&sample)),
+            ("adaptive proportion", adaptive_proportion(&sample)),
+            ("monobit", monobit(&sample)),
+            ("runs", runs(&sample)),
+            ("long run", long_run(&sample)),
+        ],
+    }
+}
+
+/// SP 800-90B, section 4.4.1: no byte value is repeated too many times in a row.
+fn repetition_count(sample: &[u8]) -> bool {
+    let mut count = 1;
+    for pair in sample.windows(2) {
+        if pair[0] == pair[1] {
+            count += 1;
+            if count >= REPETITION_CUTOFF {
+                return false;
+            }
+        } else {
+            count = 1;
+        }
+    }
+    true
+}
+
+/// SP 800-90B, section 4.4.2: the first byte of each window doesn't occur too often in the window.
+fn adaptive_proportion(sample: &[u8]) -> bool {
+    sample.chunks(ADAPTIVE_WINDOW).all(|window| {
+        let count = window.iter().filter(|&&b| b == window[0]).count();
+        count < ADAPTIVE_CUTOFF
+    })
+}
+
+/// FIPS 140-2: the number of ones among the 20000 bits is in ]9725, 10275[.
+fn monobit(sample: &[u8]) -> bool {
+    let ones: u32 = sample.iter().map(|b| b.count_ones()).sum();
+    ones > 9725 && ones < 10275
+}
+
+/// Returns the lengths of the runs of identical bits.
+fn run_lengths(sample: &[u8]) -> Vec<(bool, usize)> {
+    let mut runs = Vec::new();
+    let mut current: Option<(bool, usize)> = None;
+    for byte in sample {
+        for i in 0..8 {
+            let bit = (byte >> i) & 1 != 0;
+            current = match current {
+                Some((b, len)) if b == bit => Some((b, len + 1)),
+                Some(run) => {
+                    runs.push(run);
+                    Some((bit, 1))
+                }
+                None => Some((bit, 1)),
+            };
+        }
+    }
+    runs.extend(current);
+    runs
+}
+
+/// FIPS 140-2: the number of runs of each length (1 to 6+) of zeros and of ones is within the
+/// expected intervals.
+fn runs(sample: &[u8]) -> bool {
+    const INTERVALS: [(usize, usize); 6] = [
+        (2315, 2685),
+        (1114, 1386),
+        (527, 723),
+        (240, 384),
+        (103, 209),
+        (103, 209),
+    ];
+
+    let mut counts = [[0usize; 6]; 2];
+    for (bit, len) in run_lengths(sample) {
+        counts[bit as usize][len.min(6) - 1] += 1;
+    }
+    counts.iter().all(|counts| {
+        counts
+            .iter()
+            .zip(INTERVALS.iter())
+            .all(|(&count, &(min, max))| count >= min && count <= max)
+    })
+}
+
+/// FIPS 140-2: there is no run of 26 or more identical bits.
+fn long_run(sample: &[u8]) -> bool {
+    run_lengths(sample).iter().all(|&(_, len)| len < 26)
+}
@@ -0,0 +1,75 @@
+//! Generations of the share formats, so that a split can be restricted to shares that the existing
+//! tooling of the custodians can read.
+//!
+//! - Era v1 is the format of horcrux 0.1.0: one `x|y` share per line, compact or at a random x
+//!   coordinate, in GF(2^n) for n = 8, 16, 32, 64, 128 or 256.
+//! - Era v2 adds the share headers and checksums, compressed x coordinates, records of secrets
+//!   larger than a field element, decoy envelopes, shares of groups and of access policies, shares
+//!   sealed to recipients, the elliptic curve fields and GF(2^160), GF(2^192) and GF(2^224).
+//!
+//! Shares of all the eras remain readable, as checked by the corpus of `horcrux/tests/corpus`.
+
+use clap::ArgMatches;
+
+/// Bit sizes of the binary fields supported by horcrux 0.1.0.
+const V1_BITSIZES: [&str; 6] = ["8", "16", "32", "64", "128", "256"];
+/// Options of split whose shares have no equivalent in era v1.
+const V2_OPTIONS: [&str; 7] = [
+    "header",
+    "checksum",
+    "compress-x",
+    "decoy",
+    "groups",
+    "access",
+    "recipients",
+];
+
+/// Generation of the share formats emitted by a split.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Era {
+    V1,
+    V2,
+}
+
+impl Era {
+    /// Returns the era given by the --era argument of split.
+    pub fn new(args: &ArgMatches) -> Self {
+        match args.value_of("era").unwrap() {
+            "v1" => Era::V1,
+            _ => Era::V2,
+        }
+    }
+
+    /// Checks that a split with the given parameters only emits shares of this era.
+    pub fn check_split(self, matches: &ArgMatches, args: &ArgMatches) -> Result<(), String> {
+        if self == Era::V2 {
+            return Ok(());
+        }
+        let field = matches.value_of("field").unwrap();
+        let bitsize = matches.value_of("bitsize").unwrap();
+        if field != "binary" || !V1_BITSIZES.contains(&bitsize) {
+            return Err(format!(
+                "--era v1 only supports binary fields of {} bits",
+                V1_BITSIZES.join(", ")
+            ));
+        }
+        match V2_OPTIONS.iter().find(|option| args.is_present(option)) {
+            Some(option) => Err(format!(
+                "--{} emits shares that horcrux 0.1.0 can't read, and can't be used with --era v1",
+                option
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that a secret larger than a field element can be split into records in this era.
+    pub fn check_records(self) -> Result<(), String> {
+        match self {
+            Era::V1 => Err(
+                "--era v1 doesn't support secrets larger than a field element, which are split into records"
+                    .to_owned(),
+            ),
+            Era::V2 => Ok(()),
+        }
+    }
+}
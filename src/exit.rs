@@ -0,0 +1,106 @@
+//! Exit codes of the command line tool, so that scripts can tell failures apart without parsing
+//! error messages.
+//!
+//! Errors of the library exit with their stable code (see `ShamirError::code()`), e.g. 30 for an
+//! invalid threshold or 41 for inconsistent shares, and I/O errors exit with 60. Errors of the
+//! command line tool itself exit with the code of their category (see `ErrorCategory::code()`),
+//! e.g. 10 for invalid arguments, 20 for malformed input files or 50 for a wrong signature or
+//! passphrase. Panics, which exit with 101, are left to bugs.
+//!
+//! Failing commands don't exit on the spot: they unwind to `main()` like a panic, so that the
+//! secrets held on the way are still wiped when they're dropped.
+
+use horcrux::shamir::{ErrorCategory, ReadError, ShamirError};
+use std::fmt::Display;
+use std::panic::{self, UnwindSafe};
+
+/// Failure of a command, unwound to `main()` to exit with its code.
+struct Failure(u8);
+
+/// Errors that have an exit code.
+pub trait ExitCode: Display {
+    /// Returns the exit code of the error.
+    fn exit_code(&self) -> u8;
+}
+
+impl ExitCode for ShamirError {
+    fn exit_code(&self) -> u8 {
+        self.code()
+    }
+}
+
+impl ExitCode for ReadError {
+    fn exit_code(&self) -> u8 {
+        self.code()
+    }
+}
+
+impl ExitCode for std::io::Error {
+    fn exit_code(&self) -> u8 {
+        ErrorCategory::Io.code()
+    }
+}
+
+/// Unwraps results, failing the command with the exit code of the error otherwise.
+pub trait OrExit<T> {
+    /// Returns the value, or prints the context and the error and fails with the exit code of
+    /// the error.
+    fn or_exit(self, context: impl Display) -> T;
+}
+
+impl<T, E: ExitCode> OrExit<T> for Result<T, E> {
+    fn or_exit(self, context: impl Display) -> T {
+        self.unwrap_or_else(|e| {
+            eprintln!("{}: {}", context, e);
+            fail(&e)
+        })
+    }
+}
+
+/// Unwraps results whose errors are plain messages, failing the command with the exit code of a
+/// category otherwise.
+pub trait OrFail<T> {
+    /// Returns the value, or prints the error and fails with the exit code of the category.
+    fn or_fail(self, category: ErrorCategory) -> T;
+}
+
+impl<T, E: Display> OrFail<T> for Result<T, E> {
+    fn or_fail(self, category: ErrorCategory) -> T {
+        self.unwrap_or_else(|e| fail_with(category, e))
+    }
+}
+
+/// Fails the command with the exit code of the error, whose message was already printed.
+pub fn fail(error: &impl ExitCode) -> ! {
+    exit(error.exit_code())
+}
+
+/// Prints the message, and fails the command with the exit code of the given category.
+pub fn fail_with(category: ErrorCategory, message: impl Display) -> ! {
+    eprintln!("{}", message);
+    exit(category.code())
+}
+
+/// Fails the command with the exit code of the given category unless the condition holds, after
+/// printing the message.
+pub fn ensure(condition: bool, category: ErrorCategory, message: impl Display) {
+    if !condition {
+        fail_with(category, message)
+    }
+}
+
+fn exit(code: u8) -> ! {
+    // Unlike panic!(), this doesn't run the panic hook, which would print a backtrace.
+    panic::resume_unwind(Box::new(Failure(code)))
+}
+
+/// Runs the command line tool, and exits with the code of its failure if any. Panics are
+/// propagated.
+pub fn run(main: impl FnOnce() + UnwindSafe) {
+    if let Err(payload) = panic::catch_unwind(main) {
+        match payload.downcast::<Failure>() {
+            Ok(failure) => std::process::exit(failure.0.into()),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
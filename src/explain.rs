@@ -0,0 +1,155 @@
+//! Educational walkthrough of a real split in the toy field GF(2^8), with an annotated SVG plot of
+//! the polynomial and of the shares.
+
+use crate::exit::OrExit;
+use horcrux::field::Field;
+use horcrux::gf2n::GF8;
+use horcrux::shamir::{CompactShamir, Shamir};
+use rand::thread_rng;
+use std::fmt::Display;
+
+/// Size of the plot, and margin around the axes.
+const SIZE: usize = 640;
+const MARGIN: usize = 50;
+
+fn to_u8(x: &GF8) -> u8 {
+    u8::from_str_radix(&x.to_string(), 16).unwrap()
+}
+
+/// Returns the coordinates of a compact share, from its text format.
+fn coordinates<S: Display>(share: &S) -> (u8, GF8) {
+    let share = share.to_string();
+    let (x, y) = share.split_once('|').unwrap();
+    let y = u8::from_str_radix(y, 16).unwrap();
+    (x.parse().unwrap(), GF8::from(y))
+}
+
+/// Splits a random secret in GF(2^8) into n shares with threshold k, writes the plot to the given
+/// SVG file and returns the explanation of the split and of the reconstruction.
+pub fn explain(k: usize, n: usize, svg_filename: &str) -> String {
+    let secret = GF8::uniform(&mut thread_rng());
+    let shares = CompactShamir::split(&secret, k, n).unwrap();
+    let polynom = CompactShamir::interpolate(&shares, k).unwrap();
+    let shares: Vec<(u8, GF8)> = shares.iter().map(coordinates).collect();
+
+    let mut text = format!(
+        "Secret: s = {secret}\n\nSplitting picks a random polynomial of degree k - 1 = {} whose constant term is the secret:\n  P(x) = {}\n\n",
+        k - 1,
+        polynom
+            .coefficients()
+            .iter()
+            .enumerate()
+            .map(|(i, c)| match i {
+                0 => format!("{c}"),
+                1 => format!("{c} x"),
+                _ => format!("{c} x^{i}"),
+            })
+            .collect::<Vec<_>>()
+            .join(" + ")
+    );
+    text += "Each share is a point of the polynomial, (x, P(x)) for x = 1 to n:\n";
+    for &(x, y) in &shares {
+        text += &format!("  share {x}: P({x:02x}) = {y}\n");
+    }
+
+    text += &format!(
+        "\nAny k = {k} shares reconstruct the secret with Lagrange interpolation at x = 0:\n  s = P(0) = sum of y_i * l_i(0), with l_i(0) = product over j != i of x_j / (x_j - x_i)\n"
+    );
+    let xs: Vec<GF8> = shares[..k].iter().map(|&(x, _)| GF8::from(x)).collect();
+    let mut reconstructed = GF8::ZERO;
+    for (i, &(x, y)) in shares[..k].iter().enumerate() {
+        let mut l = GF8::ONE;
+        for (j, &xj) in xs.iter().enumerate() {
+            if j != i {
+                l = l * &xj * &(xj - xs[i]).invert();
+            }
+        }
+        let term = y * &l;
+        reconstructed += &term;
+        text += &format!("  share {x}: l(0) = {l}, y * l(0) = {y} * {l} = {term}\n");
+    }
+    text += &format!("  sum = {reconstructed}\n\n");
+    text += &format!(
+        "With only k - 1 = {} shares, every value of the secret is equally likely: for each candidate secret, exactly one polynomial of degree k - 1 passes through these shares. Over GF(2^8), the values of the polynomial look random, as shown in {svg_filename}.\n",
+        k - 1
+    );
+
+    let points: Vec<(u8, u8)> = (0..=255u8)
+        .map(|x| (x, to_u8(&polynom.evaluate(GF8::from(x)))))
+        .collect();
+    let share_points: Vec<(u8, u8)> = shares.iter().map(|(x, y)| (*x, to_u8(y))).collect();
+    std::fs::write(
+        svg_filename,
+        plot(k, n, &points, to_u8(&secret), &share_points),
+    )
+    .or_exit(format_args!("Could not write {}", svg_filename));
+
+    text
+}
+
+/// Renders the values of the polynomial (grey), the secret (green) and the shares (red, labeled) as
+/// an SVG scatter plot.
+fn plot(k: usize, n: usize, points: &[(u8, u8)], secret: u8, shares: &[(u8, u8)]) -> String {
+    let scale = (SIZE - 2 * MARGIN) as f64 / 255.0;
+    let px = |x: u8| MARGIN as f64 + x as f64 * scale;
+    let py = |y: u8| (SIZE - MARGIN) as f64 - y as f64 * scale;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SIZE}\" height=\"{SIZE}\" font-family=\"sans-serif\">\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n"
+    );
+    svg += &format!(
+        "<text x=\"{}\" y=\"30\" text-anchor=\"middle\" font-size=\"18\">P(x) over GF(2^8), k = {k}, n = {n}</text>\n",
+        SIZE / 2
+    );
+
+    // Axes, with ticks every 64 values.
+    let (origin_x, origin_y) = (px(0), py(0));
+    svg += &format!(
+        "<line x1=\"{origin_x}\" y1=\"{origin_y}\" x2=\"{}\" y2=\"{origin_y}\" stroke=\"black\"/>\n<line x1=\"{origin_x}\" y1=\"{origin_y}\" x2=\"{origin_x}\" y2=\"{}\" stroke=\"black\"/>\n",
+        px(255),
+        py(255)
+    );
+    for tick in [0u8, 64, 128, 192, 255] {
+        svg += &format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\">{tick}</text>\n<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"12\">{tick}</text>\n",
+            px(tick),
+            origin_y + 18.0,
+            origin_x - 6.0,
+            py(tick) + 4.0
+        );
+    }
+    svg += &format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\">x</text>\n<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\">P(x)</text>\n",
+        SIZE / 2,
+        SIZE - 10,
+        20,
+        MARGIN - 10
+    );
+
+    for &(x, y) in points {
+        svg += &format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"1.5\" fill=\"grey\"/>\n",
+            px(x),
+            py(y)
+        );
+    }
+    svg += &format!(
+        "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"5\" fill=\"green\"/>\n<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" fill=\"green\">secret ({secret})</text>\n",
+        px(0),
+        py(secret),
+        px(0) + 8.0,
+        py(secret) - 8.0
+    );
+    for (i, &(x, y)) in shares.iter().enumerate() {
+        svg += &format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"4\" fill=\"red\"/>\n<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" fill=\"red\">share {} ({x}, {y})</text>\n",
+            px(x),
+            py(y),
+            px(x) + 8.0,
+            py(y) - 8.0 - 14.0 * (i % 2) as f64,
+            i + 1
+        );
+    }
+    svg += "</svg>\n";
+    svg
+}
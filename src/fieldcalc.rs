@@ -0,0 +1,77 @@
+//! Arithmetic on field elements from the command line, using the same implementation as the
+//! shares. This is useful to debug interoperability issues with other implementations, or to
+//! demonstrate finite field arithmetic.
+
+use horcrux::dispatch::FieldVisitor;
+use horcrux::field::Field;
+use std::fmt::{Debug, Display};
+
+/// Parses a field element in hexadecimal, in the same format as secrets and shares.
+fn parse_element<F: Field>(s: &str) -> Result<F, String> {
+    let bytes = hex::decode(s).map_err(|e| format!("Invalid hexadecimal {}: {}", s, e))?;
+    F::from_bytes(&bytes)
+        .ok_or_else(|| format!("{} is not a valid representation of a field element", s))
+}
+
+/// Computes x^e by square-and-multiply.
+fn pow<F: Field>(x: F, e: u128) -> F {
+    let mut result = F::ONE;
+    for i in (0..128 - e.leading_zeros()).rev() {
+        result = result * &result;
+        if (e >> i) & 1 != 0 {
+            result = result * &x;
+        }
+    }
+    result
+}
+
+/// Applies the given operation to the operands, and returns the result in hexadecimal. Returns
+/// an error if the operands are invalid for the operation.
+pub fn compute<F: Field + Display>(operation: &str, operands: &[&str]) -> Result<String, String> {
+    let arity = match operation {
+        "invert" => 1,
+        _ => 2,
+    };
+    if operands.len() != arity {
+        return Err(format!("{} expects {} operands", operation, arity));
+    }
+
+    let x = parse_element::<F>(operands[0])?;
+    let result = match operation {
+        "add" => {
+            let mut x = x;
+            x += &parse_element(operands[1])?;
+            x
+        }
+        "sub" => x - parse_element(operands[1])?,
+        "mul" => x * &parse_element(operands[1])?,
+        "invert" => {
+            if x == F::ZERO {
+                return Err("Zero has no inverse".to_owned());
+            }
+            x.invert()
+        }
+        "pow" => {
+            let e = operands[1]
+                .parse::<u128>()
+                .map_err(|_| "The exponent must be a non-negative integer".to_owned())?;
+            pow(x, e)
+        }
+        _ => return Err(format!("Unsupported operation: {}", operation)),
+    };
+    Ok(result.to_string())
+}
+
+/// Operation to compute in the field selected on the command line.
+pub struct Computation<'a> {
+    pub operation: &'a str,
+    pub operands: &'a [&'a str],
+}
+
+impl FieldVisitor for Computation<'_> {
+    type Output = Result<String, String>;
+
+    fn visit<F: Field + Debug + Display>(self) -> Result<String, String> {
+        compute::<F>(self.operation, self.operands)
+    }
+}
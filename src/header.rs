@@ -0,0 +1,221 @@
+//! Self-describing shares, prefixed by a versioned header with the parameters of the split.
+//!
+//! A share with a header looks like `horcrux1:compact:gf256:3:5f1c0a2e:1|...`, i.e. the format
+//! version, the type of shares, the field, the threshold and an identifier of the split, followed by
+//! the share itself. The parameters are used as defaults when reading shares, and shares with a
+//! different identifier are rejected, so that shares of different splits can't be mixed.
+
+use clap::ArgMatches;
+use horcrux::shamir::read_lines;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::BufReader;
+
+const VERSION: &str = "horcrux1";
+
+/// Parameters of a split, embedded in each of its shares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub shamir_type: String,
+    /// Name of the field: `gf<bitsize>` for binary fields, or the name of the elliptic curve.
+    pub field: String,
+    pub threshold: usize,
+    pub set_id: String,
+}
+
+impl Header {
+    /// Returns the header matching the command line parameters, with the given set identifier.
+    pub fn new(matches: &ArgMatches, threshold: usize, set_id: String) -> Self {
+        let field = match matches.value_of("field").unwrap() {
+            "binary" => format!("gf{}", matches.value_of("bitsize").unwrap()),
+            curve => curve.to_owned(),
+        };
+        Header {
+            shamir_type: matches.value_of("type").unwrap().to_owned(),
+            field,
+            threshold,
+            set_id,
+        }
+    }
+
+    /// Splits a line into its header, if any, and the share.
+    pub fn split(line: &str) -> Result<(Option<Header>, &str), String> {
+        if !line.starts_with("horcrux") {
+            return Ok((None, line));
+        }
+        let parts: Vec<&str> = line.splitn(6, ':').collect();
+        if parts[0] != VERSION {
+            return Err(format!("Unsupported share header version: {}", parts[0]));
+        }
+        if parts.len() != 6 {
+            return Err("Truncated share header".to_owned());
+        }
+        let threshold = parts[3]
+            .parse()
+            .map_err(|_| format!("Invalid threshold in share header: {}", parts[3]))?;
+        let header = Header {
+            shamir_type: parts[1].to_owned(),
+            field: parts[2].to_owned(),
+            threshold,
+            set_id: parts[4].to_owned(),
+        };
+        Ok((Some(header), parts[5]))
+    }
+
+    /// Returns the global arguments that describe the split, in the command line syntax.
+    fn args(&self) -> Vec<(&'static str, String)> {
+        let mut args = vec![("type", self.shamir_type.clone())];
+        match self.field.strip_prefix("gf") {
+            Some(bitsize) => {
+                args.push(("field", "binary".to_owned()));
+                args.push(("bitsize", bitsize.to_owned()));
+            }
+            None => args.push(("field", self.field.clone())),
+        }
+        args.push(("threshold", self.threshold.to_string()));
+        args
+    }
+
+    /// Checks that the parameters of this header (but not the set identifier) match the expected
+    /// ones.
+    pub fn check(&self, expected: &Header) -> Result<(), String> {
+        if self.shamir_type != expected.shamir_type {
+            return Err(format!(
+                "Shares are of type {}, but --type {} was given",
+                self.shamir_type, expected.shamir_type
+            ));
+        }
+        if self.field != expected.field {
+            return Err(format!(
+                "Shares are in the field {}, but the field {} was given",
+                self.field, expected.field
+            ));
+        }
+        if self.threshold != expected.threshold {
+            return Err(format!(
+                "Shares have a threshold of {}, but --threshold {} was given",
+                self.threshold, expected.threshold
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:",
+            VERSION, self.shamir_type, self.field, self.threshold, self.set_id
+        )
+    }
+}
+
+/// Returns a random identifier for a new split.
+pub fn random_set_id() -> String {
+    hex::encode(rand::random::<[u8; 4]>())
+}
+
+/// Returns the command line arguments, completed with the parameters of the header of the first
+/// share in the file given to the subcommand, if any. Parameters explicitly given on the command
+/// line are kept, and checked against the headers when reading the shares.
+pub fn complete_args(matches: &ArgMatches) -> Option<Vec<OsString>> {
+    let (_, args) = matches.subcommand();
+    let args = args?;
+    let filename = match args.subcommand() {
+        (_, Some(nested)) => nested.value_of("shares"),
+        _ => args.value_of("shares"),
+    }?;
+
+    let reader = BufReader::new(File::open(filename).ok()?);
+    let (lines, _) = read_lines(reader, 1, |line| Ok(line.to_owned())).ok()?;
+    let header = Header::split(lines.first()?).ok()?.0?;
+
+    let mut completed: Vec<OsString> = std::env::args_os().take(1).collect();
+    for (name, value) in header.args() {
+        if matches.occurrences_of(name) == 0 {
+            completed.push(format!("--{}", name).into());
+            completed.push(value.into());
+        }
+    }
+    completed.extend(std::env::args_os().skip(1));
+    Some(completed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn header() -> Header {
+        Header {
+            shamir_type: "compact".to_owned(),
+            field: "gf256".to_owned(),
+            threshold: 3,
+            set_id: "5f1c0a2e".to_owned(),
+        }
+    }
+
+    #[test]
+    fn can_split_and_format() {
+        let line = format!("{}1|abcd", header());
+        assert_eq!(line, "horcrux1:compact:gf256:3:5f1c0a2e:1|abcd");
+        let (parsed, share) = Header::split(&line).unwrap();
+        assert_eq!(parsed, Some(header()));
+        assert_eq!(share, "1|abcd");
+
+        assert_eq!(Header::split("1|abcd").unwrap(), (None, "1|abcd"));
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        assert!(Header::split("horcrux2:compact:gf256:3:5f1c0a2e:1|abcd").is_err());
+        assert!(Header::split("horcrux1:compact:gf256:3:5f1c0a2e").is_err());
+        assert!(Header::split("horcrux1:compact:gf256:x:5f1c0a2e:1|abcd").is_err());
+        assert!(Header::split("horcrux1:compact:gf256:-1:5f1c0a2e:1|abcd").is_err());
+    }
+
+    #[test]
+    fn can_check_parameters() {
+        assert!(header().check(&header()).is_ok());
+        let other_set = Header {
+            set_id: "00000000".to_owned(),
+            ..header()
+        };
+        assert!(header().check(&other_set).is_ok());
+
+        let other_type = Header {
+            shamir_type: "randomized".to_owned(),
+            ..header()
+        };
+        assert!(header().check(&other_type).is_err());
+        let other_field = Header {
+            field: "gf128".to_owned(),
+            ..header()
+        };
+        assert!(header().check(&other_field).is_err());
+        let other_threshold = Header {
+            threshold: 2,
+            ..header()
+        };
+        assert!(header().check(&other_threshold).is_err());
+    }
+
+    #[test]
+    fn can_convert_to_args() {
+        assert_eq!(
+            header().args(),
+            vec![
+                ("type", "compact".to_owned()),
+                ("field", "binary".to_owned()),
+                ("bitsize", "256".to_owned()),
+                ("threshold", "3".to_owned()),
+            ]
+        );
+        let curve = Header {
+            field: "p256".to_owned(),
+            ..header()
+        };
+        assert_eq!(curve.args()[1], ("field", "p256".to_owned()));
+    }
+}
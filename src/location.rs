@@ -0,0 +1,323 @@
+//! Location hints of the shares, sealed with an operator passphrase, and planning of which
+//! locations to visit to recover a secret.
+//!
+//! The sealed hints are stored in a separate file, alongside the shares:
+//! ```text
+//! horcrux-locations-v2:<threshold>:<salt>[:<hash>]
+//! <share number>:<custodian>:<nonce>:<encrypted hint>
+//! ```
+//! The custodians are in clear, but each hint (e.g. "safe deposit box #12, branch X") is encrypted
+//! and authenticated with ChaCha20-Poly1305 under a random nonce, with a key derived from the
+//! passphrase with PBKDF2-HMAC-SHA256. The header, share number and custodian are authenticated
+//! along with the hint. The file alone therefore doesn't reveal where the shares are kept. With
+//! another hash algorithm, given by its identifier at the end of the header (e.g. `blake3`), PBKDF2
+//! uses the MAC of this algorithm instead of HMAC-SHA256 (see `HashAlgorithm::pbkdf2()`).
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use horcrux::hashing::HashAlgorithm;
+use rand::RngCore;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use zeroize::Zeroizing;
+
+const HEADER: &str = "horcrux-locations-v2";
+/// Number of iterations of PBKDF2, to slow down guessing of the passphrase.
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// Location of a share, as given by the operator when splitting.
+pub struct Location {
+    /// Number of the share, i.e. its position in the output of the split (starting at 1).
+    pub share: usize,
+    pub custodian: String,
+    pub hint: Zeroizing<String>,
+}
+
+/// Parses the locations of the shares, given as one `custodian: hint` line per share, in the
+/// order of the shares.
+pub fn parse(contents: &str, n: usize) -> Result<Vec<Location>, String> {
+    let mut locations = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (custodian, hint) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Line {} isn't of the form `custodian: hint`", i + 1))?;
+        let custodian = custodian.trim();
+        if custodian.is_empty() {
+            return Err(format!("Line {} has no custodian", i + 1));
+        }
+        locations.push(Location {
+            share: locations.len() + 1,
+            custodian: custodian.to_owned(),
+            hint: Zeroizing::new(hint.trim().to_owned()),
+        });
+    }
+    if locations.len() != n {
+        return Err(format!(
+            "Expected one location per share, i.e. {} locations, but found {}",
+            n,
+            locations.len()
+        ));
+    }
+    Ok(locations)
+}
+
+/// Seals the hints of the given locations with the passphrase, for a split with threshold k.
+/// Returns the contents of the sealed locations file.
+pub fn seal(locations: &[Location], k: usize, passphrase: &str, hash: HashAlgorithm) -> String {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let mut header = format!("{}:{}:{}", HEADER, k, hex::encode(salt));
+    if !hash.is_default() {
+        header = format!("{}:{}", header, hash);
+    }
+    let cipher = cipher(hash, passphrase, &salt);
+
+    let mut lines = vec![header.clone()];
+    for location in locations {
+        let prefix = format!("{}:{}", location.share, location.custodian);
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let payload = Payload {
+            msg: location.hint.as_bytes(),
+            aad: &associated_data(&header, &prefix),
+        };
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .expect("Hints fit in a ChaCha20-Poly1305 message");
+        lines.push(format!(
+            "{}:{}:{}",
+            prefix,
+            hex::encode(nonce),
+            hex::encode(ciphertext)
+        ));
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Checks and decrypts a sealed locations file with the passphrase. Returns the threshold of the
+/// split and the locations of its shares.
+pub fn open(sealed: &str, passphrase: &str) -> Result<(usize, Vec<Location>), String> {
+    let mut lines = sealed
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let header = lines.next().ok_or("Empty locations file")?;
+    let parts: Vec<&str> = header.split(':').collect();
+    if !(parts.len() == 3 || parts.len() == 4) || parts[0] != HEADER {
+        return Err(format!("Locations files must be in the {} format", HEADER));
+    }
+    let hash = match parts.get(3) {
+        Some(hash) => hash.parse().map_err(|e| format!("{}", e))?,
+        None => HashAlgorithm::default(),
+    };
+    let k = parts[1]
+        .parse::<usize>()
+        .map_err(|_| format!("Invalid threshold in the locations file: {}", parts[1]))?;
+    let salt = hex::decode(parts[2]).map_err(|e| format!("Invalid salt: {}", e))?;
+    let cipher = cipher(hash, passphrase, &salt);
+
+    let mut locations = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let invalid = || format!("Invalid location at line {}", i + 2);
+        let (share, custodian, nonce, ciphertext) = match line.split(':').collect::<Vec<_>>()[..] {
+            [share, custodian, nonce, ciphertext] => (share, custodian, nonce, ciphertext),
+            _ => return Err(invalid()),
+        };
+        let prefix = format!("{}:{}", share, custodian);
+        let share = share.parse::<usize>().map_err(|_| invalid())?;
+        let nonce = hex::decode(nonce).map_err(|_| invalid())?;
+        if nonce.len() != 12 {
+            return Err(invalid());
+        }
+        let ciphertext = hex::decode(ciphertext).map_err(|_| invalid())?;
+
+        let payload = Payload {
+            msg: &ciphertext,
+            aad: &associated_data(header, &prefix),
+        };
+        let hint = Zeroizing::new(
+            cipher
+                .decrypt(Nonce::from_slice(&nonce), payload)
+                .map_err(|_| {
+                    format!(
+                        "The location at line {} doesn't match the passphrase, the passphrase is wrong or the file was modified",
+                        i + 2
+                    )
+                })?,
+        );
+        let hint = String::from_utf8(hint.to_vec()).map_err(|_| invalid())?;
+        locations.push(Location {
+            share,
+            custodian: custodian.to_owned(),
+            hint: Zeroizing::new(hint),
+        });
+    }
+    Ok((k, locations))
+}
+
+/// Recovery plan: the locations to visit, with the shares to collect at each of them.
+pub struct Plan<'a> {
+    pub threshold: usize,
+    pub visits: Vec<(&'a str, Vec<&'a Location>)>,
+}
+
+/// Returns the smallest set of locations to visit to collect k shares held by the reachable
+/// custodians.
+pub fn plan<'a>(
+    locations: &'a [Location],
+    k: usize,
+    reachable: &[&str],
+) -> Result<Plan<'a>, String> {
+    let mut by_hint: BTreeMap<&str, Vec<&Location>> = BTreeMap::new();
+    for location in locations {
+        if reachable.contains(&location.custodian.as_str()) {
+            by_hint
+                .entry(location.hint.as_str())
+                .or_default()
+                .push(location);
+        }
+    }
+    let available: usize = by_hint.values().map(Vec::len).sum();
+    if available < k {
+        return Err(format!(
+            "The reachable custodians only hold {} shares, but the threshold is {}",
+            available, k
+        ));
+    }
+
+    // Visiting the locations with the most shares first minimizes the number of visits. The sort
+    // is stable, so ties are broken by the order of the hints.
+    let mut visits: Vec<(&str, Vec<&Location>)> = by_hint.into_iter().collect();
+    visits.sort_by_key(|(_, shares)| std::cmp::Reverse(shares.len()));
+    let mut remaining = k;
+    let mut plan = Vec::new();
+    for (hint, mut shares) in visits {
+        if remaining == 0 {
+            break;
+        }
+        shares.truncate(remaining);
+        remaining -= shares.len();
+        plan.push((hint, shares));
+    }
+    Ok(Plan {
+        threshold: k,
+        visits: plan,
+    })
+}
+
+impl Display for Plan<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Visit {} location(s) to collect {} shares:",
+            self.visits.len(),
+            self.threshold
+        )?;
+        for (hint, shares) in &self.visits {
+            writeln!(f, "- {}", hint)?;
+            for location in shares {
+                writeln!(
+                    f,
+                    "    share {} (custodian: {})",
+                    location.share, location.custodian
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the ChaCha20-Poly1305 cipher of a locations file, keyed by the passphrase stretched
+/// with PBKDF2 (RFC 8018) restricted to a single output block.
+fn cipher(hash: HashAlgorithm, passphrase: &str, salt: &[u8]) -> ChaCha20Poly1305 {
+    let key = Zeroizing::new(hash.pbkdf2(passphrase.as_bytes(), salt, PBKDF2_ITERATIONS));
+    ChaCha20Poly1305::new(Key::from_slice(&*key))
+}
+
+/// Returns the data authenticated along with a hint: the header of the file, and the share number
+/// and custodian of the hint, so that entries can't be swapped or moved to another file.
+fn associated_data(header: &str, prefix: &str) -> Vec<u8> {
+    format!("{}\n{}", header, prefix).into_bytes()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn locations() -> Vec<Location> {
+        parse("alice: safe #12\nbob: attic\n\ncarol: safe #12\n", 3).unwrap()
+    }
+
+    #[test]
+    fn can_parse_locations() {
+        let locations = locations();
+        assert_eq!(locations.len(), 3);
+        assert_eq!(locations[1].share, 2);
+        assert_eq!(locations[1].custodian, "bob");
+        assert_eq!(*locations[1].hint, "attic");
+        assert_eq!(*parse("dave: box: 3", 1).unwrap()[0].hint, "box: 3");
+    }
+
+    #[test]
+    fn rejects_invalid_locations() {
+        assert!(parse("alice safe", 1).is_err());
+        assert!(parse(" : safe", 1).is_err());
+        assert!(parse("alice: safe\nbob: attic", 3).is_err());
+    }
+
+    #[test]
+    fn can_seal_and_open() {
+        let sealed = seal(&locations(), 2, "passphrase", HashAlgorithm::default());
+        assert!(!sealed.contains("safe"));
+        let (k, opened) = open(&sealed, "passphrase").unwrap();
+        assert_eq!(k, 2);
+        assert_eq!(opened.len(), 3);
+        for (a, b) in opened.iter().zip(locations().iter()) {
+            assert_eq!(a.share, b.share);
+            assert_eq!(a.custodian, b.custodian);
+            assert_eq!(*a.hint, *b.hint);
+        }
+        assert!(open(&sealed, "wrong").is_err());
+    }
+
+    #[test]
+    fn rejects_modified_files() {
+        let sealed = seal(&locations(), 2, "passphrase", HashAlgorithm::default());
+        let lines: Vec<&str> = sealed.lines().collect();
+
+        // Swapping the custodians of two entries.
+        let swapped = sealed
+            .replacen(":alice:", ":tmp:", 1)
+            .replacen(":bob:", ":alice:", 1)
+            .replacen(":tmp:", ":bob:", 1);
+        assert!(open(&swapped, "passphrase").is_err());
+
+        // Changing the threshold in the header.
+        let header = lines[0].replacen(":2:", ":1:", 1);
+        let modified = sealed.replacen(lines[0], &header, 1);
+        assert!(open(&modified, "passphrase").is_err());
+
+        assert!(open("", "passphrase").is_err());
+        assert!(open("horcrux-locations-v1:2:00", "passphrase").is_err());
+        assert!(open(&format!("{}\n1:alice:00", lines[0]), "passphrase").is_err());
+    }
+
+    #[test]
+    fn can_plan_visits() {
+        let locations = locations();
+        let plan = plan(&locations, 2, &["alice", "bob", "carol"]).unwrap();
+        assert_eq!(plan.visits.len(), 1);
+        assert_eq!(plan.visits[0].0, "safe #12");
+        assert_eq!(plan.visits[0].1.len(), 2);
+
+        let plan = super::plan(&locations, 2, &["alice", "bob"]).unwrap();
+        assert_eq!(plan.visits.len(), 2);
+
+        assert!(super::plan(&locations, 2, &["alice"]).is_err());
+    }
+}
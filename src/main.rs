@@ -1,15 +1,70 @@
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use exit::{OrExit, OrFail};
+use header::Header;
+use horcrux::access::AccessPolicy;
+use horcrux::decoy;
+use horcrux::dispatch::{BuiltinField, DynShamir, SchemeRegistry, ShamirVisitor};
 use horcrux::field::Field;
-use horcrux::gf2n::{GF128, GF16, GF256, GF32, GF64, GF8};
-use horcrux::shamir::{CompactShamir, RandomShamir, Shamir};
+use horcrux::group::{Group, GroupShamir};
+use horcrux::hashing::HashAlgorithm;
+use horcrux::incremental::{IncrementalReconstructor, Progress};
+use horcrux::record;
+use horcrux::schema;
+use horcrux::shamir::{append_checksum, read_lines};
+use horcrux::shamir::{ErrorCategory, Ramp, RandomShamir, Shamir, ShamirError, XKey};
+use horcrux::shareset::ShareSet;
 use rand::thread_rng;
 use regex::Regex;
+use secret_output::OutFormat;
+use std::convert::TryInto;
 use std::fmt::{Debug, Display};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, Read, Write};
+use zeroize::Zeroizing;
+
+/// Maximum number of shares read from a file, which is also the maximum number of shares of a split.
+const MAX_SHARES: usize = 255;
+
+/// Shamir schemes provided by other crates, selectable with --type in addition to the built-in
+/// compact and random schemes. A crate providing a scheme is added as an optional dependency
+/// behind a feature, and its provider is listed here under that feature, e.g.
+/// `#[cfg(feature = "my-scheme")] type ExternalSchemes = (my_scheme::Provider, ());`.
+type ExternalSchemes = ();
+
+mod advise;
+#[cfg(feature = "audio")]
+mod audio;
+mod checklist;
+mod code;
+mod dual_rng;
+mod entropy;
+mod era;
+mod exit;
+mod explain;
+mod fieldcalc;
+mod header;
+mod location;
+mod manifest;
+mod ndef;
+mod policy;
+mod recipient;
+mod secret_input;
+mod secret_output;
+mod share_mac;
+mod stats;
+mod timestamp;
+mod totp;
+mod transcript;
+mod witness;
 
 fn main() {
-    let matches = App::new("Horcrux")
+    exit::run(run)
+}
+
+fn run() {
+    let mut shamir_types = vec!["compact", "random"];
+    shamir_types.extend(ExternalSchemes::names());
+    let app = App::new("Horcrux")
         .version("0.1.0")
         .author("G. Endignoux <ggendx@gmail.com>")
         .about("Split your secrets a.k.a. Shamir's secret sharing")
@@ -17,7 +72,7 @@ fn main() {
             Arg::with_name("type")
                 .long("type")
                 .takes_value(true)
-                .possible_values(&["compact", "random"])
+                .possible_values(&shamir_types)
                 .default_value("compact")
                 .help("Type of shares"),
         )
@@ -26,25 +81,46 @@ fn main() {
                 .long("bitsize")
                 .short("b")
                 .takes_value(true)
-                .possible_values(&["8", "16", "32", "64", "128", "256"])
+                .possible_values(&["8", "16", "32", "64", "128", "160", "192", "224", "256"])
                 .default_value("256")
                 .help("Size of the secret in bits"),
         )
+        .arg(
+            Arg::with_name("field")
+                .long("field")
+                .takes_value(true)
+                .possible_values(&["binary", "secp256k1", "ed25519"])
+                .default_value("binary")
+                .help("Field to split the secret in: GF(2^bitsize), or the scalar field of an elliptic curve (ignoring --bitsize)"),
+        )
         .arg(
             Arg::with_name("nshares")
                 .long("nshares")
                 .short("n")
                 .takes_value(true)
-                .required(true)
-                .help("Total number of shares (1 <= n <= 255)"),
+                .help("Total number of shares (1 <= n <= 255), required by split, checklist and explain"),
         )
         .arg(
             Arg::with_name("threshold")
                 .long("threshold")
                 .short("t")
                 .takes_value(true)
-                .required(true)
-                .help("Minimum number of shares required to reconstruct the secret (1 <= t <= n)"),
+                .help("Minimum number of shares required to reconstruct the secret (1 <= t <= n), required by all commands except advise, fieldcalc, backends and stats, unless given by the share headers"),
+        )
+        .arg(
+            Arg::with_name("x-key")
+                .long("x-key")
+                .takes_value(true)
+                .help("Key (64 hexadecimal characters) from which the x coordinates of compressed random shares are derived"),
+        )
+        .arg(
+            Arg::with_name("hash")
+                .long("hash")
+                .takes_value(true)
+                .global(true)
+                .possible_values(&["sha256", "blake3"])
+                .default_value("sha256")
+                .help("Hash algorithm of the commitments, fingerprints, digests and verification codes, and of the records, transcripts, witness shares and sealed files created; the latter record their algorithm, so it is only needed to create them"),
         )
         .subcommand(
             SubCommand::with_name("split")
@@ -53,7 +129,191 @@ fn main() {
                     Arg::with_name("secret")
                         .long("secret")
                         .takes_value(true)
-                        .help("Name of a file containing a secret to split [default: generate a random secret instead]"),
+                        .help("Name of a file containing a secret to split, or - to read it from stdin (prompted without echo on a terminal); secrets larger than a field element are split into records of several elements [default: generate a random secret instead]"),
+                )
+                .arg(
+                    Arg::with_name("secret-at")
+                        .long("secret-at")
+                        .takes_value(true)
+                        .help("x coordinate where the secret is stored [default: 0]"),
+                )
+                .arg(
+                    Arg::with_name("show-secret")
+                        .long("show-secret")
+                        .help("Also prints the secret and the polynomial used to split it"),
+                )
+                .arg(
+                    Arg::with_name("quiet")
+                        .long("quiet")
+                        .short("q")
+                        .conflicts_with("show-secret")
+                        .help("Only prints the shares (and the x key with --compress-x, or the policy override), one per line"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .help("Output format: human-readable text, or a JSON object with the parameters and the shares"),
+                )
+                .arg(
+                    Arg::with_name("dual-rng")
+                        .long("dual-rng")
+                        .takes_value(true)
+                        .conflicts_with("compress-x")
+                        .help("Name of a file or device (e.g. a hardware RNG) providing a second source of randomness, XORed with the OS RNG to sample the secret and the polynomial; the contributions of both sources are committed to in the output"),
+                )
+                .arg(
+                    Arg::with_name("header")
+                        .long("header")
+                        .help("Prefixes each share with a header describing the split (type, field, threshold and a split identifier), so that other commands don't need these parameters and detect shares of different splits"),
+                )
+                .arg(
+                    Arg::with_name("set-id")
+                        .long("set-id")
+                        .takes_value(true)
+                        .requires("header")
+                        .help("Identifier of the split in the share headers (alphanumeric characters, '-' and '_') [default: random]"),
+                )
+                .arg(
+                    Arg::with_name("compress-x")
+                        .long("compress-x")
+                        .conflicts_with("secret-at")
+                        .help("Issues random shares in a compact index|y form, with x coordinates derived from the index with --x-key [default: generate a random key]"),
+                )
+                .arg(
+                    Arg::with_name("decoy")
+                        .long("decoy")
+                        .takes_value(true)
+                        .requires("decoy-threshold")
+                        .conflicts_with_all(&["secret-at", "compress-x", "header"])
+                        .help("Name of a file containing a decoy secret, or - to read it from stdin; each share is then an envelope real;decoy of a share of the secret and a share of the decoy, at the same x coordinate"),
+                )
+                .arg(
+                    Arg::with_name("decoy-threshold")
+                        .long("decoy-threshold")
+                        .takes_value(true)
+                        .requires("decoy")
+                        .help("Minimum number of shares required to reconstruct the decoy secret, typically lower than --threshold"),
+                )
+                .arg(
+                    Arg::with_name("checksum")
+                        .long("checksum")
+                        .conflicts_with("decoy")
+                        .help("Appends a checksum to each share, so that typos are detected and located when reading the shares back"),
+                )
+                .arg(
+                    Arg::with_name("era")
+                        .long("era")
+                        .takes_value(true)
+                        .possible_values(&["v1", "v2"])
+                        .default_value("v2")
+                        .help("Generation of the share formats to emit: v1 for plain x|y shares that horcrux 0.1.0 can read, rejecting the options whose shares it can't read, or v2 for all the current formats"),
+                )
+                .arg(
+                    Arg::with_name("codes")
+                        .long("codes")
+                        .conflicts_with_all(&["quiet", "groups"])
+                        .help("Also prints a 6-digit verification code for each share, to read aloud to its custodian once the share is recorded (see info --expect-code); with --decoy, the code is of the real half of the envelope"),
+                )
+                .arg(
+                    Arg::with_name("verify-key")
+                        .long("verify-key")
+                        .takes_value(true)
+                        .conflicts_with_all(&["quiet", "groups"])
+                        .help("Name of a file containing a 32-byte verification key in hexadecimal, handed to all the custodians; also prints a MAC of each share under this key, for its custodian to keep with the share and check later (see info --verify-key); with --decoy, the MAC is of the real half of the envelope"),
+                )
+                .arg(
+                    Arg::with_name("policy")
+                        .long("policy")
+                        .takes_value(true)
+                        .requires("policy-key")
+                        .help("Name of a file containing a signed policy that the split must comply with (see policy sign)"),
+                )
+                .arg(
+                    Arg::with_name("policy-key")
+                        .long("policy-key")
+                        .takes_value(true)
+                        .requires("policy")
+                        .help("Name of a file containing the Ed25519 public key of the policy signer (32 bytes in hexadecimal)"),
+                )
+                .arg(
+                    Arg::with_name("override-policy")
+                        .long("override-policy")
+                        .takes_value(true)
+                        .requires("policy")
+                        .help("Splits even if the split is outside of the policy, with the given justification, which is recorded in the output"),
+                )
+                .arg(
+                    Arg::with_name("commit-secret")
+                        .long("commit-secret")
+                        .help("Also prints a commitment to the secret, blinded by a random salt, to verify a later reconstruction with --expect-hash"),
+                )
+                .arg(
+                    Arg::with_name("groups")
+                        .long("groups")
+                        .takes_value(true)
+                        .requires("group-threshold")
+                        .conflicts_with_all(&["secret-at", "dual-rng", "header", "compress-x", "decoy", "witnesses"])
+                        .help("Splits the secret among groups of shares, each with its own threshold, given as a comma-separated list such as 2of3,3of5 (ignoring --threshold and --nshares)"),
+                )
+                .arg(
+                    Arg::with_name("group-threshold")
+                        .long("group-threshold")
+                        .takes_value(true)
+                        .requires("groups")
+                        .help("Minimum number of groups required to reconstruct the secret"),
+                )
+                .arg(
+                    Arg::with_name("access")
+                        .long("access")
+                        .takes_value(true)
+                        .conflicts_with_all(&["groups", "secret-at", "dual-rng", "header", "compress-x", "decoy", "witnesses", "checksum", "codes", "verify-key", "locations"])
+                        .help("Splits the secret according to an access policy over named custodians, such as \"(alice and bob) or (any 3 of c1..c5)\", with nested compact shares (ignoring --threshold and --nshares)"),
+                )
+                .arg(
+                    Arg::with_name("privacy-threshold")
+                        .long("privacy-threshold")
+                        .takes_value(true)
+                        .requires("secret")
+                        .conflicts_with_all(&["groups", "access", "decoy"])
+                        .help("Splits a secret larger than a field element (read with --secret) with a ramp scheme: up to this number of shares reveal nothing about the secret, --threshold shares reconstruct it, and the shares are --threshold minus this number times smaller"),
+                )
+                .arg(
+                    Arg::with_name("witnesses")
+                        .long("witnesses")
+                        .takes_value(true)
+                        .help("Number of witness shares to issue, i.e. shares of the hash of the secret that auditors can use to verify a reconstruction"),
+                )
+                .arg(
+                    Arg::with_name("witness-threshold")
+                        .long("witness-threshold")
+                        .takes_value(true)
+                        .requires("witnesses")
+                        .help("Minimum number of witness shares required to reconstruct the hash [default: --threshold]"),
+                )
+                .arg(
+                    Arg::with_name("locations")
+                        .long("locations")
+                        .takes_value(true)
+                        .requires("sealed-locations")
+                        .conflicts_with_all(&["decoy", "groups"])
+                        .help("Name of a file containing where each share will be kept, as one `custodian: location hint` line per share in the order of the shares; the hints are sealed with a passphrase prompted on stdin (see plan-recovery)"),
+                )
+                .arg(
+                    Arg::with_name("sealed-locations")
+                        .long("sealed-locations")
+                        .takes_value(true)
+                        .requires("locations")
+                        .help("Name of a file to write the sealed location hints to, to be stored alongside the shares"),
+                )
+                .arg(
+                    Arg::with_name("recipients")
+                        .long("recipients")
+                        .takes_value(true)
+                        .conflicts_with_all(&["groups", "access", "codes", "verify-key"])
+                        .help("Name of a file containing the age recipient of each share (an age1... public key, or an X25519 public key in hexadecimal), one per line in the order of the shares; each share is then encrypted with age to its recipient (see decrypt-share)"),
                 ),
         )
         .subcommand(
@@ -63,159 +323,3074 @@ fn main() {
                     Arg::with_name("shares")
                         .long("shares")
                         .takes_value(true)
-                        .required(true)
+                        .required_unless("interactive")
                         .help("Name of a file containing the shares to reconstruct from"),
                 )
+                .arg(
+                    Arg::with_name("interactive")
+                        .long("interactive")
+                        .conflicts_with_all(&["shares", "at", "secret-at", "robust", "witness-shares", "transcript", "max-shares", "envelope", "groups"])
+                        .help("Prompts for the shares one at a time, validating each of them, and only displays the secret after confirmation"),
+                )
                 .arg(
                     Arg::with_name("at")
                         .long("at")
                         .takes_value(true)
                         .help("Where to reconstruct at [default: reconstruct the secret]"),
+                )
+                .arg(
+                    Arg::with_name("secret-at")
+                        .long("secret-at")
+                        .takes_value(true)
+                        .help("x coordinate where the secret was stored when splitting [default: 0]"),
+                )
+                .arg(
+                    Arg::with_name("allow-secret-point")
+                        .long("allow-secret-point")
+                        .requires("at")
+                        .help("Allows --at to be the x coordinate where the secret is stored (--secret-at, or 0 by default), which reveals the secret"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .help("Output format: human-readable text, or a JSON object with the parameters, the shares and the reconstructed secret"),
+                )
+                .arg(
+                    Arg::with_name("out-format")
+                        .long("out-format")
+                        .takes_value(true)
+                        .requires("out-file")
+                        .conflicts_with_all(&["interactive", "at"])
+                        .help("Writes the secret to --out-file instead of printing it: as an environment file with a single KEY_NAME=<secret> line (env:KEY_NAME), or as a credential encrypted with systemd-creds for LoadCredentialEncrypted= (systemd-credential)"),
+                )
+                .arg(
+                    Arg::with_name("out-file")
+                        .long("out-file")
+                        .takes_value(true)
+                        .requires("out-format")
+                        .help("Name of the file to write the secret to with --out-format, which must not exist yet; it's created readable only by the current user"),
+                )
+                .arg(
+                    Arg::with_name("simulate")
+                        .long("simulate")
+                        .conflicts_with_all(&["interactive", "at", "out-format"])
+                        .help("Runs the full reconstruction and consistency checks, but only outputs whether they succeed and a salted fingerprint of the secret (with the salt of --expect-hash, if any, so that it matches the commitment printed by split --commit-secret), never the secret itself"),
+                )
+                .arg(
+                    Arg::with_name("envelope")
+                        .long("envelope")
+                        .takes_value(true)
+                        .possible_values(&["real", "decoy"])
+                        .help("The shares are envelopes issued by split --decoy: reconstructs from their real or their decoy halves, with --threshold set to the corresponding threshold"),
+                )
+                .arg(
+                    Arg::with_name("groups")
+                        .long("groups")
+                        .takes_value(true)
+                        .requires("group-threshold")
+                        .conflicts_with_all(&["at", "secret-at", "robust", "witness-shares", "transcript", "envelope"])
+                        .help("The shares were split among groups by split --groups, with the same comma-separated list of groups such as 2of3,3of5 (ignoring --threshold)"),
+                )
+                .arg(
+                    Arg::with_name("group-threshold")
+                        .long("group-threshold")
+                        .takes_value(true)
+                        .requires("groups")
+                        .help("Minimum number of groups required to reconstruct the secret"),
+                )
+                .arg(
+                    Arg::with_name("access")
+                        .long("access")
+                        .takes_value(true)
+                        .conflicts_with_all(&["groups", "at", "secret-at", "robust", "witness-shares", "transcript", "envelope", "interactive"])
+                        .help("The shares were split by split --access, with the same access policy; reconstructs the secret from the custodians whose shares satisfy it, and reports them (ignoring --threshold)"),
+                )
+                .arg(
+                    Arg::with_name("max-shares")
+                        .long("max-shares")
+                        .takes_value(true)
+                        .help("Maximum number of shares to read from the file, the remaining ones are ignored; at least the threshold, and more to detect or correct corrupted shares [default: 255]"),
+                )
+                .arg(
+                    Arg::with_name("robust")
+                        .long("robust")
+                        .conflicts_with_all(&["at", "secret-at"])
+                        .help("Corrects corrupted shares when more shares than the threshold are given, and reports them"),
+                )
+                .arg(
+                    Arg::with_name("witness-shares")
+                        .long("witness-shares")
+                        .takes_value(true)
+                        .conflicts_with("at")
+                        .help("Name of a file containing witness shares to verify the reconstructed secret against"),
+                )
+                .arg(
+                    Arg::with_name("witness-threshold")
+                        .long("witness-threshold")
+                        .takes_value(true)
+                        .requires("witness-shares")
+                        .help("Minimum number of witness shares required to reconstruct the hash [default: --threshold]"),
+                )
+                .arg(
+                    Arg::with_name("expect-hash")
+                        .long("expect-hash")
+                        .takes_value(true)
+                        .conflicts_with("at")
+                        .help("Commitment to the secret printed by split --commit-secret, to verify the reconstructed secret against"),
+                )
+                .arg(
+                    Arg::with_name("transcript")
+                        .long("transcript")
+                        .takes_value(true)
+                        .requires("signing-key")
+                        .conflicts_with("at")
+                        .help("Name of a file to write a signed transcript of the reconstruction to"),
+                )
+                .arg(
+                    Arg::with_name("signing-key")
+                        .long("signing-key")
+                        .takes_value(true)
+                        .requires("transcript")
+                        .help("Name of a file containing the operator's Ed25519 signing key (32 bytes in hexadecimal) used to sign the transcript"),
                 ),
         )
-        .setting(AppSettings::SubcommandRequired)
-        .get_matches();
-
-    let bitsize_str = matches.value_of("bitsize").unwrap();
-    let shares_str = matches.value_of("nshares").unwrap();
-    let threshold_str = matches.value_of("threshold").unwrap();
-
-    let bitsize = bitsize_str
-        .parse::<usize>()
-        .expect("--bitsize must be an integer");
-    let shares = shares_str
-        .parse::<usize>()
-        .expect("--shares must be an integer");
-    let threshold = threshold_str
-        .parse::<usize>()
-        .expect("--threshold must be an integer");
-
-    assert!(
-        shares != 0 && shares <= 255,
-        "--shares must be between 1 and 255"
-    );
-    assert!(
-        threshold != 0 && threshold <= shares,
-        "--threshold must be between 1 and --shares"
-    );
-
-    match bitsize {
-        8 => dispatch_shamir_type::<GF8>(matches, threshold, shares),
-        16 => dispatch_shamir_type::<GF16>(matches, threshold, shares),
-        32 => dispatch_shamir_type::<GF32>(matches, threshold, shares),
-        64 => dispatch_shamir_type::<GF64>(matches, threshold, shares),
-        128 => dispatch_shamir_type::<GF128>(matches, threshold, shares),
-        256 => dispatch_shamir_type::<GF256>(matches, threshold, shares),
-        _ => panic!("Unsupported bitsize: {}", bitsize),
-    }
-}
-
-fn dispatch_shamir_type<F: Field + Debug + Display>(matches: ArgMatches, k: usize, n: usize) {
-    let shamir_type = matches.value_of("type").unwrap();
-    match shamir_type {
-        "compact" => process_command::<F, CompactShamir>(matches, k, n),
-        "random" => process_command::<F, RandomShamir>(matches, k, n),
-        _ => panic!("Unsupported shamir type: {}", shamir_type),
-    };
-}
-
-fn process_command<F: Field + Debug + Display, S: Shamir<F>>(
-    matches: ArgMatches,
-    k: usize,
-    n: usize,
-) where
-    S::Share: Display,
-{
-    match matches.subcommand() {
-        ("split", Some(args)) => split::<F, S>(args, k, n),
-        ("reconstruct", Some(args)) => reconstruct::<F, S>(args, k),
-        (command, _) => panic!("Unsupported command: {}", command),
-    };
-}
-
-fn split<F: Field + Debug + Display, S: Shamir<F>>(args: &ArgMatches, k: usize, n: usize)
-where
-    S::Share: Display,
-{
-    let secret = match args.value_of("secret") {
-        None => {
-            let mut rng = thread_rng();
-            F::uniform(&mut rng)
-        }
-        Some(filename) => parse_secret::<F>(filename),
-    };
-    println!("Secret = {}", secret);
-
-    let shares = S::split(&secret, k, n);
-    println!("Shares:");
-    for s in &shares {
-        println!("{}", s);
-    }
-}
-
-fn reconstruct<F: Field + Debug + Display, S: Shamir<F>>(args: &ArgMatches, k: usize)
-where
-    S::Share: Display,
-{
-    let shares = parse_shares::<F, S>(args.value_of("shares").unwrap());
-    println!("Shares:");
-    for s in &shares {
-        println!("{}", s);
-    }
-
-    assert!(
-        shares.len() >= k,
-        "Found fewer shares than the threshold, cannot reconstruct!"
-    );
-
-    match args.value_of("at") {
-        Some(at) => {
-            let x = S::parse_x(at).unwrap();
-            let share = S::reconstruct_at(&shares, k, x);
-            match share {
-                Some(s) => println!("Share = {}", s),
-                None => println!("Could not reconstruct the share..."),
-            }
-        }
-        None => {
-            let secret = S::reconstruct(&shares, k);
-            match secret {
-                Some(s) => println!("Secret = {}", s),
-                None => println!("Could not reconstruct the secret..."),
-            }
-        }
-    }
-}
-
-fn parse_secret<F: Field>(filename: &str) -> F {
-    let mut file = File::open(filename).unwrap();
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
-
-    let regex = Regex::new(r"^([0-9a-fA-F]+)\n?$").unwrap();
-    let captures = match regex.captures(&contents) {
-        Some(cap) => cap,
-        None => panic!("Secret file must contains hexadecimal characters only",),
-    };
-
-    let bytes = match hex::decode(&captures[1]) {
-        Ok(bytes) => bytes,
-        Err(e) => panic!(
-            "Couldn't parse secret file as hexadecimal characters: {}",
-            e
-        ),
-    };
-
-    match F::from_bytes(bytes.as_slice()) {
-        Some(f) => f,
-        None => panic!("Secret is not a valid represetation of a field element"),
-    }
-}
-
-fn parse_shares<F: Field + Debug + Display, S: Shamir<F>>(filename: &str) -> Vec<S::Share> {
-    let file = File::open(filename).unwrap();
-    BufReader::new(file)
-        .lines()
-        .map(|line| S::parse_share(&line.unwrap()).unwrap())
-        .collect()
+        .subcommand(
+            SubCommand::with_name("policy")
+                .about("Manage policies restricting the parameters of splits")
+                .subcommand(
+                    SubCommand::with_name("sign")
+                        .about("Signs a policy, to enforce it with split --policy")
+                        .arg(
+                            Arg::with_name("policy")
+                                .long("policy")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the unsigned policy"),
+                        )
+                        .arg(
+                            Arg::with_name("signing-key")
+                                .long("signing-key")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the Ed25519 signing key (32-byte seed in hexadecimal)"),
+                        )
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of the file to write the signed policy to"),
+                        ),
+                )
+                .setting(AppSettings::SubcommandRequired),
+        )
+        .subcommand(
+            SubCommand::with_name("manifest")
+                .about("Manage the signatures of split manifests (the JSON output of split) by the operators of a ceremony")
+                .subcommand(
+                    SubCommand::with_name("sign")
+                        .about("Adds the signature of an operator to a manifest, in place")
+                        .arg(
+                            Arg::with_name("manifest")
+                                .long("manifest")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the manifest"),
+                        )
+                        .arg(
+                            Arg::with_name("operator")
+                                .long("operator")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of the operator signing the manifest"),
+                        )
+                        .arg(
+                            Arg::with_name("signing-key")
+                                .long("signing-key")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the operator's Ed25519 signing key (32-byte seed in hexadecimal)"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about("Checks that a quorum of the expected operators validly signed a manifest")
+                        .arg(
+                            Arg::with_name("manifest")
+                                .long("manifest")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the manifest"),
+                        )
+                        .arg(
+                            Arg::with_name("public-keys")
+                                .long("public-keys")
+                                .takes_value(true)
+                                .required(true)
+                                .multiple(true)
+                                .use_delimiter(true)
+                                .help("Comma-separated names of files containing the Ed25519 public keys of the expected operators (32 bytes in hexadecimal)"),
+                        )
+                        .arg(
+                            Arg::with_name("quorum")
+                                .long("quorum")
+                                .takes_value(true)
+                                .help("Number of expected operators that must have signed the manifest [default: all of them]"),
+                        ),
+                )
+                .setting(AppSettings::SubcommandRequired),
+        )
+        .subcommand(
+            SubCommand::with_name("advise")
+                .about("Recommends a threshold for the given availability and secrecy requirements")
+                .arg(
+                    Arg::with_name("custodians")
+                        .long("custodians")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Number of custodians, each holding one share"),
+                )
+                .arg(
+                    Arg::with_name("tolerate-loss")
+                        .long("tolerate-loss")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Number of shares that may be lost without losing the secret"),
+                )
+                .arg(
+                    Arg::with_name("tolerate-compromise")
+                        .long("tolerate-compromise")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Number of custodians that may collude or be compromised without revealing the secret"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("plan-recovery")
+                .about("Prints the smallest set of locations to visit to collect enough shares, given which custodians are reachable; the location hints are revealed with the passphrase prompted on stdin")
+                .arg(
+                    Arg::with_name("locations")
+                        .long("locations")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of a file containing the sealed location hints written by split --sealed-locations"),
+                )
+                .arg(
+                    Arg::with_name("reachable")
+                        .long("reachable")
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .help("Comma-separated names of the custodians that can be reached"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("fieldcalc")
+                .about("Computes arithmetic operations on field elements (in hexadecimal), in the field selected by --field and --bitsize")
+                .arg(
+                    Arg::with_name("operation")
+                        .required(true)
+                        .possible_values(&["add", "sub", "mul", "invert", "pow"])
+                        .help("Operation to compute"),
+                )
+                .arg(
+                    Arg::with_name("operands")
+                        .required(true)
+                        .multiple(true)
+                        .help("Operands in hexadecimal, except for the exponent of pow which is a decimal integer"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("backends")
+                .about("Prints the implementations of field arithmetic used by this build on this CPU"),
+        )
+        .subcommand(
+            SubCommand::with_name("schema")
+                .about("Prints the schemas of the artifacts written by horcrux")
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about("Prints the JSON Schema of an artifact")
+                        .arg(
+                            Arg::with_name("artifact")
+                                .required(true)
+                                .possible_values(&["share", "manifest"])
+                                .help("Artifact to describe: a share in text format, or the JSON output of split"),
+                        ),
+                )
+                .setting(AppSettings::SubcommandRequired),
+        )
+        .subcommand(
+            SubCommand::with_name("decrypt-share")
+                .about("Decrypts the shares sealed to a recipient by split --recipients, ignoring the shares sealed to other recipients")
+                .arg(
+                    Arg::with_name("identity")
+                        .long("identity")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of a file containing the age identity of the recipient (an AGE-SECRET-KEY-1... key as generated by age-keygen, or an X25519 private key in hexadecimal)"),
+                )
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of a file containing the sealed shares, such as the output of split, or - to read them from stdin"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Reports the sizes, formats, x coordinates and refreshes of a set of shares, and the QR codes needed to print them")
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .takes_value(true)
+                        .required_unless("manifest")
+                        .conflicts_with("manifest")
+                        .help("Name of a file containing the shares"),
+                )
+                .arg(
+                    Arg::with_name("manifest")
+                        .long("manifest")
+                        .takes_value(true)
+                        .help("Name of a file containing the JSON output of split"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("explain")
+                .about("Explains a real split of a random secret in GF(2^8) (requires --bitsize 8), and plots the polynomial and the shares")
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .default_value("explain.svg")
+                        .help("Name of the SVG file to write the plot to"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("checklist")
+                .about("Prints a key ceremony checklist (in Markdown) tailored to the given parameters")
+                .arg(
+                    Arg::with_name("witnesses")
+                        .long("witnesses")
+                        .takes_value(true)
+                        .help("Number of witness shares that will be issued"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("entropy-test")
+                .about("Runs health tests on the random number generators, which split also runs before generating shares"),
+        )
+        .subcommand(
+            SubCommand::with_name("rotate")
+                .about("Refreshes shares, so that they reconstruct the same secret but cannot be combined with the old shares")
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of a file containing all the shares to refresh"),
+                )
+                .arg(
+                    Arg::with_name("secret-at")
+                        .long("secret-at")
+                        .takes_value(true)
+                        .help("x coordinate where the secret was stored when splitting [default: 0]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("info")
+                .about("Prints the verification code of a share, and checks it against the code read by the operator")
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of a file containing the share (only the first share is read)"),
+                )
+                .arg(
+                    Arg::with_name("envelope")
+                        .long("envelope")
+                        .takes_value(true)
+                        .possible_values(&["real", "decoy"])
+                        .help("The share is an envelope issued by split --decoy: checks its real or its decoy half"),
+                )
+                .arg(
+                    Arg::with_name("expect-code")
+                        .long("expect-code")
+                        .takes_value(true)
+                        .help("Verification code printed by split --codes, to check the share against"),
+                )
+                .arg(
+                    Arg::with_name("verify-key")
+                        .long("verify-key")
+                        .takes_value(true)
+                        .requires("expect-mac")
+                        .help("Name of a file containing the verification key given to the custodians, to check the share against its MAC"),
+                )
+                .arg(
+                    Arg::with_name("expect-mac")
+                        .long("expect-mac")
+                        .takes_value(true)
+                        .requires("verify-key")
+                        .help("MAC of the share printed by split --verify-key"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("extend")
+                .about("Issues additional shares of an existing split at new x coordinates, from at least the threshold of existing shares")
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of a file containing the existing shares, which must include all the x coordinates already issued to detect collisions"),
+                )
+                .arg(
+                    Arg::with_name("new-x")
+                        .long("new-x")
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .help("Comma-separated x coordinates of the new shares, which must differ from the existing ones, from 0 and from --secret-at"),
+                )
+                .arg(
+                    Arg::with_name("secret-at")
+                        .long("secret-at")
+                        .takes_value(true)
+                        .help("x coordinate where the secret was stored when splitting [default: 0]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("reshare")
+                .about("Converts shares into a new set of shares with another threshold, without reconstructing the secret")
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of a file containing the shares to convert"),
+                )
+                .arg(
+                    Arg::with_name("new-nshares")
+                        .long("new-nshares")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Total number of new shares (1 <= n <= 255)"),
+                )
+                .arg(
+                    Arg::with_name("new-threshold")
+                        .long("new-threshold")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Minimum number of new shares required to reconstruct the secret (1 <= t <= n)"),
+                )
+                .arg(
+                    Arg::with_name("secret-at")
+                        .long("secret-at")
+                        .takes_value(true)
+                        .help("x coordinate where the secret was stored when splitting, and stays in the new shares [default: 0]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("ndef")
+                .about("Converts shares to and from NDEF tag images, to store them on NFC tags")
+                .subcommand(
+                    SubCommand::with_name("encode")
+                        .about("Writes each share to a tag image named <output>-<i>.ndef")
+                        .arg(
+                            Arg::with_name("shares")
+                                .long("shares")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the shares to encode"),
+                        )
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Prefix of the tag images to write"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("decode")
+                        .about("Prints the shares stored in tag images")
+                        .arg(
+                            Arg::with_name("tags")
+                                .long("tags")
+                                .takes_value(true)
+                                .multiple(true)
+                                .required(true)
+                                .help("Names of files containing the tag images to decode"),
+                        ),
+                )
+                .setting(AppSettings::SubcommandRequired),
+        )
+        .subcommand(
+            SubCommand::with_name("transcript")
+                .about("Manage reconstruction transcripts")
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about("Verifies the signature and parameters of a reconstruction transcript")
+                        .arg(
+                            Arg::with_name("transcript")
+                                .long("transcript")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the transcript to verify"),
+                        )
+                        .arg(
+                            Arg::with_name("public-key")
+                                .long("public-key")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the operator's Ed25519 public key (32 bytes in hexadecimal)"),
+                        )
+                        .arg(
+                            Arg::with_name("shares")
+                                .long("shares")
+                                .takes_value(true)
+                                .help("Name of a file containing the shares that are expected to have been used"),
+                        ),
+                )
+                .setting(AppSettings::SubcommandRequired),
+        )
+        .subcommand(
+            SubCommand::with_name("totp")
+                .about("Splits batches of TOTP/2FA seeds, as otpauth:// URI lists or unencrypted Aegis or andOTP exports")
+                .subcommand(
+                    SubCommand::with_name("split")
+                        .about("Seals an export with a random key, and splits the key into shares")
+                        .arg(
+                            Arg::with_name("input")
+                                .long("input")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the export, or - to read it from stdin"),
+                        )
+                        .arg(
+                            Arg::with_name("sealed")
+                                .long("sealed")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file to write the sealed export to, to be stored alongside the shares"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("reconstruct")
+                        .about("Reconstructs the key from shares, and recovers the original export")
+                        .arg(
+                            Arg::with_name("shares")
+                                .long("shares")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the shares to reconstruct from"),
+                        )
+                        .arg(
+                            Arg::with_name("sealed")
+                                .long("sealed")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Name of a file containing the sealed export"),
+                        )
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .takes_value(true)
+                                .help("Name of a file to write the recovered export to [default: print it]"),
+                        ),
+                )
+                .setting(AppSettings::SubcommandRequired),
+        )
+        .setting(AppSettings::SubcommandRequired);
+    #[cfg(feature = "audio")]
+    let app = app.subcommand(
+        SubCommand::with_name("audio")
+            .about("Converts shares to and from FSK audio signals (experimental)")
+            .subcommand(
+                SubCommand::with_name("encode")
+                    .about("Writes each share to a WAV file named <output>-<i>.wav")
+                    .arg(
+                        Arg::with_name("shares")
+                            .long("shares")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Name of a file containing the shares to encode"),
+                    )
+                    .arg(
+                        Arg::with_name("output")
+                            .long("output")
+                            .takes_value(true)
+                            .required(true)
+                            .help("Prefix of the WAV files to write"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("decode")
+                    .about("Prints the shares transmitted in WAV recordings")
+                    .arg(
+                        Arg::with_name("input")
+                            .long("input")
+                            .takes_value(true)
+                            .multiple(true)
+                            .required(true)
+                            .help("Names of the WAV files to decode"),
+                    ),
+            )
+            .setting(AppSettings::SubcommandRequired),
+    );
+    let matches = app.clone().get_matches();
+    // Shares with a header describe the parameters of their split.
+    let matches = match header::complete_args(&matches) {
+        Some(args) => app.get_matches_from(args),
+        None => matches,
+    };
+
+    if let ("policy", Some(args)) = matches.subcommand() {
+        let args = args.subcommand_matches("sign").unwrap();
+        let key = transcript::parse_signing_key(args.value_of("signing-key").unwrap());
+        policy::Policy::read_unsigned(args.value_of("policy").unwrap())
+            .write(args.value_of("output").unwrap(), &key);
+        return;
+    }
+
+    if let ("manifest", Some(args)) = matches.subcommand() {
+        let read = |args: &ArgMatches| {
+            let filename = args.value_of("manifest").unwrap();
+            std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename))
+        };
+        match args.subcommand() {
+            ("sign", Some(args)) => {
+                let key = transcript::parse_signing_key(args.value_of("signing-key").unwrap());
+                let signed = manifest::sign(&read(args), args.value_of("operator").unwrap(), &key)
+                    .or_fail(ErrorCategory::Parse);
+                let filename = args.value_of("manifest").unwrap();
+                std::fs::write(filename, signed)
+                    .or_exit(format_args!("Could not write {}", filename));
+            }
+            ("verify", Some(args)) => {
+                let keys: Vec<_> = args
+                    .values_of("public-keys")
+                    .unwrap()
+                    .map(transcript::parse_verifying_key)
+                    .collect();
+                let quorum = args
+                    .value_of("quorum")
+                    .map_or(keys.len(), |q| parse_integer(q, "quorum"));
+                exit::ensure(
+                    quorum != 0 && quorum <= keys.len(),
+                    ErrorCategory::Usage,
+                    "--quorum must be between 1 and the number of --public-keys",
+                );
+                match manifest::verify(&read(args), &keys, quorum) {
+                    Ok(verification) => print!("{}", verification),
+                    Err(e) => exit::fail_with(
+                        ErrorCategory::Crypto,
+                        format_args!("Manifest verification failed!\n{}", e),
+                    ),
+                }
+            }
+            (command, _) => panic!("Unsupported manifest command: {}", command),
+        }
+        return;
+    }
+
+    if let ("advise", Some(args)) = matches.subcommand() {
+        let parse = |name: &str| parse_integer(args.value_of(name).unwrap(), name);
+        let requirements = advise::Requirements {
+            custodians: parse("custodians"),
+            tolerate_loss: parse("tolerate-loss"),
+            tolerate_compromise: parse("tolerate-compromise"),
+        };
+        match requirements.advise() {
+            Ok(advice) => print!("{}", advice),
+            Err(explanation) => exit::fail_with(
+                ErrorCategory::Usage,
+                format_args!("Impossible requirements!\n{}", explanation),
+            ),
+        }
+        return;
+    }
+
+    if let ("plan-recovery", Some(args)) = matches.subcommand() {
+        let filename = args.value_of("locations").unwrap();
+        let sealed =
+            std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename));
+        let passphrase = secret_input::read_passphrase("Location passphrase");
+        let (k, locations) = location::open(&sealed, &passphrase).or_fail(ErrorCategory::Crypto);
+        let reachable: Vec<&str> = args.values_of("reachable").unwrap().collect();
+        match location::plan(&locations, k, &reachable) {
+            Ok(plan) => print!("{}", plan),
+            Err(e) => exit::fail_with(
+                ErrorCategory::Threshold,
+                format_args!("Impossible recovery!\n{}", e),
+            ),
+        }
+        return;
+    }
+
+    if let ("fieldcalc", Some(args)) = matches.subcommand() {
+        let operation = args.value_of("operation").unwrap();
+        let operands: Vec<&str> = args.values_of("operands").unwrap().collect();
+        let result = selected_field(&matches)
+            .dispatch(fieldcalc::Computation {
+                operation,
+                operands: &operands,
+            })
+            .or_fail(ErrorCategory::Usage);
+        println!("{}", result);
+        return;
+    }
+
+    if let ("backends", Some(_)) = matches.subcommand() {
+        print!("{}", horcrux::backends::active());
+        return;
+    }
+
+    if let ("schema", Some(args)) = matches.subcommand() {
+        let args = args.subcommand_matches("export").unwrap();
+        match args.value_of("artifact").unwrap() {
+            "share" => print!("{}", schema::SHARE_JSON_SCHEMA),
+            _ => print!("{}", schema::MANIFEST_JSON_SCHEMA),
+        }
+        return;
+    }
+
+    if let ("decrypt-share", Some(args)) = matches.subcommand() {
+        decrypt_shares(args);
+        return;
+    }
+    if let ("stats", Some(args)) = matches.subcommand() {
+        let (filename, from_manifest) = match args.value_of("manifest") {
+            Some(filename) => (filename, true),
+            None => (args.value_of("shares").unwrap(), false),
+        };
+        let contents =
+            std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename));
+        let stats = match from_manifest {
+            true => stats::Stats::from_manifest(&contents).or_fail(ErrorCategory::Parse),
+            false => stats::Stats::from_shares(&contents),
+        };
+        print!("{}", stats);
+        return;
+    }
+
+    let required = |name: &str| {
+        matches.value_of(name).unwrap_or_else(|| {
+            clap::Error::with_description(
+                &format!("The argument '--{}' is required by this command", name),
+                clap::ErrorKind::MissingRequiredArgument,
+            )
+            .exit()
+        })
+    };
+    let bitsize_str = matches.value_of("bitsize").unwrap();
+    // The other commands don't need the number of shares.
+    let nested = matches
+        .subcommand()
+        .1
+        .and_then(|args| args.subcommand_name());
+    // Splits among groups or according to an access policy define their own thresholds and
+    // numbers of shares.
+    let grouped = matches
+        .subcommand()
+        .1
+        .is_some_and(|args| args.is_present("groups") || args.is_present("access"));
+    let shares_str = match (matches.subcommand_name(), nested) {
+        _ if grouped => matches.value_of("nshares").unwrap_or("255"),
+        (Some("split"), _)
+        | (Some("checklist"), _)
+        | (Some("explain"), _)
+        | (Some("totp"), Some("split")) => required("nshares"),
+        _ => matches.value_of("nshares").unwrap_or("255"),
+    };
+    // A single share can be inspected without knowing the threshold.
+    let threshold_str = match grouped || matches.subcommand_name() == Some("info") {
+        true => matches.value_of("threshold").unwrap_or("1"),
+        false => required("threshold"),
+    };
+
+    let bitsize = parse_integer(bitsize_str, "bitsize");
+    let shares = parse_integer(shares_str, "nshares");
+    let threshold = parse_integer(threshold_str, "threshold");
+
+    exit::ensure(
+        shares != 0 && shares <= 255,
+        ErrorCategory::Usage,
+        "--shares must be between 1 and 255",
+    );
+    exit::ensure(
+        threshold != 0 && threshold <= shares,
+        ErrorCategory::Usage,
+        "--threshold must be between 1 and --shares",
+    );
+
+    if let ("checklist", Some(args)) = matches.subcommand() {
+        let ceremony = checklist::Ceremony {
+            shamir_type: matches.value_of("type").unwrap(),
+            bitsize: bitsize_str,
+            nshares: shares,
+            threshold,
+            witnesses: args
+                .value_of("witnesses")
+                .map(|w| parse_integer(w, "witnesses")),
+        };
+        print!("{}", ceremony.checklist());
+        return;
+    }
+
+    if let ("explain", Some(args)) = matches.subcommand() {
+        exit::ensure(
+            matches.value_of("field").unwrap() == "binary" && bitsize == 8,
+            ErrorCategory::Usage,
+            "explain only supports the toy field GF(2^8), i.e. --bitsize 8",
+        );
+        print!(
+            "{}",
+            explain::explain(threshold, shares, args.value_of("output").unwrap())
+        );
+        return;
+    }
+
+    if let ("entropy-test", Some(_)) = matches.subcommand() {
+        let reports = entropy::test_sources();
+        for report in &reports {
+            println!("Source: {}", report.source);
+            for (test, ok) in &report.results {
+                println!("  {}: {}", test, if *ok { "pass" } else { "FAIL" });
+            }
+        }
+        exit::ensure(
+            reports.iter().all(|r| r.passed()),
+            ErrorCategory::Io,
+            "Some random sources failed the health tests!",
+        );
+        println!("All random sources passed the health tests");
+        return;
+    }
+
+    let shamir_type = matches.value_of("type").unwrap();
+    let scheme = DynShamir::from_name_in::<ExternalSchemes>(shamir_type, selected_field(&matches))
+        .unwrap_or_else(|| {
+            exit::fail_with(
+                ErrorCategory::Usage,
+                format_args!("Unsupported shamir type: {}", shamir_type),
+            )
+        });
+    scheme.dispatch_in::<ExternalSchemes, _>(Command {
+        matches: matches.clone(),
+        k: threshold,
+        n: shares,
+    });
+}
+
+/// Returns the field selected by --field and --bitsize.
+fn selected_field(matches: &ArgMatches) -> BuiltinField {
+    match matches.value_of("field").unwrap() {
+        "binary" => {
+            let bitsize = matches.value_of("bitsize").unwrap();
+            bitsize
+                .parse()
+                .ok()
+                .and_then(BuiltinField::binary)
+                .unwrap_or_else(|| {
+                    exit::fail_with(
+                        ErrorCategory::Usage,
+                        format_args!("Unsupported bitsize: {}", bitsize),
+                    )
+                })
+        }
+        curve => BuiltinField::from_name(curve).unwrap_or_else(|| {
+            exit::fail_with(
+                ErrorCategory::Usage,
+                format_args!("Unsupported field: {}", curve),
+            )
+        }),
+    }
+}
+
+/// Command to run with the field and Shamir scheme selected on the command line.
+struct Command<'a> {
+    matches: ArgMatches<'a>,
+    k: usize,
+    n: usize,
+}
+
+impl ShamirVisitor for Command<'_> {
+    type Output = ();
+
+    fn visit<F: Field + Debug + Display, S: Shamir<F>>(self)
+    where
+        S::Share: Display,
+    {
+        process_command::<F, S>(self.matches, self.k, self.n)
+    }
+}
+
+fn process_command<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: ArgMatches,
+    k: usize,
+    n: usize,
+) where
+    S::Share: Display,
+{
+    let x_key = matches.value_of("x-key").map(|key| {
+        exit::ensure(
+            matches.value_of("type").unwrap() == "random",
+            ErrorCategory::Usage,
+            "--x-key is only supported for random shares",
+        );
+        XKey::parse(key).or_exit("Invalid --x-key")
+    });
+    // Parameters that the headers of the shares must match, if any.
+    let expected = Header::new(&matches, k, String::new());
+
+    if let ("split", Some(args)) = matches.subcommand() {
+        era::Era::new(args)
+            .check_split(&matches, args)
+            .or_fail(ErrorCategory::Usage);
+    }
+
+    match matches.subcommand() {
+        ("split", Some(args)) if args.is_present("groups") => split_groups::<F, S>(&matches, args),
+        ("split", Some(args)) if args.is_present("access") => split_access::<F>(&matches, args),
+        ("split", Some(args)) => split::<F, S>(&matches, args, k, n, x_key),
+        ("reconstruct", Some(args)) if args.is_present("interactive") => {
+            reconstruct_interactive::<F, S>(args, k, x_key.as_ref(), &expected)
+        }
+        ("reconstruct", Some(args)) if args.is_present("groups") => {
+            reconstruct_groups::<F, S>(&matches, args)
+        }
+        ("reconstruct", Some(args)) if args.is_present("access") => {
+            reconstruct_access::<F>(&matches, args)
+        }
+        ("reconstruct", Some(args)) if args.value_of("shares").is_some_and(is_record_file) => {
+            reconstruct_records::<F, S>(&matches, args, k, &expected)
+        }
+        ("reconstruct", Some(args)) => {
+            reconstruct::<F, S>(&matches, args, k, x_key.as_ref(), &expected)
+        }
+        ("rotate", Some(args)) => rotate::<F, S>(args, k, x_key.as_ref(), &expected),
+        ("info", Some(args)) => info::<F, S>(args, x_key.as_ref(), &expected),
+        ("extend", Some(args)) => extend::<F, S>(args, k, x_key.as_ref(), &expected),
+        ("reshare", Some(args)) => reshare::<F, S>(args, k, x_key.as_ref(), &expected),
+        ("transcript", Some(args)) => match args.subcommand() {
+            ("verify", Some(args)) => {
+                verify_transcript::<F, S>(&matches, args, k, x_key.as_ref(), &expected)
+            }
+            (command, _) => panic!("Unsupported transcript command: {}", command),
+        },
+        ("totp", Some(args)) => match args.subcommand() {
+            ("split", Some(args)) => totp_split::<F, S>(args, k, n),
+            ("reconstruct", Some(args)) => {
+                totp_reconstruct::<F, S>(args, k, x_key.as_ref(), &expected)
+            }
+            (command, _) => panic!("Unsupported totp command: {}", command),
+        },
+        ("ndef", Some(args)) => match args.subcommand() {
+            ("encode", Some(args)) => {
+                ndef_encode::<F, S>(&matches, args, x_key.as_ref(), &expected)
+            }
+            ("decode", Some(args)) => ndef_decode::<F, S>(&matches, args),
+            (command, _) => panic!("Unsupported ndef command: {}", command),
+        },
+        #[cfg(feature = "audio")]
+        ("audio", Some(args)) => match args.subcommand() {
+            ("encode", Some(args)) => audio_encode::<F, S>(args, x_key.as_ref(), &expected),
+            ("decode", Some(args)) => audio_decode::<F, S>(args),
+            (command, _) => panic!("Unsupported audio command: {}", command),
+        },
+        (command, _) => panic!("Unsupported command: {}", command),
+    };
+}
+
+fn split<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+    k: usize,
+    n: usize,
+    x_key: Option<XKey>,
+) where
+    S::Share: Display,
+{
+    check_random_sources();
+    let policy_override = enforce_policy(matches, args, &[(k, n)]);
+
+    let secret_bytes = args.value_of("secret").map(read_secret);
+    if let Some(bytes) = &secret_bytes {
+        if bytes.len() > element_len::<F>() {
+            era::Era::new(args)
+                .check_records()
+                .or_fail(ErrorCategory::Usage);
+            return split_records::<F, S>(matches, args, k, n, bytes, policy_override);
+        }
+    }
+    if args.is_present("privacy-threshold") {
+        exit::fail_with(
+            ErrorCategory::Usage,
+            format_args!(
+                "--privacy-threshold is only supported for secrets larger than a field element ({} bytes), pass a plain --threshold instead",
+                element_len::<F>()
+            ),
+        );
+    }
+    let hash = hash_algorithm(args);
+    let mut dual_rng = args
+        .value_of("dual-rng")
+        .map(|filename| dual_rng::open(filename, hash));
+    let secret = Zeroizing::new(match &secret_bytes {
+        None => match &mut dual_rng {
+            Some(rng) => F::uniform(rng),
+            None => F::uniform(&mut thread_rng()),
+        },
+        Some(bytes) => secret_from_bytes::<F>(bytes),
+    });
+    let show_secret = args.is_present("show-secret");
+    let quiet = args.is_present("quiet");
+    let json = args.value_of("output") == Some("json");
+    let commit_secret = args.is_present("commit-secret");
+    let decoy = args.value_of("decoy").map(|filename| {
+        exit::ensure(
+            filename != "-" || args.value_of("secret") != Some("-"),
+            ErrorCategory::Usage,
+            "--secret and --decoy cannot both be read from stdin",
+        );
+        let k_decoy = parse_integer(args.value_of("decoy-threshold").unwrap(), "decoy-threshold");
+        (Zeroizing::new(parse_secret::<F>(filename)), k_decoy)
+    });
+
+    let mut x_key_used = None;
+    // The digest of compressed shares is the digest of the expanded shares, as read back by the
+    // other commands with the x key.
+    // The verification codes and MACs are computed on the canonical form of the shares, i.e. the
+    // expanded form of compressed shares, and the real halves of envelopes.
+    let (shares, polynom, digest, canonical): (Vec<String>, _, _, _) =
+        if args.is_present("compress-x") {
+            exit::ensure(
+                matches.value_of("type").unwrap() == "random",
+                ErrorCategory::Usage,
+                "--compress-x is only supported for random shares",
+            );
+            let key = x_key.unwrap_or_else(XKey::random);
+            let shares = RandomShamir::split_compressed(&*secret, k, n, &key)
+                .or_exit("Could not split the secret");
+            let expanded: Vec<_> = shares
+                .iter()
+                .map(|s| RandomShamir::expand_share(s, &key))
+                .collect::<Result<_, _>>()
+                .or_exit("Could not split the secret");
+            let polynom = show_secret.then(|| RandomShamir::interpolate(&expanded, k).unwrap());
+            let canonical = share_strings(&expanded);
+            let digest = ShareSet::<F, RandomShamir>::new(expanded).digest_with(hash);
+            x_key_used = Some(key);
+            (share_strings(&shares), polynom, digest, canonical)
+        } else if let Some((decoy, k_decoy)) = &decoy {
+            let envelopes = match &mut dual_rng {
+                Some(rng) => decoy::split_with_rng::<F, S, _>(&secret, k, decoy, *k_decoy, n, rng),
+                None => decoy::split::<F, S>(&secret, k, decoy, *k_decoy, n),
+            }
+            .or_exit("Could not split the secret");
+            let real: Vec<S::Share> = envelopes.iter().map(|envelope| envelope.real).collect();
+            let polynom = show_secret.then(|| S::interpolate(&real, k).unwrap());
+            // The digest is the digest of the real halves, as read back with --envelope real.
+            let canonical = share_strings(&real);
+            let digest = ShareSet::<F, S>::new(real).digest_with(hash);
+            (share_strings(&envelopes), polynom, digest, canonical)
+        } else {
+            let shares = match args.value_of("secret-at") {
+                None => match &mut dual_rng {
+                    Some(rng) => S::split_with_rng(&secret, k, n, rng),
+                    None => S::split(&secret, k, n),
+                },
+                Some(c) => {
+                    let c = S::parse_x(c).or_exit("Invalid --secret-at coordinate");
+                    match &mut dual_rng {
+                        Some(rng) => S::split_at_with_rng(&secret, k, n, c, rng),
+                        None => S::split_at(&secret, k, n, c),
+                    }
+                }
+            }
+            .or_exit("Could not split the secret");
+            let polynom = show_secret.then(|| S::interpolate(&shares, k).unwrap());
+            let digest = ShareSet::<F, S>::new(shares.clone()).digest_with(hash);
+            (
+                share_strings(&shares),
+                polynom,
+                digest,
+                share_strings(&shares),
+            )
+        };
+    let codes = share_codes(hash, &canonical);
+    let macs = args.value_of("verify-key").map(|filename| {
+        let key = Zeroizing::new(transcript::parse_key_bytes(filename));
+        canonical
+            .iter()
+            .map(|s| share_mac::tag(hash, &*key, s))
+            .collect::<Vec<_>>()
+    });
+
+    // Commitments to the contributions of the OS RNG and of the second source.
+    let commitments = dual_rng.map(|rng| {
+        let (os, second) = rng.into_inner();
+        (os.commitment(), second.commitment())
+    });
+
+    if let Some(filename) = args.value_of("locations") {
+        seal_locations(args, filename, k, n);
+    }
+
+    let shares: Vec<String> = if args.is_present("checksum") {
+        shares
+            .iter()
+            .map(|s| append_checksum(s).unwrap_or_else(checksum_too_long))
+            .collect()
+    } else {
+        shares
+    };
+
+    let header = split_header(matches, args, k);
+    let shares: Vec<String> = match &header {
+        Some(header) => shares.iter().map(|s| format!("{}{}", header, s)).collect(),
+        None => shares,
+    };
+
+    let shares = seal_shares(args, shares);
+
+    let witness_shares = args.value_of("witnesses").map(|witnesses| {
+        let witnesses = parse_integer(witnesses, "witnesses");
+        let witness_threshold = parse_witness_threshold(args, k);
+        exit::ensure(
+            witnesses != 0 && witnesses <= 255,
+            ErrorCategory::Usage,
+            "--witnesses must be between 1 and 255",
+        );
+        exit::ensure(
+            witness_threshold != 0 && witness_threshold <= witnesses,
+            ErrorCategory::Usage,
+            "--witness-threshold must be between 1 and --witnesses",
+        );
+        witness::split(hash, &*secret, witness_threshold, witnesses)
+    });
+
+    if json {
+        let mut output = json_header(matches, k);
+        output.insert("version".into(), schema::MANIFEST_VERSION.into());
+        output.insert("nshares".into(), n.into());
+        if let Some(header) = &header {
+            output.insert("set_id".into(), header.set_id.clone().into());
+        }
+        if let Some(key) = &x_key_used {
+            output.insert("x_key".into(), key.to_string().into());
+        }
+        if let Some(c) = args.value_of("secret-at") {
+            output.insert("secret_at".into(), c.into());
+        }
+        if let Some((decoy, k_decoy)) = &decoy {
+            output.insert("decoy_threshold".into(), (*k_decoy).into());
+            if show_secret {
+                output.insert("decoy".into(), decoy.to_string().into());
+            }
+        }
+        if show_secret {
+            output.insert("secret".into(), secret.to_string().into());
+        }
+        if commit_secret {
+            output.insert(
+                "secret_commitment".into(),
+                secret_commitment(hash, &*secret).into(),
+            );
+        }
+        if let Some(polynom) = &polynom {
+            let coefficients: Vec<String> = polynom
+                .coefficients()
+                .iter()
+                .map(|c| c.to_string())
+                .collect();
+            output.insert("polynom".into(), coefficients.into());
+        }
+        output.insert("shares".into(), shares.into());
+        output.insert("shares_digest".into(), hex::encode(digest).into());
+        if let Some((os, second)) = &commitments {
+            output.insert(
+                "rng_commitments".into(),
+                serde_json::json!({ "os": os, "second": second }),
+            );
+        }
+        if args.is_present("codes") {
+            output.insert("verification_codes".into(), codes.into());
+        }
+        if let Some(macs) = &macs {
+            output.insert("share_macs".into(), macs.clone().into());
+        }
+        if let Some(witness_shares) = witness_shares {
+            output.insert("witness_shares".into(), witness_shares.into());
+        }
+        if let Some(policy_override) = &policy_override {
+            output.insert("policy_override".into(), policy_override.to_json());
+        }
+        print_json(output);
+        return;
+    }
+
+    if let Some(policy_override) = &policy_override {
+        policy_override.print();
+    }
+    if show_secret {
+        println!("Secret = {}", *secret);
+        if let Some((decoy, _)) = &decoy {
+            println!("Decoy = {}", **decoy);
+        }
+    }
+    if let Some((_, k_decoy)) = &decoy {
+        if !quiet {
+            println!("Decoy threshold = {}", k_decoy);
+        }
+    }
+    if commit_secret {
+        println!("Secret commitment = {}", secret_commitment(hash, &*secret));
+    }
+    if let Some(key) = &x_key_used {
+        println!("X key = {}", key);
+    }
+    if let Some((os, second)) = &commitments {
+        if !quiet {
+            println!("OS RNG commitment = {}", os);
+            println!("Second RNG commitment = {}", second);
+        }
+    }
+    if let Some(c) = args.value_of("secret-at") {
+        if !quiet {
+            println!("Secret stored at x = {}", c);
+        }
+    }
+    if let Some(polynom) = polynom {
+        for (i, c) in polynom.coefficients().iter().enumerate() {
+            match i {
+                0 => println!("Polynom = {}", c),
+                _ => println!("    + {} x^{}", c, i),
+            }
+        }
+    }
+    if !quiet {
+        println!("Shares:");
+    }
+    for s in &shares {
+        println!("{}", s);
+    }
+    if args.is_present("codes") {
+        println!("Verification codes:");
+        for (code, s) in codes.iter().zip(&shares) {
+            println!("{}  {}", code, s);
+        }
+    }
+    if let Some(macs) = &macs {
+        println!("Share MACs:");
+        for (mac, s) in macs.iter().zip(&shares) {
+            println!("{}  {}", mac, s);
+        }
+    }
+    if let Some(witness_shares) = witness_shares {
+        if !quiet {
+            println!("Witness shares:");
+        }
+        for s in &witness_shares {
+            println!("{}", s);
+        }
+    }
+}
+
+/// Seals each share to its recipient, if --recipients is given.
+fn seal_shares(args: &ArgMatches, shares: Vec<String>) -> Vec<String> {
+    let filename = match args.value_of("recipients") {
+        Some(filename) => filename,
+        None => return shares,
+    };
+    let contents =
+        std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename));
+    let recipients =
+        recipient::parse_recipients(&contents, shares.len()).or_fail(ErrorCategory::Parse);
+    shares
+        .iter()
+        .zip(&recipients)
+        .map(|(share, key)| recipient::seal(key, share).or_fail(ErrorCategory::Crypto))
+        .collect()
+}
+
+/// Prints the shares sealed to the recipient of --identity, among the lines of --shares.
+fn decrypt_shares(args: &ArgMatches) {
+    let filename = args.value_of("identity").unwrap();
+    let identity = Zeroizing::new(
+        std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename)),
+    );
+    let identity = recipient::parse_identity(&identity).or_fail(ErrorCategory::Parse);
+
+    let mut contents = String::new();
+    match args.value_of("shares").unwrap() {
+        "-" => std::io::stdin().read_to_string(&mut contents),
+        filename => File::open(filename)
+            .or_exit(format_args!("Could not open {}", filename))
+            .read_to_string(&mut contents),
+    }
+    .or_exit("Could not read the sealed shares");
+
+    let mut count = 0;
+    for line in contents
+        .lines()
+        .map(|line| line.trim().trim_matches('"').trim_end_matches(['"', ',']))
+        .filter(|line| line.starts_with("horcrux-sealed-"))
+    {
+        if let Some(share) = recipient::open(&identity, line).or_fail(ErrorCategory::Crypto) {
+            println!("{}", *share);
+            count += 1;
+        }
+    }
+    exit::ensure(
+        count != 0,
+        ErrorCategory::Usage,
+        format_args!(
+            "No share is sealed to the recipient {}",
+            recipient::public_key(&identity)
+        ),
+    );
+}
+
+/// Seals the location hints of the shares with a passphrase, and writes them to the file given by
+/// --sealed-locations.
+fn seal_locations(args: &ArgMatches, filename: &str, k: usize, n: usize) {
+    let contents = Zeroizing::new(
+        std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename)),
+    );
+    let locations = location::parse(&contents, n).or_fail(ErrorCategory::Parse);
+    exit::ensure(
+        args.value_of("secret") != Some("-") || secret_input::is_interactive(),
+        ErrorCategory::Usage,
+        "The location passphrase cannot be read from stdin after the secret",
+    );
+    let passphrase = secret_input::read_passphrase("Location passphrase");
+    if secret_input::is_interactive() {
+        let confirmation = secret_input::read_passphrase("Confirm the location passphrase");
+        exit::ensure(
+            *passphrase == *confirmation,
+            ErrorCategory::Usage,
+            "The passphrases don't match",
+        );
+    }
+    exit::ensure(
+        !passphrase.is_empty(),
+        ErrorCategory::Usage,
+        "The location passphrase is empty",
+    );
+    let sealed = location::seal(&locations, k, &passphrase, hash_algorithm(args));
+    let output = args.value_of("sealed-locations").unwrap();
+    std::fs::write(output, sealed).or_exit(format_args!("Could not write {}", output));
+}
+
+/// Refuses to split if a random source fails the health tests.
+fn check_random_sources() {
+    for report in entropy::test_sources() {
+        for (test, ok) in &report.results {
+            exit::ensure(
+                *ok,
+                ErrorCategory::Io,
+                format_args!(
+                    "Random source {} failed the {} health test, refusing to split!",
+                    report.source, test
+                ),
+            );
+        }
+    }
+}
+
+/// Parses the `--group-threshold` and `--groups` arguments.
+fn parse_groups(args: &ArgMatches) -> (usize, Vec<Group>) {
+    let group_threshold =
+        parse_integer(args.value_of("group-threshold").unwrap(), "group-threshold");
+    let groups = args
+        .value_of("groups")
+        .unwrap()
+        .split(',')
+        .map(|group| {
+            group.parse::<Group>().unwrap_or_else(|_| {
+                exit::fail_with(
+                    ErrorCategory::Usage,
+                    format_args!("Invalid group {}, expected e.g. 2of3", group),
+                )
+            })
+        })
+        .collect();
+    (group_threshold, groups)
+}
+
+/// Parses the `--access` argument.
+fn parse_access(args: &ArgMatches) -> AccessPolicy {
+    args.value_of("access")
+        .unwrap()
+        .parse()
+        .map_err(|e| format!("Invalid --access: {}", e))
+        .or_fail(ErrorCategory::Usage)
+}
+
+/// Appends the threshold and number of children of each node of the access policy, to check them
+/// against the --policy.
+fn access_splits(access: &AccessPolicy, splits: &mut Vec<(usize, usize)>) {
+    if let AccessPolicy::Threshold(k, children) = access {
+        splits.push((*k, children.len()));
+        children
+            .iter()
+            .for_each(|child| access_splits(child, splits));
+    }
+}
+
+/// Checks the split against the policy given with --policy, if any, for the given pairs of
+/// threshold and number of shares. Refuses the split if it's outside of the policy, unless the
+/// policy is overridden, in which case the override is returned to be recorded in the output.
+fn enforce_policy(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+    splits: &[(usize, usize)],
+) -> Option<policy::Override> {
+    let filename = args.value_of("policy")?;
+    let key = transcript::parse_verifying_key(args.value_of("policy-key").unwrap());
+    let policy = policy::Policy::read_verified(filename, &key);
+    let violations = policy.violations(&Header::new(matches, 0, String::new()), splits);
+    if violations.is_empty() {
+        return None;
+    }
+    match args.value_of("override-policy") {
+        None => exit::fail_with(
+            ErrorCategory::Usage,
+            format_args!(
+                "The split is outside of the policy: {}. Pass --override-policy <justification> to split anyway",
+                violations.join("; ")
+            ),
+        ),
+        Some(justification) => {
+            eprintln!("Warning: overriding the policy: {}", violations.join("; "));
+            Some(policy::Override {
+                violations,
+                justification: justification.to_owned(),
+            })
+        }
+    }
+}
+
+/// Returns the header of the shares of a new split, if requested with --header.
+fn split_header(matches: &ArgMatches, args: &ArgMatches, k: usize) -> Option<Header> {
+    args.is_present("header").then(|| {
+        let set_id = match args.value_of("set-id") {
+            Some(id) => {
+                exit::ensure(
+                    id.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+                    ErrorCategory::Usage,
+                    "--set-id must only contain alphanumeric characters, '-' and '_'",
+                );
+                id.to_owned()
+            }
+            None => header::random_set_id(),
+        };
+        Header::new(matches, k, set_id)
+    })
+}
+
+/// Splits a secret larger than a field element into records of several elements, one per
+/// custodian (see `horcrux::record`). The last element is padded with zeros.
+fn split_records<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+    k: usize,
+    n: usize,
+    bytes: &[u8],
+    policy_override: Option<policy::Override>,
+) where
+    S::Share: Display,
+{
+    exit::ensure(
+        matches.value_of("field").unwrap() == "binary",
+        ErrorCategory::Usage,
+        "Secrets larger than a field element are only supported in binary fields",
+    );
+    for option in &[
+        "decoy",
+        "witnesses",
+        "compress-x",
+        "secret-at",
+        "dual-rng",
+        "show-secret",
+        "commit-secret",
+        "checksum",
+        "codes",
+        "verify-key",
+    ] {
+        exit::ensure(
+            !args.is_present(option),
+            ErrorCategory::Usage,
+            format_args!(
+                "--{} is not supported for secrets larger than a field element",
+                option
+            ),
+        );
+    }
+
+    let element_len = element_len::<F>();
+    let secret = Zeroizing::new(
+        bytes
+            .chunks(element_len)
+            .map(|chunk| {
+                let mut padded = Zeroizing::new(vec![0; element_len]);
+                padded[..chunk.len()].copy_from_slice(chunk);
+                secret_from_bytes::<F>(&padded)
+            })
+            .collect::<Vec<F>>(),
+    );
+    let ramp = args.value_of("privacy-threshold").map(|t| {
+        let t = parse_integer(t, "privacy-threshold");
+        Ramp::new(t, k).unwrap_or_else(|_| {
+            exit::fail_with(
+                ErrorCategory::Usage,
+                "--privacy-threshold must be between 1 and --threshold minus 1",
+            )
+        })
+    });
+    let records = match ramp {
+        None => record::split_with_hash::<F, S, _>(
+            &secret,
+            bytes.len(),
+            k,
+            n,
+            hash_algorithm(args),
+            &mut thread_rng(),
+        ),
+        Some(ramp) => record::split_ramp_with_hash::<F, S, _>(
+            &secret,
+            bytes.len(),
+            ramp,
+            n,
+            hash_algorithm(args),
+            &mut thread_rng(),
+        ),
+    }
+    .or_exit("Could not split the secret");
+    if let Some(filename) = args.value_of("locations") {
+        seal_locations(args, filename, k, n);
+    }
+    let header = split_header(matches, args, k);
+    let records: Vec<String> = records
+        .iter()
+        .map(|r| match &header {
+            Some(header) => format!("{}{}", header, r),
+            None => r.to_string(),
+        })
+        .collect();
+    let records = seal_shares(args, records);
+
+    if args.value_of("output") == Some("json") {
+        let mut output = json_header(matches, k);
+        output.insert("version".into(), schema::MANIFEST_VERSION.into());
+        output.insert("nshares".into(), n.into());
+        output.insert("secret_len".into(), bytes.len().into());
+        if let Some(ramp) = &ramp {
+            output.insert("privacy_threshold".into(), ramp.privacy().into());
+        }
+        if let Some(header) = &header {
+            output.insert("set_id".into(), header.set_id.clone().into());
+        }
+        output.insert("shares".into(), records.into());
+        if let Some(policy_override) = &policy_override {
+            output.insert("policy_override".into(), policy_override.to_json());
+        }
+        print_json(output);
+        return;
+    }
+
+    if let Some(policy_override) = &policy_override {
+        policy_override.print();
+    }
+    if !args.is_present("quiet") {
+        println!(
+            "Secret length = {} bytes, in {} field elements",
+            bytes.len(),
+            secret.len()
+        );
+        if let Some(ramp) = &ramp {
+            println!(
+                "Privacy threshold = {}, with {} field elements per share",
+                ramp.privacy(),
+                ramp.packing()
+            );
+        }
+        println!("Shares:");
+    }
+    for r in &records {
+        println!("{}", r);
+    }
+}
+
+fn split_groups<F: Field + Debug + Display, S: Shamir<F>>(matches: &ArgMatches, args: &ArgMatches)
+where
+    S::Share: Display,
+{
+    check_random_sources();
+    let (group_threshold, groups) = parse_groups(args);
+    let splits: Vec<(usize, usize)> = groups.iter().map(|g| (g.threshold, g.count)).collect();
+    let policy_override = enforce_policy(matches, args, &splits);
+
+    let secret = Zeroizing::new(match args.value_of("secret") {
+        None => F::uniform(&mut thread_rng()),
+        Some(filename) => parse_secret::<F>(filename),
+    });
+    let shares = GroupShamir::split::<F, S>(&secret, group_threshold, &groups)
+        .or_exit("Could not split the secret");
+    // The checksum covers the member's share, after the index of the group.
+    let checksum = args.is_present("checksum");
+    let shares: Vec<(u8, String)> = shares
+        .iter()
+        .map(|s| {
+            let member = s.share.to_string();
+            let member = match checksum {
+                true => append_checksum(&member).unwrap_or_else(checksum_too_long),
+                false => member,
+            };
+            (s.group, format!("{}/{}", s.group, member))
+        })
+        .collect();
+
+    if args.value_of("output") == Some("json") {
+        let mut output = json_header(matches, group_threshold);
+        output.remove("threshold");
+        output.insert("version".into(), schema::MANIFEST_VERSION.into());
+        output.insert("group_threshold".into(), group_threshold.into());
+        output.insert("groups".into(), share_strings(&groups).into());
+        if args.is_present("show-secret") {
+            output.insert("secret".into(), secret.to_string().into());
+        }
+        if args.is_present("commit-secret") {
+            output.insert(
+                "secret_commitment".into(),
+                secret_commitment(hash_algorithm(args), &*secret).into(),
+            );
+        }
+        let shares: Vec<String> = shares.into_iter().map(|(_, s)| s).collect();
+        output.insert("shares".into(), shares.into());
+        if let Some(policy_override) = &policy_override {
+            output.insert("policy_override".into(), policy_override.to_json());
+        }
+        print_json(output);
+        return;
+    }
+
+    if let Some(policy_override) = &policy_override {
+        policy_override.print();
+    }
+    if args.is_present("show-secret") {
+        println!("Secret = {}", *secret);
+    }
+    if args.is_present("commit-secret") {
+        println!(
+            "Secret commitment = {}",
+            secret_commitment(hash_algorithm(args), &*secret)
+        );
+    }
+    let quiet = args.is_present("quiet");
+    if !quiet {
+        println!("Group threshold = {}", group_threshold);
+    }
+    for (index, group) in (1..).zip(&groups) {
+        if !quiet {
+            println!(
+                "Shares of group {} ({} of {}):",
+                index, group.threshold, group.count
+            );
+        }
+        for (_, s) in shares.iter().filter(|(g, _)| *g == index) {
+            println!("{}", s);
+        }
+    }
+}
+
+fn split_access<F: Field + Debug + Display>(matches: &ArgMatches, args: &ArgMatches) {
+    exit::ensure(
+        matches.value_of("type").unwrap() == "compact",
+        ErrorCategory::Usage,
+        "--access is only supported for compact shares",
+    );
+    check_random_sources();
+    let access = parse_access(args);
+    let mut splits = Vec::new();
+    access_splits(&access, &mut splits);
+    let policy_override = enforce_policy(matches, args, &splits);
+
+    let secret = Zeroizing::new(match args.value_of("secret") {
+        None => F::uniform(&mut thread_rng()),
+        Some(filename) => parse_secret::<F>(filename),
+    });
+    let shares = access.split(&*secret).or_exit("Could not split the secret");
+
+    if args.value_of("output") == Some("json") {
+        let mut output = json_header(matches, 0);
+        output.remove("threshold");
+        output.insert("version".into(), schema::MANIFEST_VERSION.into());
+        output.insert("access".into(), access.to_string().into());
+        if args.is_present("show-secret") {
+            output.insert("secret".into(), secret.to_string().into());
+        }
+        if args.is_present("commit-secret") {
+            output.insert(
+                "secret_commitment".into(),
+                secret_commitment(hash_algorithm(args), &*secret).into(),
+            );
+        }
+        output.insert("shares".into(), share_strings(&shares).into());
+        if let Some(policy_override) = &policy_override {
+            output.insert("policy_override".into(), policy_override.to_json());
+        }
+        print_json(output);
+        return;
+    }
+
+    if let Some(policy_override) = &policy_override {
+        policy_override.print();
+    }
+    if args.is_present("show-secret") {
+        println!("Secret = {}", *secret);
+    }
+    if args.is_present("commit-secret") {
+        println!(
+            "Secret commitment = {}",
+            secret_commitment(hash_algorithm(args), &*secret)
+        );
+    }
+    if args.is_present("quiet") {
+        for s in &shares {
+            println!("{}", s);
+        }
+        return;
+    }
+    println!("Access policy = {}", access);
+    for custodian in access.custodians() {
+        println!("Shares of {}:", custodian);
+        for s in shares.iter().filter(|s| s.custodian == custodian) {
+            println!("{}", s);
+        }
+    }
+}
+
+/// Returns the parameters common to the JSON outputs.
+fn json_header(matches: &ArgMatches, k: usize) -> serde_json::Map<String, serde_json::Value> {
+    let mut output = serde_json::Map::new();
+    output.insert("scheme".into(), matches.value_of("type").unwrap().into());
+    output.insert("field".into(), matches.value_of("field").unwrap().into());
+    let bitsize: usize = matches.value_of("bitsize").unwrap().parse().unwrap();
+    output.insert("bitsize".into(), bitsize.into());
+    output.insert("threshold".into(), k.into());
+    output.insert("hash".into(), hash_algorithm(matches).id().into());
+    output
+}
+
+/// Parses --out-format and --out-file, checking that the file doesn't exist yet, so that errors are
+/// reported before reading the shares.
+/// Where to output the reconstructed secret.
+enum SecretOutput {
+    /// Prints the secret, or adds it to the JSON output.
+    Print,
+    /// Writes the secret to a file with --out-format and --out-file.
+    File(OutFormat, String),
+    /// Only outputs the fingerprint of the secret with the given algorithm and salt, with
+    /// --simulate.
+    Fingerprint(HashAlgorithm, [u8; 32]),
+}
+
+fn parse_secret_output(args: &ArgMatches) -> SecretOutput {
+    if args.is_present("simulate") {
+        // The salt of the expected commitment is reused, so that the fingerprint matches it.
+        let salt = match args.value_of("expect-hash") {
+            Some(expected_hash) => commitment_salt(expected_hash),
+            None => transcript::generate_salt(),
+        };
+        return SecretOutput::Fingerprint(hash_algorithm(args), salt);
+    }
+    let format = match args.value_of("out-format") {
+        Some(format) => format,
+        None => return SecretOutput::Print,
+    };
+    let format = OutFormat::parse(format)
+        .map_err(|e| format!("Invalid --out-format: {}", e))
+        .or_fail(ErrorCategory::Usage);
+    let filename = args.value_of("out-file").unwrap();
+    exit::ensure(
+        !std::path::Path::new(filename).exists(),
+        ErrorCategory::Usage,
+        format_args!("{} already exists, refusing to overwrite it", filename),
+    );
+    SecretOutput::File(format, filename.to_owned())
+}
+
+/// Outputs the reconstructed secret (in hexadecimal): either prints it or adds it to the JSON
+/// output, or only writes it to the file given by --out-file. With --simulate, only the fingerprint
+/// of the secret is output, in the format of the commitments printed by split --commit-secret.
+fn output_secret(
+    secret_output: &SecretOutput,
+    output: &mut Option<serde_json::Map<String, serde_json::Value>>,
+    secret: &str,
+) {
+    match (secret_output, output) {
+        (SecretOutput::File(format, filename), output) => {
+            secret_output::write(format, filename, secret);
+            match output {
+                Some(output) => {
+                    output.insert("secret_file".into(), filename.as_str().into());
+                }
+                None => println!("Secret written to {}", filename),
+            }
+        }
+        (SecretOutput::Fingerprint(hash, salt), output) => {
+            let bytes = Zeroizing::new(hex::decode(secret).unwrap());
+            let fingerprint = salted_commitment(*hash, salt, &bytes);
+            match output {
+                Some(output) => {
+                    output.insert("simulated".into(), true.into());
+                    output.insert("secret_fingerprint".into(), fingerprint.into());
+                }
+                None => {
+                    println!("Simulation: the secret can be reconstructed (not displayed)");
+                    println!("Secret fingerprint = {}", fingerprint);
+                }
+            }
+        }
+        (SecretOutput::Print, Some(output)) => {
+            output.insert("secret".into(), secret.into());
+        }
+        (SecretOutput::Print, None) => println!("Secret = {}", secret),
+    }
+}
+
+fn print_json(output: serde_json::Map<String, serde_json::Value>) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::Value::Object(output)).unwrap()
+    );
+}
+
+/// Returns the hash algorithm given with --hash.
+fn hash_algorithm(args: &ArgMatches) -> HashAlgorithm {
+    args.value_of("hash").unwrap().parse().unwrap()
+}
+
+/// Returns a new commitment to a secret, i.e. a random salt followed by the fingerprint of the
+/// secret keyed by this salt, in hexadecimal. The salt blinds the commitment, so that it can't be
+/// checked against guesses of the secret without the salt.
+fn secret_commitment<F: Field + Display>(hash: HashAlgorithm, secret: &F) -> String {
+    let secret = Zeroizing::new(secret.to_bytes());
+    salted_commitment(hash, &transcript::generate_salt(), &secret)
+}
+
+/// Returns the commitment to the bytes of a secret with the given salt.
+fn salted_commitment(hash: HashAlgorithm, salt: &[u8; 32], secret: &[u8]) -> String {
+    hex::encode(salt) + &transcript::fingerprint(hash, salt, secret)
+}
+
+/// Returns the salt of the commitment given with `--expect-hash`, i.e. its first 32 bytes.
+fn commitment_salt(expected_hash: &str) -> [u8; 32] {
+    hex::decode(expected_hash)
+        .ok()
+        .filter(|bytes| bytes.len() == 64)
+        .map(|bytes| bytes[..32].try_into().unwrap())
+        .unwrap_or_else(|| {
+            exit::fail_with(
+                ErrorCategory::Usage,
+                "--expect-hash must be 128 hexadecimal characters",
+            )
+        })
+}
+
+/// Checks that the reconstructed secret matches the commitment given with `--expect-hash`.
+fn check_commitment<F: Field + Display>(hash: HashAlgorithm, secret: &F, expected_hash: &str) {
+    let secret = Zeroizing::new(secret.to_bytes());
+    let salt = commitment_salt(expected_hash);
+    let commitment = salted_commitment(hash, &salt, &secret);
+    exit::ensure(
+        commitment.eq_ignore_ascii_case(expected_hash),
+        ErrorCategory::Crypto,
+        "The reconstructed secret doesn't match the expected hash, a share was probably mistyped!",
+    );
+}
+
+/// Fails with the exit code of `ShamirError::NotEnoughShares` if fewer shares than the threshold
+/// were found.
+fn check_enough_shares(found: usize, k: usize, action: &str) {
+    if found < k {
+        Err::<(), _>(ShamirError::NotEnoughShares).or_exit(action);
+    }
+}
+
+/// Fails with a usage error, for shares too long for a checksum.
+fn checksum_too_long() -> String {
+    exit::fail_with(ErrorCategory::Usage, "Shares are too long for a checksum")
+}
+
+/// Parses the value of the given integer argument, failing with a usage error otherwise.
+fn parse_integer(value: &str, name: &str) -> usize {
+    value.parse().unwrap_or_else(|_| {
+        exit::fail_with(
+            ErrorCategory::Usage,
+            format_args!("--{} must be an integer", name),
+        )
+    })
+}
+
+/// Returns the x coordinate where the secret was stored when splitting, given by --secret-at, or
+/// zero by default.
+fn secret_point<F: Field, S: Shamir<F>>(args: &ArgMatches) -> S::X {
+    args.value_of("secret-at").map_or(S::X::from(0), |c| {
+        S::parse_x(c).or_exit("Invalid --secret-at coordinate")
+    })
+}
+
+fn parse_witness_threshold(args: &ArgMatches, k: usize) -> usize {
+    match args.value_of("witness-threshold") {
+        None => k,
+        Some(t) => parse_integer(t, "witness-threshold"),
+    }
+}
+
+fn reconstruct<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+    k: usize,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let started = timestamp::now();
+    let secret_output = parse_secret_output(args);
+    let max_shares = args
+        .value_of("max-shares")
+        .map_or(MAX_SHARES, |m| parse_integer(m, "max-shares"));
+    exit::ensure(
+        max_shares >= k,
+        ErrorCategory::Usage,
+        "--max-shares must be at least the threshold",
+    );
+    let mut parser = ShareParser::new(x_key, expected);
+    parser.envelope = args.value_of("envelope");
+    let shares = parse_shares_with::<F, S>(parser, args.value_of("shares").unwrap(), max_shares);
+    // In JSON mode, the results are accumulated in a single object printed at the end.
+    let mut output = (args.value_of("output") == Some("json")).then(|| json_header(matches, k));
+    match &mut output {
+        Some(output) => {
+            output.insert("shares".into(), displayed_shares(args, &shares).into());
+            let digest = ShareSet::<F, S>::new(shares.clone()).digest_with(hash_algorithm(args));
+            output.insert("shares_digest".into(), hex::encode(digest).into());
+        }
+        None => {
+            println!("Shares:");
+            for s in displayed_shares(args, &shares) {
+                println!("{}", s);
+            }
+        }
+    }
+
+    check_enough_shares(shares.len(), k, "Cannot reconstruct");
+
+    // The command fails with the exit code of the error after printing the output.
+    let mut failure = None;
+    match args.value_of("at") {
+        Some(at) => {
+            let x = S::parse_x(at).or_exit("Invalid --at coordinate");
+            let c = secret_point::<F, S>(args);
+            // The share where the secret is stored would be the secret itself.
+            let share = if x == c {
+                exit::ensure(args.is_present("allow-secret-point"), ErrorCategory::Usage, format_args!("--at {} is where the secret is stored, so the share there would reveal the secret! Pass --allow-secret-point to reconstruct it anyway", at));
+                S::reconstruct_secret_at(&shares, k, x).map(|y| format!("{}|{}", at, y))
+            } else {
+                S::reconstruct_share_at(&shares, k, x, c).map(|s| s.to_string())
+            };
+            match (&mut output, share) {
+                (Some(output), Ok(s)) => {
+                    output.insert("share".into(), s.into());
+                }
+                (Some(output), Err(e)) => {
+                    output.insert("error".into(), e.to_string().into());
+                    failure = Some(e);
+                }
+                (None, Ok(s)) => println!("Share = {}", s),
+                (None, Err(e)) => {
+                    println!("Could not reconstruct the share: {}", e);
+                    failure = Some(e);
+                }
+            }
+        }
+        None => {
+            let secret = if args.is_present("robust") {
+                let result = S::reconstruct_robust(&shares, k);
+                if let Ok((_, corrupted)) = &result {
+                    let corrupted: Vec<&S::Share> = corrupted.iter().map(|&i| &shares[i]).collect();
+                    match &mut output {
+                        Some(output) => {
+                            output.insert(
+                                "corrupted".into(),
+                                displayed_shares(args, &corrupted).into(),
+                            );
+                        }
+                        None => {
+                            println!("Corrupted shares:");
+                            for s in displayed_shares(args, &corrupted) {
+                                println!("{}", s);
+                            }
+                        }
+                    }
+                }
+                result.map(|(secret, _)| secret)
+            } else {
+                match args.value_of("secret-at") {
+                    None => match S::reconstruct_verified(&shares, k) {
+                        Ok(result) if !result.inconsistent.is_empty() => {
+                            let inconsistent: Vec<&S::Share> =
+                                result.inconsistent.iter().map(|&i| &shares[i]).collect();
+                            match &mut output {
+                                Some(output) => {
+                                    output.insert(
+                                        "inconsistent".into(),
+                                        displayed_shares(args, &inconsistent).into(),
+                                    );
+                                }
+                                None => {
+                                    println!("Inconsistent shares (use --robust to correct them):");
+                                    for s in displayed_shares(args, &inconsistent) {
+                                        println!("{}", s);
+                                    }
+                                }
+                            }
+                            Err(ShamirError::InconsistentShares(result.inconsistent))
+                        }
+                        result => result.map(|result| result.secret),
+                    },
+                    Some(c) => {
+                        let c = S::parse_x(c).or_exit("Invalid --secret-at coordinate");
+                        S::reconstruct_secret_at(&shares, k, c)
+                    }
+                }
+            };
+            match secret {
+                Ok(s) => {
+                    output_secret(&secret_output, &mut output, &Zeroizing::new(s.to_string()));
+                    if let Some(expected_hash) = args.value_of("expect-hash") {
+                        check_commitment(hash_algorithm(args), &s, expected_hash);
+                        match &mut output {
+                            Some(output) => {
+                                output.insert("hash_verified".into(), true.into());
+                            }
+                            None => println!("The reconstructed secret matches the expected hash"),
+                        }
+                    }
+                    if let Some(filename) = args.value_of("witness-shares") {
+                        let witness_threshold = parse_witness_threshold(args, k);
+                        exit::ensure(
+                            witness::verify(&s, filename, witness_threshold),
+                            ErrorCategory::Crypto,
+                            "The reconstructed secret doesn't match the witness shares!",
+                        );
+                        match &mut output {
+                            Some(output) => {
+                                output.insert("witness_verified".into(), true.into());
+                            }
+                            None => {
+                                println!("The reconstructed secret matches the witness shares")
+                            }
+                        }
+                    }
+                    if let Some(filename) = args.value_of("transcript") {
+                        let key =
+                            transcript::parse_signing_key(args.value_of("signing-key").unwrap());
+                        let hash = hash_algorithm(args);
+                        let salt = transcript::generate_salt();
+                        let transcript = transcript::Transcript {
+                            shamir_type: matches.value_of("type").unwrap().to_owned(),
+                            field: matches.value_of("field").unwrap().to_owned(),
+                            bitsize: matches.value_of("bitsize").unwrap().parse().unwrap(),
+                            threshold: k,
+                            started,
+                            finished: timestamp::now(),
+                            hash,
+                            salt,
+                            shares: share_fingerprints::<F, S>(hash, &salt, &shares),
+                            secret: transcript::fingerprint(hash, &salt, &s.to_bytes()),
+                        };
+                        transcript.write(filename, &key);
+                        let public_key = hex::encode(key.verifying_key().as_bytes());
+                        match &mut output {
+                            Some(output) => {
+                                output.insert("transcript_public_key".into(), public_key.into());
+                            }
+                            None => println!("Transcript signed with public key = {}", public_key),
+                        }
+                    }
+                }
+                Err(e) => {
+                    match &mut output {
+                        Some(output) => {
+                            output.insert("error".into(), e.to_string().into());
+                        }
+                        None => println!("Could not reconstruct the secret: {}", e),
+                    }
+                    failure = Some(e);
+                }
+            }
+        }
+    }
+
+    if let Some(output) = output {
+        print_json(output);
+    }
+    if let Some(e) = failure {
+        exit::fail(&e);
+    }
+}
+
+/// Reconstructs a secret split into groups with `--groups`: the secret of each group is
+/// reconstructed from the shares of its members, and the secret from the `--group-threshold` group
+/// secrets.
+fn reconstruct_groups<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+) where
+    S::Share: Display,
+{
+    let (group_threshold, groups) = parse_groups(args);
+    let secret_output = parse_secret_output(args);
+    let max_shares = args
+        .value_of("max-shares")
+        .map_or(groups.iter().map(|g| g.count).sum(), |m| {
+            parse_integer(m, "max-shares")
+        });
+    let filename = args.value_of("shares").unwrap();
+    let file = File::open(filename).or_exit(format_args!("Could not open {}", filename));
+    let (shares, truncated) = read_lines(
+        BufReader::new(file),
+        max_shares,
+        GroupShamir::parse_share::<F, S>,
+    )
+    .or_exit(format_args!("Could not read the shares from {}", filename));
+    if truncated {
+        eprintln!(
+            "Warning: {} contains more than {} shares, ignoring the remaining ones",
+            filename, max_shares
+        );
+    }
+
+    let mut output = (args.value_of("output") == Some("json")).then(|| {
+        let mut output = json_header(matches, group_threshold);
+        output.remove("threshold");
+        output.insert("version".into(), schema::MANIFEST_VERSION.into());
+        output.insert("group_threshold".into(), group_threshold.into());
+        output.insert("groups".into(), share_strings(&groups).into());
+        output.insert("shares".into(), displayed_shares(args, &shares).into());
+        output
+    });
+    if output.is_none() {
+        println!("Shares:");
+        for s in displayed_shares(args, &shares) {
+            println!("{}", s);
+        }
+    }
+
+    let mut failure = None;
+    match GroupShamir::reconstruct::<F, S>(&shares, group_threshold, &groups) {
+        Ok(s) => {
+            output_secret(&secret_output, &mut output, &Zeroizing::new(s.to_string()));
+            if let Some(expected_hash) = args.value_of("expect-hash") {
+                check_commitment(hash_algorithm(args), &s, expected_hash);
+                match &mut output {
+                    Some(output) => {
+                        output.insert("hash_verified".into(), true.into());
+                    }
+                    None => println!("The reconstructed secret matches the expected hash"),
+                }
+            }
+        }
+        Err(e) => {
+            match &mut output {
+                Some(output) => {
+                    output.insert("error".into(), e.to_string().into());
+                }
+                None => println!("Could not reconstruct the secret: {}", e),
+            }
+            failure = Some(e);
+        }
+    }
+
+    if let Some(output) = output {
+        print_json(output);
+    }
+    if let Some(e) = failure {
+        exit::fail(&e);
+    }
+}
+
+fn reconstruct_access<F: Field + Debug + Display>(matches: &ArgMatches, args: &ArgMatches) {
+    let access = parse_access(args);
+    let secret_output = parse_secret_output(args);
+    let max_shares = args
+        .value_of("max-shares")
+        .map_or(255, |m| parse_integer(m, "max-shares"));
+    let filename = args.value_of("shares").unwrap();
+    let file = File::open(filename).or_exit(format_args!("Could not open {}", filename));
+    let (shares, truncated) = read_lines(
+        BufReader::new(file),
+        max_shares,
+        AccessPolicy::parse_share::<F>,
+    )
+    .or_exit(format_args!("Could not read the shares from {}", filename));
+    if truncated {
+        eprintln!(
+            "Warning: {} contains more than {} shares, ignoring the remaining ones",
+            filename, max_shares
+        );
+    }
+
+    let mut output = (args.value_of("output") == Some("json")).then(|| {
+        let mut output = json_header(matches, 0);
+        output.remove("threshold");
+        output.insert("version".into(), schema::MANIFEST_VERSION.into());
+        output.insert("access".into(), access.to_string().into());
+        output.insert("shares".into(), displayed_shares(args, &shares).into());
+        output
+    });
+    if output.is_none() {
+        println!("Shares:");
+        for s in displayed_shares(args, &shares) {
+            println!("{}", s);
+        }
+    }
+
+    let mut failure = None;
+    match access.reconstruct(&shares) {
+        Ok(r) => {
+            match &mut output {
+                Some(output) => {
+                    output.insert("custodians".into(), r.custodians.clone().into());
+                }
+                None => println!("Policy satisfied by: {}", r.custodians.join(", ")),
+            }
+            output_secret(
+                &secret_output,
+                &mut output,
+                &Zeroizing::new(r.secret.to_string()),
+            );
+            if let Some(expected_hash) = args.value_of("expect-hash") {
+                check_commitment(hash_algorithm(args), &r.secret, expected_hash);
+                match &mut output {
+                    Some(output) => {
+                        output.insert("hash_verified".into(), true.into());
+                    }
+                    None => println!("The reconstructed secret matches the expected hash"),
+                }
+            }
+        }
+        Err(e) => {
+            let mut supplied: Vec<&str> = shares.iter().map(|s| s.custodian.as_str()).collect();
+            supplied.sort_unstable();
+            supplied.dedup();
+            match &mut output {
+                Some(output) => {
+                    output.insert("error".into(), e.to_string().into());
+                }
+                None => println!(
+                    "Could not reconstruct the secret from the shares of {}: {}",
+                    supplied.join(", "),
+                    e
+                ),
+            }
+            failure = Some(e);
+        }
+    }
+
+    if let Some(output) = output {
+        print_json(output);
+    }
+    if let Some(e) = failure {
+        exit::fail(&e);
+    }
+}
+
+/// Returns whether the first share in the given file is a record of several elements (see
+/// `horcrux::record`), rather than a single share.
+fn is_record_file(filename: &str) -> bool {
+    let first = File::open(filename)
+        .ok()
+        .and_then(|file| read_lines(BufReader::new(file), 1, |line| Ok(line.to_owned())).ok());
+    match first {
+        Some((lines, _)) => lines.first().is_some_and(|line| {
+            // Records have 4 fields, or 5 with the identifier of their hash algorithm, whereas
+            // shares have at most 3.
+            Header::split(line).is_ok_and(|(_, share)| share.split('|').count() >= 4)
+        }),
+        None => false,
+    }
+}
+
+/// Reconstructs a secret larger than a field element from records of several elements.
+fn reconstruct_records<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+    k: usize,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    for option in &[
+        "at",
+        "secret-at",
+        "robust",
+        "witness-shares",
+        "expect-hash",
+        "transcript",
+        "envelope",
+    ] {
+        exit::ensure(
+            !args.is_present(option),
+            ErrorCategory::Usage,
+            format_args!(
+                "--{} is not supported for secrets larger than a field element",
+                option
+            ),
+        );
+    }
+    let secret_output = parse_secret_output(args);
+    let max_shares = args
+        .value_of("max-shares")
+        .map_or(MAX_SHARES, |m| parse_integer(m, "max-shares"));
+    let records = parse_lines_with(
+        ShareParser::new(None, expected),
+        args.value_of("shares").unwrap(),
+        max_shares,
+        |_, record| record::parse_record::<F, S>(record),
+    );
+
+    let mut output = (args.value_of("output") == Some("json")).then(|| {
+        let mut output = json_header(matches, k);
+        output.insert("shares".into(), displayed_shares(args, &records).into());
+        output
+    });
+    if output.is_none() {
+        println!("Shares:");
+        for r in displayed_shares(args, &records) {
+            println!("{}", r);
+        }
+    }
+    check_enough_shares(records.len(), k, "Cannot reconstruct");
+
+    let mut failure = None;
+    match record::reconstruct::<F, S>(&records, k) {
+        Ok((elements, len)) => {
+            // The padding of the last element is removed.
+            let mut secret = Zeroizing::new(String::new());
+            for e in elements.expose_secret() {
+                secret.push_str(&Zeroizing::new(e.to_string()));
+            }
+            exit::ensure(
+                2 * len <= secret.len(),
+                ErrorCategory::Parse,
+                "The length of the secret in the records is out of range",
+            );
+            secret.truncate(2 * len);
+            output_secret(&secret_output, &mut output, &secret);
+        }
+        Err(e) => {
+            match &mut output {
+                Some(output) => {
+                    output.insert("error".into(), e.to_string().into());
+                }
+                None => println!("Could not reconstruct the secret: {}", e),
+            }
+            failure = Some(e);
+        }
+    }
+
+    if let Some(output) = output {
+        print_json(output);
+    }
+    if let Some(e) = failure {
+        exit::fail(&e);
+    }
+}
+
+/// Prompts for shares on the terminal until the threshold is reached, and reconstructs the secret.
+/// Each share is validated when it's entered, and invalid shares can be entered again.
+fn reconstruct_interactive<F: Field + Debug + Display, S: Shamir<F>>(
+    args: &ArgMatches,
+    k: usize,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let stdin = std::io::stdin();
+    let mut parser = ShareParser::new(x_key, expected);
+    // The shares are only kept as the state of the reconstruction, and the lines are wiped.
+    let mut reconstructor =
+        IncrementalReconstructor::<F, S>::new(k).or_exit("Could not reconstruct the secret");
+    let prompt = |message: &str| -> Option<Zeroizing<String>> {
+        eprint!("{}", message);
+        std::io::stderr()
+            .flush()
+            .or_exit("Could not prompt for the shares");
+        let mut line = Zeroizing::new(String::new());
+        match stdin
+            .lock()
+            .read_line(&mut line)
+            .or_exit("Could not read the shares")
+        {
+            0 => None,
+            _ => Some(Zeroizing::new(line.trim().to_owned())),
+        }
+    };
+
+    eprintln!("Enter {} shares, one per line.", k);
+    loop {
+        let line = match prompt(&format!("Share {}/{}: ", reconstructor.len() + 1, k)) {
+            Some(line) => line,
+            None => {
+                eprintln!(
+                    "End of input, only {} of {} shares entered",
+                    reconstructor.len(),
+                    k
+                );
+                exit::fail(&ShamirError::NotEnoughShares)
+            }
+        };
+        if line.is_empty() {
+            continue;
+        }
+        let (header, share) = match parser.check_header(&line) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("Rejected: {}", e);
+                continue;
+            }
+        };
+        let progress = parser
+            .parse_share::<F, S>(share)
+            .and_then(|share| reconstructor.add(&share));
+        match progress {
+            Ok(progress) => {
+                parser.accept(header);
+                match progress {
+                    Progress::ThresholdMet => {
+                        eprintln!("Accepted, reconstructing the secret.");
+                        break;
+                    }
+                    Progress::Remaining(1) => eprintln!("Accepted, 1 more share needed."),
+                    Progress::Remaining(remaining) => {
+                        eprintln!("Accepted, {} more shares needed.", remaining)
+                    }
+                }
+            }
+            Err(e) => eprintln!("Rejected: {}", e),
+        }
+    }
+
+    let secret = reconstructor
+        .finalize()
+        .or_exit("Could not reconstruct the secret");
+    if let Some(expected_hash) = args.value_of("expect-hash") {
+        check_commitment(hash_algorithm(args), secret.expose_secret(), expected_hash);
+        eprintln!("The reconstructed secret matches the expected hash.");
+    }
+    let answer = prompt("The secret was reconstructed. Display it? [y/N] ").unwrap_or_default();
+    if answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes") {
+        println!("Secret = {}", secret.expose_secret());
+    } else {
+        eprintln!("The secret wasn't displayed.");
+    }
+}
+
+fn rotate<F: Field + Debug + Display, S: Shamir<F>>(
+    args: &ArgMatches,
+    k: usize,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let shares = parse_shares::<F, S>(
+        args.value_of("shares").unwrap(),
+        x_key,
+        MAX_SHARES,
+        expected,
+    );
+    check_enough_shares(shares.len(), k, "Cannot refresh");
+
+    let c = secret_point::<F, S>(args);
+    let refreshed = S::refresh(&shares, k, c).or_exit("Could not refresh the shares");
+    println!("Refreshed shares:");
+    for s in &refreshed {
+        println!("{}", s);
+    }
+}
+
+fn info<F: Field + Debug + Display, S: Shamir<F>>(
+    args: &ArgMatches,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let mut parser = ShareParser::new(x_key, expected);
+    parser.envelope = args.value_of("envelope");
+    let shares = parse_shares_with::<F, S>(parser, args.value_of("shares").unwrap(), 1);
+    check_enough_shares(shares.len(), 1, "The file doesn't contain any share");
+    let share = &shares[0];
+    let code = code::verification_code(hash_algorithm(args), &share.to_string());
+    println!("Share = {}", share);
+    println!("Verification code = {}", code);
+    if let Some(expected_code) = args.value_of("expect-code") {
+        exit::ensure(
+            code == expected_code,
+            ErrorCategory::Parse,
+            "The share doesn't match the verification code, it was probably mistyped!",
+        );
+        println!("The share matches the verification code");
+    }
+    if let Some(filename) = args.value_of("verify-key") {
+        let key = Zeroizing::new(transcript::parse_key_bytes(filename));
+        share_mac::verify(
+            &*key,
+            &share.to_string(),
+            args.value_of("expect-mac").unwrap(),
+        )
+        .or_fail(ErrorCategory::Crypto);
+        println!("The share matches its MAC");
+    }
+}
+
+fn extend<F: Field + Debug + Display, S: Shamir<F>>(
+    args: &ArgMatches,
+    k: usize,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let new_xs: Vec<S::X> = args
+        .values_of("new-x")
+        .unwrap()
+        .map(|x| S::parse_x(x).or_exit(format_args!("Invalid --new-x coordinate {}", x)))
+        .collect();
+    let shares = parse_shares::<F, S>(
+        args.value_of("shares").unwrap(),
+        x_key,
+        MAX_SHARES,
+        expected,
+    );
+    check_enough_shares(shares.len(), k, "Cannot extend");
+
+    let c = secret_point::<F, S>(args);
+    let extended =
+        S::derive_new_shares(&shares, k, &new_xs, c).or_exit("Could not derive the new shares");
+    println!("New shares:");
+    for s in &extended {
+        println!("{}", s);
+    }
+}
+
+fn reshare<F: Field + Debug + Display, S: Shamir<F>>(
+    args: &ArgMatches,
+    k: usize,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let new_n = parse_integer(args.value_of("new-nshares").unwrap(), "new-nshares");
+    let new_k = parse_integer(args.value_of("new-threshold").unwrap(), "new-threshold");
+    exit::ensure(
+        new_n != 0 && new_n <= 255,
+        ErrorCategory::Usage,
+        "--new-nshares must be between 1 and 255",
+    );
+    exit::ensure(
+        new_k != 0 && new_k <= new_n,
+        ErrorCategory::Usage,
+        "--new-threshold must be between 1 and --new-nshares",
+    );
+
+    let shares = parse_shares::<F, S>(
+        args.value_of("shares").unwrap(),
+        x_key,
+        MAX_SHARES,
+        expected,
+    );
+    check_enough_shares(shares.len(), k, "Cannot reshare");
+
+    let c = secret_point::<F, S>(args);
+    let reshared = S::reshare(&shares, k, new_k, new_n, c).or_exit("Could not reshare the secret");
+    println!("New shares:");
+    for s in &reshared {
+        println!("{}", s);
+    }
+}
+
+/// Returns the bytes of a key to seal TOTP exports with, which must have at least 128 bits.
+fn totp_key<F: Field + Display>(key: &F) -> Zeroizing<Vec<u8>> {
+    let bytes = Zeroizing::new(key.to_bytes());
+    exit::ensure(
+        bytes.len() >= 16,
+        ErrorCategory::Usage,
+        "Sealing TOTP exports requires a field of at least 128 bits",
+    );
+    bytes
+}
+
+fn totp_split<F: Field + Debug + Display, S: Shamir<F>>(args: &ArgMatches, k: usize, n: usize)
+where
+    S::Share: Display,
+{
+    let input = args.value_of("input").unwrap();
+    let mut export = Zeroizing::new(String::new());
+    match input {
+        "-" => std::io::stdin().read_to_string(&mut export),
+        _ => File::open(input)
+            .or_exit(format_args!("Could not open {}", input))
+            .read_to_string(&mut export),
+    }
+    .or_exit("Could not read the export");
+    let (format, entries) = totp::check_export(&export).or_fail(ErrorCategory::Parse);
+
+    let key = Zeroizing::new(F::uniform(&mut thread_rng()));
+    let sealed = totp::seal(hash_algorithm(args), &totp_key(&*key), export.as_bytes());
+    let filename = args.value_of("sealed").unwrap();
+    std::fs::write(filename, sealed + "\n").or_exit(format_args!("Could not write {}", filename));
+
+    let shares = S::split(&key, k, n).or_exit("Could not split the key");
+    println!(
+        "Sealed {} {} ({}) into {}",
+        entries,
+        if entries == 1 { "entry" } else { "entries" },
+        format,
+        filename
+    );
+    println!("Shares:");
+    for s in &shares {
+        println!("{}", s);
+    }
+}
+
+fn totp_reconstruct<F: Field + Debug + Display, S: Shamir<F>>(
+    args: &ArgMatches,
+    k: usize,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let shares = parse_shares::<F, S>(
+        args.value_of("shares").unwrap(),
+        x_key,
+        MAX_SHARES,
+        expected,
+    );
+    let key = Zeroizing::new(S::reconstruct(&shares, k).or_exit("Could not reconstruct the key"));
+    let filename = args.value_of("sealed").unwrap();
+    let sealed =
+        std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename));
+    let export = totp::open(&totp_key(&*key), &sealed).or_fail(ErrorCategory::Crypto);
+
+    match args.value_of("output") {
+        Some(output) => {
+            std::fs::write(output, &*export).or_exit(format_args!("Could not write {}", output));
+            eprintln!("Recovered the export into {}", output);
+        }
+        None => std::io::stdout().write_all(&export).unwrap(),
+    }
+}
+
+fn verify_transcript<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+    k: usize,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let key = transcript::parse_verifying_key(args.value_of("public-key").unwrap());
+    let transcript =
+        transcript::Transcript::read_verified(args.value_of("transcript").unwrap(), &key);
+    println!("Transcript signature is valid");
+
+    exit::ensure(
+        transcript.shamir_type == matches.value_of("type").unwrap(),
+        ErrorCategory::Usage,
+        "Transcript was made for another type of shares",
+    );
+    exit::ensure(
+        transcript.field == matches.value_of("field").unwrap(),
+        ErrorCategory::Usage,
+        "Transcript was made for another field",
+    );
+    exit::ensure(
+        transcript.bitsize.to_string() == matches.value_of("bitsize").unwrap(),
+        ErrorCategory::Usage,
+        "Transcript was made for another bitsize",
+    );
+    exit::ensure(
+        transcript.threshold == k,
+        ErrorCategory::Usage,
+        "Transcript was made for another threshold",
+    );
+
+    println!(
+        "Reconstruction from {} shares, between {} and {}",
+        transcript.shares.len(),
+        timestamp::format(transcript.started),
+        timestamp::format(transcript.finished)
+    );
+    println!("Share fingerprints:");
+    for s in &transcript.shares {
+        println!("{}", s);
+    }
+    println!("Secret fingerprint = {}", transcript.secret);
+
+    if let Some(filename) = args.value_of("shares") {
+        let shares = parse_shares::<F, S>(filename, x_key, MAX_SHARES, expected);
+        let mut expected = transcript.shares.clone();
+        expected.sort();
+        let mut actual = share_fingerprints::<F, S>(transcript.hash, &transcript.salt, &shares);
+        actual.sort();
+        exit::ensure(
+            actual == expected,
+            ErrorCategory::Crypto,
+            "Transcript wasn't made from the given shares",
+        );
+        println!("Transcript matches the given shares");
+    }
+}
+
+fn ndef_encode<F: Field + Debug + Display, S: Shamir<F>>(
+    matches: &ArgMatches,
+    args: &ArgMatches,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let compact = matches.value_of("type").unwrap() == "compact";
+    let shares = parse_shares::<F, S>(
+        args.value_of("shares").unwrap(),
+        x_key,
+        MAX_SHARES,
+        expected,
+    );
+    let output = args.value_of("output").unwrap();
+
+    for (i, share) in shares.iter().enumerate() {
+        let image = ndef::encode(&share.to_string(), compact).unwrap_or_else(|| {
+            exit::fail_with(
+                ErrorCategory::Usage,
+                "Share is too large for an NDEF record",
+            )
+        });
+        let filename = format!("{}-{}.ndef", output, i + 1);
+        std::fs::write(&filename, &image).or_exit(format_args!("Could not write {}", filename));
+        match ndef::smallest_tag(&image) {
+            Some(tag) => println!("{} ({} bytes, fits on {})", filename, image.len(), tag),
+            None => println!(
+                "{} ({} bytes, too large for NTAG tags)",
+                filename,
+                image.len()
+            ),
+        }
+    }
+}
+
+fn ndef_decode<F: Field + Debug + Display, S: Shamir<F>>(matches: &ArgMatches, args: &ArgMatches)
+where
+    S::Share: Display,
+{
+    let compact = matches.value_of("type").unwrap() == "compact";
+    for filename in args.values_of("tags").unwrap() {
+        let image = std::fs::read(filename).or_exit(format_args!("Could not read {}", filename));
+        let (is_compact, share) = ndef::decode(&image).unwrap_or_else(|| {
+            exit::fail_with(
+                ErrorCategory::Parse,
+                format_args!("{} doesn't contain a share", filename),
+            )
+        });
+        exit::ensure(
+            is_compact == compact,
+            ErrorCategory::Usage,
+            format_args!("{} contains a share of another --type", filename),
+        );
+        let share = S::parse_share(&share)
+            .or_exit(format_args!("Could not parse the share in {}", filename));
+        println!("{share}");
+    }
+}
+
+#[cfg(feature = "audio")]
+fn audio_encode<F: Field + Debug + Display, S: Shamir<F>>(
+    args: &ArgMatches,
+    x_key: Option<&XKey>,
+    expected: &Header,
+) where
+    S::Share: Display,
+{
+    let shares = parse_shares::<F, S>(
+        args.value_of("shares").unwrap(),
+        x_key,
+        MAX_SHARES,
+        expected,
+    );
+    let output = args.value_of("output").unwrap();
+
+    for (i, share) in shares.iter().enumerate() {
+        let wav = audio::encode(&share.to_string()).unwrap_or_else(|| {
+            exit::fail_with(
+                ErrorCategory::Usage,
+                "Share is too large for an audio frame",
+            )
+        });
+        let filename = format!("{}-{}.wav", output, i + 1);
+        std::fs::write(&filename, &wav).or_exit(format_args!("Could not write {}", filename));
+        println!("{filename}");
+    }
+}
+
+#[cfg(feature = "audio")]
+fn audio_decode<F: Field + Debug + Display, S: Shamir<F>>(args: &ArgMatches)
+where
+    S::Share: Display,
+{
+    for filename in args.values_of("input").unwrap() {
+        let wav = std::fs::read(filename).or_exit(format_args!("Could not read {}", filename));
+        let share = audio::decode(&wav).unwrap_or_else(|| {
+            exit::fail_with(
+                ErrorCategory::Parse,
+                format_args!("No share could be decoded from {}", filename),
+            )
+        });
+        let share = S::parse_share(&share)
+            .or_exit(format_args!("Could not parse the share in {}", filename));
+        println!("{share}");
+    }
+}
+
+fn share_strings<S: Display>(shares: &[S]) -> Vec<String> {
+    shares.iter().map(|s| s.to_string()).collect()
+}
+
+/// Returns the shares to display after reading them: their text format, or only their
+/// verification codes with --simulate, so that the output never contains the shares themselves.
+fn displayed_shares<S: Display>(args: &ArgMatches, shares: &[S]) -> Vec<String> {
+    match args.is_present("simulate") {
+        true => share_codes(hash_algorithm(args), shares),
+        false => share_strings(shares),
+    }
+}
+
+/// Returns the verification codes of the shares (see the `code` module).
+fn share_codes<S: Display>(hash: HashAlgorithm, shares: &[S]) -> Vec<String> {
+    shares
+        .iter()
+        .map(|s| code::verification_code(hash, &s.to_string()))
+        .collect()
+}
+
+/// Returns the fingerprints of the shares with the given salt, in the canonical order of the set.
+fn share_fingerprints<F: Field, S: Shamir<F>>(
+    hash: HashAlgorithm,
+    salt: &[u8; 32],
+    shares: &[S::Share],
+) -> Vec<String>
+where
+    S::Share: Display,
+{
+    let mut set = ShareSet::<F, S>::new(shares.to_vec());
+    set.canonicalize();
+    set.shares()
+        .iter()
+        .map(|s| transcript::fingerprint(hash, salt, s.to_string().as_bytes()))
+        .collect()
+}
+
+/// Parses the secret from the given file, or from stdin if the filename is `-`.
+fn parse_secret<F: Field>(filename: &str) -> F {
+    secret_from_bytes(&read_secret(filename))
+}
+
+/// Reads the bytes of the secret, in hexadecimal, from the given file, or from stdin if the
+/// filename is `-`.
+fn read_secret(filename: &str) -> Zeroizing<Vec<u8>> {
+    let contents = if filename == "-" {
+        secret_input::read_stdin()
+    } else {
+        let mut file = File::open(filename).or_exit(format_args!("Could not open {}", filename));
+        let mut contents = Zeroizing::new(String::new());
+        file.read_to_string(&mut contents)
+            .or_exit(format_args!("Could not read {}", filename));
+        contents
+    };
+
+    let regex = Regex::new(r"^([0-9a-fA-F]+)\n?$").unwrap();
+    let captures = match regex.captures(&contents) {
+        Some(cap) => cap,
+        None => exit::fail_with(
+            ErrorCategory::Parse,
+            "Secret file must contains hexadecimal characters only",
+        ),
+    };
+
+    match hex::decode(&captures[1]) {
+        Ok(bytes) => Zeroizing::new(bytes),
+        Err(e) => exit::fail_with(
+            ErrorCategory::Parse,
+            format_args!(
+                "Couldn't parse secret file as hexadecimal characters: {}",
+                e
+            ),
+        ),
+    }
+}
+
+/// Returns the number of bytes of a field element.
+fn element_len<F: Field + Display>() -> usize {
+    F::ZERO.to_string().len() / 2
+}
+
+/// Converts the bytes of the secret into a field element.
+fn secret_from_bytes<F: Field>(bytes: &[u8]) -> F {
+    match F::from_bytes(bytes) {
+        Some(f) => f,
+        None => exit::fail_with(
+            ErrorCategory::Parse,
+            "Secret is not a valid represetation of a field element",
+        ),
+    }
+}
+
+/// Parser of shares in text format, which checks that either all shares have a header from the
+/// same split, matching the expected parameters, or none has a header. If an x key is given, the
+/// shares are compressed random shares, which are expanded with the key.
+struct ShareParser<'a> {
+    x_key: Option<&'a XKey>,
+    expected: &'a Header,
+    first_header: Option<Option<Header>>,
+    /// Half of the decoy envelopes to keep, if the shares are envelopes.
+    envelope: Option<&'a str>,
+}
+
+impl<'a> ShareParser<'a> {
+    fn new(x_key: Option<&'a XKey>, expected: &'a Header) -> Self {
+        ShareParser {
+            x_key,
+            expected,
+            first_header: None,
+            envelope: None,
+        }
+    }
+
+    /// Checks the header of a share line, if any, against the accepted shares. Returns the header
+    /// and the share without it.
+    fn check_header<'l>(&self, line: &'l str) -> Result<(Option<Header>, &'l str), String> {
+        let (header, share) = Header::split(line)?;
+        match (&self.first_header, &header) {
+            (None, Some(header)) => header.check(self.expected)?,
+            (None, None) | (Some(None), None) => (),
+            (Some(Some(first)), Some(header)) => {
+                if first.set_id != header.set_id {
+                    return Err(format!(
+                        "Shares from different splits (identifiers {} and {}) cannot be mixed",
+                        first.set_id, header.set_id
+                    ));
+                }
+            }
+            _ => return Err("Either all shares or none of them must have a header".to_owned()),
+        }
+        Ok((header, share))
+    }
+
+    /// Records the header of an accepted share.
+    fn accept(&mut self, header: Option<Header>) {
+        if self.first_header.is_none() {
+            self.first_header = Some(header);
+        }
+    }
+
+    fn parse_share<F: Field + Debug + Display, S: Shamir<F>>(
+        &self,
+        share: &str,
+    ) -> Result<S::Share, ShamirError> {
+        if let Some(half) = self.envelope {
+            let envelope = decoy::parse_share::<F, S>(share)?;
+            return Ok(match half {
+                "decoy" => envelope.decoy,
+                _ => envelope.real,
+            });
+        }
+        match self.x_key {
+            None => S::parse_share(share),
+            Some(key) => {
+                let share = RandomShamir::parse_compressed_share::<F>(share, key)?;
+                S::parse_share(&share.to_string())
+            }
+        }
+    }
+}
+
+/// Parses at most `max_shares` shares stored in the given file, and ignores the remaining ones. See
+/// `ShareParser` for the supported formats.
+///
+/// The file is streamed, so that huge or malformed files don't exhaust the memory.
+fn parse_shares<F: Field + Debug + Display, S: Shamir<F>>(
+    filename: &str,
+    x_key: Option<&XKey>,
+    max_shares: usize,
+    expected: &Header,
+) -> Vec<S::Share> {
+    parse_shares_with::<F, S>(ShareParser::new(x_key, expected), filename, max_shares)
+}
+
+/// Parses at most `max_shares` shares stored in the given file with the given parser, like
+/// `parse_shares`.
+fn parse_shares_with<F: Field + Debug + Display, S: Shamir<F>>(
+    parser: ShareParser,
+    filename: &str,
+    max_shares: usize,
+) -> Vec<S::Share> {
+    parse_lines_with(parser, filename, max_shares, |parser, share| {
+        parser.parse_share::<F, S>(share)
+    })
+}
+
+/// Parses at most `max_shares` items stored in the given file, like `parse_shares_with`, but with
+/// the given function to parse each item once its header is checked.
+fn parse_lines_with<T>(
+    mut parser: ShareParser,
+    filename: &str,
+    max_shares: usize,
+    parse: impl Fn(&ShareParser, &str) -> Result<T, ShamirError>,
+) -> Vec<T> {
+    let file = File::open(filename).or_exit(format_args!("Could not open {}", filename));
+    let result = read_lines(BufReader::new(file), max_shares, |line| {
+        let (header, share) = parser.check_header(line).or_fail(ErrorCategory::Parse);
+        let share = parse(&parser, share)?;
+        parser.accept(header);
+        Ok(share)
+    });
+    let (shares, truncated) =
+        result.or_exit(format_args!("Could not read the shares from {}", filename));
+    if truncated {
+        eprintln!(
+            "Warning: {} contains more than {} shares, ignoring the remaining ones",
+            filename, max_shares
+        );
+    }
+    shares
 }
@@ -0,0 +1,212 @@
+//! Signatures of split manifests (the JSON output of `split`) by the operators of a key ceremony.
+//!
+//! Each operator signs the manifest with an Ed25519 key, and the signatures are collected into
+//! its `signatures` field. A signature covers the manifest without this field, serialized as JSON
+//! with sorted keys and without whitespace (see `horcrux::schema::SplitManifest`), so operators can
+//! sign in any order. Verifying a manifest requires a quorum of valid signatures from a list of
+//! expected operators.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use horcrux::schema::{ManifestSignature, SplitManifest, MANIFEST_SIGNATURE_DOMAIN};
+use serde_json::Value;
+use std::fmt::Display;
+
+/// Parses a manifest, checking it against its schema.
+fn parse(contents: &str) -> Result<(Value, SplitManifest), String> {
+    let value: Value =
+        serde_json::from_str(contents).map_err(|e| format!("Invalid manifest: {}", e))?;
+    if !value.is_object() {
+        return Err("Invalid manifest: expected a JSON object".to_owned());
+    }
+    let manifest =
+        serde_json::from_value(value.clone()).map_err(|e| format!("Invalid manifest: {}", e))?;
+    Ok((value, manifest))
+}
+
+/// Returns the message signed by the operators: the domain separator followed by the canonical
+/// JSON of the manifest without its signatures.
+fn signed_message(value: &Value) -> Vec<u8> {
+    let mut value = value.clone();
+    value.as_object_mut().unwrap().remove("signatures");
+    // Objects are maps with sorted keys, and `to_string()` doesn't add any whitespace.
+    let mut message = MANIFEST_SIGNATURE_DOMAIN.as_bytes().to_vec();
+    message.extend_from_slice(value.to_string().as_bytes());
+    message
+}
+
+/// Adds the signature of an operator to the manifest, and returns the new contents of the
+/// manifest.
+pub fn sign(contents: &str, operator: &str, key: &SigningKey) -> Result<String, String> {
+    let (mut value, manifest) = parse(contents)?;
+    let public_key = hex::encode(key.verifying_key().as_bytes());
+    let mut signatures = manifest.signatures.unwrap_or_default();
+    if signatures.iter().any(|s| s.public_key == public_key) {
+        return Err(format!(
+            "The manifest is already signed by the key {}",
+            public_key
+        ));
+    }
+
+    let signature = key.sign(&signed_message(&value));
+    signatures.push(ManifestSignature {
+        operator: operator.to_owned(),
+        public_key,
+        signature: hex::encode(signature.to_bytes()),
+    });
+    value.as_object_mut().unwrap().insert(
+        "signatures".into(),
+        serde_json::to_value(signatures).unwrap(),
+    );
+    Ok(serde_json::to_string_pretty(&value).unwrap() + "\n")
+}
+
+/// Result of the verification of the signatures of a manifest.
+pub struct Verification {
+    /// Operators of the expected keys who validly signed the manifest.
+    pub signed: Vec<String>,
+    /// Operators whose keys aren't expected, and whose signatures were therefore ignored.
+    pub ignored: Vec<String>,
+    pub quorum: usize,
+}
+
+/// Verifies the signatures of a manifest by the expected keys, and checks that at least `quorum`
+/// of them signed it. Any invalid signature by an expected key is an error, as the manifest was
+/// then modified after being signed.
+pub fn verify(
+    contents: &str,
+    expected: &[VerifyingKey],
+    quorum: usize,
+) -> Result<Verification, String> {
+    let (value, manifest) = parse(contents)?;
+    let message = signed_message(&value);
+
+    let mut signed = Vec::new();
+    let mut ignored = Vec::new();
+    let mut signers: Vec<&VerifyingKey> = Vec::new();
+    for s in manifest.signatures.iter().flatten() {
+        let key = match expected
+            .iter()
+            .find(|k| hex::encode(k.as_bytes()) == s.public_key)
+        {
+            Some(key) => key,
+            None => {
+                ignored.push(s.operator.clone());
+                continue;
+            }
+        };
+        let signature = hex::decode(&s.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(|| format!("Malformed signature by {}", s.operator))?;
+        if key.verify(&message, &signature).is_err() {
+            return Err(format!(
+                "Invalid signature by {}: the manifest was modified after being signed",
+                s.operator
+            ));
+        }
+        if !signers.contains(&key) {
+            signers.push(key);
+            signed.push(s.operator.clone());
+        }
+    }
+
+    if signers.len() < quorum {
+        return Err(format!(
+            "Only {} of the {} required operators signed the manifest",
+            signers.len(),
+            quorum
+        ));
+    }
+    Ok(Verification {
+        signed,
+        ignored,
+        quorum,
+    })
+}
+
+impl Display for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Manifest signed by {} operator(s), quorum of {} met:",
+            self.signed.len(),
+            self.quorum
+        )?;
+        for operator in &self.signed {
+            writeln!(f, "- {}", operator)?;
+        }
+        for operator in &self.ignored {
+            writeln!(
+                f,
+                "Ignored the signature by {}, whose key isn't expected",
+                operator
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MANIFEST: &str = r#"{"version": 1, "scheme": "compact", "field": "binary", "bitsize": 128, "threshold": 2, "shares": ["1|00", "2|01", "3|02"]}"#;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn can_sign_and_verify() {
+        let signed = sign(MANIFEST, "alice", &key(1)).unwrap();
+        let signed = sign(&signed, "bob", &key(2)).unwrap();
+        let expected = [key(1).verifying_key(), key(2).verifying_key()];
+
+        let verification = verify(&signed, &expected, 2).unwrap();
+        assert_eq!(verification.signed, vec!["alice", "bob"]);
+        assert!(verification.ignored.is_empty());
+        assert!(verify(&signed, &expected[..1], 1).is_ok());
+        assert!(verify(&signed, &expected[..1], 2).is_err());
+        assert!(verify(MANIFEST, &expected, 1).is_err());
+    }
+
+    #[test]
+    fn signatures_ignore_formatting() {
+        // The signature covers the canonical JSON, so reformatting the manifest keeps it valid.
+        let signed = sign(MANIFEST, "alice", &key(1)).unwrap();
+        let value: Value = serde_json::from_str(&signed).unwrap();
+        let compact = serde_json::to_string(&value).unwrap();
+        assert!(verify(&compact, &[key(1).verifying_key()], 1).is_ok());
+    }
+
+    #[test]
+    fn ignores_unexpected_signers() {
+        let signed = sign(MANIFEST, "mallory", &key(3)).unwrap();
+        let verification = verify(&signed, &[key(1).verifying_key()], 0).unwrap();
+        assert!(verification.signed.is_empty());
+        assert_eq!(verification.ignored, vec!["mallory"]);
+        assert!(verify(&signed, &[key(1).verifying_key()], 1).is_err());
+    }
+
+    #[test]
+    fn rejects_modified_manifests() {
+        let signed = sign(MANIFEST, "alice", &key(1)).unwrap();
+        let modified = signed.replace("\"threshold\": 2", "\"threshold\": 1");
+        assert_ne!(modified, signed);
+        assert!(verify(&modified, &[key(1).verifying_key()], 1).is_err());
+    }
+
+    #[test]
+    fn rejects_double_signatures() {
+        let signed = sign(MANIFEST, "alice", &key(1)).unwrap();
+        assert!(sign(&signed, "alice again", &key(1)).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_manifests() {
+        assert!(sign("", "alice", &key(1)).is_err());
+        assert!(sign("[]", "alice", &key(1)).is_err());
+        assert!(sign(r#"{"version": 1}"#, "alice", &key(1)).is_err());
+        assert!(verify("null", &[], 0).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Binary share format for NFC tags, so that custodians can carry their share on a keyfob.
+//!
+//! A share is stored as a single NDEF record of MIME type `application/vnd.horcrux.share`, whose
+//! payload is a type byte (0 for compact shares, 1 for random shares) followed by the bytes of the
+//! x coordinate and of the y coordinate. The NDEF message is wrapped in the TLV block used by
+//! NFC Forum Type 2 tags (such as the NTAG series), so that the resulting image can be written as
+//! is to the user memory of a tag with external NFC tools.
+
+use std::convert::TryInto;
+
+/// MIME type of the NDEF record.
+const MIME_TYPE: &[u8] = b"application/vnd.horcrux.share";
+
+/// Record header: message begin, message end, short record, TNF = MIME media type.
+const RECORD_HEADER: u8 = 0xD2;
+/// Tag of the NDEF message TLV block.
+const TLV_NDEF: u8 = 0x03;
+/// Tag of the terminator TLV block.
+const TLV_TERMINATOR: u8 = 0xFE;
+
+const COMPACT: u8 = 0;
+const RANDOM: u8 = 1;
+
+/// Common NTAG-series tags, with the size of their user memory in bytes.
+const TAGS: [(&str, usize); 3] = [("NTAG213", 144), ("NTAG215", 504), ("NTAG216", 888)];
+
+/// Returns the smallest common tag on which the given image fits.
+pub fn smallest_tag(image: &[u8]) -> Option<&'static str> {
+    TAGS.iter()
+        .find(|&&(_, capacity)| image.len() <= capacity)
+        .map(|&(name, _)| name)
+}
+
+/// Encodes a share in text format into a tag image. Returns `None` if the share isn't valid.
+pub fn encode(share: &str, compact: bool) -> Option<Vec<u8>> {
+    let (x, y) = share.split_once('|')?;
+    let mut payload = Vec::new();
+    if compact {
+        payload.push(COMPACT);
+        payload.push(x.parse::<u8>().ok()?);
+    } else {
+        payload.push(RANDOM);
+        payload.extend(hex::decode(x).ok()?);
+    }
+    payload.extend(hex::decode(y).ok()?);
+
+    let mut message = vec![
+        RECORD_HEADER,
+        MIME_TYPE.len() as u8,
+        payload.len().try_into().ok()?,
+    ];
+    message.extend_from_slice(MIME_TYPE);
+    message.extend(payload);
+
+    let mut image = vec![TLV_NDEF];
+    if message.len() < 0xFF {
+        image.push(message.len() as u8);
+    } else {
+        image.push(0xFF);
+        image.extend_from_slice(&(message.len() as u16).to_be_bytes());
+    }
+    image.extend(message);
+    image.push(TLV_TERMINATOR);
+    Some(image)
+}
+
+/// Decodes a tag image into a share in text format, together with whether it is a compact share.
+/// Returns `None` if the image doesn't contain a share.
+pub fn decode(image: &[u8]) -> Option<(bool, String)> {
+    let message = find_ndef_message(image)?;
+
+    let (&header, rest) = message.split_first()?;
+    if header != RECORD_HEADER {
+        return None;
+    }
+    let (&type_len, rest) = rest.split_first()?;
+    let (&payload_len, rest) = rest.split_first()?;
+    let (mime_type, payload) = rest.split_at_checked(type_len as usize)?;
+    if mime_type != MIME_TYPE || payload.len() != payload_len as usize {
+        return None;
+    }
+
+    let (&kind, coordinates) = payload.split_first()?;
+    match kind {
+        COMPACT => {
+            let (&x, y) = coordinates.split_first()?;
+            Some((true, format!("{}|{}", x, hex::encode(y))))
+        }
+        RANDOM if coordinates.len() % 2 == 0 => {
+            let (x, y) = coordinates.split_at(coordinates.len() / 2);
+            Some((false, format!("{}|{}", hex::encode(x), hex::encode(y))))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the value of the first NDEF message TLV block, skipping NULL blocks and other blocks.
+fn find_ndef_message(mut image: &[u8]) -> Option<&[u8]> {
+    loop {
+        let (&tag, rest) = image.split_first()?;
+        match tag {
+            0x00 => {
+                image = rest;
+                continue;
+            }
+            TLV_TERMINATOR => return None,
+            _ => (),
+        }
+
+        let (&len, rest) = rest.split_first()?;
+        let (len, rest) = if len == 0xFF {
+            let (len, rest) = rest.split_at_checked(2)?;
+            (u16::from_be_bytes([len[0], len[1]]) as usize, rest)
+        } else {
+            (len as usize, rest)
+        };
+        let (value, rest) = rest.split_at_checked(len)?;
+        if tag == TLV_NDEF {
+            return Some(value);
+        }
+        image = rest;
+    }
+}
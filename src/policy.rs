@@ -0,0 +1,323 @@
+//! Organization policies restricting the parameters of splits, loaded from signed policy files.
+//!
+//! Like a transcript, a policy is a plain text file made of `key=value` lines, followed by a final
+//! `signature` line containing an Ed25519 signature over all the preceding lines:
+//!
+//! ```text
+//! horcrux-policy-v1
+//! max_nshares=10
+//! min_threshold=3
+//! fields=gf128,gf256,secp256k1
+//! types=compact,random
+//! signature=...
+//! ```
+//!
+//! Splits outside of the policy are refused, unless the policy is explicitly overridden with a
+//! justification, which is then recorded in the output of the split.
+
+use crate::exit::{self, OrExit, OrFail};
+use crate::header::Header;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use horcrux::shamir::ErrorCategory;
+use std::fs::File;
+use std::io::Write;
+
+const HEADER: &str = "horcrux-policy-v1";
+
+/// Limits on the parameters of splits.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Policy {
+    /// Maximum number of shares of a split.
+    pub max_nshares: usize,
+    /// Minimum threshold of a split.
+    pub min_threshold: usize,
+    /// Allowed fields, named as in share headers (e.g. `gf256` or `secp256k1`).
+    pub fields: Vec<String>,
+    /// Allowed types of shares.
+    pub types: Vec<String>,
+}
+
+/// Override of a policy, with the violations that it allowed.
+pub struct Override {
+    pub violations: Vec<String>,
+    pub justification: String,
+}
+
+impl Policy {
+    /// Serializes the policy, without the signature line.
+    fn body(&self) -> String {
+        let mut body = format!("{HEADER}\n");
+        body += &format!("max_nshares={}\n", self.max_nshares);
+        body += &format!("min_threshold={}\n", self.min_threshold);
+        body += &format!("fields={}\n", self.fields.join(","));
+        body += &format!("types={}\n", self.types.join(","));
+        body
+    }
+
+    /// Reads an unsigned policy from the given file, i.e. the lines of a policy without the
+    /// signature.
+    pub fn read_unsigned(filename: &str) -> Self {
+        Self::parse_unsigned(&read_file(filename)).or_fail(ErrorCategory::Parse)
+    }
+
+    /// Signs the policy and writes it to the given file.
+    pub fn write(&self, filename: &str, key: &SigningKey) {
+        let body = self.body();
+        let signature = key.sign(body.as_bytes());
+
+        let mut file =
+            File::create(filename).or_exit(format_args!("Could not create {}", filename));
+        file.write_all(body.as_bytes())
+            .and_then(|_| writeln!(file, "signature={}", hex::encode(signature.to_bytes())))
+            .or_exit(format_args!("Could not write {}", filename));
+    }
+
+    /// Reads a policy from the given file, and verifies its signature.
+    pub fn read_verified(filename: &str, key: &VerifyingKey) -> Self {
+        let (policy, signature) =
+            Self::parse_signed(&read_file(filename)).or_fail(ErrorCategory::Parse);
+        exit::ensure(
+            key.verify(policy.body().as_bytes(), &signature).is_ok(),
+            ErrorCategory::Crypto,
+            "Invalid policy signature",
+        );
+        policy
+    }
+
+    /// Parses an unsigned policy, which must only contain the policy lines in canonical form.
+    fn parse_unsigned(contents: &str) -> Result<Self, String> {
+        let policy = Self::parse(contents)?;
+        if contents != policy.body() {
+            return Err(
+                "Unsigned policy must only contain the policy lines, in canonical form".to_owned(),
+            );
+        }
+        Ok(policy)
+    }
+
+    /// Parses a signed policy, and returns it with its signature, which remains to be verified.
+    fn parse_signed(contents: &str) -> Result<(Self, Signature), String> {
+        let policy = Self::parse(contents)?;
+        // Any non-canonical formatting, e.g. of numbers, is rejected here.
+        let signature = contents
+            .strip_prefix(&policy.body())
+            .and_then(|line| line.strip_prefix("signature="))
+            .and_then(|s| s.strip_suffix('\n'))
+            .and_then(|s| hex::decode(s).ok())
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or("Policy doesn't end with a valid signature line")?;
+        Ok((policy, signature))
+    }
+
+    /// Parses the policy lines, ignoring any following lines.
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("Unsupported policy format".to_owned());
+        }
+        let mut field = |key: &str| -> Result<String, String> {
+            let line = lines.next().unwrap_or_default();
+            match line.strip_prefix(key).and_then(|l| l.strip_prefix('=')) {
+                Some(value) => Ok(value.to_owned()),
+                None => Err(format!(
+                    "Expected policy field \"{}\", found: {}",
+                    key, line
+                )),
+            }
+        };
+        let list = |value: String| -> Vec<String> { value.split(',').map(str::to_owned).collect() };
+
+        Ok(Policy {
+            max_nshares: field("max_nshares")?
+                .parse()
+                .map_err(|_| "Policy max_nshares must be an integer")?,
+            min_threshold: field("min_threshold")?
+                .parse()
+                .map_err(|_| "Policy min_threshold must be an integer")?,
+            fields: list(field("fields")?),
+            types: list(field("types")?),
+        })
+    }
+
+    /// Returns the violations of the policy by a split with the parameters of the given header, and
+    /// the given pairs of threshold and number of shares (one per group for splits among groups).
+    pub fn violations(&self, header: &Header, splits: &[(usize, usize)]) -> Vec<String> {
+        let mut violations = Vec::new();
+        if !self.fields.contains(&header.field) {
+            violations.push(format!(
+                "the field {} is not allowed (allowed: {})",
+                header.field,
+                self.fields.join(", ")
+            ));
+        }
+        if !self.types.contains(&header.shamir_type) {
+            violations.push(format!(
+                "{} shares are not allowed (allowed: {})",
+                header.shamir_type,
+                self.types.join(", ")
+            ));
+        }
+        for &(k, n) in splits {
+            if n > self.max_nshares {
+                violations.push(format!(
+                    "{} shares exceed the maximum of {}",
+                    n, self.max_nshares
+                ));
+            }
+            if k < self.min_threshold {
+                violations.push(format!(
+                    "the threshold {} is below the minimum of {}",
+                    k, self.min_threshold
+                ));
+            }
+        }
+        violations
+    }
+}
+
+impl Override {
+    /// Returns the override as a JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "violations": self.violations,
+            "justification": self.justification,
+        })
+    }
+
+    /// Prints the override, in the text output of a split.
+    pub fn print(&self) {
+        println!("Policy overridden: {}", self.justification);
+        for violation in &self.violations {
+            println!("    - {}", violation);
+        }
+    }
+}
+
+fn read_file(filename: &str) -> String {
+    std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POLICY: &str = "horcrux-policy-v1
+max_nshares=10
+min_threshold=3
+fields=gf128,gf256,secp256k1
+types=compact,random
+";
+
+    fn policy() -> Policy {
+        Policy {
+            max_nshares: 10,
+            min_threshold: 3,
+            fields: vec![
+                "gf128".to_owned(),
+                "gf256".to_owned(),
+                "secp256k1".to_owned(),
+            ],
+            types: vec!["compact".to_owned(), "random".to_owned()],
+        }
+    }
+
+    fn header(shamir_type: &str, field: &str) -> Header {
+        Header {
+            shamir_type: shamir_type.to_owned(),
+            field: field.to_owned(),
+            threshold: 3,
+            set_id: "5f1c0a2e".to_owned(),
+        }
+    }
+
+    #[test]
+    fn can_parse_unsigned() {
+        assert_eq!(Policy::parse_unsigned(POLICY).unwrap(), policy());
+        assert_eq!(policy().body(), POLICY);
+    }
+
+    #[test]
+    fn rejects_invalid_unsigned() {
+        for (from, to) in [
+            ("horcrux-policy-v1", "horcrux-policy-v2"),
+            ("max_nshares=10", "max_nshares=010"),
+            ("min_threshold=3", "min_threshold=three"),
+            ("fields=", "field="),
+            (
+                "types=compact,random\n",
+                "types=compact,random\nsignature=00\n",
+            ),
+            ("types=compact,random\n", "types=compact,random"),
+        ] {
+            let modified = POLICY.replacen(from, to, 1);
+            assert_ne!(modified, POLICY);
+            assert!(
+                Policy::parse_unsigned(&modified).is_err(),
+                "Accepted {} instead of {}",
+                to,
+                from
+            );
+        }
+    }
+
+    #[test]
+    fn can_parse_signed() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let signature = key.sign(POLICY.as_bytes());
+        let contents = format!(
+            "{}signature={}\n",
+            POLICY,
+            hex::encode(signature.to_bytes())
+        );
+        let (parsed, signature) = Policy::parse_signed(&contents).unwrap();
+        assert_eq!(parsed, policy());
+        assert!(key
+            .verifying_key()
+            .verify(parsed.body().as_bytes(), &signature)
+            .is_ok());
+
+        let modified = contents.replacen("min_threshold=3", "min_threshold=2", 1);
+        let (parsed, signature) = Policy::parse_signed(&modified).unwrap();
+        assert!(key
+            .verifying_key()
+            .verify(parsed.body().as_bytes(), &signature)
+            .is_err());
+
+        assert!(Policy::parse_signed(POLICY).is_err());
+        assert!(
+            Policy::parse_signed(&contents.replacen("max_nshares=10", "max_nshares=+10", 1))
+                .is_err()
+        );
+        assert!(Policy::parse_signed(&format!("{}extra\n", contents)).is_err());
+    }
+
+    #[test]
+    fn can_find_violations() {
+        let policy = policy();
+        assert!(policy
+            .violations(&header("compact", "gf256"), &[(3, 10)])
+            .is_empty());
+        assert_eq!(
+            policy
+                .violations(&header("compact", "gf512"), &[(3, 5)])
+                .len(),
+            1
+        );
+        assert_eq!(
+            policy.violations(&header("dkg", "gf256"), &[(3, 5)]).len(),
+            1
+        );
+        assert_eq!(
+            policy
+                .violations(&header("compact", "gf256"), &[(2, 11), (3, 5)])
+                .len(),
+            2
+        );
+        assert_eq!(
+            policy
+                .violations(&header("compact", "gf256"), &[(2, 5), (2, 5)])
+                .len(),
+            2
+        );
+    }
+}
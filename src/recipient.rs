@@ -0,0 +1,146 @@
+//! Sealing of shares to the X25519 public keys of their recipients, so that the shares can be
+//! distributed over untrusted channels.
+//!
+//! Each share is encrypted with age (https://age-encryption.org) to its recipient, i.e. to an
+//! `age1...` public key generated by `age-keygen`, or to an X25519 public key given as 32 bytes in
+//! hexadecimal. The sealed share `horcrux-sealed-v2:<recipient>:<age file>` contains the binary age
+//! file in hexadecimal, and names its recipient (as an `age1...` key), so that each recipient finds
+//! its shares among the output of a split. Recipients can decrypt their shares either with
+//! decrypt-share, or with age itself after decoding the hexadecimal.
+
+use bech32::{FromBase32, ToBase32, Variant};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use std::convert::TryInto;
+use std::io::{Read, Write};
+use std::iter;
+use zeroize::Zeroizing;
+
+const HEADER: &str = "horcrux-sealed-v2";
+/// Human-readable part of the bech32 encoding of age public keys.
+const AGE_PUBLIC_HRP: &str = "age";
+/// Human-readable part of the bech32 encoding of age private keys.
+const AGE_SECRET_HRP: &str = "age-secret-key-";
+
+/// Parses the public keys of the recipients, one per line in the order of the shares, ignoring
+/// empty lines and comments. Returns an error if a key has a small order, i.e. if the sealed share
+/// wouldn't depend on the ephemeral key of age.
+pub fn parse_recipients(contents: &str, n: usize) -> Result<Vec<age::x25519::Recipient>, String> {
+    let recipients = key_lines(contents)
+        .map(|(i, line)| {
+            let key = parse_key(line, AGE_PUBLIC_HRP).ok_or_else(|| {
+                format!(
+                    "Line {} isn't an X25519 public key in hexadecimal or an age1... recipient",
+                    i + 1
+                )
+            })?;
+            // Clamped scalars are multiples of the cofactor, so the product is zero exactly for
+            // keys of small order.
+            if MontgomeryPoint(*key).mul_clamped([0x55; 32]) == MontgomeryPoint([0; 32]) {
+                return Err(format!("Line {} is a public key of small order", i + 1));
+            }
+            Ok(to_age(&key, AGE_PUBLIC_HRP).parse().unwrap())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if recipients.len() != n {
+        return Err(format!(
+            "Expected {} recipients, one per share, but got {}",
+            n,
+            recipients.len()
+        ));
+    }
+    Ok(recipients)
+}
+
+/// Parses a private key, ignoring empty lines and comments, as in the files of `age-keygen`.
+pub fn parse_identity(contents: &str) -> Result<age::x25519::Identity, String> {
+    let keys: Vec<(usize, &str)> = key_lines(contents).collect();
+    match keys[..] {
+        [(_, line)] => match parse_key(line, AGE_SECRET_HRP) {
+            Some(key) => Ok(to_age(&key, AGE_SECRET_HRP).parse().unwrap()),
+            None => Err(
+                "The identity isn't an X25519 private key in hexadecimal or an AGE-SECRET-KEY-1... key"
+                    .to_owned(),
+            ),
+        },
+        _ => Err("The identity file must contain exactly one private key".to_owned()),
+    }
+}
+
+/// Seals a share to the given recipient.
+pub fn seal(recipient: &age::x25519::Recipient, share: &str) -> Result<String, String> {
+    let encryptor = age::Encryptor::with_recipients(vec![Box::new(recipient.clone())])
+        .expect("A recipient is given");
+    let mut sealed = Vec::new();
+    let mut writer = encryptor
+        .wrap_output(&mut sealed)
+        .map_err(|e| format!("Could not seal the share: {}", e))?;
+    writer
+        .write_all(share.as_bytes())
+        .and_then(|_| writer.finish())
+        .map_err(|e| format!("Could not seal the share: {}", e))?;
+    Ok(format!("{}:{}:{}", HEADER, recipient, hex::encode(sealed)))
+}
+
+/// Checks and decrypts a sealed share with the private key of its recipient. Returns `None` if the
+/// share is sealed to another recipient.
+pub fn open(
+    identity: &age::x25519::Identity,
+    sealed: &str,
+) -> Result<Option<Zeroizing<String>>, String> {
+    let (recipient, sealed) = match sealed.trim().split(':').collect::<Vec<_>>()[..] {
+        [HEADER, recipient, sealed] => (recipient, sealed),
+        _ => return Err(format!("Sealed shares must be in the {} format", HEADER)),
+    };
+    if recipient != identity.to_public().to_string() {
+        return Ok(None);
+    }
+    let sealed = hex::decode(sealed).map_err(|e| format!("Invalid sealed share: {}", e))?;
+    let modified = |_| "The sealed share doesn't match the private key, it was modified".to_owned();
+    let decryptor = match age::Decryptor::new(&sealed[..]).map_err(modified)? {
+        age::Decryptor::Recipients(decryptor) => decryptor,
+        _ => return Err("Invalid sealed share: it isn't sealed to a public key".to_owned()),
+    };
+    let mut share = Zeroizing::new(Vec::new());
+    decryptor
+        .decrypt(iter::once(identity as &dyn age::Identity))
+        .map_err(modified)?
+        .read_to_end(&mut share)
+        .map_err(|_| {
+            "The sealed share doesn't match the private key, it was modified".to_owned()
+        })?;
+    let share = String::from_utf8(share.to_vec())
+        .map_err(|_| "Invalid sealed share: the share isn't valid text".to_owned())?;
+    Ok(Some(Zeroizing::new(share)))
+}
+
+/// Returns the public key of a private key, as an `age1...` recipient.
+pub fn public_key(identity: &age::x25519::Identity) -> String {
+    identity.to_public().to_string()
+}
+
+/// Returns the lines of a key file with their numbers, ignoring empty lines and comments.
+fn key_lines(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+    contents
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Parses a 32-byte key, in hexadecimal or in bech32 with the given human-readable part, which
+/// is case insensitive.
+fn parse_key(s: &str, hrp: &str) -> Option<Zeroizing<[u8; 32]>> {
+    let bytes = Zeroizing::new(match s.len() {
+        64 => hex::decode(s).ok()?,
+        _ => match bech32::decode(s).ok()? {
+            (prefix, data, Variant::Bech32) if prefix == hrp => Vec::from_base32(&data).ok()?,
+            _ => return None,
+        },
+    });
+    bytes[..].try_into().ok().map(Zeroizing::new)
+}
+
+/// Encodes a 32-byte key in bech32 with the given human-readable part, as parsed by age.
+fn to_age(key: &[u8; 32], hrp: &str) -> Zeroizing<String> {
+    Zeroizing::new(bech32::encode(hrp, key.to_base32(), Variant::Bech32).unwrap())
+}
@@ -0,0 +1,103 @@
+//! Reading of the secret to split (or of a passphrase) from standard input, so that it never has to
+//! be written to the filesystem. On a terminal, it is prompted for with the echo disabled.
+
+use crate::exit::OrExit;
+use std::io::{BufRead, IsTerminal, Read, Write};
+use zeroize::Zeroizing;
+
+/// Reads the secret from standard input. If standard input is a terminal, prompts for the secret on
+/// stderr without echoing it.
+pub fn read_stdin() -> Zeroizing<String> {
+    let stdin = std::io::stdin();
+    let mut contents = Zeroizing::new(String::new());
+    if stdin.is_terminal() {
+        eprint!("Secret (hexadecimal, not echoed): ");
+        std::io::stderr()
+            .flush()
+            .or_exit("Could not prompt for the secret");
+        let _echo = EchoGuard::disable();
+        stdin
+            .lock()
+            .read_line(&mut contents)
+            .or_exit("Could not read the secret");
+    } else {
+        stdin
+            .lock()
+            .read_to_string(&mut contents)
+            .or_exit("Could not read the secret");
+    }
+    contents
+}
+
+/// Reads a passphrase from standard input. If standard input is a terminal, prompts for it on
+/// stderr without echoing it, otherwise reads the first line. The trailing newline is removed.
+pub fn read_passphrase(prompt: &str) -> Zeroizing<String> {
+    let stdin = std::io::stdin();
+    let mut line = Zeroizing::new(String::new());
+    let _echo = stdin.is_terminal().then(|| {
+        eprint!("{} (not echoed): ", prompt);
+        std::io::stderr()
+            .flush()
+            .or_exit("Could not prompt for the passphrase");
+        EchoGuard::disable()
+    });
+    stdin
+        .lock()
+        .read_line(&mut line)
+        .or_exit("Could not read the passphrase");
+    Zeroizing::new(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// Returns whether standard input is a terminal, i.e. whether the operator is prompted.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+/// Disables the echo of the terminal on standard input (except for the final newline), until
+/// dropped.
+struct EchoGuard {
+    #[cfg(unix)]
+    original: Option<libc::termios>,
+}
+
+impl EchoGuard {
+    #[cfg(unix)]
+    fn disable() -> Self {
+        // SAFETY: the termios structure is plain data, fully initialized by tcgetattr on success.
+        let original = unsafe {
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                None
+            } else {
+                let mut hidden = termios;
+                hidden.c_lflag &= !libc::ECHO;
+                hidden.c_lflag |= libc::ECHONL;
+                assert_eq!(
+                    libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &hidden),
+                    0,
+                    "Could not disable the terminal echo"
+                );
+                Some(termios)
+            }
+        };
+        EchoGuard { original }
+    }
+
+    #[cfg(not(unix))]
+    fn disable() -> Self {
+        eprintln!("Warning: the terminal echo can't be disabled on this platform");
+        EchoGuard {}
+    }
+}
+
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(original) = &self.original {
+            // SAFETY: restores the settings previously returned by tcgetattr.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original);
+            }
+        }
+    }
+}
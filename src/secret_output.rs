@@ -0,0 +1,101 @@
+//! Writing of the reconstructed secret in formats consumed by deployment tooling, so that it doesn't
+//! have to be copied from the terminal into configuration files.
+
+use crate::exit::{self, OrExit};
+use horcrux::shamir::ErrorCategory;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+/// Format of the secret file.
+pub enum OutFormat {
+    /// Environment file (e.g. for dotenv or systemd's `EnvironmentFile=`) with a single
+    /// `<KEY_NAME>=<secret>` line.
+    Env(String),
+    /// Credential encrypted with `systemd-creds encrypt`, to the host key and/or the TPM depending on
+    /// the system's defaults, for systemd's `LoadCredentialEncrypted=`.
+    SystemdCredential,
+}
+
+impl OutFormat {
+    /// Parses a format given as `env:<KEY_NAME>` or `systemd-credential`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.strip_prefix("env:") {
+            Some(key) => {
+                let mut chars = key.chars();
+                let valid = chars
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if valid {
+                    Ok(OutFormat::Env(key.to_owned()))
+                } else {
+                    Err(format!("Invalid environment variable name: {:?}", key))
+                }
+            }
+            None if s == "systemd-credential" => Ok(OutFormat::SystemdCredential),
+            None => Err("The format must be env:<KEY_NAME> or systemd-credential".to_owned()),
+        }
+    }
+}
+
+/// Writes the secret (in hexadecimal) to a new file in the given format. The file must not exist
+/// yet, so that it's created with permissions restricted to the current user (0600).
+pub fn write(format: &OutFormat, filename: &str, secret: &str) {
+    match format {
+        OutFormat::Env(key) => {
+            let mut file = create_private(filename);
+            let line = Zeroizing::new(format!("{}={}\n", key, secret));
+            file.write_all(line.as_bytes())
+                .or_exit(format_args!("Could not write {}", filename));
+        }
+        OutFormat::SystemdCredential => {
+            // The credential is named after the file, as systemd-creds does by default.
+            let name = Path::new(filename)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_else(|| {
+                    exit::fail_with(
+                        ErrorCategory::Usage,
+                        format_args!("Invalid credential file name: {}", filename),
+                    )
+                });
+            let mut child = Command::new("systemd-creds")
+                .args(["encrypt", &format!("--name={}", name), "-", "-"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .or_exit("Could not run systemd-creds");
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(secret.as_bytes())
+                .or_exit("Could not pass the secret to systemd-creds");
+            let output = child
+                .wait_with_output()
+                .or_exit("Could not run systemd-creds");
+            exit::ensure(
+                output.status.success(),
+                ErrorCategory::Io,
+                format_args!("systemd-creds failed with {}", output.status),
+            );
+            create_private(filename)
+                .write_all(&output.stdout)
+                .or_exit(format_args!("Could not write {}", filename));
+        }
+    }
+}
+
+/// Creates a new file readable and writable only by the current user.
+fn create_private(filename: &str) -> File {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(filename)
+        .or_exit(format_args!("Could not create {}", filename))
+}
@@ -0,0 +1,78 @@
+//! Authentication tags of shares under a verification key, so that each custodian can check the
+//! integrity of their own share at any time, without contacting anyone.
+//!
+//! The verification key is random and independent of the secret, so it can be handed to all the
+//! custodians along with their shares: it doesn't reveal anything about the secret. The tag of a
+//! share is a MAC of the share in canonical text format under this key (HMAC-SHA256 by default, see
+//! `HashAlgorithm::mac()`), in hexadecimal and prefixed by the identifier of the hash algorithm if
+//! it isn't the default one (e.g. `blake3:<hex>`), so that it can be verified with the key alone.
+//!
+//! As every custodian knows the key, a valid tag doesn't prove who issued the share: tags detect
+//! corrupted or mistyped shares, and shares replaced by someone who doesn't have the key.
+
+use horcrux::ct;
+use horcrux::hashing::HashAlgorithm;
+
+/// Domain separator of the tags, prepended to the share.
+const DOMAIN: &[u8] = b"horcrux-share-mac-v1:";
+
+/// Returns the tag of a share in canonical text format.
+pub fn tag(hash: HashAlgorithm, key: &[u8], share: &str) -> String {
+    let mac = hex::encode(hash.mac(key, &[DOMAIN, share.as_bytes()]));
+    match hash.is_default() {
+        true => mac,
+        false => format!("{}:{}", hash, mac),
+    }
+}
+
+/// Checks the tag of a share in canonical text format.
+pub fn verify(key: &[u8], share: &str, tag: &str) -> Result<(), String> {
+    let (hash, mac) = match tag.trim().split_once(':') {
+        None => (HashAlgorithm::default(), tag.trim()),
+        Some((hash, mac)) => (hash.parse().map_err(|e| format!("{}", e))?, mac),
+    };
+    let mac = hex::decode(mac).map_err(|e| format!("Invalid share MAC: {}", e))?;
+    let expected = hash.mac(key, &[DOMAIN, share.as_bytes()]);
+    if !ct::bytes_eq(&mac, &expected) {
+        return Err(
+            "The share doesn't match its MAC, it was corrupted or the verification key is wrong!"
+                .to_owned(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: &[u8] = b"verification key";
+
+    #[test]
+    fn can_tag_and_verify() {
+        let tag = tag(HashAlgorithm::default(), KEY, "1|abcd");
+        assert_eq!(tag.len(), 64);
+        assert!(verify(KEY, "1|abcd", &tag).is_ok());
+        assert!(verify(KEY, "1|abcd", &format!(" {}\n", tag)).is_ok());
+        assert!(verify(KEY, "1|abce", &tag).is_err());
+        assert!(verify(b"other key", "1|abcd", &tag).is_err());
+    }
+
+    #[test]
+    fn can_tag_with_another_hash() {
+        let tag = tag(HashAlgorithm::Blake3, KEY, "1|abcd");
+        assert!(tag.starts_with("blake3:"));
+        assert!(verify(KEY, "1|abcd", &tag).is_ok());
+        let stripped = tag.strip_prefix("blake3:").unwrap();
+        assert!(verify(KEY, "1|abcd", stripped).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_tags() {
+        let tag = tag(HashAlgorithm::default(), KEY, "1|abcd");
+        assert!(verify(KEY, "1|abcd", "").is_err());
+        assert!(verify(KEY, "1|abcd", "not hex").is_err());
+        assert!(verify(KEY, "1|abcd", &format!("md5:{}", tag)).is_err());
+        assert!(verify(KEY, "1|abcd", &tag[..62]).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Statistics about the shares of a split, read from a file of shares or from a split manifest, to
+//! plan the storage media of the shares and to review the health of a ceremony.
+//!
+//! The x coordinates of compact shares are the consecutive integers 1, 2, ..., n. Those of random
+//! shares should look uniformly random, i.e. their hexadecimal digits should carry close to 4 bits
+//! of entropy each: a much lower estimate hints at a broken random number generator. Refreshed
+//! shares keep their x coordinates, so x coordinates issued more than once reveal shares of
+//! several refreshes (or of several splits) stored together.
+//!
+//! QR code sizes assume the byte mode with error correction level M, as shares contain lowercase
+//! letters, which the alphanumeric mode doesn't support.
+
+use horcrux::schema::{FieldKind, Scheme, ShareHeader, ShareLine, SplitManifest};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Number of bytes that a QR code of each version (1 to 40) holds in byte mode, with error
+/// correction level M.
+const QR_CAPACITIES: [usize; 40] = [
+    14, 26, 42, 62, 84, 106, 122, 152, 180, 213, 251, 287, 331, 362, 412, 450, 504, 560, 624, 666,
+    711, 779, 857, 911, 997, 1059, 1125, 1190, 1264, 1370, 1452, 1538, 1628, 1722, 1809, 1911,
+    1989, 2099, 2213, 2331,
+];
+/// Size of a printed module of a QR code, in millimeters, which most phone cameras read reliably.
+const QR_MODULE_MM: f64 = 0.5;
+/// Minimum number of hexadecimal digits of random x coordinates to warn about a low entropy, as
+/// the estimate is biased downwards for fewer digits.
+const MIN_ENTROPY_DIGITS: usize = 64;
+/// Entropy estimate (in bits per hexadecimal digit) below which random x coordinates are
+/// suspicious.
+const MIN_ENTROPY: f64 = 3.5;
+
+/// Statistics about a set of shares.
+pub struct Stats {
+    /// Lines of the shares, in text format.
+    lines: Vec<String>,
+    /// Parsed share lines, or `None` for shares in other formats (records, envelopes, etc.).
+    shares: Vec<Option<ShareLine>>,
+    /// Manifest describing the split, if the shares were read from one.
+    manifest: Option<SplitManifest>,
+}
+
+impl Stats {
+    /// Reads the shares of a file, one per line, ignoring empty lines and comments.
+    pub fn from_shares(contents: &str) -> Self {
+        let lines: Vec<String> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect();
+        Self::new(lines, None)
+    }
+
+    /// Reads the shares of a split manifest.
+    pub fn from_manifest(contents: &str) -> Result<Self, String> {
+        let manifest: SplitManifest =
+            serde_json::from_str(contents).map_err(|e| format!("Invalid manifest: {}", e))?;
+        Ok(Self::new(manifest.shares.clone(), Some(manifest)))
+    }
+
+    fn new(lines: Vec<String>, manifest: Option<SplitManifest>) -> Self {
+        let shares = lines.iter().map(|line| line.parse().ok()).collect();
+        Stats {
+            lines,
+            shares,
+            manifest,
+        }
+    }
+
+    /// Returns the value of a header field if all the headers agree on it, or the value given by
+    /// the manifest.
+    fn describe<T: PartialEq + Display>(
+        &self,
+        from_header: impl Fn(&ShareHeader) -> T,
+        from_manifest: impl Fn(&SplitManifest) -> Option<T>,
+    ) -> Option<String> {
+        let mut values = self
+            .shares
+            .iter()
+            .flatten()
+            .filter_map(|s| s.header.as_ref());
+        match values.next() {
+            Some(first) => {
+                let value = from_header(first);
+                Some(match values.all(|h| from_header(h) == value) {
+                    true => value.to_string(),
+                    false => format!("mixed, starting with {}", value),
+                })
+            }
+            None => self
+                .manifest
+                .as_ref()
+                .and_then(from_manifest)
+                .map(|v| v.to_string()),
+        }
+    }
+
+    /// Returns the x coordinates of the parsed shares, in order.
+    fn xs(&self) -> Vec<&str> {
+        self.shares
+            .iter()
+            .flatten()
+            .map(|s| s.share.split('|').next().unwrap())
+            .collect()
+    }
+
+    /// Returns the type of the shares, given by the headers or the manifest, or guessed from the
+    /// x coordinates: those of compact shares are decimal integers of at most 3 digits, whereas
+    /// those of random shares are as long as the y coordinates.
+    fn scheme(&self) -> Option<Scheme> {
+        let mut schemes = self
+            .shares
+            .iter()
+            .flatten()
+            .filter_map(|s| s.header.as_ref());
+        if let Some(first) = schemes.next() {
+            return schemes
+                .all(|h| h.scheme == first.scheme)
+                .then_some(first.scheme);
+        }
+        if let Some(manifest) = &self.manifest {
+            return Some(manifest.scheme);
+        }
+        let xs = self.xs();
+        if xs.is_empty() {
+            return None;
+        }
+        match xs.iter().all(|x| x.len() <= 3 && x.parse::<u8>().is_ok()) {
+            true => Some(Scheme::Compact),
+            false => Some(Scheme::Random),
+        }
+    }
+}
+
+fn scheme_name(scheme: Scheme) -> &'static str {
+    match scheme {
+        Scheme::Compact => "compact",
+        Scheme::Random => "random",
+    }
+}
+
+fn field_name(manifest: &SplitManifest) -> String {
+    match manifest.field {
+        FieldKind::Binary => format!("gf{}", manifest.bitsize),
+        FieldKind::Secp256k1 => "secp256k1".to_owned(),
+        FieldKind::Ed25519 => "ed25519".to_owned(),
+    }
+}
+
+/// Formats sorted integers as comma-separated ranges, e.g. `1-3, 5`.
+fn ranges(values: &[usize]) -> String {
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        let mut j = i;
+        while j + 1 < values.len() && values[j + 1] == values[j] + 1 {
+            j += 1;
+        }
+        result.push(match i == j {
+            true => values[i].to_string(),
+            false => format!("{}-{}", values[i], values[j]),
+        });
+        i = j + 1;
+    }
+    result.join(", ")
+}
+
+/// Estimates the entropy of the hexadecimal digits of x coordinates, in bits per digit, from
+/// their frequencies.
+fn digit_entropy(xs: &[&str]) -> (usize, f64) {
+    let mut counts = [0usize; 16];
+    let mut total = 0;
+    for c in xs.iter().flat_map(|x| x.chars()) {
+        counts[c.to_digit(16).unwrap() as usize] += 1;
+        total += 1;
+    }
+    let entropy = counts
+        .iter()
+        .filter(|&&count| count != 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum();
+    (total, entropy)
+}
+
+/// Returns the smallest QR code version holding the given number of bytes, if any.
+fn qr_version(len: usize) -> Option<usize> {
+    QR_CAPACITIES
+        .iter()
+        .position(|&capacity| capacity >= len)
+        .map(|i| i + 1)
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let unknown = || "unknown (no share header)".to_owned();
+        writeln!(f, "Shares = {}", self.lines.len())?;
+        if let Some(nshares) = self.manifest.as_ref().and_then(|m| m.nshares) {
+            writeln!(f, "Shares issued by the split = {}", nshares)?;
+        }
+        if let Some(witnesses) = self
+            .manifest
+            .as_ref()
+            .and_then(|m| m.witness_shares.as_ref())
+        {
+            writeln!(f, "Witness shares = {}", witnesses.len())?;
+        }
+        let scheme = self.describe(|h| scheme_name(h.scheme), |m| Some(scheme_name(m.scheme)));
+        match (scheme, self.scheme()) {
+            (Some(scheme), _) => writeln!(f, "Type = {}", scheme)?,
+            (None, Some(scheme)) => writeln!(
+                f,
+                "Type = {} (guessed from the x coordinates)",
+                scheme_name(scheme)
+            )?,
+            (None, None) => writeln!(f, "Type = {}", unknown())?,
+        }
+        let field = self.describe(|h| h.field.clone(), |m| Some(field_name(m)));
+        writeln!(f, "Field = {}", field.unwrap_or_else(unknown))?;
+        let threshold = self.describe(|h| h.threshold, |m| m.threshold);
+        writeln!(f, "Threshold = {}", threshold.unwrap_or_else(unknown))?;
+
+        // Formats and sizes.
+        let parsed: Vec<&ShareLine> = self.shares.iter().flatten().collect();
+        let with_header = parsed.iter().filter(|s| s.header.is_some()).count();
+        let with_checksum = parsed
+            .iter()
+            .filter(|s| s.share.matches('|').count() == 2)
+            .count();
+        writeln!(f, "Formats:")?;
+        writeln!(f, "- {} share(s) with a header", with_header)?;
+        writeln!(f, "- {} share(s) with a checksum", with_checksum)?;
+        writeln!(
+            f,
+            "- {} share(s) in another format (records, envelopes, shares of groups, etc.)",
+            self.lines.len() - parsed.len()
+        )?;
+        let ybits: Vec<usize> = parsed
+            .iter()
+            .map(|s| 4 * s.share.split('|').nth(1).unwrap().len())
+            .collect();
+        if let (Some(min), Some(max)) = (ybits.iter().min(), ybits.iter().max()) {
+            match min == max {
+                true => writeln!(f, "y coordinates = {} bits", min)?,
+                false => writeln!(f, "y coordinates = {} to {} bits", min, max)?,
+            }
+        }
+        let lens: Vec<usize> = self.lines.iter().map(String::len).collect();
+        let (min_len, max_len) = match (lens.iter().min(), lens.iter().max()) {
+            (Some(&min), Some(&max)) => (min, max),
+            _ => return Ok(()),
+        };
+        let total: usize = lens.iter().sum();
+        match min_len == max_len {
+            true => writeln!(
+                f,
+                "Share length = {} characters, {} in total",
+                max_len, total
+            )?,
+            false => writeln!(
+                f,
+                "Share length = {} to {} characters, {} in total",
+                min_len, max_len, total
+            )?,
+        }
+
+        // Distribution of the x coordinates.
+        let xs = self.xs();
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for x in &xs {
+            *counts.entry(x).or_default() += 1;
+        }
+        writeln!(f, "Distinct x coordinates = {}", counts.len())?;
+        match self.scheme() {
+            Some(Scheme::Compact) => {
+                let mut values: Vec<usize> = counts.keys().filter_map(|x| x.parse().ok()).collect();
+                values.sort_unstable();
+                writeln!(f, "x coordinates = {}", ranges(&values))?;
+            }
+            Some(Scheme::Random) => {
+                let distinct: Vec<&str> = counts.keys().copied().collect();
+                let (digits, entropy) = digit_entropy(&distinct);
+                writeln!(
+                    f,
+                    "x entropy = {:.2} bits per hexadecimal digit (4 for uniform x coordinates), over {} digits",
+                    entropy, digits
+                )?;
+                if digits >= MIN_ENTROPY_DIGITS && entropy < MIN_ENTROPY {
+                    writeln!(
+                        f,
+                        "Warning: the x coordinates don't look random, check the random number generator of the split!"
+                    )?;
+                }
+            }
+            None => (),
+        }
+
+        // Refresh history.
+        let mut sets: Vec<(&str, usize)> = Vec::new();
+        for header in parsed.iter().filter_map(|s| s.header.as_ref()) {
+            match sets.iter_mut().find(|(id, _)| *id == header.set_id) {
+                Some((_, count)) => *count += 1,
+                None => sets.push((&header.set_id, 1)),
+            }
+        }
+        if !sets.is_empty() {
+            writeln!(f, "Splits, by order of first appearance:")?;
+            for (id, count) in &sets {
+                writeln!(f, "- {}: {} share(s)", id, count)?;
+            }
+        }
+        let reissued = counts.values().filter(|&&count| count > 1).count();
+        if reissued != 0 {
+            writeln!(
+                f,
+                "x coordinates issued more than once = {}, up to {} times (refreshed shares, or shares of several splits)",
+                reissued,
+                counts.values().max().unwrap()
+            )?;
+        }
+
+        // Printing requirements.
+        match qr_version(max_len) {
+            Some(version) => {
+                let modules = 17 + 4 * version;
+                writeln!(
+                    f,
+                    "QR code = version {} ({}x{} modules), at least {:.0} mm wide with its quiet zone at {} mm per module",
+                    version,
+                    modules,
+                    modules,
+                    (modules + 8) as f64 * QR_MODULE_MM,
+                    QR_MODULE_MM
+                )?;
+            }
+            None => writeln!(
+                f,
+                "QR code = none, the longest share doesn't fit in a single QR code"
+            )?,
+        }
+        Ok(())
+    }
+}
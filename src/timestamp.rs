@@ -0,0 +1,176 @@
+//! Locale-independent timestamps in the fixed RFC 3339 layout `YYYY-MM-DDTHH:MM:SSZ` (UTC, second
+//! precision), so that ceremony artifacts remain readable on any machine.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86400;
+
+/// Returns the current time, as seconds since the UNIX epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Formats seconds since the UNIX epoch as an RFC 3339 timestamp in UTC.
+pub fn format(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days(timestamp / SECONDS_PER_DAY);
+    let seconds = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Parses an RFC 3339 timestamp in the layout produced by `format`, returning seconds since the
+/// UNIX epoch. Returns `None` for any other layout or for an invalid date.
+pub fn parse(s: &str) -> Option<u64> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 20
+        || !bytes
+            .iter()
+            .zip(b"dddd-dd-ddTdd:dd:ddZ")
+            .all(layout_matches)
+    {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| s[range].parse::<u64>().unwrap();
+    let (year, month, day) = (number(0..4), number(5..7), number(8..10));
+    let (hour, minute, second) = (number(11..13), number(14..16), number(17..19));
+
+    if year < 1970
+        || !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second)
+}
+
+fn layout_matches((&c, &expected): (&u8, &u8)) -> bool {
+    match expected {
+        b'd' => c.is_ascii_digit(),
+        _ => c == expected,
+    }
+}
+
+fn is_leap_year(year: u64) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Converts a date of the proleptic Gregorian calendar (from 1970) to days since the UNIX epoch.
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    // Count years from March, so that the leap day is at the end of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    // Days from 0000-03-01 to 1970-01-01.
+    era * 146097 + day_of_era - 719468
+}
+
+/// Converts days since the UNIX epoch to a date of the proleptic Gregorian calendar.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format, parse};
+
+    #[test]
+    fn can_format_epoch() {
+        assert_eq!(format(0), "1970-01-01T00:00:00Z");
+        assert_eq!(parse("1970-01-01T00:00:00Z"), Some(0));
+    }
+
+    #[test]
+    fn can_round_trip() {
+        for &(timestamp, text) in &[
+            // Leap days, including a leap century.
+            (951782400, "2000-02-29T00:00:00Z"),
+            (1709251199, "2024-02-29T23:59:59Z"),
+            // Ends of months.
+            (1706745599, "2024-01-31T23:59:59Z"),
+            (1709251200, "2024-03-01T00:00:00Z"),
+            (1719791999, "2024-06-30T23:59:59Z"),
+            (1735689599, "2024-12-31T23:59:59Z"),
+            (1677628799, "2023-02-28T23:59:59Z"),
+            (4107542400, "2100-03-01T00:00:00Z"),
+        ] {
+            assert_eq!(format(timestamp), text);
+            assert_eq!(parse(text), Some(timestamp));
+        }
+        for timestamp in (0..5_000_000_000).step_by(86_399 * 97) {
+            assert_eq!(parse(&format(timestamp)), Some(timestamp));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for text in &[
+            "2023-02-29T00:00:00Z",
+            "2100-02-29T00:00:00Z",
+            "2024-04-31T00:00:00Z",
+            "2024-13-01T00:00:00Z",
+            "2024-00-01T00:00:00Z",
+            "2024-01-00T00:00:00Z",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:60:00Z",
+            "2024-01-01T00:00:60Z",
+            "1969-12-31T23:59:59Z",
+        ] {
+            assert_eq!(parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for text in &[
+            "",
+            "1700000000",
+            "2024-01-01",
+            "2024-01-01T00:00:00",
+            "2024-01-01T00:00:00+00:00",
+            "2024-01-01 00:00:00Z",
+            "2024-01-01t00:00:00z",
+            "2024-1-01T00:00:00Z",
+            " 2024-01-01T00:00:00Z",
+            "2024-01-01T00:00:00Z\n",
+            "+024-01-01T00:00:00Z",
+            "２024-01-01T00:00:00Z",
+        ] {
+            assert_eq!(parse(text), None, "{:?}", text);
+        }
+    }
+}
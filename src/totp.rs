@@ -0,0 +1,149 @@
+//! Splitting of TOTP/2FA seed batches, as lists of `otpauth://` URIs or as unencrypted Aegis or
+//! andOTP JSON exports.
+//!
+//! An export doesn't fit in a single field element, so it is sealed with a random key: the export
+//! is encrypted and authenticated with ChaCha20-Poly1305 under the SHA-256 hash of the key (or its
+//! BLAKE3 hash, in which case the header of the sealed export is followed by `:blake3`), and only
+//! the key is split into shares. The sealed export reveals nothing without the key (except its
+//! length), and can be stored alongside the shares. Reconstruction yields the original export byte
+//! for byte, which can be imported back into the authenticator app.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use horcrux::hashing::HashAlgorithm;
+use rand::{thread_rng, RngCore};
+use zeroize::Zeroizing;
+
+const HEADER: &str = "horcrux-totp-v2";
+
+/// Checks that the contents are a TOTP export in one of the supported formats. Returns the name of
+/// the format and the number of entries.
+pub fn check_export(contents: &str) -> Result<(&'static str, usize), String> {
+    let trimmed = contents.trim();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        let json: serde_json::Value =
+            serde_json::from_str(trimmed).map_err(|e| format!("Invalid JSON export: {}", e))?;
+        return match &json {
+            serde_json::Value::Object(export) => match export.get("db") {
+                Some(serde_json::Value::String(_)) => Err(
+                    "Encrypted Aegis exports aren't supported, export the vault without encryption"
+                        .to_owned(),
+                ),
+                Some(db) => {
+                    let entries = db["entries"]
+                        .as_array()
+                        .ok_or("Aegis export without entries")?;
+                    check_entries(entries, |entry| &entry["info"]["secret"])?;
+                    Ok(("Aegis", entries.len()))
+                }
+                None => Err("Unrecognized JSON export".to_owned()),
+            },
+            serde_json::Value::Array(entries) => {
+                check_entries(entries, |entry| &entry["secret"])?;
+                Ok(("andOTP", entries.len()))
+            }
+            _ => Err("Unrecognized JSON export".to_owned()),
+        };
+    }
+
+    let mut count = 0;
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if !line.starts_with("otpauth://") || !line.contains("secret=") {
+            return Err(format!(
+                "Line {} isn't an otpauth:// URI with a secret",
+                i + 1
+            ));
+        }
+        count += 1;
+    }
+    if count == 0 {
+        return Err("The export doesn't contain any entry".to_owned());
+    }
+    Ok(("otpauth URIs", count))
+}
+
+/// Checks that all the entries of a JSON export have a secret.
+fn check_entries(
+    entries: &[serde_json::Value],
+    secret: impl Fn(&serde_json::Value) -> &serde_json::Value,
+) -> Result<(), String> {
+    if entries.is_empty() {
+        return Err("The export doesn't contain any entry".to_owned());
+    }
+    match entries.iter().position(|entry| !secret(entry).is_string()) {
+        Some(i) => Err(format!("Entry {} of the export has no secret", i + 1)),
+        None => Ok(()),
+    }
+}
+
+/// Encrypts and authenticates the export with the given key, which is hashed into a
+/// ChaCha20-Poly1305 key, under a random nonce. Returns the sealed export, in text format.
+pub fn seal(hash: HashAlgorithm, key: &[u8], export: &[u8]) -> String {
+    let header = header(hash);
+    let mut nonce = [0u8; 12];
+    thread_rng().fill_bytes(&mut nonce);
+    let payload = Payload {
+        msg: export,
+        aad: header.as_bytes(),
+    };
+    let ciphertext = cipher(hash, key)
+        .encrypt(Nonce::from_slice(&nonce), payload)
+        .expect("Exports fit in a ChaCha20-Poly1305 message");
+    format!(
+        "{}:{}:{}",
+        header,
+        hex::encode(nonce),
+        hex::encode(ciphertext)
+    )
+}
+
+/// Checks and decrypts a sealed export with the given key.
+pub fn open(key: &[u8], sealed: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let parts: Vec<&str> = sealed.trim().split(':').collect();
+    let (hash, nonce, ciphertext) = match parts[..] {
+        [HEADER, nonce, ciphertext] => (HashAlgorithm::default(), nonce, ciphertext),
+        [HEADER, hash, nonce, ciphertext] => (
+            hash.parse().map_err(|e| format!("{}", e))?,
+            nonce,
+            ciphertext,
+        ),
+        _ => return Err(format!("Sealed exports must be in the {} format", HEADER)),
+    };
+    let nonce = hex::decode(nonce).map_err(|e| format!("Invalid sealed export: {}", e))?;
+    if nonce.len() != 12 {
+        return Err("Invalid sealed export: the nonce must be 12 bytes".to_owned());
+    }
+    let ciphertext =
+        hex::decode(ciphertext).map_err(|e| format!("Invalid sealed export: {}", e))?;
+
+    let header = header(hash);
+    let payload = Payload {
+        msg: &ciphertext,
+        aad: header.as_bytes(),
+    };
+    match cipher(hash, key).decrypt(Nonce::from_slice(&nonce), payload) {
+        Ok(export) => Ok(Zeroizing::new(export)),
+        Err(_) => Err(
+            "The sealed export doesn't match the key, the shares are wrong or the file was modified"
+                .to_owned(),
+        ),
+    }
+}
+
+/// Returns the header of sealed exports whose key is hashed with the given algorithm.
+fn header(hash: HashAlgorithm) -> String {
+    match hash.is_default() {
+        true => HEADER.to_owned(),
+        false => format!("{}:{}", HEADER, hash),
+    }
+}
+
+/// Returns the ChaCha20-Poly1305 cipher keyed by the hash of the key.
+fn cipher(hash: HashAlgorithm, key: &[u8]) -> ChaCha20Poly1305 {
+    let key = Zeroizing::new(hash.digest(key));
+    ChaCha20Poly1305::new(Key::from_slice(&*key))
+}
@@ -0,0 +1,339 @@
+//! Signed transcripts of a reconstruction, allowing to later prove which shares were used.
+//!
+//! A transcript is a plain text file made of `key=value` lines, followed by a final `signature`
+//! line containing an Ed25519 signature over all the preceding lines. Timestamps are in RFC 3339
+//! format, and numbers in plain decimal, independently of the locale. The fingerprints are
+//! HMAC-SHA256 tags (or keyed BLAKE3 hashes, if a `hash` line gives this algorithm) keyed by the
+//! random `salt` of the transcript, so that the fingerprints of shares and secrets can't be looked
+//! up in precomputed tables, nor compared across transcripts.
+
+use crate::exit::{self, OrExit, OrFail};
+use crate::timestamp;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use horcrux::hashing::HashAlgorithm;
+use horcrux::shamir::ErrorCategory;
+use rand::{thread_rng, RngCore};
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::Write;
+
+const HEADER: &str = "horcrux-transcript-v1";
+
+/// Content of a reconstruction transcript.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Transcript {
+    pub shamir_type: String,
+    /// Name of the field, as given by `--field`.
+    pub field: String,
+    pub bitsize: usize,
+    pub threshold: usize,
+    pub started: u64,
+    pub finished: u64,
+    /// Hash algorithm of the fingerprints.
+    pub hash: HashAlgorithm,
+    /// Salt of the fingerprints.
+    pub salt: [u8; 32],
+    pub shares: Vec<String>,
+    pub secret: String,
+}
+
+impl Transcript {
+    /// Serializes the transcript, without the signature line.
+    fn body(&self) -> String {
+        let mut body = format!("{HEADER}\n");
+        body += &format!("type={}\n", self.shamir_type);
+        body += &format!("field={}\n", self.field);
+        body += &format!("bitsize={}\n", self.bitsize);
+        body += &format!("threshold={}\n", self.threshold);
+        body += &format!("started={}\n", timestamp::format(self.started));
+        body += &format!("finished={}\n", timestamp::format(self.finished));
+        if !self.hash.is_default() {
+            body += &format!("hash={}\n", self.hash);
+        }
+        body += &format!("salt={}\n", hex::encode(self.salt));
+        for s in &self.shares {
+            body += &format!("share={s}\n");
+        }
+        body += &format!("secret={}\n", self.secret);
+        body
+    }
+
+    /// Signs the transcript and writes it to the given file.
+    pub fn write(&self, filename: &str, key: &SigningKey) {
+        let body = self.body();
+        let signature = key.sign(body.as_bytes());
+
+        let mut file =
+            File::create(filename).or_exit(format_args!("Could not create {}", filename));
+        file.write_all(body.as_bytes())
+            .and_then(|_| writeln!(file, "signature={}", hex::encode(signature.to_bytes())))
+            .or_exit(format_args!("Could not write {}", filename));
+    }
+
+    /// Reads a transcript from the given file, and verifies its signature.
+    pub fn read_verified(filename: &str, key: &VerifyingKey) -> Self {
+        let (transcript, signature) =
+            Self::parse_signed(&read_file(filename)).or_fail(ErrorCategory::Parse);
+        exit::ensure(
+            key.verify(transcript.body().as_bytes(), &signature).is_ok(),
+            ErrorCategory::Crypto,
+            "Invalid transcript signature",
+        );
+        transcript
+    }
+
+    /// Parses a signed transcript, and returns it with its signature, which remains to be
+    /// verified.
+    fn parse_signed(contents: &str) -> Result<(Self, Signature), String> {
+        let transcript = Self::parse(contents)?;
+        // Any non-canonical formatting, e.g. of numbers, is rejected here.
+        let signature_line = contents
+            .strip_prefix(&transcript.body())
+            .ok_or("Transcript is not in canonical form")?;
+        let signature = signature_line
+            .strip_prefix("signature=")
+            .and_then(|s| s.strip_suffix('\n'))
+            .and_then(|s| hex::decode(s).ok())
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or("Transcript doesn't end with a valid signature line")?;
+        Ok((transcript, signature))
+    }
+
+    /// Parses the lines of a transcript, ignoring any following lines.
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("Unsupported transcript format".to_owned());
+        }
+        let parse_timestamp = |t: String| {
+            timestamp::parse(&t).ok_or_else(|| {
+                format!(
+                    "Transcript timestamp must be in RFC 3339 format YYYY-MM-DDTHH:MM:SSZ, found: {}",
+                    t
+                )
+            })
+        };
+
+        let mut field = |key: &str| -> Result<String, String> {
+            let line = lines.next().unwrap_or_default();
+            match line.strip_prefix(key).and_then(|l| l.strip_prefix('=')) {
+                Some(value) => Ok(value.to_owned()),
+                None => Err(format!(
+                    "Expected transcript field \"{}\", found: {}",
+                    key, line
+                )),
+            }
+        };
+
+        let shamir_type = field("type")?;
+        let field_name = field("field")?;
+        let bitsize = field("bitsize")?
+            .parse()
+            .map_err(|_| "Transcript bitsize must be an integer")?;
+        let threshold = field("threshold")?
+            .parse()
+            .map_err(|_| "Transcript threshold must be an integer")?;
+        let started = parse_timestamp(field("started")?)?;
+        let finished = parse_timestamp(field("finished")?)?;
+
+        let mut lines = lines.peekable();
+        let hash = match lines.next_if(|l| l.starts_with("hash=")) {
+            Some(line) => line["hash=".len()..]
+                .parse()
+                .map_err(|e| format!("Invalid transcript: {}", e))?,
+            None => HashAlgorithm::default(),
+        };
+        let salt = lines
+            .next()
+            .and_then(|l| l.strip_prefix("salt="))
+            .and_then(|s| hex::decode(s).ok())
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or("Expected transcript field \"salt\" with 32 bytes in hexadecimal")?;
+        let mut shares = Vec::new();
+        while let Some(share) = lines.next_if(|l| l.starts_with("share=")) {
+            shares.push(share["share=".len()..].to_owned());
+        }
+        let secret = lines
+            .next()
+            .and_then(|l| l.strip_prefix("secret="))
+            .ok_or("Expected transcript field \"secret\"")?
+            .to_owned();
+
+        Ok(Transcript {
+            shamir_type,
+            field: field_name,
+            bitsize,
+            threshold,
+            started,
+            finished,
+            hash,
+            salt,
+            shares,
+            secret,
+        })
+    }
+}
+
+/// Returns a new random salt for fingerprints.
+pub fn generate_salt() -> [u8; 32] {
+    let mut salt = [0u8; 32];
+    thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+/// Computes the fingerprint of the given bytes in hexadecimal, i.e. their MAC keyed by the salt.
+pub fn fingerprint(hash: HashAlgorithm, salt: &[u8; 32], bytes: &[u8]) -> String {
+    hex::encode(hash.mac(salt, &[bytes]))
+}
+
+/// Parses an Ed25519 signing key (32-byte seed) from a file containing hexadecimal characters.
+pub fn parse_signing_key(filename: &str) -> SigningKey {
+    SigningKey::from_bytes(&parse_key_bytes(filename))
+}
+
+/// Parses an Ed25519 public key from a file containing hexadecimal characters.
+pub fn parse_verifying_key(filename: &str) -> VerifyingKey {
+    VerifyingKey::from_bytes(&parse_key_bytes(filename))
+        .map_err(|e| format!("Invalid public key: {}", e))
+        .or_fail(ErrorCategory::Parse)
+}
+
+/// Parses a 32-byte key from a file containing hexadecimal characters.
+pub fn parse_key_bytes(filename: &str) -> [u8; 32] {
+    decode_key(&read_file(filename)).or_fail(ErrorCategory::Parse)
+}
+
+/// Decodes a 32-byte key in hexadecimal, optionally followed by a newline.
+fn decode_key(contents: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(contents.trim_end())
+        .map_err(|e| format!("Couldn't parse key file as hexadecimal characters: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| "Key file must contain exactly 32 bytes".to_owned())
+}
+
+/// Reads the contents of the given file.
+fn read_file(filename: &str) -> String {
+    std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn transcript() -> Transcript {
+        Transcript {
+            shamir_type: "compact".to_owned(),
+            field: "binary".to_owned(),
+            bitsize: 128,
+            threshold: 2,
+            started: 1_700_000_000,
+            finished: 1_700_000_042,
+            hash: HashAlgorithm::default(),
+            salt: [7; 32],
+            shares: vec!["aa".repeat(32), "bb".repeat(32)],
+            secret: "cc".repeat(32),
+        }
+    }
+
+    fn signed(transcript: &Transcript, key: &SigningKey) -> String {
+        let body = transcript.body();
+        let signature = key.sign(body.as_bytes());
+        format!("{}signature={}\n", body, hex::encode(signature.to_bytes()))
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        for hash in [HashAlgorithm::Sha256, HashAlgorithm::Blake3] {
+            let expected = Transcript {
+                hash,
+                ..transcript()
+            };
+            let (parsed, signature) = Transcript::parse_signed(&signed(&expected, &key)).unwrap();
+            assert_eq!(parsed, expected);
+            assert!(key
+                .verifying_key()
+                .verify(parsed.body().as_bytes(), &signature)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn can_parse_without_shares() {
+        let expected = Transcript {
+            shares: vec![],
+            ..transcript()
+        };
+        let contents = signed(&expected, &SigningKey::from_bytes(&[1; 32]));
+        assert_eq!(Transcript::parse_signed(&contents).unwrap().0, expected);
+    }
+
+    #[test]
+    fn rejects_non_canonical_transcripts() {
+        let contents = signed(&transcript(), &SigningKey::from_bytes(&[1; 32]));
+        for (from, to) in [
+            ("threshold=2", "threshold=02"),
+            ("threshold=2", "threshold=+2"),
+            ("horcrux-transcript-v1", "horcrux-transcript-v0"),
+            ("\nfield=binary", ""),
+            (
+                "started=2023-11-14T22:13:20Z",
+                "started=2023-11-14 22:13:20",
+            ),
+            ("\nsignature=", "\n"),
+        ] {
+            let modified = contents.replacen(from, to, 1);
+            assert_ne!(modified, contents);
+            assert!(
+                Transcript::parse_signed(&modified).is_err(),
+                "Accepted {} instead of {}",
+                to,
+                from
+            );
+        }
+        assert!(Transcript::parse_signed(&contents[..contents.len() - 1]).is_err());
+        assert!(Transcript::parse_signed(&format!("{}extra\n", contents)).is_err());
+        assert!(Transcript::parse_signed("").is_err());
+    }
+
+    #[test]
+    fn detects_modified_transcripts() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let contents = signed(&transcript(), &key);
+        let modified = contents.replacen("secret=cc", "secret=dd", 1);
+        let (parsed, signature) = Transcript::parse_signed(&modified).unwrap();
+        assert!(key
+            .verifying_key()
+            .verify(parsed.body().as_bytes(), &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn fingerprints_depend_on_the_salt() {
+        let a = fingerprint(HashAlgorithm::default(), &[1; 32], b"secret");
+        assert_eq!(a.len(), 64);
+        assert_eq!(
+            a,
+            fingerprint(HashAlgorithm::default(), &[1; 32], b"secret")
+        );
+        assert_ne!(
+            a,
+            fingerprint(HashAlgorithm::default(), &[2; 32], b"secret")
+        );
+        assert_ne!(a, fingerprint(HashAlgorithm::Blake3, &[1; 32], b"secret"));
+    }
+
+    #[test]
+    fn can_decode_keys() {
+        assert_eq!(decode_key(&"01".repeat(32)).unwrap(), [1; 32]);
+        assert_eq!(
+            decode_key(&format!("{}\n", "ab".repeat(32))).unwrap(),
+            [0xab; 32]
+        );
+        assert!(decode_key(&"01".repeat(31)).is_err());
+        assert!(decode_key(&"01".repeat(33)).is_err());
+        assert!(decode_key(&"zz".repeat(32)).is_err());
+        assert!(decode_key("").is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Witness shares, i.e. shares of a hash of the secret rather than of the secret itself.
+//!
+//! Witness shares are meant for auditors, who can take part in verification ceremonies by
+//! reconstructing the hash of the secret, but can never reconstruct the secret itself. They are
+//! compact shares over GF(2^256) of the hash of the secret, labeled with a `witness:` prefix so
+//! that they cannot be mistaken for regular shares. The hash is SHA-256 by default, otherwise the
+//! prefix is followed by the identifier of the algorithm, e.g. `witness:blake3:`.
+//!
+//! Note that for small secrets (e.g. 8 or 16 bits), the hash allows to recover the secret by brute
+//! force, so witnesses must then be trusted as much as regular custodians.
+
+use crate::exit::{self, OrExit, OrFail};
+use horcrux::field::Field;
+use horcrux::gf2n::GF256;
+use horcrux::hashing::HashAlgorithm;
+use horcrux::shamir::{CompactShamir, ErrorCategory, Shamir};
+
+const PREFIX: &str = "witness:";
+
+type WitnessShare = <CompactShamir as Shamir<GF256>>::Share;
+
+/// Computes the hash of a secret, as an element of GF(2^256).
+fn hash_secret<F: Field>(hash: HashAlgorithm, secret: &F) -> GF256 {
+    GF256::from_bytes(&hash.digest(&secret.to_bytes())).unwrap()
+}
+
+/// Splits the hash of the secret into n labeled witness shares, with k of them being sufficient to
+/// reconstruct the hash.
+pub fn split<F: Field>(hash: HashAlgorithm, secret: &F, k: usize, n: usize) -> Vec<String> {
+    let prefix = match hash.is_default() {
+        true => PREFIX.to_owned(),
+        false => format!("{PREFIX}{hash}:"),
+    };
+    CompactShamir::split(&hash_secret(hash, secret), k, n)
+        .unwrap()
+        .iter()
+        .map(|s| format!("{prefix}{s}"))
+        .collect()
+}
+
+/// Checks that the witness shares stored in the given file reconstruct the hash of the secret.
+pub fn verify<F: Field>(secret: &F, filename: &str, k: usize) -> bool {
+    let contents =
+        std::fs::read_to_string(filename).or_exit(format_args!("Could not read {}", filename));
+    let (hash, shares) = parse(&contents).or_fail(ErrorCategory::Parse);
+    exit::ensure(
+        shares.len() >= k,
+        ErrorCategory::Threshold,
+        "Found fewer witness shares than the witness threshold, cannot verify!",
+    );
+    CompactShamir::reconstruct(&shares, k) == Ok(hash_secret(hash, secret))
+}
+
+/// Parses witness shares, one per line, and returns them with their hash algorithm.
+fn parse(contents: &str) -> Result<(HashAlgorithm, Vec<WitnessShare>), String> {
+    let mut hashes = Vec::new();
+    let shares = contents
+        .lines()
+        .map(|line| {
+            let share = line
+                .strip_prefix(PREFIX)
+                .ok_or_else(|| format!("Not a witness share: {}", line))?;
+            let (hash, share) = match share.split_once(':') {
+                Some((hash, share)) => (hash.parse().map_err(|e| format!("{}", e))?, share),
+                None => (HashAlgorithm::default(), share),
+            };
+            hashes.push(hash);
+            CompactShamir::parse_share(share)
+                .map_err(|e| format!("Invalid witness share {}: {}", line, e))
+        })
+        .collect::<Result<_, String>>()?;
+    hashes.dedup();
+    if hashes.len() > 1 {
+        return Err("The witness shares don't all use the same hash algorithm".to_owned());
+    }
+    Ok((hashes.pop().unwrap_or_default(), shares))
+}